@@ -0,0 +1,729 @@
+use crate::api::feed::invalidate_feed_cache;
+use crate::api::json::RequestJson;
+use crate::api::pagination::{insert_pagination_headers, mongo_total, PageInfo};
+use crate::auth::AdminUser;
+use crate::config::Config;
+use crate::db::{id_filter, mongo_collection, DbPool};
+use crate::entities::user;
+use crate::jobs::{backfill_top_stats, remove_feeds_from_rankings};
+use crate::kafka::{
+    dead_letter_topic, peek_dead_letters, read_dead_letter, FeedUnlikedEvent, KafkaProducer,
+};
+use crate::metrics;
+use crate::models::{
+    AdminUserResponse, FeedReport, FeedReportResponse, ReplayDeadLetterRequest, ReportAction,
+    ReportStatus, ResolveReportRequest, SeedRequest,
+};
+use crate::repositories::{FeedChanges, FeedRepository, LikeRepository, UserRepository};
+use crate::services::rate_limit::{acquire_cooldown, release_cooldown};
+use crate::services::seed::{seed, SeedPlan};
+use actix_web::{web, HttpRequest, HttpResponse, Result as ActixResult};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use mongodb::bson::doc;
+use mongodb::options::FindOptions;
+use mongodb::Database as MongoDatabase;
+use redis::Client as RedisClient;
+use sea_orm::{ColumnTrait, Condition, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::Deserialize;
+use serde_json::json;
+
+const MAX_ADMIN_USERS_LIMIT: u64 = 100;
+const MAX_ADMIN_REPORTS_LIMIT: u64 = 100;
+const MAX_DLQ_LIMIT: u64 = 100;
+
+/// Consumed topics whose `{topic}_dlq` can be inspected and replayed into
+const DLQ_SOURCE_TOPICS: [&str; 2] = ["feed_events", "user_events"];
+
+/// Held while a rankings backfill runs, so only one runs across all instances.
+/// Expires on its own if the instance dies mid-run.
+const BACKFILL_LOCK_KEY: &str = "top:backfill:lock";
+const BACKFILL_LOCK_SECONDS: u64 = 3600;
+
+const MAX_SEED_USERS: u64 = 100;
+const MAX_SEED_FEEDS_PER_USER: u64 = 20;
+const DEFAULT_SEED_LIKES_PER_FEED: u64 = 5;
+const DEFAULT_SEED_COMMENTS_PER_FEED: u64 = 3;
+const MAX_SEED_COMMENTS_PER_FEED: u64 = 20;
+const DEFAULT_SEED_VIEWS_PER_FEED: u64 = 20;
+const MAX_SEED_VIEWS_PER_FEED: u64 = 100;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AdminUserSort {
+    /// Newest accounts first
+    #[default]
+    Newest,
+    Oldest,
+    /// Username A-Z
+    Username,
+    /// Email A-Z
+    Email,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct AdminUserQuery {
+    #[schema(example = 1)]
+    pub page: Option<u64>,
+    #[schema(example = 20)]
+    pub limit: Option<u64>,
+    /// Case-insensitive substring of the email
+    pub email: Option<String>,
+    /// Case-insensitive substring of the username
+    pub username: Option<String>,
+    /// Only users created at or after this timestamp (RFC 3339)
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only users created strictly before this timestamp (RFC 3339)
+    pub created_before: Option<DateTime<Utc>>,
+    pub sort: Option<AdminUserSort>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/users",
+    params(
+        ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 20, max: 100)"),
+        ("email" = Option<String>, Query, description = "Filter: email contains"),
+        ("username" = Option<String>, Query, description = "Filter: username contains"),
+        ("created_after" = Option<String>, Query, description = "Filter: created at or after (RFC 3339)"),
+        ("created_before" = Option<String>, Query, description = "Filter: created before (RFC 3339)"),
+        ("sort" = Option<String>, Query, description = "newest (default), oldest, username or email")
+    ),
+    responses(
+        (status = 200, description = "Matching users", body = Vec<AdminUserResponse>),
+        (status = 400, description = "Invalid query parameters"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn list_users(
+    _admin: AdminUser,
+    pool: web::Data<DbPool>,
+    config: web::Data<Config>,
+    query: web::Query<AdminUserQuery>,
+) -> ActixResult<HttpResponse> {
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_ADMIN_USERS_LIMIT);
+    let offset = (page - 1) * limit;
+
+    let mut condition = Condition::all();
+    if let Some(email) = query.email.as_deref().filter(|s| !s.is_empty()) {
+        condition = condition.add(user::Column::Email.contains(email));
+    }
+    let username = query
+        .username
+        .as_deref()
+        .map(|username| config.usernames.normalize(username))
+        .filter(|s| !s.is_empty());
+    if let Some(username) = username {
+        condition = condition.add(user::Column::Username.contains(&username));
+    }
+    if let Some(created_after) = query.created_after {
+        condition = condition.add(user::Column::CreatedAt.gte(created_after));
+    }
+    if let Some(created_before) = query.created_before {
+        condition = condition.add(user::Column::CreatedAt.lt(created_before));
+    }
+
+    let select = user::Entity::find().filter(condition);
+    // Id breaks ties so pages are stable
+    let select = match query.sort.unwrap_or_default() {
+        AdminUserSort::Newest => select
+            .order_by_desc(user::Column::CreatedAt)
+            .order_by_desc(user::Column::Id),
+        AdminUserSort::Oldest => select
+            .order_by_asc(user::Column::CreatedAt)
+            .order_by_asc(user::Column::Id),
+        AdminUserSort::Username => select
+            .order_by_asc(user::Column::Username)
+            .order_by_asc(user::Column::Id),
+        AdminUserSort::Email => select
+            .order_by_asc(user::Column::Email)
+            .order_by_asc(user::Column::Id),
+    };
+
+    let users: Vec<AdminUserResponse> = select
+        .limit(limit)
+        .offset(offset)
+        .all(pool.get_ref())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .into_iter()
+        .map(|user_model| AdminUserResponse {
+            id: user_model.id,
+            email: user_model.email,
+            username: user_model.username,
+            is_admin: user_model.is_admin,
+            is_verified: user_model.is_verified,
+            created_at: user_model.created_at,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(users))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/admin/feed/{feed_id}/like/{user_id}",
+    params(
+        ("feed_id" = i64, Path, description = "Feed ID"),
+        ("user_id" = i64, Path, description = "User whose like is removed")
+    ),
+    responses(
+        (status = 200, description = "Like removed"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Like not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn remove_like(
+    path: web::Path<(i64, i64)>,
+    admin: AdminUser,
+    likes: web::Data<dyn LikeRepository>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+    kafka_producer: web::Data<KafkaProducer>,
+) -> ActixResult<HttpResponse> {
+    let (feed_id, user_id) = path.into_inner();
+
+    // Through the repository, so `feeds.like_count` follows with LIKE_COUNT_DENORMALIZED
+    let removed = likes
+        .delete(feed_id, user_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    if removed == 0 {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "Like not found"
+        })));
+    }
+
+    log::info!(
+        "Admin {} removed like of user {} on feed {}",
+        admin.user_id,
+        user_id,
+        feed_id
+    );
+
+    invalidate_feed_cache(&redis_client, &config).await;
+
+    // Rankings are adjusted by the consumer
+    let event = FeedUnlikedEvent::new(feed_id, user_id);
+    if let Ok(event_json) = serde_json::to_string(&event) {
+        if let Err(e) = kafka_producer
+            .send_message_with_retry("feed_events", &feed_id.to_string(), &event_json)
+            .await
+        {
+            log::warn!("Failed to send Kafka event: {:?}", e);
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(json!({"message": "Like removed"})))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ReportQuery {
+    #[schema(example = 1)]
+    pub page: Option<u64>,
+    #[schema(example = 20)]
+    pub limit: Option<u64>,
+    /// Only reports in this state (default: `open`)
+    pub status: Option<ReportStatus>,
+    /// Only reports of this feed
+    pub feed_id: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/reports",
+    params(
+        ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 20, max: 100)"),
+        ("status" = Option<String>, Query, description = "open (default), dismissed or hidden"),
+        ("feed_id" = Option<i64>, Query, description = "Only reports of this feed")
+    ),
+    responses(
+        (status = 200, description = "Feed reports, oldest first; Link and X-Total-Count headers when enabled", body = Vec<FeedReportResponse>),
+        (status = 400, description = "Invalid query parameters"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn list_reports(
+    http_req: HttpRequest,
+    _admin: AdminUser,
+    query: web::Query<ReportQuery>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_ADMIN_REPORTS_LIMIT);
+    let status = query.status.unwrap_or(ReportStatus::Open);
+
+    let collection = mongo_collection::<FeedReport>(&mongo_db, &config, "feed_reports");
+    let mut filter = doc! {"status": status.as_str()};
+    if let Some(feed_id) = query.feed_id {
+        filter.insert("feed_id", feed_id);
+    }
+    // Oldest first, so the queue is worked through in the order reports came in
+    let options = FindOptions::builder()
+        .sort(doc! {"created_at": 1, "_id": 1})
+        .skip((page - 1) * limit)
+        .limit(limit as i64)
+        .build();
+    let (total, cursor) = tokio::join!(
+        mongo_total(&config, &collection, filter.clone()),
+        collection.find(filter, options)
+    );
+    let total = total.map_err(actix_web::error::ErrorInternalServerError)?;
+    let reports: Vec<FeedReportResponse> = cursor
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .map_ok(FeedReportResponse::from)
+        .try_collect()
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let mut response = HttpResponse::Ok();
+    insert_pagination_headers(
+        &mut response,
+        &http_req,
+        &config,
+        &PageInfo {
+            page,
+            limit,
+            total,
+            returned: reports.len(),
+            max_depth: None,
+        },
+    );
+    Ok(response.json(reports))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/reports/{report_id}/resolve",
+    params(
+        ("report_id" = String, Path, description = "Report ID")
+    ),
+    request_body = ResolveReportRequest,
+    responses(
+        (status = 200, description = "Report resolved", body = FeedReportResponse),
+        (status = 400, description = "Unknown action"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "Report not found"),
+        (status = 409, description = "Report already resolved")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn resolve_report(
+    path: web::Path<String>,
+    admin: AdminUser,
+    req: RequestJson<ResolveReportRequest>,
+    feeds: web::Data<dyn FeedRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let report_id = path.into_inner();
+    let collection = mongo_collection::<FeedReport>(&mongo_db, &config, "feed_reports");
+    let Some(report) = collection
+        .find_one(doc! {"_id": id_filter(&report_id)}, None)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+    else {
+        return Ok(HttpResponse::NotFound().json(json!({"error": "Report not found"})));
+    };
+    if report.status != ReportStatus::Open {
+        return Ok(HttpResponse::Conflict().json(json!({
+            "error": "Report already resolved",
+            "status": report.status
+        })));
+    }
+
+    let now = Utc::now();
+    let (status, filter) = match req.action {
+        ReportAction::Dismiss => (
+            ReportStatus::Dismissed,
+            doc! {"_id": id_filter(&report_id), "status": ReportStatus::Open.as_str()},
+        ),
+        ReportAction::Hide => {
+            let changes = FeedChanges {
+                hidden_at: Some(Some(now)),
+                ..Default::default()
+            };
+            match feeds.update(report.feed_id, changes).await {
+                // A deleted feed is as hidden as it gets
+                Ok(_) | Err(DbErr::RecordNotFound(_)) => {}
+                Err(e) => return Err(actix_web::error::ErrorInternalServerError(e)),
+            }
+            // Cached anonymous pages may still list the feed
+            invalidate_feed_cache(&redis_client, &config).await;
+            remove_hidden_feed_from_rankings(&redis_client, &config, report.feed_id).await;
+            // Every open report of the feed is settled by hiding it
+            (
+                ReportStatus::Hidden,
+                doc! {"feed_id": report.feed_id, "status": ReportStatus::Open.as_str()},
+            )
+        }
+    };
+    let update = doc! {"$set": {
+        "status": status.as_str(),
+        "resolved_by": admin.user_id,
+        "resolved_at": now.timestamp(),
+    }};
+    let resolved = collection
+        .update_many(filter, update, None)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    log::info!(
+        "Admin {} resolved {} report(s) of feed {} as {}",
+        admin.user_id,
+        resolved.modified_count,
+        report.feed_id,
+        status.as_str()
+    );
+
+    Ok(
+        HttpResponse::Ok().json(FeedReportResponse::from(FeedReport {
+            status,
+            resolved_by: Some(admin.user_id),
+            resolved_at: Some(now),
+            ..report
+        })),
+    )
+}
+
+/// Take a hidden feed out of the `top:*` rankings. On failure the hourly rebuild and the
+/// orphan sweep drop it later, and the rankings skip hidden feeds meanwhile.
+async fn remove_hidden_feed_from_rankings(
+    redis_client: &RedisClient,
+    config: &Config,
+    feed_id: i64,
+) {
+    let mut pipe = redis::pipe();
+    remove_feeds_from_rankings(&mut pipe, config, &[feed_id]);
+    let removed = match redis_client.get_async_connection().await {
+        Ok(mut conn) => pipe.query_async::<_, ()>(&mut conn).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = removed {
+        log::warn!(
+            "Failed to remove hidden feed {} from the rankings: {:?}",
+            feed_id,
+            e
+        );
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct DeadLetterQuery {
+    /// Source topic whose dead letters to read (default: `feed_events`)
+    #[schema(example = "feed_events")]
+    pub topic: Option<String>,
+    #[schema(example = 20)]
+    pub limit: Option<u64>,
+}
+
+/// The consumed topic `topic` names (default `feed_events`); `None` for any other topic
+fn dead_letter_source(topic: Option<&str>) -> Option<&'static str> {
+    let topic = topic.unwrap_or(DLQ_SOURCE_TOPICS[0]);
+    DLQ_SOURCE_TOPICS
+        .into_iter()
+        .find(|source| *source == topic)
+}
+
+fn unknown_topic() -> HttpResponse {
+    HttpResponse::BadRequest().json(json!({
+        "error": "Unknown topic",
+        "topics": DLQ_SOURCE_TOPICS
+    }))
+}
+
+fn kafka_unavailable(e: impl std::fmt::Debug) -> HttpResponse {
+    log::error!("Failed to read dead-letter topic: {:?}", e);
+    HttpResponse::ServiceUnavailable().json(json!({"error": "Kafka is unavailable"}))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/dlq",
+    params(
+        ("topic" = Option<String>, Query, description = "Source topic: feed_events (default) or user_events"),
+        ("limit" = Option<u64>, Query, description = "Messages to return (default: 20, max: 100)")
+    ),
+    responses(
+        (status = 200, description = "Most recent dead letters of {topic}_dlq, newest first", body = Vec<DeadLetterMessage>),
+        (status = 400, description = "Unknown topic"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required"),
+        (status = 503, description = "Kafka is unavailable")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn list_dead_letters(
+    _admin: AdminUser,
+    query: web::Query<DeadLetterQuery>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let Some(source) = dead_letter_source(query.topic.as_deref()) else {
+        return Ok(unknown_topic());
+    };
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_DLQ_LIMIT) as usize;
+
+    // rdkafka's consumer API is blocking
+    let topic = dead_letter_topic(source);
+    let read = web::block(move || peek_dead_letters(&config, &topic, limit)).await?;
+    match read {
+        Ok(letters) => Ok(HttpResponse::Ok().json(letters)),
+        Err(e) => Ok(kafka_unavailable(e)),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/dlq/replay",
+    request_body = ReplayDeadLetterRequest,
+    responses(
+        (status = 200, description = "Dead letter republished to its original topic"),
+        (status = 400, description = "Unknown topic, or nothing to replay (empty payload and no replacement)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "No dead letter at this partition and offset"),
+        (status = 503, description = "Kafka is unavailable")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn replay_dead_letter(
+    admin: AdminUser,
+    req: RequestJson<ReplayDeadLetterRequest>,
+    kafka_producer: web::Data<KafkaProducer>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let Some(source) = dead_letter_source(req.topic.as_deref()) else {
+        return Ok(unknown_topic());
+    };
+    let (partition, offset) = (req.partition, req.offset);
+
+    let topic = dead_letter_topic(source);
+    let read = web::block(move || read_dead_letter(&config, &topic, partition, offset)).await?;
+    let letter = match read {
+        Ok(Some(letter)) => letter,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "Dead letter not found"
+            })))
+        }
+        Err(e) => return Ok(kafka_unavailable(e)),
+    };
+
+    // Replaying an empty message would only dead-letter it again
+    let payload = req.payload.as_deref().unwrap_or(&letter.payload);
+    if payload.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": "Dead letter has no payload; pass one to replay it"
+        })));
+    }
+    let key = letter.key.unwrap_or_default();
+    if let Err(e) = kafka_producer
+        .send_message_with_retry(source, &key, payload)
+        .await
+    {
+        log::error!("Failed to replay dead letter: {:?}", e);
+        return Ok(HttpResponse::ServiceUnavailable().json(json!({
+            "error": "Kafka is unavailable"
+        })));
+    }
+    metrics::record_dead_letter_replay(source);
+
+    log::info!(
+        "Admin {} replayed {}[{}]@{} to {}",
+        admin.user_id,
+        letter.topic,
+        partition,
+        offset,
+        source
+    );
+    Ok(HttpResponse::Ok().json(json!({
+        "message": "Dead letter replayed",
+        "topic": source,
+        "key": key
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/top/backfill",
+    responses(
+        (status = 202, description = "Backfill started; progress is logged"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required"),
+        (status = 409, description = "A backfill is already running")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+pub async fn backfill_rankings(
+    admin: AdminUser,
+    pool: web::Data<DbPool>,
+    mongo_db: web::Data<MongoDatabase>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    match acquire_cooldown(
+        &redis_client,
+        &config,
+        BACKFILL_LOCK_KEY,
+        BACKFILL_LOCK_SECONDS,
+    )
+    .await
+    {
+        Ok(None) => {}
+        Ok(Some(retry_after)) => {
+            return Ok(HttpResponse::Conflict()
+                .insert_header(("Retry-After", retry_after.to_string()))
+                .json(json!({
+                    "error": "A rankings backfill is already running",
+                    "retry_after": retry_after
+                })));
+        }
+        Err(e) => {
+            log::error!("Failed to take the backfill lock: {:?}", e);
+            return Ok(HttpResponse::ServiceUnavailable().json(json!({
+                "error": "Redis is unavailable"
+            })));
+        }
+    }
+
+    log::info!("Admin {} started a top stats backfill", admin.user_id);
+
+    // Rebuilding from the whole history can take minutes, so it outlives the request
+    let (pool, mongo_db, redis_client, config) = (
+        pool.get_ref().clone(),
+        mongo_db.get_ref().clone(),
+        redis_client.get_ref().clone(),
+        config.get_ref().clone(),
+    );
+    actix_web::rt::spawn(async move {
+        backfill_top_stats(&pool, &mongo_db, &redis_client, &config).await;
+        if let Err(e) = release_cooldown(&redis_client, &config, BACKFILL_LOCK_KEY).await {
+            log::warn!("Failed to release the backfill lock: {:?}", e);
+        }
+    });
+
+    Ok(HttpResponse::Accepted().json(json!({"message": "Rankings backfill started"})))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/seed",
+    request_body = SeedRequest,
+    responses(
+        (status = 201, description = "Demo data created", body = SeedResponse),
+        (status = 400, description = "Counts out of range"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required"),
+        (status = 404, description = "ENABLE_SEED_ENDPOINT is not set")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "admin"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn seed_data(
+    admin: AdminUser,
+    req: RequestJson<SeedRequest>,
+    users: web::Data<dyn UserRepository>,
+    feeds: web::Data<dyn FeedRepository>,
+    likes: web::Data<dyn LikeRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+    redis_client: web::Data<RedisClient>,
+    kafka_producer: web::Data<KafkaProducer>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    if !config.server.seed_endpoint {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "Not found"
+        })));
+    }
+
+    if !(1..=MAX_SEED_USERS).contains(&req.users) {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": format!("users must be between 1 and {}", MAX_SEED_USERS)
+        })));
+    }
+    if req.feeds_per_user > MAX_SEED_FEEDS_PER_USER {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": format!("feeds_per_user must be at most {}", MAX_SEED_FEEDS_PER_USER)
+        })));
+    }
+    // A feed can be liked by every seeded user but its author
+    let max_likes = req
+        .max_likes_per_feed
+        .unwrap_or(DEFAULT_SEED_LIKES_PER_FEED)
+        .min(req.users - 1);
+    let max_comments = req
+        .max_comments_per_feed
+        .unwrap_or(DEFAULT_SEED_COMMENTS_PER_FEED)
+        .min(MAX_SEED_COMMENTS_PER_FEED);
+    let max_views = req
+        .max_views_per_feed
+        .unwrap_or(DEFAULT_SEED_VIEWS_PER_FEED)
+        .min(MAX_SEED_VIEWS_PER_FEED);
+
+    let plan = SeedPlan::random(
+        req.users,
+        req.feeds_per_user,
+        max_likes,
+        max_comments,
+        max_views,
+    );
+    let created = seed(
+        users.get_ref(),
+        feeds.get_ref(),
+        likes.get_ref(),
+        &mongo_db,
+        &kafka_producer,
+        &config,
+        &plan,
+    )
+    .await
+    .map_err(actix_web::error::ErrorInternalServerError)?;
+    invalidate_feed_cache(&redis_client, &config).await;
+
+    log::info!(
+        "Admin {} seeded {} users and {} feeds",
+        admin.user_id,
+        created.user_ids.len(),
+        created.feed_ids.len()
+    );
+
+    Ok(HttpResponse::Created().json(created))
+}
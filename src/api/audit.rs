@@ -0,0 +1,81 @@
+use crate::api::request_id::RequestId;
+use crate::auth::Claims;
+use crate::config::Config;
+use crate::db::mongo_collection;
+use crate::models::AuditLogEntry;
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::Method,
+    middleware::Next,
+    web, Error, HttpMessage,
+};
+use chrono::Utc;
+use mongodb::Database as MongoDatabase;
+use uuid::Uuid;
+
+/// Records each authenticated request to the scope it wraps (`/api/admin`) in the
+/// `audit_log` collection: who, which route and path parameters, and the response status.
+///
+/// The caller comes from the `Claims` the `AuthenticatedUser` extractor stored, so
+/// requests rejected with `401` are not recorded while those rejected with `403` are.
+/// A failed write is logged and does not fail the request.
+pub async fn record_admin_action(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let config = req.app_data::<web::Data<Config>>().cloned();
+    let mongo_db = req.app_data::<web::Data<MongoDatabase>>().cloned();
+
+    let res = next.call(req).await?;
+    let (Some(config), Some(mongo_db)) = (config, mongo_db) else {
+        return Ok(res);
+    };
+    if !config.audit_log.enabled
+        || (!config.audit_log.include_reads && res.request().method() == Method::GET)
+    {
+        return Ok(res);
+    }
+    let user_id = res
+        .request()
+        .extensions()
+        .get::<Claims>()
+        .and_then(|claims| claims.sub.parse::<i64>().ok());
+    let Some(user_id) = user_id else {
+        return Ok(res);
+    };
+
+    let request = res.request();
+    let route = request
+        .match_pattern()
+        .unwrap_or_else(|| request.path().to_string());
+    let status = res.status();
+    let entry = AuditLogEntry {
+        id: Some(Uuid::new_v4().to_string()),
+        user_id,
+        action: format!("{} {}", request.method(), route),
+        target: request
+            .match_info()
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        status: status.as_u16(),
+        success: status.is_success(),
+        request_id: request
+            .extensions()
+            .get::<RequestId>()
+            .map(|id| id.as_str().to_string()),
+        created_at: Utc::now(),
+    };
+
+    let collection = mongo_collection::<AuditLogEntry>(&mongo_db, &config, "audit_log");
+    if let Err(e) = collection.insert_one(&entry, None).await {
+        log::error!(
+            "Failed to write audit log entry for {} by user {}: {:?}",
+            entry.action,
+            entry.user_id,
+            e
+        );
+    }
+    Ok(res)
+}
@@ -1,11 +1,20 @@
-use crate::auth::{create_token, hash_password, verify_password, Claims};
+use crate::api::json::RequestJson;
+use crate::auth::{
+    consume_nonce, create_token, hash_password, issue_nonce, list_sessions, record_session,
+    revoke_session, verify_password, AuthenticatedUser, Claims,
+};
 use crate::config::Config;
 use crate::db::DbPool;
 use crate::entities::user;
 use crate::kafka::{KafkaProducer, UserCreatedEvent};
-use crate::models::{AuthResponse, LoginRequest, SignupRequest, UserResponse};
-use actix_web::{web, HttpResponse, Result as ActixResult};
-use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter};
+use crate::models::{
+    AuthResponse, ChangePasswordRequest, LoginRequest, NonceResponse, SignupRequest, UserResponse,
+};
+use crate::repositories::{is_unique_violation, UserRepository};
+use crate::services::locale::preferred_language;
+use actix_web::{http::header, web, HttpRequest, HttpResponse, Result as ActixResult};
+use redis::Client as RedisClient;
+use sea_orm::{ActiveModelTrait, EntityTrait};
 use serde_json::json;
 
 #[utoipa::path(
@@ -13,26 +22,34 @@ use serde_json::json;
     path = "/api/auth/signup",
     request_body = SignupRequest,
     responses(
-        (status = 200, description = "User created successfully", body = AuthResponse),
-        (status = 400, description = "Bad request"),
-        (status = 409, description = "User already exists")
+        (status = 201, description = "User created successfully", body = AuthResponse),
+        (status = 400, description = "Bad request, including an unknown client_type or, with SERVER_STRICT_JSON, an unknown field"),
+        (status = 409, description = "User already exists, including when a concurrent signup wins the race")
     ),
     tag = "auth"
 )]
 pub async fn signup(
-    req: web::Json<SignupRequest>,
-    pool: web::Data<DbPool>,
+    http_req: HttpRequest,
+    req: RequestJson<SignupRequest>,
+    users: web::Data<dyn UserRepository>,
+    redis_client: web::Data<RedisClient>,
     config: web::Data<Config>,
     kafka_producer: web::Data<KafkaProducer>,
 ) -> ActixResult<HttpResponse> {
-    // Check if user exists using SeaORM
-    let existing_user = user::Entity::find()
-        .filter(
-            Condition::any()
-                .add(user::Column::Email.eq(&req.email))
-                .add(user::Column::Username.eq(&req.username)),
-        )
-        .one(pool.get_ref())
+    let client_type = req.client_type.as_deref();
+    if client_type.is_some_and(|client_type| !config.jwt.client_types.contains_key(client_type)) {
+        return Ok(unknown_client_type());
+    }
+
+    let username = config.usernames.normalize(&req.username);
+    if username.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": "Username must not be empty"
+        })));
+    }
+
+    let existing_user = users
+        .find_by_email_or_username(&req.email, &username)
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
@@ -45,24 +62,37 @@ pub async fn signup(
     let password_hash =
         hash_password(&req.password).map_err(actix_web::error::ErrorInternalServerError)?;
 
-    // Create user using SeaORM
-    let new_user = user::ActiveModel {
-        email: sea_orm::Set(req.email.clone()),
-        username: sea_orm::Set(req.username.clone()),
-        password_hash: sea_orm::Set(password_hash),
-        ..Default::default()
+    // A concurrent signup can pass the check above too; the unique indexes decide
+    let user = match users.create(&req.email, &username, &password_hash).await {
+        Ok(user) => user,
+        Err(e) if is_unique_violation(&e) => {
+            return Ok(HttpResponse::Conflict().json(json!({
+                "error": "User with this email or username already exists"
+            })));
+        }
+        Err(e) => return Err(actix_web::error::ErrorInternalServerError(e)),
     };
 
-    let user = user::Entity::insert(new_user)
-        .exec_with_returning(pool.get_ref())
-        .await
-        .map_err(actix_web::error::ErrorInternalServerError)?;
-
-    let claims = Claims::new(user.id, user.email.clone(), config.jwt.expiration_hours);
+    let Some(claims) = client_claims(&config, &user, client_type) else {
+        return Ok(unknown_client_type());
+    };
     let token = create_token(&claims, &config.jwt.secret)
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
-    let event = UserCreatedEvent::new(user.id as u64, user.email.clone(), user.username.clone());
+    if let Err(e) = record_session(
+        &redis_client,
+        &config,
+        user.id,
+        &claims,
+        user_agent(&http_req),
+    )
+    .await
+    {
+        log::warn!("Failed to record session: {:?}", e);
+    }
+
+    let event = UserCreatedEvent::new(user.id as u64, user.email.clone(), user.username.clone())
+        .with_language(request_language(&http_req));
     if let Ok(event_json) = serde_json::to_string(&event) {
         if let Err(e) = kafka_producer
             .send_message("user_events", &user.id.to_string(), &event_json)
@@ -88,20 +118,21 @@ pub async fn signup(
     request_body = LoginRequest,
     responses(
         (status = 200, description = "Login successful", body = AuthResponse),
+        (status = 400, description = "Unknown client_type, or an unknown field with SERVER_STRICT_JSON"),
         (status = 401, description = "Invalid credentials"),
         (status = 404, description = "User not found")
     ),
     tag = "auth"
 )]
 pub async fn login(
-    req: web::Json<LoginRequest>,
-    pool: web::Data<DbPool>,
+    http_req: HttpRequest,
+    req: RequestJson<LoginRequest>,
+    users: web::Data<dyn UserRepository>,
+    redis_client: web::Data<RedisClient>,
     config: web::Data<Config>,
 ) -> ActixResult<HttpResponse> {
-    // Find user by email using SeaORM
-    let user = user::Entity::find()
-        .filter(user::Column::Email.eq(&req.email))
-        .one(pool.get_ref())
+    let user = users
+        .find_by_email(&req.email)
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
@@ -123,10 +154,24 @@ pub async fn login(
         })));
     }
 
-    let claims = Claims::new(user.id, user.email.clone(), config.jwt.expiration_hours);
+    let Some(claims) = client_claims(&config, &user, req.client_type.as_deref()) else {
+        return Ok(unknown_client_type());
+    };
     let token = create_token(&claims, &config.jwt.secret)
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
+    if let Err(e) = record_session(
+        &redis_client,
+        &config,
+        user.id,
+        &claims,
+        user_agent(&http_req),
+    )
+    .await
+    {
+        log::warn!("Failed to record session: {:?}", e);
+    }
+
     Ok(HttpResponse::Ok().json(AuthResponse {
         token,
         user: UserResponse {
@@ -136,3 +181,204 @@ pub async fn login(
         },
     }))
 }
+
+/// Claims for a new token of `client_type`, with its audience and lifetime.
+/// `None` if the client type is not configured.
+fn client_claims(config: &Config, user: &user::Model, client_type: Option<&str>) -> Option<Claims> {
+    let client_type = client_type.unwrap_or(&config.jwt.default_client_type);
+    let expiration_hours = *config.jwt.client_types.get(client_type)?;
+    Some(Claims::new(user.id, user.email.clone(), expiration_hours).with_audience(client_type))
+}
+
+fn unknown_client_type() -> HttpResponse {
+    HttpResponse::BadRequest().json(json!({
+        "error": "Unknown client_type"
+    }))
+}
+
+/// Most preferred language of the request's `Accept-Language` header, normalized
+pub(crate) fn request_language(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(preferred_language)
+}
+
+fn user_agent(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get("User-Agent")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/auth/sessions",
+    responses(
+        (status = 200, description = "Active sessions of the current user", body = Vec<SessionResponse>),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "auth"
+)]
+pub async fn get_sessions(
+    user: AuthenticatedUser,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let sessions = list_sessions(&redis_client, &config, user.user_id, &user.jti)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/auth/sessions/{jti}",
+    params(
+        ("jti" = String, Path, description = "Token ID of the session to revoke")
+    ),
+    responses(
+        (status = 200, description = "Session revoked"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Session not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "auth"
+)]
+pub async fn delete_session(
+    path: web::Path<String>,
+    user: AuthenticatedUser,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let jti = path.into_inner();
+
+    let revoked = revoke_session(&redis_client, &config, user.user_id, &jti)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    if !revoked {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "Session not found"
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(json!({"message": "Session revoked"})))
+}
+
+/// Validate and consume the nonce of a sensitive request.
+/// Returns the `400` response to send if the nonce is missing (when required), unknown,
+/// expired or already used; a supplied nonce is always checked even when not required.
+pub(crate) async fn check_nonce(
+    redis_client: &RedisClient,
+    config: &Config,
+    user_id: i64,
+    nonce: Option<&str>,
+) -> ActixResult<Option<HttpResponse>> {
+    let nonce = match nonce {
+        Some(nonce) => nonce,
+        None if config.jwt.require_nonce => {
+            return Ok(Some(HttpResponse::BadRequest().json(json!({
+                "error": "nonce is required"
+            }))));
+        }
+        None => return Ok(None),
+    };
+
+    let valid = consume_nonce(redis_client, config, user_id, nonce)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    if !valid {
+        return Ok(Some(HttpResponse::BadRequest().json(json!({
+            "error": "nonce is invalid, expired or already used"
+        }))));
+    }
+
+    Ok(None)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/auth/nonce",
+    responses(
+        (status = 200, description = "One-time nonce for a sensitive request", body = NonceResponse),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "auth"
+)]
+pub async fn get_nonce(
+    user: AuthenticatedUser,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let nonce = issue_nonce(&redis_client, &config, user.user_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(NonceResponse {
+        nonce,
+        expires_in: config.jwt.nonce_ttl_seconds,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/auth/password",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 200, description = "Password changed"),
+        (status = 400, description = "Missing, expired or reused nonce"),
+        (status = 401, description = "Unauthorized or wrong current password")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "auth"
+)]
+pub async fn change_password(
+    user: AuthenticatedUser,
+    req: web::Json<ChangePasswordRequest>,
+    pool: web::Data<DbPool>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    if let Some(resp) =
+        check_nonce(&redis_client, &config, user.user_id, req.nonce.as_deref()).await?
+    {
+        return Ok(resp);
+    }
+
+    let user_model = user::Entity::find_by_id(user.user_id)
+        .one(pool.get_ref())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not found"))?;
+
+    let valid = verify_password(&req.current_password, &user_model.password_hash)
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    if !valid {
+        return Ok(HttpResponse::Unauthorized().json(json!({
+            "error": "Current password is incorrect"
+        })));
+    }
+
+    let password_hash =
+        hash_password(&req.new_password).map_err(actix_web::error::ErrorInternalServerError)?;
+    let mut active: user::ActiveModel = user_model.into();
+    active.password_hash = sea_orm::Set(password_hash);
+    active
+        .update(pool.get_ref())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(json!({"message": "Password changed"})))
+}
@@ -0,0 +1,51 @@
+use crate::config::Config;
+use actix_web::{web, HttpRequest};
+use ipnet::IpNet;
+use std::net::IpAddr;
+
+/// Real client IP of a request.
+///
+/// When the socket peer is one of `TRUSTED_PROXIES`, `X-Forwarded-For` is read from
+/// the right, skipping further trusted proxies, and the first other address is the
+/// client. Otherwise the header may be forged and the peer itself is the client.
+/// `None` only when there is no peer address (e.g. in-process test requests).
+pub fn client_ip(req: &HttpRequest) -> Option<IpAddr> {
+    let peer = req.peer_addr()?.ip();
+    let trusted = req
+        .app_data::<web::Data<Config>>()
+        .map(|config| config.server.trusted_proxies.as_slice())
+        .unwrap_or_default();
+    let forwarded_for: Vec<&str> = req
+        .headers()
+        .get_all("X-Forwarded-For")
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    Some(resolve_client_ip(peer, &forwarded_for, trusted))
+}
+
+/// `client_ip` without the request, given the peer and the `X-Forwarded-For` values
+/// in the order they appear
+pub fn resolve_client_ip(peer: IpAddr, forwarded_for: &[&str], trusted: &[IpNet]) -> IpAddr {
+    let is_trusted = |ip: &IpAddr| trusted.iter().any(|network| network.contains(ip));
+    if !is_trusted(&peer) {
+        return peer;
+    }
+
+    let mut client = peer;
+    let hops = forwarded_for
+        .iter()
+        .flat_map(|value| value.split(','))
+        .rev()
+        .map(str::trim);
+    for hop in hops {
+        // A malformed entry is as far as the chain can be followed
+        let Ok(ip) = hop.parse::<IpAddr>() else {
+            break;
+        };
+        client = ip;
+        if !is_trusted(&ip) {
+            break;
+        }
+    }
+    client
+}
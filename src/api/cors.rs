@@ -0,0 +1,40 @@
+use crate::config::CorsConfig;
+use actix_cors::Cors;
+use actix_web::http::Method;
+
+/// Response headers set by API handlers that browser clients need to read
+const EXPOSED_HEADERS: [&str; 5] = [
+    "Link",
+    "X-Total-Count",
+    "X-Trending-Window",
+    "Content-Disposition",
+    "X-Refreshed-Token",
+];
+
+/// CORS middleware for one `/api` scope. Preflight `OPTIONS` requests are answered
+/// here, before routing, so they never reach auth extractors.
+///
+/// Allowed request headers are `CORS_ALLOWED_HEADERS` plus this scope's entry in
+/// `CORS_ROUTE_ALLOWED_HEADERS`. Config is checked by `Config::validate()`, since
+/// invalid origins or header names make the middleware panic at startup.
+pub fn cors(config: &CorsConfig, scope: &str) -> Cors {
+    let mut cors = Cors::default()
+        .allowed_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+        .allowed_headers(config.allowed_headers.iter().map(String::as_str))
+        .expose_headers(EXPOSED_HEADERS)
+        .max_age(config.max_age_seconds);
+
+    if let Some(headers) = config.route_allowed_headers.get(scope) {
+        cors = cors.allowed_headers(headers.iter().map(String::as_str));
+    }
+
+    if config.allowed_origins.iter().any(|origin| origin == "*") {
+        cors = cors.allow_any_origin();
+    } else {
+        for origin in &config.allowed_origins {
+            cors = cors.allowed_origin(origin);
+        }
+    }
+
+    cors
+}
@@ -1,27 +1,64 @@
+use crate::api::client_ip::client_ip;
+use crate::api::json::RequestJson;
+use crate::api::pagination::{insert_pagination_headers, mongo_total, PageInfo};
+use crate::api::request_id::RequestId;
+use crate::api::versioning::{insert_version_headers, versioned_feeds};
 use crate::auth::AuthenticatedUser;
-use crate::config::Config;
-use crate::db::DbPool;
-use crate::entities::{feed, feed_like};
+use crate::config::{ApiVersion, Config};
+use crate::db::{id_filter, mongo_collection};
+use crate::entities::feed;
 use crate::kafka::{
     FeedCommentedEvent, FeedCreatedEvent, FeedLikedEvent, FeedViewedEvent, KafkaProducer,
 };
 use crate::models::{
-    Comment, CommentRequest, CommentResponse, CreateFeedRequest, FeedResponse, FeedView,
+    BatchResult, Comment, CommentPage, CommentRequest, CommentResponse, CommentTreeNode,
+    CommentTreeResponse, CreateFeedRequest, FeedBatchRequest, FeedCategoriesResponse, FeedEdit,
+    FeedPreviewRequest, FeedPreviewResponse, FeedReport, FeedReportResponse, FeedResponse,
+    FeedRevision, FeedView, LikedStatus, LikedStatusRequest, LikedStatusResult, MentionPreview,
+    ReactRequest, ReactionType, ReportFeedRequest, ReportStatus, UnseenCountResponse,
+    UpdateFeedRequest, VersionedFeed,
 };
-use actix_web::{web, HttpResponse, Result as ActixResult};
-use chrono::Utc;
+use crate::repositories::{
+    is_unique_violation, BookmarkRepository, FeedChanges, FeedRepository, LikeRepository,
+    ReactionRepository, UserRepository,
+};
+use crate::services::content;
+use crate::services::feed_cache::{self, FeedSnapshot};
+use crate::services::feed_marker;
+use crate::services::rate_limit::{
+    acquire_cooldown, comment_cooldown_key, ip_bucket_key, release_cooldown, take_token,
+    token_bucket_key,
+};
+use crate::services::search::SearchBackend;
+use actix_web::{web, HttpRequest, HttpResponse, Result as ActixResult};
+use chrono::{DateTime, Duration, Utc};
+use futures::stream::{self, StreamExt, TryStreamExt};
+use mongodb::bson::{oid::ObjectId, Bson};
 use mongodb::Database as MongoDatabase;
-use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use redis::Client as RedisClient;
 use serde::Deserialize;
 use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+/// Maximum number of feed IDs accepted by `liked-status` and `batch`
+const MAX_BATCH_FEED_IDS: usize = 100;
+
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct FeedQuery {
     #[schema(example = 1)]
     pub page: Option<u64>,
     #[schema(example = 20)]
     pub limit: Option<u64>,
+    /// Only feeds detected as this ISO 639-3 language
+    #[schema(example = "eng")]
+    pub lang: Option<String>,
+    /// Only feeds filed under this category
+    #[schema(example = "news")]
+    pub category: Option<String>,
+    /// Only this author's feeds, with their pinned feeds first
+    #[schema(example = 1)]
+    pub user_id: Option<i64>,
 }
 
 #[utoipa::path(
@@ -29,7 +66,9 @@ pub struct FeedQuery {
     path = "/api/feed",
     request_body = CreateFeedRequest,
     responses(
-        (status = 200, description = "Feed created successfully", body = FeedResponse),
+        (status = 200, description = "Feed created successfully; FeedResponseV2 when v2 is negotiated", body = FeedResponse),
+        (status = 406, description = "Accept names only unsupported API versions"),
+        (status = 400, description = "Empty or too long content, expires_at not in the future or past FEED_EXPIRY_MAX_LIFETIME_HOURS, a category not in CONTENT_FEED_CATEGORIES, or an unknown field with SERVER_STRICT_JSON"),
         (status = 401, description = "Unauthorized")
     ),
     security(
@@ -37,45 +76,443 @@ pub struct FeedQuery {
     ),
     tag = "feed"
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn create_feed(
-    req: web::Json<CreateFeedRequest>,
+    req: RequestJson<CreateFeedRequest>,
     user: AuthenticatedUser,
-    pool: web::Data<DbPool>,
-    _config: web::Data<Config>,
+    version: ApiVersion,
+    feeds: web::Data<dyn FeedRepository>,
+    users: web::Data<dyn UserRepository>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
     kafka_producer: web::Data<KafkaProducer>,
+    request_id: RequestId,
 ) -> ActixResult<HttpResponse> {
     let user_id = user.user_id;
 
-    // Create feed using SeaORM
-    let new_feed = feed::ActiveModel {
-        user_id: sea_orm::Set(user_id),
-        content: sea_orm::Set(req.content.clone()),
-        ..Default::default()
+    if let Err(e) = content::validate_feed_content(&req.content)
+        .and_then(|_| content::validate_mentions(&req.content, &config))
+    {
+        return Ok(HttpResponse::BadRequest().json(json!({"error": e})));
+    }
+    if let Err(e) = validate_expiry(&req, &config, Utc::now()) {
+        return Ok(HttpResponse::BadRequest().json(json!({"error": e})));
+    }
+    let category = match req
+        .category
+        .as_deref()
+        .map(|category| content::validate_category(category, &config))
+        .transpose()
+    {
+        Ok(category) => category,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(json!({"error": e}))),
+    };
+    let feed_content = content::sanitize(&req.content);
+    let language = if config.content.language_detection {
+        content::detect_language(&feed_content)
+    } else {
+        None
     };
 
-    let feed = feed::Entity::insert(new_feed)
-        .exec_with_returning(pool.get_ref())
+    // Feeds scheduled for the future stay unpublished until the scheduler picks them up
+    let is_published = req
+        .publish_at
+        .is_none_or(|publish_at| publish_at <= Utc::now());
+
+    let feed = feeds
+        .create(
+            user_id,
+            &feed_content,
+            language.as_deref(),
+            category.as_deref(),
+            req.publish_at,
+            req.expires_at,
+            is_published,
+        )
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
-    let event = FeedCreatedEvent::new(feed.id as u64, user_id, req.content.clone());
-    if let Ok(event_json) = serde_json::to_string(&event) {
-        if let Err(e) = kafka_producer
-            .send_message("feed_events", &feed.id.to_string(), &event_json)
-            .await
-        {
-            log::warn!("Failed to send Kafka event: {:?}", e);
+    if is_published {
+        invalidate_feed_cache(&redis_client, &config).await;
+        let event = FeedCreatedEvent::new(feed.id as u64, user_id, feed_content.clone())
+            .with_request_id(request_id.as_str());
+        if let Ok(event_json) = serde_json::to_string(&event) {
+            if let Err(e) = kafka_producer
+                .send_message_with_retry("feed_events", &feed.id.to_string(), &event_json)
+                .await
+            {
+                log::warn!("Failed to send Kafka event: {:?}", e);
+            }
         }
     }
 
-    Ok(HttpResponse::Ok().json(FeedResponse {
+    let created = FeedResponse {
         id: feed.id,
         user_id,
-        content: req.content.clone(),
+        content: feed_content,
         like_count: 0,
         comment_count: 0,
+        counts_degraded: false,
+        reactions: HashMap::new(),
+        language,
+        category: feed.category,
         is_liked: false,
+        is_bookmarked: false,
+        is_pinned: false,
+        publish_at: feed.publish_at,
+        expires_at: feed.expires_at,
         created_at: feed.created_at,
+        updated_at: None,
+    };
+    let mut response = HttpResponse::Ok();
+    insert_version_headers(&mut response, version);
+    let mut versioned = versioned_feeds(version, vec![created], users.get_ref()).await?;
+    Ok(response.json(versioned.remove(0)))
+}
+
+/// `expires_at` must come after the feed goes live (`publish_at`, or now) and, with
+/// `FEED_EXPIRY_MAX_LIFETIME_HOURS`, at most that many hours after it
+fn validate_expiry(
+    req: &CreateFeedRequest,
+    config: &Config,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let Some(expires_at) = req.expires_at else {
+        return Ok(());
+    };
+    let live_at = req.publish_at.map_or(now, |publish_at| publish_at.max(now));
+    if expires_at <= live_at {
+        return Err(
+            if req.publish_at.is_some_and(|publish_at| publish_at > now) {
+                "expires_at must be after publish_at".to_string()
+            } else {
+                "expires_at must be in the future".to_string()
+            },
+        );
+    }
+    let max_hours = config.feed_expiry.max_lifetime_hours;
+    if max_hours > 0 && expires_at - live_at > Duration::hours(max_hours as i64) {
+        return Err(format!(
+            "expires_at must be at most {} hours after the feed goes live",
+            max_hours
+        ));
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    patch,
+    path = "/api/feed/{feed_id}",
+    request_body = UpdateFeedRequest,
+    responses(
+        (status = 200, description = "Feed with only the given fields changed; FeedResponseV2 when v2 is negotiated", body = FeedResponse),
+        (status = 406, description = "Accept names only unsupported API versions"),
+        (status = 400, description = "No fields, null content, invalid content, or rescheduling a published feed"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the author of the feed"),
+        (status = 404, description = "Feed not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn update_feed(
+    path: web::Path<i64>,
+    req: web::Json<UpdateFeedRequest>,
+    user: AuthenticatedUser,
+    version: ApiVersion,
+    feeds: web::Data<dyn FeedRepository>,
+    users: web::Data<dyn UserRepository>,
+    likes: web::Data<dyn LikeRepository>,
+    reactions: web::Data<dyn ReactionRepository>,
+    bookmarks: web::Data<dyn BookmarkRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+    kafka_producer: web::Data<KafkaProducer>,
+    request_id: RequestId,
+) -> ActixResult<HttpResponse> {
+    let user_id = user.user_id;
+    let feed_id = path.into_inner();
+
+    if req.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(json!({"error": "No fields to update"})));
+    }
+
+    let existing = feeds
+        .find_by_id(feed_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let existing = match existing {
+        Some(feed) => feed,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({"error": "Feed not found"})));
+        }
+    };
+    if existing.user_id != user_id {
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "error": "You can only edit your own feeds"
+        })));
+    }
+
+    let mut changes = FeedChanges::default();
+    match &req.content {
+        Some(None) => {
+            return Ok(
+                HttpResponse::BadRequest().json(json!({"error": "content must not be null"}))
+            );
+        }
+        Some(Some(new_content)) => {
+            if let Err(e) = content::validate_feed_content(new_content)
+                .and_then(|_| content::validate_mentions(new_content, &config))
+            {
+                return Ok(HttpResponse::BadRequest().json(json!({"error": e})));
+            }
+            let new_content = content::sanitize(new_content);
+            changes.language = Some(if config.content.language_detection {
+                content::detect_language(&new_content)
+            } else {
+                None
+            });
+            changes.content = Some(new_content);
+        }
+        None => {}
+    }
+
+    // Only scheduled feeds can move; a published one has already been announced
+    let mut publish_now = false;
+    if let Some(publish_at) = req.publish_at {
+        if existing.is_published {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "error": "Feed is already published"
+            })));
+        }
+        publish_now = publish_at.is_none_or(|publish_at| publish_at <= Utc::now());
+        changes.publish_at = Some(publish_at);
+        if publish_now {
+            changes.is_published = Some(true);
+        }
+    }
+
+    let content_changed = changes
+        .content
+        .as_ref()
+        .is_some_and(|new_content| *new_content != existing.content);
+    let updated = feeds
+        .update(feed_id, changes)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    invalidate_feed_cache(&redis_client, &config).await;
+    if content_changed && config.feed_history.enabled {
+        record_feed_edit(&mongo_db, &config, &existing).await;
+    }
+
+    if publish_now {
+        let event = FeedCreatedEvent::new(feed_id as u64, user_id, updated.content.clone())
+            .with_request_id(request_id.as_str());
+        if let Ok(event_json) = serde_json::to_string(&event) {
+            if let Err(e) = kafka_producer
+                .send_message_with_retry("feed_events", &feed_id.to_string(), &event_json)
+                .await
+            {
+                log::warn!("Failed to send Kafka event: {:?}", e);
+            }
+        }
+    }
+
+    let responses = feed_responses(
+        vec![updated],
+        Some(user_id),
+        likes.get_ref(),
+        reactions.get_ref(),
+        bookmarks.get_ref(),
+        &mongo_db,
+        &config,
+    )
+    .await?;
+    let mut response = HttpResponse::Ok();
+    insert_version_headers(&mut response, version);
+    let mut versioned = versioned_feeds(version, responses, users.get_ref()).await?;
+    Ok(response.json(versioned.remove(0)))
+}
+
+/// Keep the content `feed` had before an edit in `feed_edits`, then drop the revisions past
+/// `FEED_HISTORY_MAX_REVISIONS`. The edit itself is already saved, so failures are only
+/// logged.
+async fn record_feed_edit(mongo_db: &MongoDatabase, config: &Config, feed: &feed::Model) {
+    let collection = mongo_collection::<FeedEdit>(mongo_db, config, "feed_edits");
+    let edit = FeedEdit {
+        id: Some(Uuid::new_v4().to_string()),
+        feed_id: feed.id,
+        user_id: feed.user_id,
+        content: feed.content.clone(),
+        edited_at: Utc::now(),
+    };
+    if let Err(e) = collection.insert_one(&edit, None).await {
+        log::warn!("Failed to record edit of feed {}: {:?}", feed.id, e);
+        return;
+    }
+
+    let options = mongodb::options::FindOptions::builder()
+        .sort(mongodb::bson::doc! {"edited_at": -1, "_id": -1})
+        .skip(config.feed_history.max_revisions)
+        .build();
+    let stale_ids: Result<Vec<String>, _> = match collection
+        .find(mongodb::bson::doc! {"feed_id": feed.id}, options)
+        .await
+    {
+        Ok(cursor) => {
+            cursor
+                .map_ok(|edit| edit.id.unwrap_or_default())
+                .try_collect()
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    let trimmed = match stale_ids {
+        Ok(ids) if ids.is_empty() => Ok(()),
+        Ok(ids) => collection
+            .delete_many(mongodb::bson::doc! {"_id": {"$in": ids}}, None)
+            .await
+            .map(|_| ()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = trimmed {
+        log::warn!("Failed to trim edit history of feed {}: {:?}", feed.id, e);
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/feed/{feed_id}/history",
+    params(
+        ("feed_id" = i64, Path, description = "Feed ID")
+    ),
+    responses(
+        (status = 200, description = "Previous contents of the feed, newest first, at most FEED_HISTORY_MAX_REVISIONS", body = Vec<FeedRevision>),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the author of the feed"),
+        (status = 404, description = "Feed not found, or history is disabled")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+pub async fn get_feed_history(
+    path: web::Path<i64>,
+    user: AuthenticatedUser,
+    feeds: web::Data<dyn FeedRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    if !config.feed_history.enabled {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "Feed history is disabled"
+        })));
+    }
+    let feed_id = path.into_inner();
+
+    let existing = feeds
+        .find_by_id(feed_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let Some(existing) = existing else {
+        return Ok(HttpResponse::NotFound().json(json!({"error": "Feed not found"})));
+    };
+    if existing.user_id != user.user_id {
+        return Ok(HttpResponse::Forbidden().json(json!({
+            "error": "You can only view the history of your own feeds"
+        })));
+    }
+
+    let options = mongodb::options::FindOptions::builder()
+        .sort(mongodb::bson::doc! {"edited_at": -1, "_id": -1})
+        .limit(config.feed_history.max_revisions as i64)
+        .build();
+    let revisions: Vec<FeedRevision> =
+        mongo_collection::<FeedEdit>(&mongo_db, &config, "feed_edits")
+            .find(mongodb::bson::doc! {"feed_id": feed_id}, options)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?
+            .map_ok(FeedRevision::from)
+            .try_collect()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Ok().json(revisions))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/feed/preview",
+    request_body = FeedPreviewRequest,
+    responses(
+        (status = 200, description = "Parsed content; nothing is stored", body = FeedPreviewResponse),
+        (status = 400, description = "Empty or too long content"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+pub async fn preview_feed(
+    req: web::Json<FeedPreviewRequest>,
+    _user: AuthenticatedUser,
+    users: web::Data<dyn UserRepository>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    if let Err(e) = content::validate_feed_content(&req.content)
+        .and_then(|_| content::validate_mentions(&req.content, &config))
+    {
+        return Ok(HttpResponse::BadRequest().json(json!({"error": e})));
+    }
+    let feed_content = content::sanitize(&req.content);
+    let usernames = &config.usernames;
+
+    // Mentions past CONTENT_MAX_MENTIONS are not resolved and render as plain text
+    let (mentioned, _) =
+        content::capped_mentions(&feed_content, config.content.max_mentions, |name| {
+            usernames.normalize(name)
+        });
+    let mut known = users
+        .find_by_usernames(&mentioned)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    // MySQL compares case-insensitively; narrow down to the configured matching
+    known.retain(|u| {
+        mentioned
+            .iter()
+            .any(|name| usernames.matches(name, &u.username))
+    });
+    // Keep the order mentions appear in
+    known.sort_by_key(|u| {
+        mentioned
+            .iter()
+            .position(|name| usernames.matches(name, &u.username))
+    });
+
+    let user_id = |name: &str| {
+        known
+            .iter()
+            .find(|u| usernames.matches(name, &u.username))
+            .map(|u| u.id)
+    };
+
+    Ok(HttpResponse::Ok().json(FeedPreviewResponse {
+        html: content::render_html(&feed_content, user_id),
+        hashtags: content::hashtags(&feed_content),
+        mentions: known
+            .into_iter()
+            .map(|u| MentionPreview {
+                user_id: u.id,
+                username: u.username,
+            })
+            .collect(),
+        content: feed_content,
     }))
 }
 
@@ -84,180 +521,1101 @@ pub async fn create_feed(
     path = "/api/feed",
     params(
         ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
-        ("limit" = Option<u64>, Query, description = "Items per page (default: 20)")
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 20)"),
+        ("lang" = Option<String>, Query, description = "Only feeds detected as this ISO 639-3 language, e.g. eng"),
+        ("category" = Option<String>, Query, description = "Only feeds filed under this category (case-insensitive)"),
+        ("user_id" = Option<i64>, Query, description = "Only this author's feeds; their pinned feeds come first")
     ),
     responses(
-        (status = 200, description = "List of feeds", body = Vec<FeedResponse>)
+        (status = 200, description = "List of feeds; Link and X-Total-Count headers when enabled; FeedResponseV2 items when v2 is negotiated", body = Vec<FeedResponse>),
+        (status = 406, description = "Accept names only unsupported API versions"),
+        (status = 400, description = "offset + limit is beyond the caller's SERVER_FEED_MAX_DEPTH_* cap, page * limit overflows, or a category not in CONTENT_FEED_CATEGORIES"),
+        (status = 503, description = "Comment counts unavailable and MONGODB_DEGRADE_COUNTS is off")
     ),
     tag = "feed"
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_feeds(
+    http_req: HttpRequest,
     user: Option<AuthenticatedUser>,
-    pool: web::Data<DbPool>,
+    version: ApiVersion,
+    feeds: web::Data<dyn FeedRepository>,
+    users: web::Data<dyn UserRepository>,
+    likes: web::Data<dyn LikeRepository>,
+    reactions: web::Data<dyn ReactionRepository>,
+    bookmarks: web::Data<dyn BookmarkRepository>,
     mongo_db: web::Data<MongoDatabase>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
     query: web::Query<FeedQuery>,
 ) -> ActixResult<HttpResponse> {
     let user_id = user.map(|u| u.user_id);
 
-    let page = query.page.unwrap_or(1);
+    let page = query.page.unwrap_or(1).max(1);
     let limit = query.limit.unwrap_or(20);
-    let offset = (page - 1) * limit;
-
-    // Get feeds using SeaORM
-    let feeds = feed::Entity::find()
-        .order_by_desc(feed::Column::CreatedAt)
-        .limit(limit)
-        .offset(offset)
-        .all(pool.get_ref())
-        .await
-        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let Some(offset) = (page - 1).checked_mul(limit) else {
+        return Ok(HttpResponse::BadRequest().json(json!({"error": "page is out of range"})));
+    };
 
-    let mut feed_responses = Vec::new();
-    for feed in feeds {
-        let feed_id = feed.id;
+    // Deep pages are capped so anonymous clients cannot scrape the whole table
+    let max_depth = match user_id {
+        Some(_) => config.server.feed_max_depth_authenticated,
+        None => config.server.feed_max_depth_anonymous,
+    };
+    let max_depth = (max_depth > 0).then_some(max_depth);
+    if let Some(max_depth) = max_depth {
+        if offset.saturating_add(limit) > max_depth {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "error": format!("Feeds can only be paged up to {} items deep", max_depth),
+                "max_depth": max_depth
+            })));
+        }
+    }
 
-        // Count likes using SeaORM
-        let like_count = feed_like::Entity::find()
-            .filter(feed_like::Column::FeedId.eq(feed_id))
-            .all(pool.get_ref())
-            .await
-            .unwrap_or_default()
-            .len() as i64;
+    let language = query
+        .lang
+        .as_deref()
+        .map(|l| l.trim().to_lowercase())
+        .filter(|l| !l.is_empty());
+    // Same check as create_feed, so only configured categories reach queries and cache keys
+    let category = match query
+        .category
+        .as_deref()
+        .filter(|c| !c.trim().is_empty())
+        .map(|c| content::validate_category(c, &config))
+        .transpose()
+    {
+        Ok(category) => category,
+        Err(e) => return Ok(HttpResponse::BadRequest().json(json!({"error": e}))),
+    };
 
-        let comment_count = {
-            let collection = mongo_db.collection::<Comment>("comments");
-            let filter = mongodb::bson::doc! {"feed_id": feed_id};
-            collection.count_documents(filter, None).await.unwrap_or(0) as i64
-        };
+    let author_id = query.user_id;
 
-        let is_liked = if let Some(uid) = user_id {
-            feed_like::Entity::find()
-                .filter(
-                    Condition::all()
-                        .add(feed_like::Column::FeedId.eq(feed_id))
-                        .add(feed_like::Column::UserId.eq(uid)),
-                )
-                .one(pool.get_ref())
-                .await
-                .unwrap_or(None)
-                .is_some()
+    // Anonymous first pages look the same to everyone, so they can come from Redis
+    let cache_key =
+        if config.feed_cache.enabled && user_id.is_none() && author_id.is_none() && page == 1 {
+            feed_cache::snapshot_key(
+                &redis_client,
+                &config,
+                page,
+                limit,
+                language.as_deref(),
+                category.as_deref(),
+            )
+            .await
+            .map_err(|e| log::warn!("Feed cache unavailable: {:?}", e))
+            .ok()
         } else {
-            false
+            None
         };
+    let cached = match &cache_key {
+        Some(key) => feed_cache::load_snapshot(&redis_client, key)
+            .await
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to read feed cache: {:?}", e);
+                None
+            }),
+        None => None,
+    };
 
-        feed_responses.push(FeedResponse {
-            id: feed_id,
-            user_id: feed.user_id,
-            content: feed.content,
-            like_count,
-            comment_count,
-            is_liked,
-            created_at: feed.created_at,
-        });
+    let (total, feed_responses) = match cached {
+        Some(snapshot) => (snapshot.total, snapshot.feeds),
+        None => {
+            // Only counted when the X-Total-Count header will be sent
+            let total = if config.server.pagination_headers {
+                let total = match author_id {
+                    Some(author_id) => {
+                        feeds
+                            .count_visible_by_user(
+                                author_id,
+                                language.as_deref(),
+                                category.as_deref(),
+                            )
+                            .await
+                    }
+                    None => {
+                        feeds
+                            .count_visible(language.as_deref(), category.as_deref())
+                            .await
+                    }
+                };
+                Some(total.map_err(actix_web::error::ErrorInternalServerError)?)
+            } else {
+                None
+            };
+            let page_feeds = match author_id {
+                Some(author_id) => {
+                    feeds
+                        .list_visible_by_user(
+                            author_id,
+                            language.as_deref(),
+                            category.as_deref(),
+                            offset,
+                            limit,
+                        )
+                        .await
+                }
+                None => {
+                    feeds
+                        .list_visible(language.as_deref(), category.as_deref(), offset, limit)
+                        .await
+                }
+            }
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+
+            let feed_responses = feed_responses(
+                page_feeds,
+                user_id,
+                likes.get_ref(),
+                reactions.get_ref(),
+                bookmarks.get_ref(),
+                &mongo_db,
+                &config,
+            )
+            .await?;
+
+            let snapshot = FeedSnapshot {
+                total,
+                feeds: feed_responses,
+            };
+            // Placeholder comment counts must not outlive the MongoDB outage
+            if let Some(key) = &cache_key {
+                if !snapshot.feeds.iter().any(|feed| feed.counts_degraded) {
+                    if let Err(e) =
+                        feed_cache::store_snapshot(&redis_client, &config, key, &snapshot).await
+                    {
+                        log::warn!("Failed to write feed cache: {:?}", e);
+                    }
+                }
+            }
+            (snapshot.total, snapshot.feeds)
+        }
+    };
+
+    // The unfiltered first page is the timeline the unseen count is measured against
+    if let Some(user_id) = user_id.filter(|_| {
+        config.feed_marker.enabled
+            && page == 1
+            && author_id.is_none()
+            && language.is_none()
+            && category.is_none()
+    }) {
+        if let Some(newest) = feed_responses.iter().map(|feed| feed.id).max() {
+            if let Err(e) =
+                feed_marker::advance_marker(&redis_client, &config, user_id, newest).await
+            {
+                log::warn!("Failed to update feed marker for user {}: {:?}", user_id, e);
+            }
+        }
     }
 
-    Ok(HttpResponse::Ok().json(feed_responses))
+    let mut response = HttpResponse::Ok();
+    insert_pagination_headers(
+        &mut response,
+        &http_req,
+        &config,
+        &PageInfo {
+            page,
+            limit,
+            total,
+            returned: feed_responses.len(),
+            max_depth,
+        },
+    );
+    insert_version_headers(&mut response, version);
+    Ok(response.json(versioned_feeds(version, feed_responses, users.get_ref()).await?))
 }
 
 #[utoipa::path(
-    post,
-    path = "/api/feed/{feed_id}/like",
+    get,
+    path = "/api/feed/categories",
     responses(
-        (status = 200, description = "Feed liked successfully"),
-        (status = 401, description = "Unauthorized")
+        (status = 200, description = "Categories a feed can be created with (CONTENT_FEED_CATEGORIES)", body = FeedCategoriesResponse)
     ),
-    security(
-        ("bearer_auth" = [])
+    tag = "feed"
+)]
+pub async fn get_feed_categories(config: web::Data<Config>) -> ActixResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(FeedCategoriesResponse {
+        categories: config.content.feed_categories.clone(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/feed/{feed_id}",
+    params(
+        ("feed_id" = i64, Path, description = "Feed ID")
+    ),
+    responses(
+        (status = 200, description = "The feed; FeedResponseV2 when v2 is negotiated", body = FeedResponse),
+        (status = 406, description = "Accept names only unsupported API versions"),
+        (status = 404, description = "Feed not found, hidden, or scheduled by someone else"),
+        (status = 410, description = "Feed has expired"),
+        (status = 503, description = "Comment counts unavailable and MONGODB_DEGRADE_COUNTS is off")
     ),
     tag = "feed"
 )]
-pub async fn like_feed(
+#[allow(clippy::too_many_arguments)]
+pub async fn get_feed(
     path: web::Path<i64>,
-    user: AuthenticatedUser,
-    pool: web::Data<DbPool>,
-    kafka_producer: web::Data<KafkaProducer>,
+    user: Option<AuthenticatedUser>,
+    version: ApiVersion,
+    feeds: web::Data<dyn FeedRepository>,
+    users: web::Data<dyn UserRepository>,
+    likes: web::Data<dyn LikeRepository>,
+    reactions: web::Data<dyn ReactionRepository>,
+    bookmarks: web::Data<dyn BookmarkRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
 ) -> ActixResult<HttpResponse> {
-    let user_id = user.user_id;
     let feed_id = path.into_inner();
+    let user_id = user.map(|u| u.user_id);
+    let now = Utc::now();
 
-    // Check if already liked
-    let existing = feed_like::Entity::find()
-        .filter(
-            Condition::all()
-                .add(feed_like::Column::FeedId.eq(feed_id))
-                .add(feed_like::Column::UserId.eq(user_id)),
-        )
-        .one(pool.get_ref())
+    let feed = feeds
+        .find_by_id(feed_id)
         .await
-        .map_err(|e| {
-            log::error!("Database error checking existing like: {:?}", e);
-            actix_web::error::ErrorInternalServerError("Database error")
-        })?;
-
-    if existing.is_some() {
-        return Ok(HttpResponse::Ok().json(json!({"message": "Already liked"})));
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .filter(|f| f.hidden_at.is_none() && (f.is_live(now) || Some(f.user_id) == user_id));
+    let Some(feed) = feed else {
+        return Ok(HttpResponse::NotFound().json(json!({"error": "Feed not found"})));
+    };
+    // Gone rather than not found, so clients can tell an expired story from a bad link
+    if feed.has_expired(now) {
+        return Ok(feed_expired_response(&feed));
     }
 
-    // Verify feed exists
-    let feed_exists = feed::Entity::find_by_id(feed_id)
-        .one(pool.get_ref())
-        .await
-        .map_err(|e| {
-            log::error!("Database error checking feed existence: {:?}", e);
-            actix_web::error::ErrorInternalServerError("Database error")
-        })?;
+    let responses = feed_responses(
+        vec![feed],
+        user_id,
+        likes.get_ref(),
+        reactions.get_ref(),
+        bookmarks.get_ref(),
+        &mongo_db,
+        &config,
+    )
+    .await?;
+    let mut response = HttpResponse::Ok();
+    insert_version_headers(&mut response, version);
+    let mut versioned = versioned_feeds(version, responses, users.get_ref()).await?;
+    Ok(response.json(versioned.remove(0)))
+}
 
-    if feed_exists.is_none() {
+#[utoipa::path(
+    get,
+    path = "/api/feed/unseen-count",
+    responses(
+        (status = 200, description = "Feeds posted since the caller last loaded the first timeline page", body = UnseenCountResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "FEED_MARKER_ENABLED is not set"),
+        (status = 503, description = "Redis is unavailable")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+pub async fn get_unseen_count(
+    user: AuthenticatedUser,
+    feeds: web::Data<dyn FeedRepository>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    if !config.feed_marker.enabled {
         return Ok(HttpResponse::NotFound().json(json!({
-            "error": "Feed not found"
+            "error": "Not found"
         })));
     }
 
-    // Create like using SeaORM
-    let new_like = feed_like::ActiveModel {
-        feed_id: sea_orm::Set(feed_id),
-        user_id: sea_orm::Set(user_id),
-        ..Default::default()
-    };
-
-    match feed_like::Entity::insert(new_like)
-        .exec(pool.get_ref())
-        .await
-    {
+    let marker = match feed_marker::load_marker(&redis_client, &config, user.user_id).await {
+        Ok(marker) => marker,
+        Err(e) => {
+            log::error!("Failed to read feed marker: {:?}", e);
+            return Ok(HttpResponse::ServiceUnavailable().json(json!({
+                "error": "Redis is unavailable"
+            })));
+        }
+    };
+    // Before the first visit everything would count as new, which is not useful for a badge
+    let count = match marker {
+        Some(marker) => feeds
+            .count_visible_after(marker, user.user_id)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?,
+        None => 0,
+    };
+
+    Ok(HttpResponse::Ok().json(UnseenCountResponse {
+        count,
+        last_seen_feed_id: marker,
+    }))
+}
+
+/// Feeds with their like, comment and reaction counts, and whether `user_id` liked them
+pub(crate) async fn feed_responses(
+    page_feeds: Vec<feed::Model>,
+    user_id: Option<i64>,
+    likes: &dyn LikeRepository,
+    reactions: &dyn ReactionRepository,
+    bookmarks: &dyn BookmarkRepository,
+    mongo_db: &MongoDatabase,
+    config: &Config,
+) -> ActixResult<Vec<FeedResponse>> {
+    // One query for the whole page rather than one per feed
+    let bookmarked: HashSet<i64> = match user_id {
+        Some(uid) => {
+            let feed_ids: Vec<i64> = page_feeds.iter().map(|f| f.id).collect();
+            bookmarks
+                .bookmarked_feed_ids(uid, &feed_ids)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?
+                .into_iter()
+                .collect()
+        }
+        None => HashSet::new(),
+    };
+
+    let mut feed_responses = Vec::new();
+    for feed in page_feeds {
+        let feed_id = feed.id;
+
+        let like_count = if config.like_counts.denormalized {
+            feed.like_count
+        } else {
+            likes.count_for_feed(feed_id).await.unwrap_or_default() as i64
+        };
+
+        // A MongoDB error must not look like a feed without comments
+        let collection = mongo_db.collection::<Comment>("comments");
+        let filter = mongodb::bson::doc! {"feed_id": feed_id};
+        let (comment_count, counts_degraded) = match collection.count_documents(filter, None).await
+        {
+            Ok(count) => (count as i64, false),
+            Err(e) if config.mongodb.degrade_counts => {
+                log::warn!("Failed to count comments of feed {}: {:?}", feed_id, e);
+                (0, true)
+            }
+            Err(e) => {
+                log::error!("Failed to count comments of feed {}: {:?}", feed_id, e);
+                return Err(actix_web::error::ErrorServiceUnavailable(
+                    "Comment counts are unavailable",
+                ));
+            }
+        };
+
+        let is_liked = if let Some(uid) = user_id {
+            likes.find(feed_id, uid).await.unwrap_or(None).is_some()
+        } else {
+            false
+        };
+
+        let reactions = reaction_counts(reactions, feed_id, like_count).await;
+
+        feed_responses.push(FeedResponse {
+            id: feed_id,
+            user_id: feed.user_id,
+            content: feed.content,
+            like_count,
+            comment_count,
+            counts_degraded,
+            reactions,
+            language: feed.language,
+            category: feed.category,
+            is_liked,
+            is_bookmarked: bookmarked.contains(&feed_id),
+            is_pinned: feed.pinned_at.is_some(),
+            publish_at: feed.publish_at,
+            expires_at: feed.expires_at,
+            created_at: feed.created_at,
+            // Both columns default to the insert time, so they differ once the row changed
+            updated_at: (feed.updated_at > feed.created_at).then_some(feed.updated_at),
+        });
+    }
+    Ok(feed_responses)
+}
+
+const MAX_SEARCH_LIMIT: u64 = 50;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct FeedSearchQuery {
+    /// Text to look for in feed content
+    #[schema(example = "rust")]
+    pub q: String,
+    #[schema(example = 1)]
+    pub page: Option<u64>,
+    #[schema(example = 20)]
+    pub limit: Option<u64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/feed/search",
+    params(
+        ("q" = String, Query, description = "Text to look for in feed content"),
+        ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 20, max: 50)")
+    ),
+    responses(
+        (status = 200, description = "Matching published feeds, in the search backend's order; FeedResponseV2 items when v2 is negotiated", body = Vec<FeedResponse>),
+        (status = 406, description = "Accept names only unsupported API versions"),
+        (status = 400, description = "Empty query"),
+        (status = 503, description = "Search backend or comment counts unavailable")
+    ),
+    tag = "feed"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn search_feeds(
+    user: Option<AuthenticatedUser>,
+    version: ApiVersion,
+    search: web::Data<dyn SearchBackend>,
+    feeds: web::Data<dyn FeedRepository>,
+    users: web::Data<dyn UserRepository>,
+    likes: web::Data<dyn LikeRepository>,
+    reactions: web::Data<dyn ReactionRepository>,
+    bookmarks: web::Data<dyn BookmarkRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
+    query: web::Query<FeedSearchQuery>,
+) -> ActixResult<HttpResponse> {
+    let text = query.q.trim();
+    if text.is_empty() {
+        return Ok(HttpResponse::BadRequest().json(json!({"error": "q must not be empty"})));
+    }
+
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).min(MAX_SEARCH_LIMIT);
+    let feed_ids = match search.search_feeds(text, (page - 1) * limit, limit).await {
+        Ok(feed_ids) => feed_ids,
+        Err(e) => {
+            log::error!("Feed search failed: {:?}", e);
+            return Ok(
+                HttpResponse::ServiceUnavailable().json(json!({"error": "Search is unavailable"}))
+            );
+        }
+    };
+
+    let found = feeds
+        .find_by_ids(&feed_ids)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let responses = feed_responses(
+        found,
+        user.map(|u| u.user_id),
+        likes.get_ref(),
+        reactions.get_ref(),
+        bookmarks.get_ref(),
+        &mongo_db,
+        &config,
+    )
+    .await?;
+    let mut response = HttpResponse::Ok();
+    insert_version_headers(&mut response, version);
+    Ok(response.json(versioned_feeds(version, responses, users.get_ref()).await?))
+}
+
+/// Per-type reaction counts of a feed, with `like` taken from `feed_likes`
+async fn reaction_counts(
+    reactions: &dyn ReactionRepository,
+    feed_id: i64,
+    like_count: i64,
+) -> HashMap<String, i64> {
+    let mut counts: HashMap<String, i64> = reactions
+        .counts_for_feed(feed_id)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(reaction, count)| (reaction.as_str().to_string(), count as i64))
+        .collect();
+    if like_count > 0 {
+        counts.insert(ReactionType::Like.as_str().to_string(), like_count);
+    }
+    counts
+}
+
+/// Drop cached `GET /api/feed` pages after a write that changes them.
+/// Failures are only logged; the snapshots then expire after `FEED_CACHE_TTL_SECONDS`.
+pub(crate) async fn invalidate_feed_cache(redis_client: &RedisClient, config: &Config) {
+    if !config.feed_cache.enabled {
+        return;
+    }
+    if let Err(e) = feed_cache::invalidate(redis_client, config).await {
+        log::warn!("Failed to invalidate feed cache: {:?}", e);
+    }
+}
+
+/// Likes and unlikes share one token bucket per user, so toggling a like to move
+/// rankings is limited too. Returns the 429 response to send when out of tokens.
+async fn check_like_rate(
+    redis_client: &RedisClient,
+    config: &Config,
+    user_id: i64,
+) -> Option<HttpResponse> {
+    match take_token(
+        redis_client,
+        config,
+        &token_bucket_key("like", user_id),
+        config.rate_limit.like_burst,
+        config.rate_limit.like_per_minute,
+    )
+    .await
+    {
+        Ok(None) => None,
+        Ok(Some(retry_after)) => Some(
+            HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", retry_after.to_string()))
+                .json(json!({
+                    "error": "You are liking too fast, please wait",
+                    "retry_after": retry_after
+                })),
+        ),
+        Err(e) => {
+            // Fail open: a Redis outage should not block liking
+            log::error!("Failed to check like rate limit: {:?}", e);
+            None
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/feed/{feed_id}/like",
+    responses(
+        (status = 200, description = "Feed liked successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Feed not found, hidden, or scheduled by someone else"),
+        (status = 409, description = "A concurrent like of the same feed won the race"),
+        (status = 410, description = "Feed has expired"),
+        (status = 429, description = "Too many like/unlike operations")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn like_feed(
+    path: web::Path<i64>,
+    user: AuthenticatedUser,
+    feeds: web::Data<dyn FeedRepository>,
+    likes: web::Data<dyn LikeRepository>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+    kafka_producer: web::Data<KafkaProducer>,
+    request_id: RequestId,
+) -> ActixResult<HttpResponse> {
+    let user_id = user.user_id;
+    let feed_id = path.into_inner();
+
+    if let Some(resp) = check_like_rate(&redis_client, &config, user_id).await {
+        return Ok(resp);
+    }
+
+    // Check if already liked
+    let existing = likes.find(feed_id, user_id).await.map_err(|e| {
+        log::error!("Database error checking existing like: {:?}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+
+    if existing.is_some() {
+        return Ok(HttpResponse::Ok().json(json!({"message": "Already liked"})));
+    }
+
+    // Verify feed exists; hidden feeds cannot be liked back into the rankings
+    let feed = match live_feed(feeds.get_ref(), feed_id, Some(user_id)).await? {
+        Ok(feed) => feed,
+        Err(resp) => return Ok(resp),
+    };
+    if let Some(resp) = reject_expired_feed(&feed) {
+        return Ok(resp);
+    }
+
+    match likes.insert(feed_id, user_id).await {
+        Ok(_) => {
+            invalidate_feed_cache(&redis_client, &config).await;
+            let event = FeedLikedEvent::new(feed_id, user_id).with_request_id(request_id.as_str());
+            if let Ok(event_json) = serde_json::to_string(&event) {
+                if let Err(e) = kafka_producer
+                    .send_message_with_retry("feed_events", &feed_id.to_string(), &event_json)
+                    .await
+                {
+                    log::warn!("Failed to send Kafka event: {:?}", e);
+                }
+            }
+
+            Ok(HttpResponse::Ok().json(json!({"message": "Feed liked"})))
+        }
+        // A concurrent like of the same feed won the race past the check above
+        Err(e) if is_unique_violation(&e) => Ok(HttpResponse::Conflict().json(json!({
+            "error": "Feed already liked"
+        }))),
+        Err(e) => {
+            log::error!("Database error inserting like: {:?}", e);
+            Ok(HttpResponse::BadRequest().json(json!({
+                "error": "Failed to like feed"
+            })))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/feed/{feed_id}/like",
+    responses(
+        (status = 200, description = "Feed unliked successfully"),
+        (status = 401, description = "Unauthorized"),
+        (status = 429, description = "Too many like/unlike operations")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+pub async fn unlike_feed(
+    path: web::Path<i64>,
+    user: AuthenticatedUser,
+    likes: web::Data<dyn LikeRepository>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let user_id = user.user_id;
+    let feed_id = path.into_inner();
+
+    if let Some(resp) = check_like_rate(&redis_client, &config, user_id).await {
+        return Ok(resp);
+    }
+
+    let result = likes.delete(feed_id, user_id).await;
+
+    match result {
+        Ok(_) => {
+            invalidate_feed_cache(&redis_client, &config).await;
+            Ok(HttpResponse::Ok().json(json!({"message": "Feed unliked"})))
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to unlike feed"
+            })))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/feed/{feed_id}/bookmark",
+    params(
+        ("feed_id" = i64, Path, description = "Feed ID")
+    ),
+    responses(
+        (status = 200, description = "Feed bookmarked (or already bookmarked)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Feed not found, hidden, or scheduled by someone else"),
+        (status = 409, description = "A concurrent bookmark of the same feed won the race")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+pub async fn bookmark_feed(
+    path: web::Path<i64>,
+    user: AuthenticatedUser,
+    feeds: web::Data<dyn FeedRepository>,
+    bookmarks: web::Data<dyn BookmarkRepository>,
+) -> ActixResult<HttpResponse> {
+    let user_id = user.user_id;
+    let feed_id = path.into_inner();
+
+    let already_bookmarked = !bookmarks
+        .bookmarked_feed_ids(user_id, &[feed_id])
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .is_empty();
+    if already_bookmarked {
+        return Ok(HttpResponse::Ok().json(json!({"message": "Already bookmarked"})));
+    }
+
+    if let Err(resp) = live_feed(feeds.get_ref(), feed_id, Some(user_id)).await? {
+        return Ok(resp);
+    }
+
+    // Bookmarks are private: no event, and anonymous cached pages do not show them
+    match bookmarks.insert(feed_id, user_id).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(json!({"message": "Feed bookmarked"}))),
+        Err(e) if is_unique_violation(&e) => Ok(HttpResponse::Conflict().json(json!({
+            "error": "Feed already bookmarked"
+        }))),
+        Err(e) => Err(actix_web::error::ErrorInternalServerError(e)),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/feed/{feed_id}/bookmark",
+    params(
+        ("feed_id" = i64, Path, description = "Feed ID")
+    ),
+    responses(
+        (status = 200, description = "Bookmark removed (or there was none)"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+pub async fn unbookmark_feed(
+    path: web::Path<i64>,
+    user: AuthenticatedUser,
+    bookmarks: web::Data<dyn BookmarkRepository>,
+) -> ActixResult<HttpResponse> {
+    bookmarks
+        .delete(path.into_inner(), user.user_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    Ok(HttpResponse::Ok().json(json!({"message": "Bookmark removed"})))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/feed/{feed_id}/pin",
+    params(
+        ("feed_id" = i64, Path, description = "Feed ID")
+    ),
+    responses(
+        (status = 200, description = "Feed pinned (or already pinned)"),
+        (status = 400, description = "Already at CONTENT_MAX_PINNED_FEEDS pinned feeds"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the author of the feed"),
+        (status = 404, description = "Feed not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+pub async fn pin_feed(
+    path: web::Path<i64>,
+    user: AuthenticatedUser,
+    feeds: web::Data<dyn FeedRepository>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let feed_id = path.into_inner();
+    let existing = match own_feed(feeds.get_ref(), feed_id, user.user_id, "pin").await? {
+        Ok(feed) => feed,
+        Err(response) => return Ok(response),
+    };
+    if existing.pinned_at.is_some() {
+        return Ok(HttpResponse::Ok().json(json!({"message": "Already pinned"})));
+    }
+
+    let max_pinned = config.content.max_pinned_feeds;
+    let pinned = feeds
+        .count_pinned(user.user_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    if pinned >= max_pinned {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": format!("You can pin at most {} feeds; unpin one first", max_pinned),
+            "max_pinned": max_pinned
+        })));
+    }
+
+    let changes = FeedChanges {
+        pinned_at: Some(Some(Utc::now())),
+        ..Default::default()
+    };
+    feeds
+        .update(feed_id, changes)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    // Cached pages carry `is_pinned`
+    invalidate_feed_cache(&redis_client, &config).await;
+    Ok(HttpResponse::Ok().json(json!({"message": "Feed pinned"})))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/feed/{feed_id}/pin",
+    params(
+        ("feed_id" = i64, Path, description = "Feed ID")
+    ),
+    responses(
+        (status = 200, description = "Feed unpinned (or was not pinned)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Not the author of the feed"),
+        (status = 404, description = "Feed not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+pub async fn unpin_feed(
+    path: web::Path<i64>,
+    user: AuthenticatedUser,
+    feeds: web::Data<dyn FeedRepository>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let feed_id = path.into_inner();
+    let existing = match own_feed(feeds.get_ref(), feed_id, user.user_id, "unpin").await? {
+        Ok(feed) => feed,
+        Err(response) => return Ok(response),
+    };
+    if existing.pinned_at.is_none() {
+        return Ok(HttpResponse::Ok().json(json!({"message": "Feed unpinned"})));
+    }
+
+    let changes = FeedChanges {
+        pinned_at: Some(None),
+        ..Default::default()
+    };
+    feeds
+        .update(feed_id, changes)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    invalidate_feed_cache(&redis_client, &config).await;
+    Ok(HttpResponse::Ok().json(json!({"message": "Feed unpinned"})))
+}
+
+/// The feed if `user_id` wrote it, otherwise the `404`/`403` response to send
+async fn own_feed(
+    feeds: &dyn FeedRepository,
+    feed_id: i64,
+    user_id: i64,
+    action: &str,
+) -> ActixResult<Result<feed::Model, HttpResponse>> {
+    let existing = feeds
+        .find_by_id(feed_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    Ok(match existing {
+        None => Err(HttpResponse::NotFound().json(json!({"error": "Feed not found"}))),
+        Some(feed) if feed.user_id != user_id => Err(HttpResponse::Forbidden().json(json!({
+            "error": format!("You can only {} your own feeds", action)
+        }))),
+        Some(feed) => Ok(feed),
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/feed/{feed_id}/report",
+    params(
+        ("feed_id" = i64, Path, description = "Feed ID")
+    ),
+    request_body = ReportFeedRequest,
+    responses(
+        (status = 201, description = "Report queued for moderators", body = FeedReportResponse),
+        (status = 200, description = "The caller already has an open report of this feed"),
+        (status = 400, description = "Empty reason or longer than MODERATION_MAX_REASON_CHARS"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Feed not found, hidden or scheduled by someone else, or reporting is disabled")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+pub async fn report_feed(
+    path: web::Path<i64>,
+    user: AuthenticatedUser,
+    req: RequestJson<ReportFeedRequest>,
+    feeds: web::Data<dyn FeedRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    if !config.moderation.reports_enabled {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "Reporting feeds is disabled"
+        })));
+    }
+    let feed_id = path.into_inner();
+
+    let reason = req.reason.trim();
+    let max_chars = config.moderation.max_reason_chars;
+    if reason.is_empty() || reason.chars().count() > max_chars {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": format!("Reason must be 1 to {} characters", max_chars)
+        })));
+    }
+
+    // Hidden feeds are already out of view, so there is nothing left to report
+    if let Err(resp) = live_feed(feeds.get_ref(), feed_id, Some(user.user_id)).await? {
+        return Ok(resp);
+    }
+
+    let collection = mongo_collection::<FeedReport>(&mongo_db, &config, "feed_reports");
+    let open_filter = mongodb::bson::doc! {
+        "feed_id": feed_id,
+        "user_id": user.user_id,
+        "status": ReportStatus::Open.as_str(),
+    };
+    if collection
+        .find_one(open_filter, None)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .is_some()
+    {
+        return Ok(HttpResponse::Ok().json(json!({"message": "Already reported"})));
+    }
+
+    let report = FeedReport {
+        id: Some(Uuid::new_v4().to_string()),
+        feed_id,
+        user_id: user.user_id,
+        reason: reason.to_string(),
+        status: ReportStatus::Open,
+        created_at: Utc::now(),
+        resolved_by: None,
+        resolved_at: None,
+    };
+    collection
+        .insert_one(&report, None)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    Ok(HttpResponse::Created().json(FeedReportResponse::from(report)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/feed/{feed_id}/react",
+    request_body = ReactRequest,
+    responses(
+        (status = 200, description = "Reaction added"),
+        (status = 400, description = "Unknown reaction type"),
+        (status = 409, description = "Reaction already added"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Feed not found, hidden, or scheduled by someone else"),
+        (status = 410, description = "Feed has expired"),
+        (status = 429, description = "Too many reaction operations")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn react_feed(
+    path: web::Path<i64>,
+    req: web::Json<ReactRequest>,
+    user: AuthenticatedUser,
+    feeds: web::Data<dyn FeedRepository>,
+    likes: web::Data<dyn LikeRepository>,
+    reactions: web::Data<dyn ReactionRepository>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+    kafka_producer: web::Data<KafkaProducer>,
+    request_id: RequestId,
+) -> ActixResult<HttpResponse> {
+    let reaction = req.reaction_type;
+    // Likes keep their own table, events and rankings
+    if reaction == ReactionType::Like {
+        return like_feed(
+            path,
+            user,
+            feeds,
+            likes,
+            redis_client,
+            config,
+            kafka_producer,
+            request_id,
+        )
+        .await;
+    }
+
+    let user_id = user.user_id;
+    let feed_id = path.into_inner();
+
+    if let Some(resp) = check_like_rate(&redis_client, &config, user_id).await {
+        return Ok(resp);
+    }
+
+    let feed = match live_feed(feeds.get_ref(), feed_id, Some(user_id)).await? {
+        Ok(feed) => feed,
+        Err(resp) => return Ok(resp),
+    };
+    if let Some(resp) = reject_expired_feed(&feed) {
+        return Ok(resp);
+    }
+
+    match reactions.insert(feed_id, user_id, reaction).await {
+        Ok(_) => {
+            invalidate_feed_cache(&redis_client, &config).await;
+            Ok(HttpResponse::Ok().json(json!({"message": "Reaction added"})))
+        }
+        Err(e) if is_unique_violation(&e) => Ok(HttpResponse::Conflict().json(json!({
+            "error": "Already reacted"
+        }))),
+        Err(e) => {
+            log::error!("Database error inserting reaction: {:?}", e);
+            Ok(HttpResponse::BadRequest().json(json!({
+                "error": "Failed to add reaction"
+            })))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/feed/{feed_id}/react/{reaction_type}",
+    params(
+        ("feed_id" = i64, Path, description = "Feed ID"),
+        ("reaction_type" = ReactionType, Path, description = "Reaction to remove")
+    ),
+    responses(
+        (status = 200, description = "Reaction removed"),
+        (status = 401, description = "Unauthorized"),
+        (status = 429, description = "Too many reaction operations")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "feed"
+)]
+pub async fn unreact_feed(
+    path: web::Path<(i64, ReactionType)>,
+    user: AuthenticatedUser,
+    likes: web::Data<dyn LikeRepository>,
+    reactions: web::Data<dyn ReactionRepository>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let (feed_id, reaction) = path.into_inner();
+    if reaction == ReactionType::Like {
+        return unlike_feed(web::Path::from(feed_id), user, likes, redis_client, config).await;
+    }
+
+    let user_id = user.user_id;
+    if let Some(resp) = check_like_rate(&redis_client, &config, user_id).await {
+        return Ok(resp);
+    }
+
+    match reactions.delete(feed_id, user_id, reaction).await {
         Ok(_) => {
-            let event = FeedLikedEvent::new(feed_id, user_id);
-            if let Ok(event_json) = serde_json::to_string(&event) {
-                if let Err(e) = kafka_producer
-                    .send_message("feed_events", &feed_id.to_string(), &event_json)
-                    .await
-                {
-                    log::warn!("Failed to send Kafka event: {:?}", e);
-                }
-            }
-
-            Ok(HttpResponse::Ok().json(json!({"message": "Feed liked"})))
+            invalidate_feed_cache(&redis_client, &config).await;
+            Ok(HttpResponse::Ok().json(json!({"message": "Reaction removed"})))
         }
         Err(e) => {
-            // Check if it's a unique constraint violation (race condition)
-            let error_msg =
-                if e.to_string().contains("unique") || e.to_string().contains("Duplicate") {
-                    "Feed already liked"
-                } else {
-                    log::error!("Database error inserting like: {:?}", e);
-                    "Failed to like feed"
-                };
-            Ok(HttpResponse::BadRequest().json(json!({
-                "error": error_msg
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to remove reaction"
             })))
         }
     }
 }
 
 #[utoipa::path(
-    delete,
-    path = "/api/feed/{feed_id}/like",
+    post,
+    path = "/api/feed/liked-status",
+    request_body = LikedStatusRequest,
     responses(
-        (status = 200, description = "Feed unliked successfully"),
+        (status = 200, description = "Whether the user liked each existing feed; unknown feed IDs are listed as failed", body = LikedStatusResult),
+        (status = 400, description = "Too many feed IDs"),
         (status = 401, description = "Unauthorized")
     ),
     security(
@@ -265,33 +1623,170 @@ pub async fn like_feed(
     ),
     tag = "feed"
 )]
-pub async fn unlike_feed(
-    path: web::Path<i64>,
+pub async fn get_liked_status(
     user: AuthenticatedUser,
-    pool: web::Data<DbPool>,
+    feeds: web::Data<dyn FeedRepository>,
+    likes: web::Data<dyn LikeRepository>,
+    req: web::Json<LikedStatusRequest>,
 ) -> ActixResult<HttpResponse> {
-    let user_id = user.user_id;
-    let feed_id = path.into_inner();
+    if req.feed_ids.len() > MAX_BATCH_FEED_IDS {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": format!("At most {} feed IDs are allowed", MAX_BATCH_FEED_IDS)
+        })));
+    }
 
-    // Delete like using SeaORM
-    let result = feed_like::Entity::delete_many()
-        .filter(
-            Condition::all()
-                .add(feed_like::Column::FeedId.eq(feed_id))
-                .add(feed_like::Column::UserId.eq(user_id)),
-        )
-        .exec(pool.get_ref())
-        .await;
+    let mut result = LikedStatusResult::new();
+    let found = visible_feeds_by_ids(feeds.get_ref(), &req.feed_ids, Some(user.user_id))
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let found_ids: Vec<i64> = found.iter().map(|f| f.id).collect();
 
-    match result {
-        Ok(_) => Ok(HttpResponse::Ok().json(json!({"message": "Feed unliked"}))),
-        Err(e) => {
-            log::error!("Database error: {:?}", e);
-            Ok(HttpResponse::InternalServerError().json(json!({
-                "error": "Failed to unlike feed"
-            })))
+    // Single query for all found feeds
+    let liked = if found_ids.is_empty() {
+        Vec::new()
+    } else {
+        likes
+            .liked_feed_ids(user.user_id, &found_ids)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?
+    };
+
+    for feed_id in distinct(&req.feed_ids) {
+        if found_ids.contains(&feed_id) {
+            result.succeeded.push(LikedStatus {
+                feed_id,
+                liked: liked.contains(&feed_id),
+            });
+        } else {
+            result.fail(feed_id, "Feed not found");
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(result))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/feed/batch",
+    request_body = FeedBatchRequest,
+    responses(
+        (status = 200, description = "Found feeds in request order; unknown feed IDs are listed as failed; FeedResponseV2 items when v2 is negotiated", body = FeedBatchResult),
+        (status = 406, description = "Accept names only unsupported API versions"),
+        (status = 400, description = "Too many feed IDs"),
+        (status = 503, description = "Comment counts unavailable and MONGODB_DEGRADE_COUNTS is off")
+    ),
+    tag = "feed"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_feeds_batch(
+    user: Option<AuthenticatedUser>,
+    version: ApiVersion,
+    feeds: web::Data<dyn FeedRepository>,
+    users: web::Data<dyn UserRepository>,
+    likes: web::Data<dyn LikeRepository>,
+    reactions: web::Data<dyn ReactionRepository>,
+    bookmarks: web::Data<dyn BookmarkRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
+    req: web::Json<FeedBatchRequest>,
+) -> ActixResult<HttpResponse> {
+    if req.feed_ids.len() > MAX_BATCH_FEED_IDS {
+        return Ok(HttpResponse::BadRequest().json(json!({
+            "error": format!("At most {} feed IDs are allowed", MAX_BATCH_FEED_IDS)
+        })));
+    }
+
+    let user_id = user.map(|u| u.user_id);
+    let found = visible_feeds_by_ids(feeds.get_ref(), &req.feed_ids, user_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let mut result = BatchResult::<VersionedFeed>::new();
+    for feed_id in distinct(&req.feed_ids) {
+        if !found.iter().any(|f| f.id == feed_id) {
+            result.fail(feed_id, "Feed not found");
+        }
+    }
+    let responses = feed_responses(
+        found,
+        user_id,
+        likes.get_ref(),
+        reactions.get_ref(),
+        bookmarks.get_ref(),
+        &mongo_db,
+        &config,
+    )
+    .await?;
+    result.succeeded = versioned_feeds(version, responses, users.get_ref()).await?;
+
+    let mut response = HttpResponse::Ok();
+    insert_version_headers(&mut response, version);
+    Ok(response.json(result))
+}
+
+/// IDs in request order with duplicates removed
+fn distinct(ids: &[i64]) -> Vec<i64> {
+    let mut distinct = Vec::with_capacity(ids.len());
+    for id in ids {
+        if !distinct.contains(id) {
+            distinct.push(*id);
         }
     }
+    distinct
+}
+
+/// `410 Gone` for a feed past its `expires_at`
+fn feed_expired_response(feed: &feed::Model) -> HttpResponse {
+    HttpResponse::Gone().json(json!({
+        "error": "Feed has expired",
+        "expires_at": feed.expires_at
+    }))
+}
+
+/// `410` when `feed` has expired. Likes, comments and views of an expired feed would
+/// otherwise put it back into the realtime rankings.
+fn reject_expired_feed(feed: &feed::Model) -> Option<HttpResponse> {
+    feed.has_expired(Utc::now())
+        .then(|| feed_expired_response(feed))
+}
+
+/// The feed if `user_id` may act on it: it is not hidden by a moderator, and it has gone
+/// live or they wrote it. Otherwise the `404` to send, so neither a hidden nor a scheduled
+/// feed can be confirmed or ranked through likes, comments, views or reactions.
+async fn live_feed(
+    feeds: &dyn FeedRepository,
+    feed_id: i64,
+    user_id: Option<i64>,
+) -> ActixResult<Result<feed::Model, HttpResponse>> {
+    let feed = feeds.find_by_id(feed_id).await.map_err(|e| {
+        log::error!("Database error checking feed existence: {:?}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+    Ok(feed
+        .filter(|feed| {
+            feed.hidden_at.is_none() && (feed.is_live(Utc::now()) || Some(feed.user_id) == user_id)
+        })
+        .ok_or_else(|| HttpResponse::NotFound().json(json!({"error": "Feed not found"}))))
+}
+
+/// Feeds among `ids` (in request order, without duplicates) that `user_id` may see:
+/// published ones, plus their own scheduled feeds, but none a moderator hid or that expired
+async fn visible_feeds_by_ids(
+    feeds: &dyn FeedRepository,
+    ids: &[i64],
+    user_id: Option<i64>,
+) -> Result<Vec<feed::Model>, sea_orm::DbErr> {
+    let now = Utc::now();
+    Ok(feeds
+        .find_by_ids(&distinct(ids))
+        .await?
+        .into_iter()
+        .filter(|f| {
+            f.hidden_at.is_none()
+                && !f.has_expired(now)
+                && (f.is_live(now) || Some(f.user_id) == user_id)
+        })
+        .collect())
 }
 
 #[utoipa::path(
@@ -300,23 +1795,88 @@ pub async fn unlike_feed(
     request_body = CommentRequest,
     responses(
         (status = 200, description = "Comment created successfully", body = CommentResponse),
-        (status = 401, description = "Unauthorized")
+        (status = 400, description = "parent_comment_id is not a comment on this feed, or an unknown field with SERVER_STRICT_JSON"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Feed not found, hidden, or scheduled by someone else"),
+        (status = 410, description = "Feed has expired"),
+        (status = 429, description = "Commenting on this feed again too soon")
     ),
     security(
         ("bearer_auth" = [])
     ),
     tag = "feed"
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn comment_feed(
     path: web::Path<i64>,
-    req: web::Json<CommentRequest>,
+    req: RequestJson<CommentRequest>,
     user: AuthenticatedUser,
+    feeds: web::Data<dyn FeedRepository>,
     mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
+    redis_client: web::Data<RedisClient>,
     kafka_producer: web::Data<KafkaProducer>,
+    request_id: RequestId,
 ) -> ActixResult<HttpResponse> {
     let user_id = user.user_id;
     let feed_id = path.into_inner();
 
+    if let Err(e) = content::validate_mentions(&req.content, &config) {
+        return Ok(HttpResponse::BadRequest().json(json!({"error": e})));
+    }
+
+    let feed = match live_feed(feeds.get_ref(), feed_id, Some(user_id)).await? {
+        Ok(feed) => feed,
+        Err(resp) => return Ok(resp),
+    };
+    if let Some(resp) = reject_expired_feed(&feed) {
+        return Ok(resp);
+    }
+
+    // Replies must stay on the parent's feed, or the comment tree could not place them
+    if let Some(parent_comment_id) = &req.parent_comment_id {
+        let parent = mongo_db
+            .collection::<Comment>("comments")
+            .find_one(
+                mongodb::bson::doc! {
+                    "_id": id_filter(parent_comment_id),
+                    "feed_id": feed_id
+                },
+                None,
+            )
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        if parent.is_none() {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "error": "parent_comment_id does not refer to a comment on this feed"
+            })));
+        }
+    }
+
+    let cooldown_key = comment_cooldown_key(feed_id, user_id);
+    match acquire_cooldown(
+        &redis_client,
+        &config,
+        &cooldown_key,
+        config.rate_limit.comment_cooldown_seconds,
+    )
+    .await
+    {
+        Ok(None) => {}
+        Ok(Some(retry_after)) => {
+            return Ok(HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", retry_after.to_string()))
+                .json(json!({
+                    "error": "You are commenting too fast, please wait",
+                    "retry_after": retry_after
+                })));
+        }
+        Err(e) => {
+            // Fail open: a Redis outage should not block commenting
+            log::error!("Failed to check comment rate limit: {:?}", e);
+        }
+    }
+
     let comment_id = Uuid::new_v4().to_string();
     let comment = Comment {
         id: Some(comment_id.clone()),
@@ -324,18 +1884,23 @@ pub async fn comment_feed(
         user_id,
         content: req.content.clone(),
         created_at: Utc::now(),
+        updated_at: None,
+        parent_comment_id: req.parent_comment_id.clone(),
     };
 
-    let collection = mongo_db.collection::<Comment>("comments");
-    collection
-        .insert_one(&comment, None)
-        .await
-        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let collection = mongo_collection::<Comment>(&mongo_db, &config, "comments");
+    if let Err(e) = collection.insert_one(&comment, None).await {
+        // Don't hold the cooldown for a comment that was never saved
+        let _ = release_cooldown(&redis_client, &config, &cooldown_key).await;
+        return Err(actix_web::error::ErrorInternalServerError(e));
+    }
+    invalidate_feed_cache(&redis_client, &config).await;
 
-    let event = FeedCommentedEvent::new(feed_id, user_id, comment_id.clone(), req.content.clone());
+    let event = FeedCommentedEvent::new(feed_id, user_id, comment_id.clone(), req.content.clone())
+        .with_request_id(request_id.as_str());
     if let Ok(event_json) = serde_json::to_string(&event) {
         if let Err(e) = kafka_producer
-            .send_message("feed_events", &feed_id.to_string(), &event_json)
+            .send_message_with_retry("feed_events", &feed_id.to_string(), &event_json)
             .await
         {
             log::warn!("Failed to send Kafka event: {:?}", e);
@@ -348,51 +1913,160 @@ pub async fn comment_feed(
         user_id: comment.user_id,
         content: comment.content,
         created_at: comment.created_at,
+        updated_at: comment.updated_at,
+        parent_comment_id: comment.parent_comment_id,
     }))
 }
 
+#[derive(Debug, Clone, Copy, Default, Deserialize, utoipa::ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentSort {
+    #[default]
+    Newest,
+    Oldest,
+}
+
+impl CommentSort {
+    /// MongoDB sort direction for `created_at`
+    fn direction(self) -> i32 {
+        match self {
+            CommentSort::Newest => -1,
+            CommentSort::Oldest => 1,
+        }
+    }
+}
+
+/// How `GET /api/feed/{feed_id}/comments` pages through comments
+#[derive(Debug, Clone, Copy, Default, Deserialize, utoipa::ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CommentPagination {
+    /// `page`/`limit`; a plain array. Pages shift when comments are added meanwhile
+    #[default]
+    Offset,
+    /// `cursor`/`limit`; a `CommentPage`. Stable under concurrent inserts
+    Cursor,
+}
+
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct CommentQuery {
     #[schema(example = 1)]
     pub page: Option<u64>,
     #[schema(example = 20)]
     pub limit: Option<u64>,
+    /// Sort order by creation time (default: newest)
+    pub sort: Option<CommentSort>,
+    /// `offset` (default) or `cursor`; a `cursor` implies cursor mode
+    pub pagination: Option<CommentPagination>,
+    /// `next_cursor` of the previous page, in cursor mode
+    pub cursor: Option<String>,
+}
+
+/// Position after the last comment of a page: its `created_at` (Unix seconds, as stored)
+/// and `_id` as the tiebreaker, encoded as `<seconds>_<id>`
+#[derive(Debug, PartialEq, Eq)]
+struct CommentCursor {
+    created_at: i64,
+    id: String,
+}
+
+impl CommentCursor {
+    fn parse(value: &str) -> Option<Self> {
+        let (created_at, id) = value.split_once('_')?;
+        if id.is_empty() {
+            return None;
+        }
+        Some(CommentCursor {
+            created_at: created_at.parse().ok()?,
+            id: id.to_string(),
+        })
+    }
+
+    fn encode(&self) -> String {
+        format!("{}_{}", self.created_at, self.id)
+    }
+
+    /// Filter for the comments past this cursor in `sort` order: a `created_at` further
+    /// along, or the same second and an `_id` further along. `_id`s sort by BSON type
+    /// first and legacy ObjectIds come after UUID strings, so on a tie the other type is
+    /// entirely past the cursor or entirely behind it.
+    fn past(&self, sort: CommentSort) -> mongodb::bson::Document {
+        let op = match sort {
+            CommentSort::Newest => "$lt",
+            CommentSort::Oldest => "$gt",
+        };
+        let id = match ObjectId::parse_str(&self.id) {
+            Ok(oid) => Bson::ObjectId(oid),
+            Err(_) => Bson::String(self.id.clone()),
+        };
+        let mut tie = vec![mongodb::bson::doc! {"_id": {op: id.clone()}}];
+        match (sort, &id) {
+            (CommentSort::Newest, Bson::ObjectId(_)) => {
+                tie.push(mongodb::bson::doc! {"_id": {"$type": "string"}})
+            }
+            (CommentSort::Oldest, Bson::String(_)) => {
+                tie.push(mongodb::bson::doc! {"_id": {"$type": "objectId"}})
+            }
+            _ => {}
+        }
+        mongodb::bson::doc! {"$or": [
+            {"created_at": {op: self.created_at}},
+            {"created_at": self.created_at, "$or": tie},
+        ]}
+    }
 }
 
+/// Most comments one page (offset or cursor) can return
+const MAX_COMMENT_PAGE_LIMIT: u64 = 100;
+
 #[utoipa::path(
     get,
     path = "/api/feed/{feed_id}/comments",
     params(
         ("feed_id" = i64, Path, description = "Feed ID"),
         ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
-        ("limit" = Option<u64>, Query, description = "Items per page (default: 20)")
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 20, max: 100)"),
+        ("sort" = Option<CommentSort>, Query, description = "Sort order: newest or oldest (default: newest)"),
+        ("pagination" = Option<CommentPagination>, Query, description = "offset (default, page/limit) or cursor (cursor/limit, returns a CommentPage)"),
+        ("cursor" = Option<String>, Query, description = "next_cursor of the previous page; implies pagination=cursor")
     ),
     responses(
-        (status = 200, description = "List of comments", body = Vec<CommentResponse>)
+        (status = 200, description = "List of comments; Link and X-Total-Count headers when enabled. A CommentPage in cursor mode", body = Vec<CommentResponse>),
+        (status = 400, description = "Invalid sort value or cursor, or page is out of range")
     ),
     tag = "feed"
 )]
 pub async fn get_comments(
+    http_req: HttpRequest,
     path: web::Path<i64>,
     query: web::Query<CommentQuery>,
     mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
 ) -> ActixResult<HttpResponse> {
     let feed_id = path.into_inner();
-    let page = query.page.unwrap_or(1);
-    let limit = query.limit.unwrap_or(20) as i64;
-    let skip = ((page - 1) * limit as u64) as i64;
+    if query.cursor.is_some() || query.pagination == Some(CommentPagination::Cursor) {
+        return get_comments_by_cursor(feed_id, &query, &mongo_db).await;
+    }
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_COMMENT_PAGE_LIMIT);
+    let Some(skip) = (page - 1).checked_mul(limit) else {
+        return Ok(HttpResponse::BadRequest().json(json!({"error": "page is out of range"})));
+    };
+    let sort = query.sort.unwrap_or_default();
 
     let collection = mongo_db.collection::<Comment>("comments");
     let filter = mongodb::bson::doc! {"feed_id": feed_id};
     let options = mongodb::options::FindOptions::builder()
-        .sort(mongodb::bson::doc! {"created_at": -1})
-        .limit(limit)
-        .skip(skip as u64)
+        .sort(mongodb::bson::doc! {"created_at": sort.direction()})
+        .limit(limit as i64)
+        .skip(skip)
         .build();
-    let mut cursor = collection
-        .find(filter, options)
-        .await
-        .map_err(actix_web::error::ErrorInternalServerError)?;
+    // The count for X-Total-Count runs alongside the page query, not after it
+    let (total, cursor) = tokio::join!(
+        mongo_total(&config, &collection, filter.clone()),
+        collection.find(filter, options)
+    );
+    let total = total.map_err(actix_web::error::ErrorInternalServerError)?;
+    let mut cursor = cursor.map_err(actix_web::error::ErrorInternalServerError)?;
 
     let mut comments = Vec::new();
     while let Ok(true) = cursor.advance().await {
@@ -411,41 +2085,398 @@ pub async fn get_comments(
             user_id: comment.user_id,
             content: comment.content,
             created_at: comment.created_at,
+            updated_at: comment.updated_at,
+            parent_comment_id: comment.parent_comment_id,
+        });
+    }
+
+    let mut response = HttpResponse::Ok();
+    insert_pagination_headers(
+        &mut response,
+        &http_req,
+        &config,
+        &PageInfo {
+            page,
+            limit,
+            total,
+            returned: comments.len(),
+            max_depth: None,
+        },
+    );
+    Ok(response.json(comments))
+}
+
+/// Cursor mode of `get_comments`: the `limit` comments past `cursor` (from the start
+/// without one), ordered by `created_at` then `_id` so every comment has one position
+async fn get_comments_by_cursor(
+    feed_id: i64,
+    query: &CommentQuery,
+    mongo_db: &MongoDatabase,
+) -> ActixResult<HttpResponse> {
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_COMMENT_PAGE_LIMIT);
+    let sort = query.sort.unwrap_or_default();
+    let mut filter = mongodb::bson::doc! {"feed_id": feed_id};
+    if let Some(cursor) = &query.cursor {
+        let Some(cursor) = CommentCursor::parse(cursor) else {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "error": "Invalid cursor"
+            })));
+        };
+        filter.extend(cursor.past(sort));
+    }
+
+    let options = mongodb::options::FindOptions::builder()
+        .sort(mongodb::bson::doc! {"created_at": sort.direction(), "_id": sort.direction()})
+        .limit(limit as i64)
+        .build();
+    let mut cursor = mongo_db
+        .collection::<Comment>("comments")
+        .find(filter, options)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let mut items = Vec::new();
+    while let Ok(true) = cursor.advance().await {
+        let comment: Comment = cursor
+            .deserialize_current()
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        items.push(CommentResponse {
+            id: comment.id.unwrap_or_default(),
+            feed_id: comment.feed_id,
+            user_id: comment.user_id,
+            content: comment.content,
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+            parent_comment_id: comment.parent_comment_id,
         });
     }
 
-    Ok(HttpResponse::Ok().json(comments))
+    let next_cursor = if items.len() as u64 == limit {
+        items.last().map(|comment| {
+            CommentCursor {
+                created_at: comment.created_at.timestamp(),
+                id: comment.id.clone(),
+            }
+            .encode()
+        })
+    } else {
+        None
+    };
+    Ok(HttpResponse::Ok().json(CommentPage { items, next_cursor }))
+}
+
+const DEFAULT_COMMENT_TREE_LIMIT: u64 = 200;
+const MAX_COMMENT_TREE_LIMIT: u64 = 500;
+const DEFAULT_COMMENT_TREE_DEPTH: u32 = 5;
+const MAX_COMMENT_TREE_DEPTH: u32 = 10;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CommentTreeQuery {
+    /// Most comments returned, replies included (default 200, max 500)
+    #[schema(example = 200)]
+    pub limit: Option<u64>,
+    /// Levels returned; 1 is top-level comments only (default 5, max 10)
+    #[schema(example = 5)]
+    pub max_depth: Option<u32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/feed/{feed_id}/comment-tree",
+    params(
+        ("feed_id" = i64, Path, description = "Feed ID"),
+        ("limit" = Option<u64>, Query, description = "Most comments returned, replies included (default: 200, max: 500)"),
+        ("max_depth" = Option<u32>, Query, description = "Levels of replies returned, 1 = top-level only (default: 5, max: 10)")
+    ),
+    responses(
+        (status = 200, description = "Comments nested by parent_comment_id, oldest first", body = CommentTreeResponse),
+        (status = 404, description = "Feed not found")
+    ),
+    tag = "feed"
+)]
+pub async fn get_comment_tree(
+    path: web::Path<i64>,
+    query: web::Query<CommentTreeQuery>,
+    feeds: web::Data<dyn FeedRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+) -> ActixResult<HttpResponse> {
+    let feed_id = path.into_inner();
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_COMMENT_TREE_LIMIT)
+        .clamp(1, MAX_COMMENT_TREE_LIMIT);
+    let max_depth = query
+        .max_depth
+        .unwrap_or(DEFAULT_COMMENT_TREE_DEPTH)
+        .clamp(1, MAX_COMMENT_TREE_DEPTH);
+
+    if feeds
+        .find_by_id(feed_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .is_none()
+    {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "Feed not found"
+        })));
+    }
+
+    // One query per level, so the node budget bounds both the reads and the response:
+    // top-level comments first, then replies to the previous level, oldest first
+    let collection = mongo_db.collection::<Comment>("comments");
+    let mut levels: Vec<Vec<Comment>> = Vec::new();
+    let mut remaining = limit;
+    let mut truncated = false;
+    let mut filter = mongodb::bson::doc! {"feed_id": feed_id, "parent_comment_id": null};
+    loop {
+        let options = mongodb::options::FindOptions::builder()
+            .sort(mongodb::bson::doc! {"created_at": 1, "_id": 1})
+            .limit((remaining + 1) as i64)
+            .build();
+        let mut level: Vec<Comment> = collection
+            .find(filter, options)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?
+            .try_collect()
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        if level.len() as u64 > remaining {
+            level.truncate(remaining as usize);
+            truncated = true;
+        }
+        remaining -= level.len() as u64;
+
+        let ids: Vec<String> = level.iter().filter_map(|c| c.id.clone()).collect();
+        levels.push(level);
+        if ids.is_empty() || truncated {
+            break;
+        }
+        filter = mongodb::bson::doc! {"feed_id": feed_id, "parent_comment_id": {"$in": ids}};
+        if levels.len() as u32 == max_depth || remaining == 0 {
+            // Only report truncation if replies were actually left out
+            truncated = collection
+                .find_one(filter, None)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?
+                .is_some();
+            break;
+        }
+    }
+
+    let returned = levels.iter().map(Vec::len).sum();
+    Ok(HttpResponse::Ok().json(CommentTreeResponse {
+        feed_id,
+        comments: assemble_comment_tree(levels),
+        returned,
+        truncated,
+    }))
+}
+
+/// Nest each level's comments under their parents in the level above, keeping the
+/// oldest-first order within every list
+fn assemble_comment_tree(levels: Vec<Vec<Comment>>) -> Vec<CommentTreeNode> {
+    let mut children: HashMap<String, Vec<CommentTreeNode>> = HashMap::new();
+    let mut nodes = Vec::new();
+    for level in levels.into_iter().rev() {
+        let mut next: HashMap<String, Vec<CommentTreeNode>> = HashMap::new();
+        nodes = Vec::new();
+        for comment in level {
+            let id = comment.id.unwrap_or_default();
+            let node = CommentTreeNode {
+                replies: children.remove(&id).unwrap_or_default(),
+                id,
+                user_id: comment.user_id,
+                content: comment.content,
+                created_at: comment.created_at,
+                updated_at: comment.updated_at,
+            };
+            match comment.parent_comment_id {
+                Some(parent) => next.entry(parent).or_default().push(node),
+                None => nodes.push(node),
+            }
+        }
+        children = next;
+    }
+    nodes
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/feed/{feed_id}/comments/export",
+    params(
+        ("feed_id" = i64, Path, description = "Feed ID")
+    ),
+    responses(
+        (status = 200, description = "All comments, oldest first, as NDJSON (one CommentResponse per line). A cursor failure ends the stream with an {\"error\": ...} line", content_type = "application/x-ndjson"),
+        (status = 404, description = "Feed not found")
+    ),
+    tag = "feed"
+)]
+pub async fn export_comments(
+    path: web::Path<i64>,
+    feeds: web::Data<dyn FeedRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+) -> ActixResult<HttpResponse> {
+    let feed_id = path.into_inner();
+
+    if feeds
+        .find_by_id(feed_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .is_none()
+    {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "Feed not found"
+        })));
+    }
+
+    let options = mongodb::options::FindOptions::builder()
+        .sort(mongodb::bson::doc! {"created_at": 1})
+        .build();
+    let cursor = mongo_db
+        .collection::<Comment>("comments")
+        .find(mongodb::bson::doc! {"feed_id": feed_id}, options)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    // One line per comment, read from the cursor as the client consumes the body.
+    // The status is already sent when a cursor error occurs, so it ends the stream
+    // with an error line instead.
+    let body = stream::unfold(Some(cursor), move |cursor| async move {
+        let mut cursor = cursor?;
+        let (line, next) = match cursor.next().await? {
+            Ok(comment) => {
+                let response = CommentResponse {
+                    id: comment.id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+                    feed_id: comment.feed_id,
+                    user_id: comment.user_id,
+                    content: comment.content,
+                    created_at: comment.created_at,
+                    updated_at: comment.updated_at,
+                    parent_comment_id: comment.parent_comment_id,
+                };
+                (
+                    serde_json::to_value(response).unwrap_or_default(),
+                    Some(cursor),
+                )
+            }
+            Err(e) => {
+                log::error!("Comment export of feed {} failed: {:?}", feed_id, e);
+                (json!({"error": "Comment export failed"}), None)
+            }
+        };
+        let mut bytes = line.to_string().into_bytes();
+        bytes.push(b'\n');
+        Some((Ok::<_, actix_web::Error>(web::Bytes::from(bytes)), next))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"feed-{}-comments.ndjson\"", feed_id),
+        ))
+        .streaming(body))
+}
+
+/// Views are limited per client IP (views can be anonymous) so one client flooding
+/// view events cannot swamp ingestion. `Retry-After` gets random jitter so limited
+/// clients do not all retry at the same moment.
+async fn check_view_rate(
+    http_req: &HttpRequest,
+    redis_client: &RedisClient,
+    config: &Config,
+) -> Option<HttpResponse> {
+    let ip = client_ip(http_req)
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    match take_token(
+        redis_client,
+        config,
+        &ip_bucket_key("view", &ip),
+        config.rate_limit.view_burst,
+        config.rate_limit.view_per_minute,
+    )
+    .await
+    {
+        Ok(None) => None,
+        Ok(Some(retry_after)) => {
+            let retry_after = retry_after
+                + rand::random::<u64>() % (config.rate_limit.view_retry_jitter_seconds + 1);
+            Some(
+                HttpResponse::TooManyRequests()
+                    .insert_header(("Retry-After", retry_after.to_string()))
+                    .json(json!({
+                        "error": "Too many views, please slow down",
+                        "retry_after": retry_after
+                    })),
+            )
+        }
+        Err(e) => {
+            // Fail open: a Redis outage should not stop view tracking
+            log::error!("Failed to check view rate limit: {:?}", e);
+            None
+        }
+    }
 }
 
 #[utoipa::path(
     post,
     path = "/api/feed/{feed_id}/view",
     responses(
-        (status = 200, description = "Feed view recorded")
+        (status = 200, description = "Feed view recorded"),
+        (status = 404, description = "Feed not found, hidden, or scheduled by someone else"),
+        (status = 410, description = "Feed has expired"),
+        (status = 429, description = "Too many views from this client IP")
     ),
     tag = "feed"
 )]
+#[allow(clippy::too_many_arguments)]
 pub async fn view_feed(
+    http_req: HttpRequest,
     path: web::Path<i64>,
     user: Option<AuthenticatedUser>,
+    feeds: web::Data<dyn FeedRepository>,
     mongo_db: web::Data<MongoDatabase>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
     kafka_producer: web::Data<KafkaProducer>,
 ) -> ActixResult<HttpResponse> {
-    let user_id = user.map(|u| u.user_id).unwrap_or(0);
+    let viewer_id = user.map(|u| u.user_id);
+    let user_id = viewer_id.unwrap_or(0);
     let feed_id = path.into_inner();
 
-    let feed_view = FeedView {
-        id: Some(Uuid::new_v4().to_string()),
-        feed_id,
-        user_id,
-        viewed_at: Utc::now(),
+    if let Some(resp) = check_view_rate(&http_req, &redis_client, &config).await {
+        return Ok(resp);
+    }
+
+    let feed = match live_feed(feeds.get_ref(), feed_id, viewer_id).await? {
+        Ok(feed) => feed,
+        Err(resp) => return Ok(resp),
     };
+    if let Some(resp) = reject_expired_feed(&feed) {
+        return Ok(resp);
+    }
 
-    let collection = mongo_db.collection::<FeedView>("feed_views");
-    collection
-        .insert_one(&feed_view, None)
-        .await
-        .map_err(actix_web::error::ErrorInternalServerError)?;
+    // Only a sampled subset is stored, each document weighted to stand for the skipped views;
+    // the Kafka event below (realtime Redis counts) is sent for every view
+    let sample_rate = config.mongodb.feed_view_sample_rate;
+    if sample_rate >= 1.0 || rand::random::<f64>() < sample_rate {
+        let feed_view = FeedView {
+            id: Some(Uuid::new_v4().to_string()),
+            feed_id,
+            user_id,
+            viewed_at: Utc::now(),
+            weight: 1.0 / sample_rate,
+        };
+
+        let collection = mongo_collection::<FeedView>(&mongo_db, &config, "feed_views");
+        collection
+            .insert_one(&feed_view, None)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+    }
 
     let event = FeedViewedEvent::new(feed_id, user_id);
     if let Ok(event_json) = serde_json::to_string(&event) {
@@ -0,0 +1,74 @@
+use crate::config::Config;
+use crate::kafka::consumer_heartbeat_key;
+use actix_web::{web, HttpResponse, Result as ActixResult};
+use redis::Client as RedisClient;
+use serde_json::json;
+use std::collections::HashMap;
+
+#[utoipa::path(
+    get,
+    path = "/ready",
+    responses(
+        (status = 200, description = "Every Kafka consumer has a recent heartbeat"),
+        (status = 503, description = "A consumer heartbeat is missing or stale, or Redis is unreachable")
+    ),
+    tag = "health"
+)]
+pub async fn ready(
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    let heartbeats: redis::RedisResult<HashMap<String, i64>> = async {
+        let mut conn = redis_client.get_async_connection().await?;
+        redis::cmd("HGETALL")
+            .arg(consumer_heartbeat_key(&config))
+            .query_async(&mut conn)
+            .await
+    }
+    .await;
+
+    let heartbeats = match heartbeats {
+        Ok(heartbeats) => heartbeats,
+        Err(e) => {
+            log::error!("Failed to read consumer heartbeats: {:?}", e);
+            return Ok(HttpResponse::ServiceUnavailable().json(json!({
+                "status": "unavailable",
+                "error": "Redis unavailable"
+            })));
+        }
+    };
+
+    // Seconds since each consumer (keyed by its topics) last checked in
+    let now = chrono::Utc::now().timestamp();
+    let ages: HashMap<String, i64> = heartbeats
+        .into_iter()
+        .map(|(topics, at)| (topics, now - at))
+        .collect();
+
+    let max_age = config.kafka.consumer_heartbeat_max_age_seconds as i64;
+    if ages.is_empty() || ages.values().any(|age| *age > max_age) {
+        return Ok(HttpResponse::ServiceUnavailable().json(json!({
+            "status": "unavailable",
+            "consumers": ages
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(json!({
+        "status": "ready",
+        "consumers": ages
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text format", content_type = "text/plain")
+    ),
+    tag = "health"
+)]
+pub async fn metrics() -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(crate::metrics::render())
+}
@@ -0,0 +1,76 @@
+use crate::api::client_ip::client_ip;
+use crate::config::Config;
+use crate::services::rate_limit::{ip_bucket_key, take_token};
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web, Error, HttpResponse,
+};
+use redis::Client as RedisClient;
+use serde_json::json;
+
+/// Coarse limit on every request to the scope it wraps (`/api`), per client IP, on top of
+/// the per-action limiters: a token bucket of `RATE_LIMIT_IP_BURST` tokens refilled at
+/// `RATE_LIMIT_IP_PER_MINUTE`. Over the limit the request gets `429` with `Retry-After`
+/// and never reaches the handler.
+///
+/// The IP comes from `client_ip()`, so clients behind `TRUSTED_PROXIES` are told apart.
+/// Addresses in `RATE_LIMIT_IP_ALLOWLIST` and requests without a peer address are not
+/// limited. Fails open if Redis is down.
+pub async fn throttle_by_ip(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, Error> {
+    if let Some(retry_after) = throttled(&req).await {
+        let response = HttpResponse::TooManyRequests()
+            .insert_header(("Retry-After", retry_after.to_string()))
+            .json(json!({
+                "error": "Too many requests, please slow down",
+                "retry_after": retry_after
+            }));
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+    next.call(req)
+        .await
+        .map(ServiceResponse::map_into_left_body)
+}
+
+/// Seconds until the client may retry if it is over its limit, `None` if it may go on
+async fn throttled(req: &ServiceRequest) -> Option<u64> {
+    let config = req.app_data::<web::Data<Config>>()?;
+    let redis_client = req.app_data::<web::Data<RedisClient>>()?;
+    let limits = &config.rate_limit;
+    if limits.ip_per_minute == 0 {
+        return None;
+    }
+    let ip = client_ip(req.request())?;
+    if limits
+        .ip_allowlist
+        .iter()
+        .any(|network| network.contains(&ip))
+    {
+        return None;
+    }
+
+    match take_token(
+        redis_client,
+        config,
+        &ip_bucket_key("api", &ip.to_string()),
+        limits.ip_burst,
+        limits.ip_per_minute,
+    )
+    .await
+    {
+        Ok(None) => None,
+        Ok(Some(retry_after)) => {
+            log::warn!("Throttled {} {} from {}", req.method(), req.path(), ip);
+            Some(retry_after)
+        }
+        Err(e) => {
+            // Fail open: a Redis outage should not take the whole API down
+            log::error!("Failed to check IP rate limit: {:?}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::config::Config;
+use actix_web::{
+    dev::Payload, error::InternalError, web, Error, FromRequest, HttpRequest, HttpResponse,
+};
+use serde::de::{self, DeserializeOwned, Visitor};
+use serde_json::{json, Value};
+use std::future::Future;
+use std::ops::Deref;
+use std::pin::Pin;
+
+/// JSON request body. Like `web::Json`, except that with `SERVER_STRICT_JSON` a field `T`
+/// does not declare is rejected with `400` instead of being ignored, so a typo such as
+/// `publsh_at` cannot silently drop a value
+#[derive(Debug)]
+pub struct RequestJson<T>(pub T);
+
+impl<T> Deref for RequestJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for RequestJson<T> {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let strict = req
+            .app_data::<web::Data<Config>>()
+            .is_some_and(|config| config.server.strict_json);
+        let body = web::Json::<Value>::from_request(req, payload);
+
+        Box::pin(async move {
+            let body = body.await?.into_inner();
+            if strict {
+                if let Some(field) = unknown_field::<T>(&body) {
+                    let expected = struct_fields::<T>().join(", ");
+                    return Err(bad_request(format!(
+                        "Unknown field `{}`, expected one of: {}",
+                        field, expected
+                    )));
+                }
+            }
+            serde_json::from_value(body)
+                .map(RequestJson)
+                .map_err(|e| bad_request(e.to_string()))
+        })
+    }
+}
+
+fn bad_request(message: String) -> Error {
+    let response = HttpResponse::BadRequest().json(json!({ "error": message }));
+    InternalError::from_response(message, response).into()
+}
+
+/// First key of a JSON object that is not a field of `T`. Non-objects and types that
+/// are not plain structs are left to `T`'s own deserializer.
+fn unknown_field<T: DeserializeOwned>(body: &Value) -> Option<&str> {
+    let fields = struct_fields::<T>();
+    if fields.is_empty() {
+        return None;
+    }
+    body.as_object()?
+        .keys()
+        .map(String::as_str)
+        .find(|key| !fields.contains(key))
+}
+
+/// Field names a derived `Deserialize` struct accepts, read from the list serde passes to
+/// `deserialize_struct`; empty for anything else
+fn struct_fields<T: DeserializeOwned>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> de::Deserializer<'de> for FieldNames<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(de::Error::custom("field names collected"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
@@ -1,12 +1,36 @@
+pub mod admin;
+pub mod audit;
 pub mod auth;
+pub mod client_ip;
+pub mod cors;
 pub mod feed;
+pub mod health;
+pub mod ip_throttle;
+pub mod json;
 pub mod notify;
+pub mod pagination;
+pub mod query_log;
+pub mod redact;
+pub mod request_id;
+pub mod sliding_session;
 pub mod top;
+pub mod users;
+pub mod versioning;
 
 use crate::models::{
-    AuthResponse, Comment, CommentRequest, CommentResponse, CreateFeedRequest, FeedResponse,
-    FeedView, LoginRequest, Notification, NotificationResponse, NotificationType, SignupRequest,
-    TopFeed, TopUser, UserResponse,
+    ActivityItem, ActivityResponse, ActivityType, AdminUserResponse, AuthResponse, BatchFailure,
+    ChangePasswordRequest, Comment, CommentPage, CommentRequest, CommentResponse, CommentTreeNode,
+    CommentTreeResponse, CreateFeedRequest, DeadLetterMessage, DeleteAccountRequest, ExportedFeed,
+    ExportedLike, ExportedNotification, ExportedProfile, FeedAuthor, FeedBatchRequest,
+    FeedBatchResult, FeedCategoriesResponse, FeedEdit, FeedPreviewRequest, FeedPreviewResponse,
+    FeedReportResponse, FeedResponse, FeedResponseV2, FeedRevision, FeedView, LikedStatus,
+    LikedStatusRequest, LikedStatusResult, LoginRequest, MarkReadRequest, MarkReadResult,
+    MentionPreview, NonceResponse, Notification, NotificationPreferences, NotificationResponse,
+    NotificationType, RawScore, ReactRequest, ReactionType, ReplayDeadLetterRequest, ReportAction,
+    ReportFeedRequest, ReportStatus, ResolveReportRequest, ScoreBreakdown, SeedRequest,
+    SeedResponse, SessionResponse, SignupRequest, TopDebug, TopFeed, TopUser, UnseenCountResponse,
+    UpdateFeedRequest, UpdateNotificationPreferencesRequest, UpdateProfileRequest, UserDataExport,
+    UserProfile, UserProfileResponse, UserResponse,
 };
 use utoipa::OpenApi;
 
@@ -16,22 +40,71 @@ use utoipa::OpenApi;
         // Auth endpoints
         auth::signup,
         auth::login,
+        auth::get_sessions,
+        auth::delete_session,
+        auth::get_nonce,
+        auth::change_password,
         // Feed endpoints
         feed::create_feed,
+        feed::preview_feed,
+        feed::update_feed,
         feed::get_feeds,
+        feed::get_feed_categories,
+        feed::get_feed,
         feed::like_feed,
         feed::unlike_feed,
+        feed::bookmark_feed,
+        feed::unbookmark_feed,
+        feed::pin_feed,
+        feed::unpin_feed,
+        feed::search_feeds,
+        feed::react_feed,
+        feed::unreact_feed,
         feed::comment_feed,
         feed::get_comments,
+        feed::export_comments,
+        feed::get_comment_tree,
         feed::view_feed,
+        feed::get_liked_status,
+        feed::get_feeds_batch,
+        feed::get_unseen_count,
+        feed::report_feed,
+        feed::get_feed_history,
         // Notification endpoints
         notify::get_notifications,
         notify::mark_notification_read,
+        notify::mark_notification_unread,
+        notify::mark_notifications_read,
         // Top stats endpoints
         top::get_top_users_liked,
         top::get_top_comments,
         top::get_top_feeds_viewed,
         top::get_top_feeds_liked,
+        top::stream_top_feeds_liked,
+        top::get_trending_feeds,
+        // User endpoints
+        users::get_user,
+        users::update_my_profile,
+        users::update_my_notification_preferences,
+        users::delete_my_account,
+        users::purge_my_account,
+        users::export_my_data,
+        users::get_user_activity,
+        users::get_common_likes,
+        users::get_user_liked_feeds,
+        users::get_my_bookmarks,
+        // Admin endpoints
+        admin::remove_like,
+        admin::list_users,
+        admin::backfill_rankings,
+        admin::seed_data,
+        admin::list_reports,
+        admin::resolve_report,
+        admin::list_dead_letters,
+        admin::replay_dead_letter,
+        // Health endpoints
+        health::ready,
+        health::metrics,
     ),
     components(schemas(
         // Auth schemas
@@ -39,31 +112,106 @@ use utoipa::OpenApi;
         LoginRequest,
         AuthResponse,
         UserResponse,
+        SessionResponse,
+        NonceResponse,
+        ChangePasswordRequest,
+        DeleteAccountRequest,
+        UserDataExport,
+        ExportedProfile,
+        ExportedFeed,
+        ExportedLike,
+        ExportedNotification,
         // Feed schemas
         CreateFeedRequest,
+        UpdateFeedRequest,
+        FeedPreviewRequest,
+        FeedPreviewResponse,
+        MentionPreview,
         FeedResponse,
+        FeedResponseV2,
+        FeedAuthor,
+        FeedCategoriesResponse,
         CommentRequest,
         CommentResponse,
+        CommentPage,
+        CommentTreeNode,
+        CommentTreeResponse,
         Comment,
         FeedView,
+        FeedBatchRequest,
+        FeedBatchResult,
+        LikedStatus,
+        LikedStatusResult,
+        BatchFailure,
+        UnseenCountResponse,
+        ReportFeedRequest,
+        FeedReportResponse,
+        ReportStatus,
+        FeedEdit,
+        FeedRevision,
         // Notification schemas
         Notification,
         NotificationResponse,
         NotificationType,
+        MarkReadRequest,
+        MarkReadResult,
+        LikedStatusRequest,
+        ReactionType,
+        ReactRequest,
         // Top stats schemas
         TopUser,
         TopFeed,
+        RawScore,
+        TopDebug,
+        ScoreBreakdown,
         top::TopQuery,
+        top::TopFeedsQuery,
+        top::ExplainQuery,
+        top::TopStreamQuery,
+        top::TrendingQuery,
+        // Admin schemas
+        AdminUserResponse,
+        SeedRequest,
+        SeedResponse,
+        admin::AdminUserQuery,
+        admin::AdminUserSort,
+        admin::ReportQuery,
+        ResolveReportRequest,
+        ReportAction,
+        admin::DeadLetterQuery,
+        DeadLetterMessage,
+        ReplayDeadLetterRequest,
+        crate::jobs::TrendingWindow,
+        // User schemas
+        UserProfile,
+        UserProfileResponse,
+        UpdateProfileRequest,
+        NotificationPreferences,
+        UpdateNotificationPreferencesRequest,
+        ActivityType,
+        ActivityItem,
+        ActivityResponse,
         // Query schemas
         feed::FeedQuery,
+        feed::FeedSearchQuery,
         feed::CommentQuery,
+        feed::CommentTreeQuery,
+        feed::CommentSort,
+        feed::CommentPagination,
         notify::NotificationQuery,
+        users::ActivityQuery,
+        users::CommonLikesQuery,
+        users::BookmarksQuery,
+        users::LikedFeedsQuery,
     )),
     tags(
         (name = "auth", description = "Authentication endpoints"),
         (name = "feed", description = "Feed management endpoints"),
         (name = "notify", description = "Notification endpoints"),
         (name = "top", description = "Top statistics endpoints"),
+        (name = "users", description = "User profile endpoints"),
+        (name = "admin", description = "Moderation endpoints (admin only)"),
+        (name = "health", description = "Health and readiness checks"),
     ),
     modifiers(&SecurityAddon),
 )]
@@ -1,16 +1,29 @@
+use crate::api::pagination::{insert_pagination_headers, mongo_total, PageInfo};
 use crate::auth::AuthenticatedUser;
-use crate::models::{Notification, NotificationResponse};
-use actix_web::{web, HttpResponse, Result as ActixResult};
+use crate::config::Config;
+use crate::db::{id_filter, ids_filter};
+use crate::models::{MarkReadRequest, MarkReadResult, Notification, NotificationResponse};
+use crate::services::notification::{displayed_content, recipient_language};
+use actix_web::{web, HttpRequest, HttpResponse, Result as ActixResult};
 use mongodb::Database as MongoDatabase;
 use serde::Deserialize;
 use utoipa::ToSchema;
 
+/// Maximum number of notification IDs accepted by a batch mark-read request
+const MAX_MARK_READ_IDS: usize = 100;
+
+/// Most notifications one page can return
+const MAX_NOTIFICATION_PAGE_LIMIT: u64 = 100;
+
 #[derive(Deserialize, ToSchema)]
 pub struct NotificationQuery {
     #[schema(example = 1)]
     pub page: Option<u64>,
     #[schema(example = 50)]
     pub limit: Option<u64>,
+    /// Only notifications about this feed
+    #[schema(example = 1)]
+    pub feed_id: Option<i64>,
 }
 
 #[utoipa::path(
@@ -18,10 +31,12 @@ pub struct NotificationQuery {
     path = "/api/notify",
     params(
         ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
-        ("limit" = Option<u64>, Query, description = "Items per page (default: 50)")
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 50, max: 100)"),
+        ("feed_id" = Option<i64>, Query, description = "Only notifications about this feed, e.g. the likes and comments on one post")
     ),
     responses(
-        (status = 200, description = "List of notifications", body = Vec<NotificationResponse>),
+        (status = 200, description = "List of notifications; Link and X-Total-Count headers when enabled", body = Vec<NotificationResponse>),
+        (status = 400, description = "Invalid feed_id, or page is out of range"),
         (status = 401, description = "Unauthorized")
     ),
     security(
@@ -30,49 +45,97 @@ pub struct NotificationQuery {
     tag = "notify"
 )]
 pub async fn get_notifications(
+    http_req: HttpRequest,
     user: AuthenticatedUser,
     mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
     query: web::Query<NotificationQuery>,
 ) -> ActixResult<HttpResponse> {
     let user_id = user.user_id;
 
-    let page = query.page.unwrap_or(1);
-    let limit = query.limit.unwrap_or(50) as i64;
-    let skip = ((page - 1) * limit as u64) as i64;
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query
+        .limit
+        .unwrap_or(50)
+        .clamp(1, MAX_NOTIFICATION_PAGE_LIMIT);
+    let Some(skip) = (page - 1).checked_mul(limit) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "page is out of range"
+        })));
+    };
 
     let collection = mongo_db.collection::<Notification>("notifications");
-    let filter = mongodb::bson::doc! {
+    let mut filter = mongodb::bson::doc! {
         "user_id": user_id
     };
+    if let Some(feed_id) = query.feed_id {
+        if feed_id <= 0 {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "feed_id must be a positive integer"
+            })));
+        }
+        filter.insert("feed_id", feed_id);
+    }
     let options = mongodb::options::FindOptions::builder()
         .sort(mongodb::bson::doc! {"created_at": -1})
-        .limit(limit)
-        .skip(skip as u64)
+        .limit(limit as i64)
+        .skip(skip)
         .build();
 
-    let mut cursor = collection
-        .find(filter, options)
-        .await
-        .map_err(actix_web::error::ErrorInternalServerError)?;
+    // The count for X-Total-Count runs alongside the page query, not after it
+    let (total, cursor) = tokio::join!(
+        mongo_total(&config, &collection, filter.clone()),
+        collection.find(filter, options)
+    );
+    let total = total.map_err(actix_web::error::ErrorInternalServerError)?;
+    let mut cursor = cursor.map_err(actix_web::error::ErrorInternalServerError)?;
 
-    let mut notifications = Vec::new();
+    let mut stored: Vec<Notification> = Vec::new();
     while let Ok(true) = cursor.advance().await {
-        let notif = cursor
-            .deserialize_current()
-            .map_err(actix_web::error::ErrorInternalServerError)?;
+        stored.push(
+            cursor
+                .deserialize_current()
+                .map_err(actix_web::error::ErrorInternalServerError)?,
+        );
+    }
+
+    // Aggregate likes are worded from their current count, in the recipient's language
+    let language = if stored.iter().any(|n| n.actor_count.is_some_and(|c| c > 1)) {
+        recipient_language(&mongo_db, user_id).await
+    } else {
+        None
+    };
+    let mut notifications = Vec::new();
+    for notif in stored {
+        let content = displayed_content(&notif, &config, language.as_deref());
         notifications.push(NotificationResponse {
             id: notif.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
             from_user_id: notif.from_user_id,
             from_username: notif.from_username,
             feed_id: notif.feed_id,
             notification_type: notif.notification_type,
-            content: notif.content,
+            content,
             created_at: notif.created_at,
             is_read: notif.is_read,
+            actor_count: notif.actor_count.unwrap_or(1),
+            recent_actors: notif.recent_actors,
         });
     }
 
-    Ok(HttpResponse::Ok().json(notifications))
+    let mut response = HttpResponse::Ok();
+    insert_pagination_headers(
+        &mut response,
+        &http_req,
+        &config,
+        &PageInfo {
+            page,
+            limit,
+            total,
+            returned: notifications.len(),
+            max_depth: None,
+        },
+    );
+    Ok(response.json(notifications))
 }
 
 #[utoipa::path(
@@ -80,7 +143,8 @@ pub async fn get_notifications(
     path = "/api/notify/{notification_id}/read",
     responses(
         (status = 200, description = "Notification marked as read"),
-        (status = 401, description = "Unauthorized")
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Notification not found")
     ),
     security(
         ("bearer_auth" = [])
@@ -97,17 +161,147 @@ pub async fn mark_notification_read(
 
     let collection = mongo_db.collection::<Notification>("notifications");
     let filter = mongodb::bson::doc! {
-        "_id": &notification_id,
+        "_id": id_filter(&notification_id),
         "user_id": user_id
     };
     let update = mongodb::bson::doc! {
         "$set": {"is_read": true}
     };
 
-    collection
+    let result = collection
         .update_one(filter, update, None)
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
+    if result.matched_count == 0 {
+        return Ok(
+            HttpResponse::NotFound().json(serde_json::json!({"error": "Notification not found"}))
+        );
+    }
+
     Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Notification marked as read"})))
 }
+
+#[utoipa::path(
+    put,
+    path = "/api/notify/{notification_id}/unread",
+    responses(
+        (status = 200, description = "Notification marked as unread"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Notification not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "notify"
+)]
+pub async fn mark_notification_unread(
+    path: web::Path<String>,
+    user: AuthenticatedUser,
+    mongo_db: web::Data<MongoDatabase>,
+) -> ActixResult<HttpResponse> {
+    let user_id = user.user_id;
+    let notification_id = path.into_inner();
+
+    let collection = mongo_db.collection::<Notification>("notifications");
+    let filter = mongodb::bson::doc! {
+        "_id": id_filter(&notification_id),
+        "user_id": user_id
+    };
+    let update = mongodb::bson::doc! {
+        "$set": {"is_read": false}
+    };
+
+    let result = collection
+        .update_one(filter, update, None)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    if result.matched_count == 0 {
+        return Ok(
+            HttpResponse::NotFound().json(serde_json::json!({"error": "Notification not found"}))
+        );
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Notification marked as unread"})))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/notify/read",
+    request_body = MarkReadRequest,
+    responses(
+        (status = 200, description = "IDs marked as read (already read ones included) and IDs not found among the caller's notifications", body = MarkReadResult),
+        (status = 400, description = "Too many IDs"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "notify"
+)]
+pub async fn mark_notifications_read(
+    req: web::Json<MarkReadRequest>,
+    user: AuthenticatedUser,
+    mongo_db: web::Data<MongoDatabase>,
+) -> ActixResult<HttpResponse> {
+    let user_id = user.user_id;
+
+    if req.ids.len() > MAX_MARK_READ_IDS {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("At most {} notification IDs are allowed", MAX_MARK_READ_IDS)
+        })));
+    }
+
+    let mut ids: Vec<String> = Vec::with_capacity(req.ids.len());
+    for id in &req.ids {
+        if !ids.contains(id) {
+            ids.push(id.clone());
+        }
+    }
+
+    let mut result = MarkReadResult::new();
+    if ids.is_empty() {
+        return Ok(HttpResponse::Ok().json(result));
+    }
+
+    // The user_id filter ensures only the caller's own notifications are updated
+    let collection = mongo_db.collection::<Notification>("notifications");
+    let filter = mongodb::bson::doc! {
+        "_id": ids_filter(&ids),
+        "user_id": user_id
+    };
+
+    // Look up which IDs exist first so each one can be reported individually
+    let mut owned: Vec<String> = Vec::new();
+    let mut cursor = collection
+        .find(filter.clone(), None)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    while let Ok(true) = cursor.advance().await {
+        if let Ok(Notification { id: Some(id), .. }) = cursor.deserialize_current() {
+            owned.push(id);
+        }
+    }
+
+    if !owned.is_empty() {
+        let update = mongodb::bson::doc! {
+            "$set": {"is_read": true}
+        };
+        collection
+            .update_many(filter, update, None)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+    }
+
+    for id in ids {
+        // ObjectIds come back as lowercase hex
+        if owned.contains(&id) || owned.contains(&id.to_lowercase()) {
+            result.succeeded.push(id);
+        } else {
+            result.fail(id, "Notification not found");
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(result))
+}
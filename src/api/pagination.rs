@@ -0,0 +1,91 @@
+use crate::config::Config;
+use actix_web::{HttpRequest, HttpResponseBuilder};
+use mongodb::bson::Document;
+use mongodb::Collection;
+
+/// Position of the returned page within a listing, used to build pagination headers
+pub struct PageInfo {
+    pub page: u64,
+    pub limit: u64,
+    /// Total number of matching items, when the endpoint counts them
+    pub total: Option<u64>,
+    /// Number of items on the returned page
+    pub returned: usize,
+    /// Deepest `offset + limit` the endpoint serves; no `next` link past it
+    pub max_depth: Option<u64>,
+}
+
+impl PageInfo {
+    fn has_next(&self) -> bool {
+        if let Some(max_depth) = self.max_depth {
+            if (self.page + 1).saturating_mul(self.limit) > max_depth {
+                return false;
+            }
+        }
+        match self.total {
+            Some(total) => self.page.saturating_mul(self.limit) < total,
+            // Without a total, a full page may have a successor
+            None => self.returned as u64 >= self.limit,
+        }
+    }
+}
+
+/// Adds RFC 5988 `Link` (`rel="next"`, `rel="prev"`) and `X-Total-Count` headers
+/// when `SERVER_PAGINATION_HEADERS` is enabled
+pub fn insert_pagination_headers(
+    builder: &mut HttpResponseBuilder,
+    req: &HttpRequest,
+    config: &Config,
+    info: &PageInfo,
+) {
+    if !config.server.pagination_headers {
+        return;
+    }
+
+    let mut links = Vec::new();
+    if info.has_next() {
+        links.push(format!("<{}>; rel=\"next\"", page_url(req, info.page + 1)));
+    }
+    if info.page > 1 {
+        links.push(format!("<{}>; rel=\"prev\"", page_url(req, info.page - 1)));
+    }
+    if !links.is_empty() {
+        builder.insert_header(("Link", links.join(", ")));
+    }
+    if let Some(total) = info.total {
+        builder.insert_header(("X-Total-Count", total.to_string()));
+    }
+}
+
+/// `PageInfo::total` of a MongoDB listing: `count_documents` on the listing's filter, or
+/// `None` without counting when `SERVER_PAGINATION_HEADERS` is off. Run it concurrently
+/// with the page query (`tokio::join!`) so the count adds no round-trip.
+pub async fn mongo_total<T: Send + Sync>(
+    config: &Config,
+    collection: &Collection<T>,
+    filter: Document,
+) -> mongodb::error::Result<Option<u64>> {
+    if !config.server.pagination_headers {
+        return Ok(None);
+    }
+    collection.count_documents(filter, None).await.map(Some)
+}
+
+/// Current request URL with `page` replaced, keeping every other query parameter
+fn page_url(req: &HttpRequest, page: u64) -> String {
+    let conn = req.connection_info();
+    let page_param = format!("page={}", page);
+    let mut params: Vec<&str> = req
+        .query_string()
+        .split('&')
+        .filter(|param| !param.is_empty() && param.split('=').next() != Some("page"))
+        .collect();
+    params.push(&page_param);
+    format!(
+        "{}://{}{}?{}",
+        conn.scheme(),
+        conn.host(),
+        req.path(),
+        params.join("&")
+    )
+}
@@ -0,0 +1,33 @@
+use crate::db::count_queries;
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    Error,
+};
+
+/// Logs how many SeaORM queries a request ran, with its route pattern, to make
+/// N+1 queries visible. Enabled by `SERVER_QUERY_COUNT_LOGGING`.
+/// Queries run while a streaming body is sent are not included.
+pub async fn log_query_count(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let method = req.method().clone();
+    let path = req.path().to_string();
+    let (result, stats) = count_queries(next.call(req)).await;
+
+    let route = result
+        .as_ref()
+        .ok()
+        .and_then(|res| res.request().match_pattern())
+        .unwrap_or(path);
+    log::info!(
+        "{} {} ran {} queries in {:?}",
+        method,
+        route,
+        stats.count,
+        stats.elapsed
+    );
+    result
+}
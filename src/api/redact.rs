@@ -0,0 +1,135 @@
+use crate::api::request_id::RequestId;
+use crate::config::Config;
+use actix_web::{
+    body::{to_bytes, BoxBody, MessageBody},
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    http::header::{HeaderMap, CONTENT_TYPE},
+    middleware::{Logger, Next},
+    web, Error, HttpMessage,
+};
+use serde_json::Value;
+
+const MASK: &str = "***";
+
+/// Whether a JSON key or query parameter is sensitive: it equals one of `fields` or ends
+/// with `_<field>` (so `token` also covers `refresh_token`), ignoring case
+fn is_redacted(key: &str, fields: &[String]) -> bool {
+    let key = key.to_ascii_lowercase();
+    fields.iter().any(|field| {
+        let field = field.to_ascii_lowercase();
+        key == field || key.ends_with(&format!("_{}", field))
+    })
+}
+
+/// Replace the values of sensitive fields, at any depth, with `***`
+pub fn redact_json(value: &mut Value, fields: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_redacted(key, fields) {
+                    *value = Value::String(MASK.to_string());
+                } else {
+                    redact_json(value, fields);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_json(item, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A body as it should appear in logs. Bodies that are not valid JSON are summarized
+/// rather than logged, since they cannot be redacted.
+pub fn redact_body(body: &[u8], fields: &[String]) -> String {
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut value) => {
+            redact_json(&mut value, fields);
+            value.to_string()
+        }
+        Err(_) => format!("<{} bytes, not JSON>", body.len()),
+    }
+}
+
+/// A query string with the values of sensitive parameters replaced by `***`
+pub fn redact_query(query: &str, fields: &[String]) -> String {
+    query
+        .split('&')
+        .map(|param| match param.split_once('=') {
+            Some((key, _)) if is_redacted(key, fields) => format!("{}={}", key, MASK),
+            _ => param.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn redacted_fields(req: &ServiceRequest) -> Vec<String> {
+    req.app_data::<web::Data<Config>>()
+        .map(|config| config.server.log_redacted_fields.clone())
+        .unwrap_or_default()
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+/// Access log in the default `Logger` format, with sensitive query parameters masked
+/// in the request line
+pub fn access_logger() -> Logger {
+    Logger::new(r#"%a "%{request_line}xi" %s %b "%{Referer}i" "%{User-Agent}i" %T %{request_id}xi"#)
+        .custom_request_replace("request_line", |req| {
+            let query = req.query_string();
+            let uri = if query.is_empty() {
+                req.path().to_string()
+            } else {
+                format!(
+                    "{}?{}",
+                    req.path(),
+                    redact_query(query, &redacted_fields(req))
+                )
+            };
+            format!("{} {} {:?}", req.method(), uri, req.version())
+        })
+        .custom_request_replace("request_id", |req| {
+            req.extensions()
+                .get::<RequestId>()
+                .map(|id| id.as_str().to_string())
+                .unwrap_or_else(|| "-".to_string())
+        })
+}
+
+/// Logs JSON request and response bodies at debug level with sensitive fields masked.
+/// Enabled by `SERVER_BODY_LOGGING`; other content types (e.g. the NDJSON comment
+/// export) are passed through without being buffered.
+pub async fn log_bodies(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let fields = redacted_fields(&req);
+    let route = format!("{} {}", req.method(), req.path());
+
+    if is_json(req.headers()) {
+        let body = req.extract::<web::Bytes>().await?;
+        log::debug!("{} request: {}", route, redact_body(&body, &fields));
+        req.set_payload(Payload::from(body));
+    }
+
+    let res = next.call(req).await?;
+    if !is_json(res.headers()) {
+        return Ok(res.map_into_boxed_body());
+    }
+
+    let (req, res) = res.into_parts();
+    let (res, body) = res.into_parts();
+    let body = to_bytes(body)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(e.into()))?;
+    log::debug!("{} response: {}", route, redact_body(&body, &fields));
+    Ok(ServiceResponse::new(req, res.set_body(BoxBody::new(body))))
+}
@@ -0,0 +1,73 @@
+use crate::config::Config;
+use actix_web::{
+    body::MessageBody,
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+    web, Error, FromRequest, HttpMessage, HttpRequest,
+};
+use std::future::{ready, Ready};
+use uuid::Uuid;
+
+/// Longest client-supplied request ID that is reused; longer ones are replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// ID of the current request, shared by the access log, Kafka events and the
+/// consumers that handle them so one action can be traced end to end
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Reuse a client-supplied ID if it is short and printable ASCII, otherwise generate one
+    fn from_header(value: Option<&HeaderValue>) -> Self {
+        value
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_REQUEST_ID_LEN
+                    && id.bytes().all(|b| b.is_ascii_graphic())
+            })
+            .map(|id| RequestId(id.to_string()))
+            .unwrap_or_else(|| RequestId(Uuid::new_v4().to_string()))
+    }
+}
+
+/// Assigns a `RequestId` from the `SERVER_REQUEST_ID_HEADER` request header (or a new
+/// UUID) and echoes it in the same response header
+pub async fn propagate_request_id(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let header = req
+        .app_data::<web::Data<Config>>()
+        .and_then(|config| HeaderName::from_bytes(config.server.request_id_header.as_bytes()).ok())
+        .unwrap_or_else(|| HeaderName::from_static("x-request-id"));
+    let request_id = RequestId::from_header(req.headers().get(&header));
+    req.extensions_mut().insert(request_id.clone());
+
+    let mut res = next.call(req).await?;
+    if let Ok(value) = HeaderValue::from_str(request_id.as_str()) {
+        res.headers_mut().insert(header, value);
+    }
+    Ok(res)
+}
+
+impl FromRequest for RequestId {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    /// Without the middleware (e.g. in tests) every extraction gets a fresh ID
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let request_id = req
+            .extensions()
+            .get::<RequestId>()
+            .cloned()
+            .unwrap_or_else(|| RequestId::from_header(None));
+        ready(Ok(request_id))
+    }
+}
@@ -0,0 +1,86 @@
+use crate::auth::{create_token, record_session, Claims};
+use crate::config::{Config, JwtExpiryMode};
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    http::header::{HeaderName, HeaderValue},
+    middleware::Next,
+    web, Error, HttpMessage,
+};
+use chrono::{Duration, Utc};
+use redis::Client as RedisClient;
+use uuid::Uuid;
+
+/// Response header carrying the re-issued token
+pub const REFRESHED_TOKEN_HEADER: &str = "x-refreshed-token";
+
+/// With `JWT_EXPIRY_MODE=sliding`, re-issues the caller's token when it has fewer than
+/// `JWT_SLIDING_THRESHOLD_MINUTES` left and returns it in `X-Refreshed-Token`.
+///
+/// Reads the `Claims` the `AuthenticatedUser` extractor stored, so only requests that
+/// authenticated are refreshed. The new token keeps the `jti`, so it stays the same
+/// session: revoking it revokes every token of the chain.
+pub async fn refresh_sliding_session(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let config = req.app_data::<web::Data<Config>>().cloned();
+    let redis_client = req.app_data::<web::Data<RedisClient>>().cloned();
+
+    let mut res = next.call(req).await?;
+    let Some(config) = config.filter(|c| c.jwt.expiry_mode == JwtExpiryMode::Sliding) else {
+        return Ok(res);
+    };
+    let claims = res.request().extensions().get::<Claims>().cloned();
+    let Some(claims) = claims.filter(|claims| {
+        claims.exp - Utc::now().timestamp() < config.jwt.sliding_threshold_minutes * 60
+    }) else {
+        return Ok(res);
+    };
+
+    let refreshed = refreshed_claims(&config, claims);
+    let token = match create_token(&refreshed, &config.jwt.secret) {
+        Ok(token) => token,
+        Err(e) => {
+            log::error!("Failed to refresh token: {:?}", e);
+            return Ok(res);
+        }
+    };
+
+    if let (Some(redis_client), Ok(user_id)) = (redis_client, refreshed.sub.parse::<i64>()) {
+        let user_agent = res
+            .request()
+            .headers()
+            .get("User-Agent")
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        if let Err(e) =
+            record_session(&redis_client, &config, user_id, &refreshed, user_agent).await
+        {
+            log::warn!("Failed to record refreshed session: {:?}", e);
+        }
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&token) {
+        res.headers_mut()
+            .insert(HeaderName::from_static(REFRESHED_TOKEN_HEADER), value);
+    }
+    Ok(res)
+}
+
+/// Same identity, session and audience, with a full lifetime from now
+fn refreshed_claims(config: &Config, claims: Claims) -> Claims {
+    let now = Utc::now();
+    let hours = config.jwt.lifetime_hours(claims.aud.as_deref());
+    Claims {
+        exp: (now + Duration::hours(hours)).timestamp(),
+        iat: now.timestamp(),
+        // Tokens from before session tracking have no jti; start a session for them
+        jti: if claims.jti.is_empty() {
+            Uuid::new_v4().to_string()
+        } else {
+            claims.jti
+        },
+        ..claims
+    }
+}
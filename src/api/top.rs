@@ -1,11 +1,29 @@
-use crate::db::DbPool;
-use crate::entities::{feed, user};
-use crate::models::{TopFeed, TopUser};
-use actix_web::{web, HttpResponse, Result as ActixResult};
+use crate::api::pagination::{insert_pagination_headers, PageInfo};
+use crate::auth::AdminUser;
+use crate::config::Config;
+use crate::db::{redis_key, DbPool};
+use crate::entities::{feed, feed_like, user};
+use crate::jobs::{
+    calculate_top_comments, calculate_top_comments_by_user, calculate_top_feeds_liked,
+    calculate_top_feeds_liked_by_user, calculate_top_feeds_viewed, calculate_top_users_liked,
+    calculate_trending_feeds, top_stats_window_start, TrendingWindow,
+};
+use crate::models::{RawScore, ScoreBreakdown, TopDebug, TopFeed, TopUser};
+use crate::services::content::truncate_chars;
+use actix_web::{
+    web, FromRequest, HttpRequest, HttpResponse, HttpResponseBuilder, Result as ActixResult,
+};
+use chrono::Utc;
+use futures::stream;
 use log;
+use mongodb::Database as MongoDatabase;
 use redis::Client as RedisClient;
-use sea_orm::EntityTrait;
-use serde::Deserialize;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QuerySelect};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
 use utoipa::ToSchema;
 
 #[derive(Deserialize, ToSchema)]
@@ -14,6 +32,168 @@ pub struct TopQuery {
     pub page: Option<u64>,
     #[schema(example = 10)]
     pub limit: Option<u64>,
+    /// Admins only: wrap the ranking as `{"items": [...], "debug": {...}}` with the raw
+    /// Redis scores and the entries dropped during enrichment
+    pub debug: Option<bool>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TopFeedsQuery {
+    /// Only rank feeds posted by this user
+    #[schema(example = 1)]
+    pub user_id: Option<i64>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ExplainQuery {
+    /// Admins only: add `explain` with each returned feed's Redis score next to a live
+    /// MySQL like count
+    pub explain: Option<bool>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TrendingQuery {
+    /// `day` (default) or `week`
+    pub window: Option<TrendingWindow>,
+}
+
+/// Requested `(page, limit)` with defaults applied and `limit` clamped to
+/// `TOP_STATS_MAX_LIMIT`, so one request cannot ask Redis for a huge range
+pub fn page_and_limit(query: &TopQuery, config: &Config) -> (u64, u64) {
+    (
+        query.page.unwrap_or(1).max(1),
+        query
+            .limit
+            .unwrap_or(10)
+            .clamp(1, config.top_stats.max_limit.max(1)),
+    )
+}
+
+/// 0-based inclusive rank range for the requested page, or `None` when it lies past the
+/// ranks Redis can address (signed 64-bit)
+pub fn rank_range(query: &TopQuery, config: &Config) -> Option<(u64, u64)> {
+    let (page, limit) = page_and_limit(query, config);
+    let start = (page - 1).checked_mul(limit)?;
+    let stop = start.checked_add(limit - 1)?;
+    (stop <= i64::MAX as u64).then_some((start, stop))
+}
+
+fn page_out_of_range() -> HttpResponse {
+    HttpResponse::BadRequest().json(json!({"error": "page is out of range"}))
+}
+
+/// 200 response with pagination headers. Rankings have no cheap total, so
+/// `X-Total-Count` is omitted and `next` is offered after a full page.
+fn ranking_response(
+    http_req: &HttpRequest,
+    config: &Config,
+    query: &TopQuery,
+    returned: usize,
+) -> HttpResponseBuilder {
+    let (page, limit) = page_and_limit(query, config);
+    let mut response = HttpResponse::Ok();
+    insert_pagination_headers(
+        &mut response,
+        http_req,
+        config,
+        &PageInfo {
+            page,
+            limit,
+            total: None,
+            returned,
+            max_depth: None,
+        },
+    );
+    response
+}
+
+/// Whether `?debug=true` was asked for. Only admins may: anyone else gets the
+/// `AdminUser` rejection (`401`/`403`).
+async fn debug_requested(http_req: &HttpRequest, query: &TopQuery) -> ActixResult<bool> {
+    admin_flag(http_req, query.debug).await
+}
+
+/// Whether an admin-only flag is set, rejecting non-admins who set it
+async fn admin_flag(http_req: &HttpRequest, flag: Option<bool>) -> ActixResult<bool> {
+    if !flag.unwrap_or(false) {
+        return Ok(false);
+    }
+    AdminUser::extract(http_req).await?;
+    Ok(true)
+}
+
+/// Ranking body: the plain array, or with `debug` the array under `items` next to it
+fn ranking_body<T: Serialize>(
+    mut response: HttpResponseBuilder,
+    items: Vec<T>,
+    debug: Option<TopDebug>,
+) -> HttpResponse {
+    match debug {
+        Some(debug) => response.json(json!({ "items": items, "debug": debug })),
+        None => response.json(items),
+    }
+}
+
+/// Ranks read from a `top:*` sorted set, with what `?debug=true` reports about them
+struct CachedPage<T> {
+    items: Vec<T>,
+    debug: TopDebug,
+}
+
+impl<T> CachedPage<T> {
+    fn empty() -> Self {
+        CachedPage {
+            items: Vec::new(),
+            debug: TopDebug::default(),
+        }
+    }
+
+    /// `items` enriched from the Redis `results`; members whose ID is not among them
+    /// were dropped
+    fn new(results: Vec<(String, f64)>, items: Vec<T>, id: impl Fn(&T) -> i64) -> Self {
+        let kept: HashSet<i64> = items.iter().map(id).collect();
+        let dropped = results
+            .iter()
+            .filter(|(member, _)| member.parse::<i64>().map_or(true, |id| !kept.contains(&id)))
+            .map(|(member, _)| member.clone())
+            .collect::<Vec<_>>();
+        if !dropped.is_empty() {
+            log::warn!(
+                "Dropped top ranking entries without a feed/user: {:?}",
+                dropped
+            );
+        }
+        CachedPage {
+            items,
+            debug: TopDebug {
+                raw_scores: results
+                    .into_iter()
+                    .map(|(member, score)| RawScore { member, score })
+                    .collect(),
+                dropped,
+            },
+        }
+    }
+}
+
+/// Cut each feed's content to `TOP_STATS_CONTENT_PREVIEW_CHARS`
+fn content_previews(config: &Config, mut ranking: Vec<TopFeed>) -> Vec<TopFeed> {
+    for top_feed in &mut ranking {
+        top_feed.content =
+            truncate_chars(&top_feed.content, config.top_stats.content_preview_chars);
+    }
+    ranking
+}
+
+/// Part of a rank range beyond what the job caches in Redis, as `(offset, count)`.
+/// Those ranks are computed from the databases on demand instead of returning nothing.
+fn uncached_range(config: &Config, start: u64, stop: u64) -> Option<(u64, u64)> {
+    let cache_size = config.top_stats.cache_size;
+    if stop < cache_size {
+        return None;
+    }
+    let offset = start.max(cache_size);
+    Some((offset, stop - offset + 1))
 }
 
 #[utoipa::path(
@@ -21,30 +201,72 @@ pub struct TopQuery {
     path = "/api/top/users-liked",
     params(
         ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
-        ("limit" = Option<u64>, Query, description = "Items per page (default: 10)")
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 10, larger values are clamped to TOP_STATS_MAX_LIMIT)"),
+        ("debug" = Option<bool>, Query, description = "Admins only: return {items, debug} with the raw Redis scores and dropped entries")
     ),
     responses(
-        (status = 200, description = "Top users liked", body = Vec<TopUser>)
+        (status = 200, description = "Top users liked", body = Vec<TopUser>),
+        (status = 400, description = "page * limit is past the ranks Redis can address"),
+        (status = 401, description = "debug=true without a valid token"),
+        (status = 403, description = "debug=true by a non-admin")
     ),
     tag = "top"
 )]
 pub async fn get_top_users_liked(
+    http_req: HttpRequest,
     redis_client: web::Data<RedisClient>,
     pool: web::Data<DbPool>,
+    config: web::Data<Config>,
     query: web::Query<TopQuery>,
 ) -> ActixResult<HttpResponse> {
-    let page = query.page.unwrap_or(1);
-    let limit = query.limit.unwrap_or(10);
-    let start = ((page - 1) * limit) as i64;
-    let stop = start + limit as i64 - 1;
+    let Some((start, stop)) = rank_range(&query, &config) else {
+        return Ok(page_out_of_range());
+    };
+    let debug = debug_requested(&http_req, &query).await?;
+
+    let cached = if start < config.top_stats.cache_size {
+        cached_users_liked(
+            &redis_client,
+            &pool,
+            &config,
+            start,
+            stop.min(config.top_stats.cache_size - 1),
+        )
+        .await?
+    } else {
+        CachedPage::empty()
+    };
+    let mut ranking = cached.items;
 
+    if let Some((offset, count)) = uncached_range(&config, start, stop) {
+        ranking.extend(
+            calculate_top_users_liked(pool.get_ref(), top_stats_window_start(), offset, count)
+                .await,
+        );
+    }
+
+    let response = ranking_response(&http_req, &config, &query, ranking.len());
+    Ok(ranking_body(
+        response,
+        ranking,
+        debug.then_some(cached.debug),
+    ))
+}
+
+async fn cached_users_liked(
+    redis_client: &RedisClient,
+    pool: &DbPool,
+    config: &Config,
+    start: u64,
+    stop: u64,
+) -> ActixResult<CachedPage<TopUser>> {
     let mut conn = redis_client
         .get_async_connection()
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
     let results: Vec<(String, f64)> = redis::cmd("ZREVRANGE")
-        .arg("top:users_liked")
+        .arg(redis_key(config, "top:users_liked"))
         .arg(start)
         .arg(stop)
         .arg("WITHSCORES")
@@ -53,7 +275,7 @@ pub async fn get_top_users_liked(
         .unwrap_or_default();
 
     if results.is_empty() {
-        return Ok(HttpResponse::Ok().json(Vec::<TopUser>::new()));
+        return Ok(CachedPage::empty());
     }
 
     let user_ids: Vec<i64> = results
@@ -62,14 +284,16 @@ pub async fn get_top_users_liked(
         .collect();
 
     if user_ids.is_empty() {
-        return Ok(HttpResponse::Ok().json(Vec::<TopUser>::new()));
+        return Ok(CachedPage::new(results, Vec::new(), |user: &TopUser| {
+            user.user_id
+        }));
     }
 
     let mut username_map: std::collections::HashMap<i64, String> = std::collections::HashMap::new();
 
     // Batch fetch usernames using SeaORM
     for user_id in &user_ids {
-        if let Ok(Some(user_model)) = user::Entity::find_by_id(*user_id).one(pool.get_ref()).await {
+        if let Ok(Some(user_model)) = user::Entity::find_by_id(*user_id).one(pool).await {
             username_map.insert(*user_id, user_model.username);
         }
     }
@@ -89,7 +313,9 @@ pub async fn get_top_users_liked(
         })
         .collect();
 
-    Ok(HttpResponse::Ok().json(top_users))
+    Ok(CachedPage::new(results, top_users, |user: &TopUser| {
+        user.user_id
+    }))
 }
 
 #[utoipa::path(
@@ -97,30 +323,99 @@ pub async fn get_top_users_liked(
     path = "/api/top/feeds-commented",
     params(
         ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
-        ("limit" = Option<u64>, Query, description = "Items per page (default: 10)")
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 10, larger values are clamped to TOP_STATS_MAX_LIMIT)"),
+        ("debug" = Option<bool>, Query, description = "Admins only: return {items, debug} with the raw Redis scores and dropped entries"),
+        ("user_id" = Option<i64>, Query, description = "Only rank feeds posted by this user")
     ),
     responses(
-        (status = 200, description = "Top feeds by comments", body = Vec<TopFeed>)
+        (status = 200, description = "Top feeds by comments", body = Vec<TopFeed>),
+        (status = 400, description = "page * limit is past the ranks Redis can address"),
+        (status = 401, description = "debug=true without a valid token"),
+        (status = 403, description = "debug=true by a non-admin")
     ),
     tag = "top"
 )]
 pub async fn get_top_comments(
+    http_req: HttpRequest,
     redis_client: web::Data<RedisClient>,
     pool: web::Data<DbPool>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
     query: web::Query<TopQuery>,
+    feeds_query: web::Query<TopFeedsQuery>,
 ) -> ActixResult<HttpResponse> {
-    let page = query.page.unwrap_or(1);
-    let limit = query.limit.unwrap_or(10);
-    let start = ((page - 1) * limit) as i64;
-    let stop = start + limit as i64 - 1;
+    let Some((start, stop)) = rank_range(&query, &config) else {
+        return Ok(page_out_of_range());
+    };
+    let debug = debug_requested(&http_req, &query).await?;
+
+    if let Some(user_id) = feeds_query.user_id {
+        let ranking = calculate_top_comments_by_user(
+            mongo_db.get_ref(),
+            pool.get_ref(),
+            user_id,
+            top_stats_window_start(),
+            start,
+            stop - start + 1,
+        )
+        .await;
+        let response = ranking_response(&http_req, &config, &query, ranking.len());
+        return Ok(ranking_body(
+            response,
+            content_previews(&config, ranking),
+            debug.then(TopDebug::default),
+        ));
+    }
+
+    let cached = if start < config.top_stats.cache_size {
+        cached_comments(
+            &redis_client,
+            &pool,
+            &config,
+            start,
+            stop.min(config.top_stats.cache_size - 1),
+        )
+        .await?
+    } else {
+        CachedPage::empty()
+    };
+    let mut ranking = cached.items;
+
+    if let Some((offset, count)) = uncached_range(&config, start, stop) {
+        ranking.extend(
+            calculate_top_comments(
+                mongo_db.get_ref(),
+                pool.get_ref(),
+                top_stats_window_start(),
+                offset,
+                count,
+            )
+            .await,
+        );
+    }
+
+    let response = ranking_response(&http_req, &config, &query, ranking.len());
+    Ok(ranking_body(
+        response,
+        content_previews(&config, ranking),
+        debug.then_some(cached.debug),
+    ))
+}
 
+async fn cached_comments(
+    redis_client: &RedisClient,
+    pool: &DbPool,
+    config: &Config,
+    start: u64,
+    stop: u64,
+) -> ActixResult<CachedPage<TopFeed>> {
     let mut conn = redis_client
         .get_async_connection()
         .await
         .map_err(actix_web::error::ErrorInternalServerError)?;
 
     let results: Vec<(String, f64)> = redis::cmd("ZREVRANGE")
-        .arg("top:comments")
+        .arg(redis_key(config, "top:comments"))
         .arg(start)
         .arg(stop)
         .arg("WITHSCORES")
@@ -132,7 +427,7 @@ pub async fn get_top_comments(
 
     if results.is_empty() {
         log::info!("get_top_comments: No results from Redis");
-        return Ok(HttpResponse::Ok().json(Vec::<TopFeed>::new()));
+        return Ok(CachedPage::empty());
     }
 
     let feed_ids: Vec<i64> = results
@@ -144,7 +439,9 @@ pub async fn get_top_comments(
 
     if feed_ids.is_empty() {
         log::warn!("get_top_comments: Failed to parse feed_ids from Redis results");
-        return Ok(HttpResponse::Ok().json(Vec::<TopFeed>::new()));
+        return Ok(CachedPage::new(results, Vec::new(), |feed: &TopFeed| {
+            feed.feed_id
+        }));
     }
 
     let mut feed_map: std::collections::HashMap<i64, (i64, String, String)> =
@@ -153,17 +450,25 @@ pub async fn get_top_comments(
     // Fetch feed info with user using SeaORM
     for feed_id in &feed_ids {
         log::debug!("get_top_comments: Looking up feed_id: {}", feed_id);
-        match feed::Entity::find_by_id(*feed_id).one(pool.get_ref()).await {
+        match feed::Entity::find_by_id(*feed_id).one(pool).await {
+            // Hidden, scheduled and expired feeds stay out of the rankings until they leave Redis
+            Ok(Some(feed_model))
+                if feed_model.hidden_at.is_some()
+                    || !feed_model.is_live(Utc::now())
+                    || feed_model.has_expired(Utc::now()) =>
+            {
+                log::debug!(
+                    "get_top_comments: Skipping hidden, scheduled or expired feed {}",
+                    feed_id
+                );
+            }
             Ok(Some(feed_model)) => {
                 log::debug!(
                     "get_top_comments: Found feed {} with user_id: {}",
                     feed_id,
                     feed_model.user_id
                 );
-                match user::Entity::find_by_id(feed_model.user_id)
-                    .one(pool.get_ref())
-                    .await
-                {
+                match user::Entity::find_by_id(feed_model.user_id).one(pool).await {
                     Ok(Some(user_model)) => {
                         log::debug!(
                             "get_top_comments: Found user {} with username: {}",
@@ -229,7 +534,9 @@ pub async fn get_top_comments(
         top_feeds.len(),
         results.len()
     );
-    Ok(HttpResponse::Ok().json(top_feeds))
+    Ok(CachedPage::new(results, top_feeds, |feed: &TopFeed| {
+        feed.feed_id
+    }))
 }
 
 #[utoipa::path(
@@ -237,23 +544,72 @@ pub async fn get_top_comments(
     path = "/api/top/feeds-viewed",
     params(
         ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
-        ("limit" = Option<u64>, Query, description = "Items per page (default: 10)")
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 10, larger values are clamped to TOP_STATS_MAX_LIMIT)"),
+        ("debug" = Option<bool>, Query, description = "Admins only: return {items, debug} with the raw Redis scores and dropped entries")
     ),
     responses(
-        (status = 200, description = "Top feeds viewed", body = Vec<TopFeed>)
+        (status = 200, description = "Top feeds viewed", body = Vec<TopFeed>),
+        (status = 400, description = "page * limit is past the ranks Redis can address"),
+        (status = 401, description = "debug=true without a valid token"),
+        (status = 403, description = "debug=true by a non-admin")
     ),
     tag = "top"
 )]
 pub async fn get_top_feeds_viewed(
+    http_req: HttpRequest,
     redis_client: web::Data<RedisClient>,
     pool: web::Data<DbPool>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
     query: web::Query<TopQuery>,
 ) -> ActixResult<HttpResponse> {
-    let page = query.page.unwrap_or(1);
-    let limit = query.limit.unwrap_or(10);
-    let start = ((page - 1) * limit) as i64;
-    let stop = start + limit as i64 - 1;
+    let Some((start, stop)) = rank_range(&query, &config) else {
+        return Ok(page_out_of_range());
+    };
+    let debug = debug_requested(&http_req, &query).await?;
+
+    let cached = if start < config.top_stats.cache_size {
+        cached_feeds_viewed(
+            &redis_client,
+            &pool,
+            &config,
+            start,
+            stop.min(config.top_stats.cache_size - 1),
+        )
+        .await?
+    } else {
+        CachedPage::empty()
+    };
+    let mut ranking = cached.items;
+
+    if let Some((offset, count)) = uncached_range(&config, start, stop) {
+        ranking.extend(
+            calculate_top_feeds_viewed(
+                mongo_db.get_ref(),
+                pool.get_ref(),
+                top_stats_window_start(),
+                offset,
+                count,
+            )
+            .await,
+        );
+    }
+
+    let response = ranking_response(&http_req, &config, &query, ranking.len());
+    Ok(ranking_body(
+        response,
+        content_previews(&config, ranking),
+        debug.then_some(cached.debug),
+    ))
+}
 
+async fn cached_feeds_viewed(
+    redis_client: &RedisClient,
+    pool: &DbPool,
+    config: &Config,
+    start: u64,
+    stop: u64,
+) -> ActixResult<CachedPage<TopFeed>> {
     let mut conn = redis_client
         .get_async_connection()
         .await
@@ -262,7 +618,7 @@ pub async fn get_top_feeds_viewed(
     // Use ZREVRANGE with WITHSCORES to get feed_ids and scores
     // Now we only store feed_id as member, score is view count
     let results: Vec<(String, f64)> = redis::cmd("ZREVRANGE")
-        .arg("top:feeds_viewed")
+        .arg(redis_key(config, "top:feeds_viewed"))
         .arg(start)
         .arg(stop)
         .arg("WITHSCORES")
@@ -271,7 +627,7 @@ pub async fn get_top_feeds_viewed(
         .unwrap_or_default();
 
     if results.is_empty() {
-        return Ok(HttpResponse::Ok().json(Vec::<TopFeed>::new()));
+        return Ok(CachedPage::empty());
     }
 
     let feed_ids: Vec<i64> = results
@@ -280,7 +636,9 @@ pub async fn get_top_feeds_viewed(
         .collect();
 
     if feed_ids.is_empty() {
-        return Ok(HttpResponse::Ok().json(Vec::<TopFeed>::new()));
+        return Ok(CachedPage::new(results, Vec::new(), |feed: &TopFeed| {
+            feed.feed_id
+        }));
     }
 
     let mut feed_map: std::collections::HashMap<i64, (i64, String, String)> =
@@ -288,10 +646,15 @@ pub async fn get_top_feeds_viewed(
 
     // Fetch feed info with user using SeaORM
     for feed_id in &feed_ids {
-        if let Ok(Some(feed_model)) = feed::Entity::find_by_id(*feed_id).one(pool.get_ref()).await {
-            if let Ok(Some(user_model)) = user::Entity::find_by_id(feed_model.user_id)
-                .one(pool.get_ref())
-                .await
+        if let Ok(Some(feed_model)) = feed::Entity::find_by_id(*feed_id).one(pool).await {
+            if feed_model.hidden_at.is_some()
+                || !feed_model.is_live(Utc::now())
+                || feed_model.has_expired(Utc::now())
+            {
+                continue;
+            }
+            if let Ok(Some(user_model)) =
+                user::Entity::find_by_id(feed_model.user_id).one(pool).await
             {
                 feed_map.insert(
                     *feed_id,
@@ -319,7 +682,11 @@ pub async fn get_top_feeds_viewed(
         })
         .collect();
 
-    Ok(HttpResponse::Ok().json(top_feeds_viewed))
+    Ok(CachedPage::new(
+        results,
+        top_feeds_viewed,
+        |feed: &TopFeed| feed.feed_id,
+    ))
 }
 
 #[utoipa::path(
@@ -327,23 +694,194 @@ pub async fn get_top_feeds_viewed(
     path = "/api/top/feeds-liked",
     params(
         ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
-        ("limit" = Option<u64>, Query, description = "Items per page (default: 10)")
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 10, larger values are clamped to TOP_STATS_MAX_LIMIT)"),
+        ("debug" = Option<bool>, Query, description = "Admins only: return {items, debug} with the raw Redis scores and dropped entries"),
+        ("user_id" = Option<i64>, Query, description = "Only rank feeds posted by this user"),
+        ("explain" = Option<bool>, Query, description = "Admins only: return {items, explain} with each feed's Redis score, live MySQL like count and their delta")
     ),
     responses(
-        (status = 200, description = "Top feeds liked", body = Vec<TopFeed>)
+        (status = 200, description = "Top feeds liked", body = Vec<TopFeed>),
+        (status = 400, description = "page * limit is past the ranks Redis can address"),
+        (status = 401, description = "debug=true or explain=true without a valid token"),
+        (status = 403, description = "debug=true or explain=true by a non-admin")
     ),
     tag = "top"
 )]
 pub async fn get_top_feeds_liked(
+    http_req: HttpRequest,
     redis_client: web::Data<RedisClient>,
     pool: web::Data<DbPool>,
+    config: web::Data<Config>,
     query: web::Query<TopQuery>,
+    feeds_query: web::Query<TopFeedsQuery>,
+    explain_query: web::Query<ExplainQuery>,
 ) -> ActixResult<HttpResponse> {
-    let page = query.page.unwrap_or(1);
-    let limit = query.limit.unwrap_or(10);
-    let start = ((page - 1) * limit) as i64;
-    let stop = start + limit as i64 - 1;
+    let Some((start, stop)) = rank_range(&query, &config) else {
+        return Ok(page_out_of_range());
+    };
+    let debug = debug_requested(&http_req, &query).await?;
+    let explain = admin_flag(&http_req, explain_query.explain).await?;
+
+    if let Some(user_id) = feeds_query.user_id {
+        let ranking = calculate_top_feeds_liked_by_user(
+            pool.get_ref(),
+            &config.top_stats,
+            user_id,
+            top_stats_window_start(),
+            start,
+            stop - start + 1,
+        )
+        .await;
+        let response = ranking_response(&http_req, &config, &query, ranking.len());
+        let breakdowns = if explain {
+            Some(score_breakdowns(&redis_client, &pool, &config, &ranking).await?)
+        } else {
+            None
+        };
+        return Ok(explained_body(
+            response,
+            content_previews(&config, ranking),
+            debug.then(TopDebug::default),
+            breakdowns,
+        ));
+    }
+
+    let cached = if start < config.top_stats.cache_size {
+        cached_feeds_liked(
+            &redis_client,
+            &pool,
+            &config,
+            start,
+            stop.min(config.top_stats.cache_size - 1),
+        )
+        .await?
+    } else {
+        CachedPage::empty()
+    };
+    let mut ranking = cached.items;
+
+    if let Some((offset, count)) = uncached_range(&config, start, stop) {
+        ranking.extend(
+            calculate_top_feeds_liked(
+                pool.get_ref(),
+                &config.top_stats,
+                top_stats_window_start(),
+                offset,
+                count,
+            )
+            .await,
+        );
+    }
+
+    let response = ranking_response(&http_req, &config, &query, ranking.len());
+    let breakdowns = if explain {
+        Some(score_breakdowns(&redis_client, &pool, &config, &ranking).await?)
+    } else {
+        None
+    };
+    Ok(explained_body(
+        response,
+        content_previews(&config, ranking),
+        debug.then_some(cached.debug),
+        breakdowns,
+    ))
+}
+
+/// `ranking_body`, plus `explain` next to `items` when it was asked for
+fn explained_body(
+    mut response: HttpResponseBuilder,
+    items: Vec<TopFeed>,
+    debug: Option<TopDebug>,
+    explain: Option<Vec<ScoreBreakdown>>,
+) -> HttpResponse {
+    let Some(explain) = explain else {
+        return ranking_body(response, items, debug);
+    };
+    let mut body = json!({ "items": items, "explain": explain });
+    if let Some(debug) = debug {
+        body["debug"] = json!(debug);
+    }
+    response.json(body)
+}
+
+/// Each feed's `top:feeds_liked` score next to a live `COUNT(*)` of its likes in the
+/// ranking window and their weighted score, to tell a stale or drifted cache from the
+/// database it mirrors
+async fn score_breakdowns(
+    redis_client: &RedisClient,
+    pool: &DbPool,
+    config: &Config,
+    ranking: &[TopFeed],
+) -> ActixResult<Vec<ScoreBreakdown>> {
+    if ranking.is_empty() {
+        return Ok(Vec::new());
+    }
+    let feed_ids: Vec<i64> = ranking.iter().map(|f| f.feed_id).collect();
 
+    let mut conn = redis_client
+        .get_async_connection()
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let ranking_key = redis_key(config, "top:feeds_liked");
+    let mut pipe = redis::pipe();
+    for feed_id in &feed_ids {
+        pipe.cmd("ZSCORE").arg(&ranking_key).arg(feed_id);
+    }
+    let redis_scores: Vec<Option<f64>> = pipe
+        .query_async(&mut conn)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    // Likes per feed and liker flags, so each group can be weighted like the ranking does
+    let rows: Vec<(i64, Option<bool>, Option<bool>, i64)> = feed_like::Entity::find()
+        .select_only()
+        .column(feed_like::Column::FeedId)
+        .column(user::Column::IsAdmin)
+        .column(user::Column::IsVerified)
+        .column_as(feed_like::Column::Id.count(), "count")
+        .left_join(user::Entity)
+        .filter(feed_like::Column::FeedId.is_in(feed_ids.clone()))
+        .filter(feed_like::Column::CreatedAt.gte(top_stats_window_start()))
+        .group_by(feed_like::Column::FeedId)
+        .group_by(user::Column::IsAdmin)
+        .group_by(user::Column::IsVerified)
+        .into_tuple()
+        .all(pool)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let mut counts: HashMap<i64, (i64, f64)> = HashMap::new();
+    for (feed_id, is_admin, is_verified, count) in rows {
+        let weight = config
+            .top_stats
+            .like_weight(is_admin.unwrap_or(false), is_verified.unwrap_or(false));
+        let (mysql_count, mysql_score) = counts.entry(feed_id).or_default();
+        *mysql_count += count;
+        *mysql_score += weight * count as f64;
+    }
+
+    Ok(feed_ids
+        .into_iter()
+        .zip(redis_scores)
+        .map(|(feed_id, redis_score)| {
+            let (mysql_count, mysql_score) = counts.get(&feed_id).copied().unwrap_or_default();
+            ScoreBreakdown {
+                feed_id,
+                redis_score,
+                mysql_count,
+                mysql_score,
+                delta: redis_score.map(|score| score - mysql_score),
+            }
+        })
+        .collect())
+}
+
+async fn cached_feeds_liked(
+    redis_client: &RedisClient,
+    pool: &DbPool,
+    config: &Config,
+    start: u64,
+    stop: u64,
+) -> ActixResult<CachedPage<TopFeed>> {
     let mut conn = redis_client
         .get_async_connection()
         .await
@@ -352,7 +890,7 @@ pub async fn get_top_feeds_liked(
     // Use ZREVRANGE with WITHSCORES to get feed_ids and scores
     // Now we only store feed_id as member, score is like count
     let results: Vec<(String, f64)> = redis::cmd("ZREVRANGE")
-        .arg("top:feeds_liked")
+        .arg(redis_key(config, "top:feeds_liked"))
         .arg(start)
         .arg(stop)
         .arg("WITHSCORES")
@@ -361,7 +899,7 @@ pub async fn get_top_feeds_liked(
         .unwrap_or_default();
 
     if results.is_empty() {
-        return Ok(HttpResponse::Ok().json(Vec::<TopFeed>::new()));
+        return Ok(CachedPage::empty());
     }
 
     let feed_ids: Vec<i64> = results
@@ -370,7 +908,9 @@ pub async fn get_top_feeds_liked(
         .collect();
 
     if feed_ids.is_empty() {
-        return Ok(HttpResponse::Ok().json(Vec::<TopFeed>::new()));
+        return Ok(CachedPage::new(results, Vec::new(), |feed: &TopFeed| {
+            feed.feed_id
+        }));
     }
 
     let mut feed_map: std::collections::HashMap<i64, (i64, String, String)> =
@@ -378,10 +918,15 @@ pub async fn get_top_feeds_liked(
 
     // Fetch feed info with user using SeaORM
     for feed_id in &feed_ids {
-        if let Ok(Some(feed_model)) = feed::Entity::find_by_id(*feed_id).one(pool.get_ref()).await {
-            if let Ok(Some(user_model)) = user::Entity::find_by_id(feed_model.user_id)
-                .one(pool.get_ref())
-                .await
+        if let Ok(Some(feed_model)) = feed::Entity::find_by_id(*feed_id).one(pool).await {
+            if feed_model.hidden_at.is_some()
+                || !feed_model.is_live(Utc::now())
+                || feed_model.has_expired(Utc::now())
+            {
+                continue;
+            }
+            if let Ok(Some(user_model)) =
+                user::Entity::find_by_id(feed_model.user_id).one(pool).await
             {
                 feed_map.insert(
                     *feed_id,
@@ -409,5 +954,147 @@ pub async fn get_top_feeds_liked(
         })
         .collect();
 
-    Ok(HttpResponse::Ok().json(top_feeds_liked))
+    Ok(CachedPage::new(
+        results,
+        top_feeds_liked,
+        |feed: &TopFeed| feed.feed_id,
+    ))
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct TopStreamQuery {
+    /// Feeds per event (default: 10, at most `TOP_STATS_CACHE_SIZE`)
+    #[schema(example = 10)]
+    pub limit: Option<u64>,
+}
+
+/// SSE event name of `GET /api/top/feeds-liked/stream`
+pub const FEEDS_LIKED_EVENT: &str = "top-feeds-liked";
+
+#[utoipa::path(
+    get,
+    path = "/api/top/feeds-liked/stream",
+    params(
+        ("limit" = Option<u64>, Query, description = "Feeds per event (default: 10, at most TOP_STATS_CACHE_SIZE)")
+    ),
+    responses(
+        (status = 200, description = "Server-sent events named top-feeds-liked, each with the current top feeds (Vec<TopFeed>) as data. The first event is sent right away, later ones only when the ranking changed", content_type = "text/event-stream"),
+        (status = 404, description = "Streaming disabled (TOP_STATS_STREAM_INTERVAL_SECONDS=0)")
+    ),
+    tag = "top"
+)]
+pub async fn stream_top_feeds_liked(
+    redis_client: web::Data<RedisClient>,
+    pool: web::Data<DbPool>,
+    config: web::Data<Config>,
+    query: web::Query<TopStreamQuery>,
+) -> ActixResult<HttpResponse> {
+    let interval_seconds = config.top_stats.stream_interval_seconds;
+    if interval_seconds == 0 {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "Top feed streaming is disabled"
+        })));
+    }
+    let limit = query
+        .limit
+        .unwrap_or(10)
+        .clamp(1, config.top_stats.cache_size.max(1));
+
+    // The first snapshot is read before the status is sent, so a Redis outage is a 500
+    let first = top_feeds_liked_event(&redis_client, &pool, &config, limit).await?;
+
+    // Polls the sorted set and sends an event only when the ranking differs from the
+    // last one sent. The stream is dropped, and polling stops, when the client goes away.
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_seconds));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let state = (Some(first), String::new(), interval);
+    let body = stream::unfold(state, move |(pending, mut last, mut interval)| {
+        let (redis_client, pool, config) = (redis_client.clone(), pool.clone(), config.clone());
+        async move {
+            let mut next = pending;
+            while next.as_ref().is_none_or(|event| *event == last) {
+                interval.tick().await;
+                next = match top_feeds_liked_event(&redis_client, &pool, &config, limit).await {
+                    Ok(event) => Some(event),
+                    Err(e) => {
+                        log::warn!("Failed to refresh top feeds stream: {:?}", e);
+                        None
+                    }
+                };
+            }
+            last = next.unwrap_or_default();
+            let bytes = web::Bytes::from(last.clone());
+            Some((Ok::<_, actix_web::Error>(bytes), (None, last, interval)))
+        }
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        // Compressed bodies are buffered, which would hold events back
+        .insert_header(("Content-Encoding", "identity"))
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(body))
+}
+
+/// SSE event with the top `limit` feeds of `top:feeds_liked`
+async fn top_feeds_liked_event(
+    redis_client: &RedisClient,
+    pool: &DbPool,
+    config: &Config,
+    limit: u64,
+) -> ActixResult<String> {
+    let cached = cached_feeds_liked(redis_client, pool, config, 0, limit - 1).await?;
+    let data = serde_json::to_string(&content_previews(config, cached.items))
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    Ok(format!("event: {}\ndata: {}\n\n", FEEDS_LIKED_EVENT, data))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/top/trending",
+    params(
+        ("window" = Option<String>, Query, description = "Time window: day (default) or week"),
+        ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 10, larger values are clamped to TOP_STATS_MAX_LIMIT)"),
+        ("debug" = Option<bool>, Query, description = "Admins only: return {items, debug} with the raw Redis scores and dropped entries")
+    ),
+    responses(
+        (status = 200, description = "Feeds ranked by trending score (count = composite score); window echoed in X-Trending-Window", body = Vec<TopFeed>),
+        (status = 400, description = "Invalid window, or page * limit past the ranks Redis can address"),
+        (status = 401, description = "debug=true without a valid token"),
+        (status = 403, description = "debug=true by a non-admin")
+    ),
+    tag = "top"
+)]
+pub async fn get_trending_feeds(
+    http_req: HttpRequest,
+    pool: web::Data<DbPool>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
+    query: web::Query<TopQuery>,
+    trending: web::Query<TrendingQuery>,
+) -> ActixResult<HttpResponse> {
+    let window = trending.window.unwrap_or_default();
+    let Some((start, stop)) = rank_range(&query, &config) else {
+        return Ok(page_out_of_range());
+    };
+    let debug = debug_requested(&http_req, &query).await?;
+
+    let ranking = calculate_trending_feeds(
+        mongo_db.get_ref(),
+        pool.get_ref(),
+        window.start(),
+        start,
+        stop - start + 1,
+    )
+    .await;
+
+    let mut response = ranking_response(&http_req, &config, &query, ranking.len());
+    response.insert_header(("X-Trending-Window", window.as_str()));
+    Ok(ranking_body(
+        response,
+        content_previews(&config, ranking),
+        debug.then(TopDebug::default),
+    ))
 }
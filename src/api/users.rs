@@ -0,0 +1,952 @@
+use crate::api::auth::{check_nonce, request_language};
+use crate::api::feed::{feed_responses, invalidate_feed_cache};
+use crate::api::pagination::{insert_pagination_headers, PageInfo};
+use crate::auth::{revoke_all_sessions, verify_password, AuthenticatedUser};
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::entities::{feed, feed_like, user};
+use crate::kafka::{KafkaProducer, UserDeletedEvent};
+use crate::models::{
+    ActivityItem, ActivityResponse, ActivityType, AdminUserResponse, Comment, CommentResponse,
+    DeleteAccountRequest, ExportedFeed, ExportedLike, ExportedNotification, ExportedProfile,
+    Notification, UpdateNotificationPreferencesRequest, UpdateProfileRequest, UserDataExport,
+    UserProfile, UserProfileResponse,
+};
+use crate::repositories::{
+    BookmarkRepository, FeedRepository, LikeRepository, ReactionRepository, UserRepository,
+};
+use crate::services::account::purge_user_data;
+use crate::services::locale::normalize_language;
+use actix_web::{web, HttpRequest, HttpResponse, Result as ActixResult};
+use chrono::{DateTime, Utc};
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument};
+use mongodb::Database as MongoDatabase;
+use redis::Client as RedisClient;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::Deserialize;
+use serde_json::json;
+use std::cmp::Reverse;
+
+const MAX_BIO_LENGTH: usize = 500;
+const MAX_AVATAR_URL_LENGTH: usize = 2048;
+const MAX_ACTIVITY_LIMIT: u64 = 100;
+const MAX_COMMON_LIKES_LIMIT: u64 = 50;
+const MAX_BOOKMARKS_LIMIT: u64 = 50;
+const MAX_LIKED_FEEDS_LIMIT: u64 = 50;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct ActivityQuery {
+    /// Only return activity strictly older than this timestamp (RFC 3339)
+    pub before: Option<DateTime<Utc>>,
+    #[schema(example = 20)]
+    pub limit: Option<u64>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CommonLikesQuery {
+    #[schema(example = 1)]
+    pub page: Option<u64>,
+    #[schema(example = 20)]
+    pub limit: Option<u64>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct LikedFeedsQuery {
+    #[schema(example = 1)]
+    pub page: Option<u64>,
+    #[schema(example = 20)]
+    pub limit: Option<u64>,
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct BookmarksQuery {
+    #[schema(example = 1)]
+    pub page: Option<u64>,
+    #[schema(example = 20)]
+    pub limit: Option<u64>,
+}
+
+fn validate_avatar_url(url: &str) -> Result<(), &'static str> {
+    if url.len() > MAX_AVATAR_URL_LENGTH {
+        return Err("avatar_url is too long");
+    }
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or("avatar_url must be an http or https URL")?;
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    if host.is_empty() || url.chars().any(char::is_whitespace) {
+        return Err("avatar_url is not a valid URL");
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/users/me/profile",
+    request_body = UpdateProfileRequest,
+    responses(
+        (status = 200, description = "Profile updated", body = UserProfileResponse),
+        (status = 400, description = "Invalid bio, avatar_url or language"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn update_my_profile(
+    http_req: HttpRequest,
+    req: web::Json<UpdateProfileRequest>,
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    mongo_db: web::Data<MongoDatabase>,
+) -> ActixResult<HttpResponse> {
+    let user_id = user.user_id;
+
+    let user_model = user::Entity::find_by_id(user_id)
+        .one(pool.get_ref())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("User no longer exists"))?;
+
+    let mut set = Document::new();
+    if let Some(bio) = &req.bio {
+        if bio.chars().count() > MAX_BIO_LENGTH {
+            return Ok(HttpResponse::BadRequest().json(json!({
+                "error": format!("bio must be at most {} characters", MAX_BIO_LENGTH)
+            })));
+        }
+        set.insert("bio", bio.trim());
+    }
+    if let Some(avatar_url) = &req.avatar_url {
+        let avatar_url = avatar_url.trim();
+        if avatar_url.is_empty() {
+            set.insert("avatar_url", Bson::Null);
+        } else {
+            if let Err(e) = validate_avatar_url(avatar_url) {
+                return Ok(HttpResponse::BadRequest().json(json!({ "error": e })));
+            }
+            set.insert("avatar_url", avatar_url);
+        }
+    }
+    if let Some(language) = &req.language {
+        if language.trim().is_empty() {
+            set.insert("language", Bson::Null);
+        } else {
+            match normalize_language(language) {
+                Some(language) => set.insert("language", language),
+                None => {
+                    return Ok(HttpResponse::BadRequest().json(json!({
+                        "error": "language must be a language tag such as \"en\" or \"pt-BR\""
+                    })));
+                }
+            };
+        }
+    }
+    if let Some(likes_public) = req.likes_public {
+        set.insert("likes_public", likes_public);
+    }
+
+    // Defaults for a profile that was never seeded (e.g. the user_created event was lost)
+    let mut set_on_insert = doc! {"joined_at": Utc::now().timestamp()};
+    if !set.contains_key("bio") {
+        set_on_insert.insert("bio", "");
+    }
+    if !set.contains_key("avatar_url") {
+        set_on_insert.insert("avatar_url", Bson::Null);
+    }
+    if !set.contains_key("language") {
+        // A profile created here starts in the browser's language
+        let language = request_language(&http_req);
+        set_on_insert.insert("language", language.map_or(Bson::Null, Bson::String));
+    }
+
+    let mut update = doc! {"$setOnInsert": set_on_insert};
+    if !set.is_empty() {
+        update.insert("$set", set);
+    }
+
+    let options = FindOneAndUpdateOptions::builder()
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .build();
+    let profile = mongo_db
+        .collection::<UserProfile>("user_profiles")
+        .find_one_and_update(doc! {"_id": user_id}, update, options)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Profile upsert failed"))?;
+
+    Ok(HttpResponse::Ok().json(UserProfileResponse {
+        id: user_model.id,
+        username: user_model.username,
+        bio: profile.bio,
+        avatar_url: profile.avatar_url,
+        joined_at: profile.joined_at,
+        language: profile.language,
+        likes_public: profile.likes_public,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/users/me/notification-preferences",
+    request_body = UpdateNotificationPreferencesRequest,
+    responses(
+        (status = 200, description = "Updated notification preferences", body = NotificationPreferences),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn update_my_notification_preferences(
+    req: web::Json<UpdateNotificationPreferencesRequest>,
+    user: AuthenticatedUser,
+    mongo_db: web::Data<MongoDatabase>,
+) -> ActixResult<HttpResponse> {
+    let mut set = Document::new();
+    for (field, value) in [
+        ("likes_enabled", req.likes_enabled),
+        ("comments_enabled", req.comments_enabled),
+        ("mentions_enabled", req.mentions_enabled),
+    ] {
+        if let Some(value) = value {
+            set.insert(format!("notification_preferences.{}", field), value);
+        }
+    }
+
+    // Defaults for a profile that was never seeded
+    let mut update = doc! {
+        "$setOnInsert": {"bio": "", "avatar_url": Bson::Null, "joined_at": Utc::now().timestamp()}
+    };
+    if !set.is_empty() {
+        update.insert("$set", set);
+    }
+
+    let options = FindOneAndUpdateOptions::builder()
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .build();
+    let profile = mongo_db
+        .collection::<UserProfile>("user_profiles")
+        .find_one_and_update(doc! {"_id": user.user_id}, update, options)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("Profile upsert failed"))?;
+
+    Ok(HttpResponse::Ok().json(profile.notification_preferences))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/me/export",
+    responses(
+        (status = 200, description = "All data stored about the caller, as a JSON attachment", body = UserDataExport),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn export_my_data(
+    user: AuthenticatedUser,
+    pool: web::Data<DbPool>,
+    mongo_db: web::Data<MongoDatabase>,
+) -> ActixResult<HttpResponse> {
+    let user_id = user.user_id;
+
+    let account = user::Entity::find_by_id(user_id)
+        .one(pool.get_ref())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not found"))?;
+
+    let profile = mongo_db
+        .collection::<UserProfile>("user_profiles")
+        .find_one(doc! {"_id": user_id}, None)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .map(|profile| ExportedProfile {
+            bio: profile.bio,
+            avatar_url: profile.avatar_url,
+            joined_at: profile.joined_at,
+            notification_preferences: profile.notification_preferences,
+            language: profile.language,
+            likes_public: profile.likes_public,
+        });
+
+    let feeds = feed::Entity::find()
+        .filter(feed::Column::UserId.eq(user_id))
+        .order_by_asc(feed::Column::CreatedAt)
+        .all(pool.get_ref())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .into_iter()
+        .map(|f| ExportedFeed {
+            id: f.id,
+            content: f.content,
+            publish_at: f.publish_at,
+            expires_at: f.expires_at,
+            is_published: f.is_published,
+            created_at: f.created_at,
+        })
+        .collect();
+
+    let likes = feed_like::Entity::find()
+        .filter(feed_like::Column::UserId.eq(user_id))
+        .order_by_asc(feed_like::Column::CreatedAt)
+        .all(pool.get_ref())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .into_iter()
+        .map(|l| ExportedLike {
+            feed_id: l.feed_id,
+            created_at: l.created_at,
+        })
+        .collect();
+
+    // Only comments the user wrote, not other users' comments on the user's feeds
+    let options = FindOptions::builder().sort(doc! {"created_at": 1}).build();
+    let mut cursor = mongo_db
+        .collection::<Comment>("comments")
+        .find(doc! {"user_id": user_id}, options)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let mut comments = Vec::new();
+    while let Ok(true) = cursor.advance().await {
+        let comment: Comment = cursor
+            .deserialize_current()
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        comments.push(CommentResponse {
+            id: comment.id.unwrap_or_default(),
+            feed_id: comment.feed_id,
+            user_id: comment.user_id,
+            content: comment.content,
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+            parent_comment_id: comment.parent_comment_id,
+        });
+    }
+
+    // Notifications describe other users' actions; keep only what concerns this user
+    let options = FindOptions::builder().sort(doc! {"created_at": 1}).build();
+    let mut cursor = mongo_db
+        .collection::<Notification>("notifications")
+        .find(doc! {"user_id": user_id}, options)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let mut notifications = Vec::new();
+    while let Ok(true) = cursor.advance().await {
+        let notification: Notification = cursor
+            .deserialize_current()
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        notifications.push(ExportedNotification {
+            id: notification.id.unwrap_or_default(),
+            notification_type: notification.notification_type,
+            feed_id: notification.feed_id,
+            is_read: notification.is_read,
+            created_at: notification.created_at,
+        });
+    }
+
+    let export = UserDataExport {
+        exported_at: Utc::now(),
+        account: AdminUserResponse {
+            id: account.id,
+            email: account.email,
+            username: account.username,
+            is_admin: account.is_admin,
+            is_verified: account.is_verified,
+            created_at: account.created_at,
+        },
+        profile,
+        feeds,
+        likes,
+        comments,
+        notifications,
+    };
+
+    Ok(HttpResponse::Ok()
+        .insert_header((
+            "Content-Disposition",
+            format!("attachment; filename=\"user-{}-export.json\"", user_id),
+        ))
+        .json(export))
+}
+
+/// Checks the nonce and password of an account deletion request, returning the
+/// error response to send if either is rejected
+async fn check_deletion_request(
+    user: &AuthenticatedUser,
+    req: &DeleteAccountRequest,
+    pool: &DbPool,
+    redis_client: &RedisClient,
+    config: &Config,
+) -> ActixResult<Option<HttpResponse>> {
+    if let Some(resp) =
+        check_nonce(redis_client, config, user.user_id, req.nonce.as_deref()).await?
+    {
+        return Ok(Some(resp));
+    }
+
+    let user_model = user::Entity::find_by_id(user.user_id)
+        .one(pool)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("User not found"))?;
+
+    let valid = verify_password(&req.password, &user_model.password_hash)
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    if !valid {
+        return Ok(Some(HttpResponse::Unauthorized().json(json!({
+            "error": "Password is incorrect"
+        }))));
+    }
+
+    Ok(None)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me",
+    request_body = DeleteAccountRequest,
+    responses(
+        (status = 200, description = "Account deleted"),
+        (status = 400, description = "Missing, expired or reused nonce"),
+        (status = 401, description = "Unauthorized or wrong password")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn delete_my_account(
+    user: AuthenticatedUser,
+    req: web::Json<DeleteAccountRequest>,
+    pool: web::Data<DbPool>,
+    mongo_db: web::Data<MongoDatabase>,
+    redis_client: web::Data<RedisClient>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    if let Some(resp) = check_deletion_request(&user, &req, &pool, &redis_client, &config).await? {
+        return Ok(resp);
+    }
+
+    // Feeds and likes are removed by the foreign key cascade
+    user::Entity::delete_by_id(user.user_id)
+        .exec(pool.get_ref())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    if let Err(e) = mongo_db
+        .collection::<Document>("user_profiles")
+        .delete_one(doc! {"_id": user.user_id}, None)
+        .await
+    {
+        log::warn!("Failed to delete profile of user {}: {:?}", user.user_id, e);
+    }
+
+    // The cascade took the user's feeds and likes with it
+    invalidate_feed_cache(&redis_client, &config).await;
+
+    if let Err(e) = revoke_all_sessions(&redis_client, &config, user.user_id).await {
+        log::warn!(
+            "Failed to revoke sessions of user {}: {:?}",
+            user.user_id,
+            e
+        );
+    }
+
+    Ok(HttpResponse::Ok().json(json!({"message": "Account deleted"})))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/purge",
+    request_body = DeleteAccountRequest,
+    responses(
+        (status = 200, description = "Account and all related data purged"),
+        (status = 400, description = "Missing, expired or reused nonce"),
+        (status = 401, description = "Unauthorized or wrong password")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+pub async fn purge_my_account(
+    user: AuthenticatedUser,
+    req: web::Json<DeleteAccountRequest>,
+    pool: web::Data<DbPool>,
+    mongo_db: web::Data<MongoDatabase>,
+    redis_client: web::Data<RedisClient>,
+    kafka_producer: web::Data<KafkaProducer>,
+    config: web::Data<Config>,
+) -> ActixResult<HttpResponse> {
+    if let Some(resp) = check_deletion_request(&user, &req, &pool, &redis_client, &config).await? {
+        return Ok(resp);
+    }
+
+    if let Err(e) = purge_user_data(&pool, &mongo_db, &redis_client, &config, user.user_id).await {
+        log::error!("Failed to purge user {}: {:?}", user.user_id, e);
+        return Err(actix_web::error::ErrorInternalServerError(
+            "Failed to purge account",
+        ));
+    }
+    invalidate_feed_cache(&redis_client, &config).await;
+
+    if let Err(e) = revoke_all_sessions(&redis_client, &config, user.user_id).await {
+        log::warn!(
+            "Failed to revoke sessions of user {}: {:?}",
+            user.user_id,
+            e
+        );
+    }
+
+    let event = UserDeletedEvent::new(user.user_id);
+    if let Ok(event_json) = serde_json::to_string(&event) {
+        if let Err(e) = kafka_producer
+            .send_message_with_retry("user_events", &user.user_id.to_string(), &event_json)
+            .await
+        {
+            log::warn!("Failed to send Kafka event: {:?}", e);
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(json!({"message": "Account purged"})))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}",
+    params(
+        ("id" = i64, Path, description = "User ID")
+    ),
+    responses(
+        (status = 200, description = "User profile", body = UserProfileResponse),
+        (status = 404, description = "User not found")
+    ),
+    tag = "users"
+)]
+pub async fn get_user(
+    path: web::Path<i64>,
+    users: web::Data<dyn UserRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+) -> ActixResult<HttpResponse> {
+    let user_id = path.into_inner();
+
+    let user_model = match users
+        .find_by_id(user_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+    {
+        Some(u) => u,
+        None => {
+            return Ok(HttpResponse::NotFound().json(json!({
+                "error": "User not found"
+            })));
+        }
+    };
+
+    let profile = mongo_db
+        .collection::<UserProfile>("user_profiles")
+        .find_one(doc! {"_id": user_id}, None)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let (bio, avatar_url, joined_at, language, likes_public) = match profile {
+        Some(p) => (p.bio, p.avatar_url, p.joined_at, p.language, p.likes_public),
+        None => (String::new(), None, user_model.created_at, None, true),
+    };
+
+    Ok(HttpResponse::Ok().json(UserProfileResponse {
+        id: user_model.id,
+        username: user_model.username,
+        bio,
+        avatar_url,
+        joined_at,
+        language,
+        likes_public,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}/activity",
+    params(
+        ("id" = i64, Path, description = "User ID"),
+        ("before" = Option<String>, Query, description = "Only return activity older than this RFC 3339 timestamp (use `next_before` from the previous page)"),
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 20, max: 100)")
+    ),
+    responses(
+        (status = 200, description = "User activity, newest first", body = ActivityResponse),
+        (status = 404, description = "User not found")
+    ),
+    tag = "users"
+)]
+pub async fn get_user_activity(
+    path: web::Path<i64>,
+    query: web::Query<ActivityQuery>,
+    pool: web::Data<DbPool>,
+    mongo_db: web::Data<MongoDatabase>,
+) -> ActixResult<HttpResponse> {
+    let user_id = path.into_inner();
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_ACTIVITY_LIMIT);
+    let before = query.before.unwrap_or_else(Utc::now);
+
+    let user_exists = user::Entity::find_by_id(user_id)
+        .one(pool.get_ref())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .is_some();
+    if !user_exists {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "User not found"
+        })));
+    }
+
+    // Fetch up to `limit` of each kind, then merge; the newest `limit` overall are among them
+    let feeds = feed::Entity::find()
+        .filter(feed::Column::UserId.eq(user_id))
+        .filter(feed::Column::IsPublished.eq(true))
+        .filter(feed::Column::CreatedAt.lt(before))
+        .order_by_desc(feed::Column::CreatedAt)
+        .limit(limit)
+        .all(pool.get_ref())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let likes = feed_like::Entity::find()
+        .filter(feed_like::Column::UserId.eq(user_id))
+        .filter(feed_like::Column::CreatedAt.lt(before))
+        .order_by_desc(feed_like::Column::CreatedAt)
+        .limit(limit)
+        .all(pool.get_ref())
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let options = FindOptions::builder()
+        .sort(doc! {"created_at": -1})
+        .limit(limit as i64)
+        .build();
+    let mut cursor = mongo_db
+        .collection::<Comment>("comments")
+        .find(
+            doc! {"user_id": user_id, "created_at": {"$lt": before.timestamp()}},
+            options,
+        )
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let mut items: Vec<ActivityItem> = Vec::new();
+    items.extend(feeds.into_iter().map(|f| ActivityItem {
+        activity_type: ActivityType::Feed,
+        feed_id: f.id,
+        comment_id: None,
+        content: Some(f.content),
+        created_at: f.created_at,
+    }));
+    items.extend(likes.into_iter().map(|l| ActivityItem {
+        activity_type: ActivityType::Like,
+        feed_id: l.feed_id,
+        comment_id: None,
+        content: None,
+        created_at: l.created_at,
+    }));
+    while let Ok(true) = cursor.advance().await {
+        let comment: Comment = cursor
+            .deserialize_current()
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        items.push(ActivityItem {
+            activity_type: ActivityType::Comment,
+            feed_id: comment.feed_id,
+            comment_id: comment.id,
+            content: Some(comment.content),
+            created_at: comment.created_at,
+        });
+    }
+
+    items.sort_by_key(|item| Reverse(item.created_at));
+    items.truncate(limit as usize);
+
+    let next_before = if items.len() as u64 == limit {
+        items.last().map(|item| item.created_at)
+    } else {
+        None
+    };
+
+    Ok(HttpResponse::Ok().json(ActivityResponse { items, next_before }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}/common-likes",
+    params(
+        ("id" = i64, Path, description = "User ID"),
+        ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 20, max: 50)")
+    ),
+    responses(
+        (status = 200, description = "Feeds liked by both the caller and the user, newest first; Link and X-Total-Count headers when enabled", body = Vec<FeedResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "User not found"),
+        (status = 503, description = "Comment counts unavailable and MONGODB_DEGRADE_COUNTS is off")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_common_likes(
+    http_req: HttpRequest,
+    path: web::Path<i64>,
+    user: AuthenticatedUser,
+    users: web::Data<dyn UserRepository>,
+    feeds: web::Data<dyn FeedRepository>,
+    likes: web::Data<dyn LikeRepository>,
+    reactions: web::Data<dyn ReactionRepository>,
+    bookmarks: web::Data<dyn BookmarkRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
+    query: web::Query<CommonLikesQuery>,
+) -> ActixResult<HttpResponse> {
+    let other_user_id = path.into_inner();
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_COMMON_LIKES_LIMIT);
+
+    let other_exists = users
+        .find_by_id(other_user_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .is_some();
+    if !other_exists {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "User not found"
+        })));
+    }
+
+    // Only counted when the X-Total-Count header will be sent
+    let total = if config.server.pagination_headers {
+        Some(
+            feeds
+                .count_liked_by_both(user.user_id, other_user_id)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?,
+        )
+    } else {
+        None
+    };
+    let common = feeds
+        .list_liked_by_both(user.user_id, other_user_id, (page - 1) * limit, limit)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let feed_responses = feed_responses(
+        common,
+        Some(user.user_id),
+        likes.get_ref(),
+        reactions.get_ref(),
+        bookmarks.get_ref(),
+        &mongo_db,
+        &config,
+    )
+    .await?;
+
+    let mut response = HttpResponse::Ok();
+    insert_pagination_headers(
+        &mut response,
+        &http_req,
+        &config,
+        &PageInfo {
+            page,
+            limit,
+            total,
+            returned: feed_responses.len(),
+            max_depth: None,
+        },
+    );
+    Ok(response.json(feed_responses))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}/liked-feeds",
+    params(
+        ("id" = i64, Path, description = "User ID"),
+        ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 20, max: 50)")
+    ),
+    responses(
+        (status = 200, description = "Feeds liked by the user, most recently liked first; Link and X-Total-Count headers when enabled", body = Vec<FeedResponse>),
+        (status = 403, description = "The user keeps their likes private"),
+        (status = 404, description = "User not found"),
+        (status = 503, description = "Comment counts unavailable and MONGODB_DEGRADE_COUNTS is off")
+    ),
+    tag = "users"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_user_liked_feeds(
+    http_req: HttpRequest,
+    path: web::Path<i64>,
+    user: Option<AuthenticatedUser>,
+    users: web::Data<dyn UserRepository>,
+    likes: web::Data<dyn LikeRepository>,
+    reactions: web::Data<dyn ReactionRepository>,
+    bookmarks: web::Data<dyn BookmarkRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
+    query: web::Query<LikedFeedsQuery>,
+) -> ActixResult<HttpResponse> {
+    let user_id = path.into_inner();
+    let viewer_id = user.map(|u| u.user_id);
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_LIKED_FEEDS_LIMIT);
+
+    let exists = users
+        .find_by_id(user_id)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .is_some();
+    if !exists {
+        return Ok(HttpResponse::NotFound().json(json!({
+            "error": "User not found"
+        })));
+    }
+
+    // Users always see their own likes; a missing profile has the default (public)
+    if viewer_id != Some(user_id) {
+        let likes_public = mongo_db
+            .collection::<UserProfile>("user_profiles")
+            .find_one(doc! {"_id": user_id}, None)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?
+            .is_none_or(|profile| profile.likes_public);
+        if !likes_public {
+            return Ok(HttpResponse::Forbidden().json(json!({
+                "error": "This user's likes are private"
+            })));
+        }
+    }
+
+    // Only counted when the X-Total-Count header will be sent
+    let total = if config.server.pagination_headers {
+        Some(
+            likes
+                .count_liked(user_id)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?,
+        )
+    } else {
+        None
+    };
+    let liked = likes
+        .list_liked(user_id, (page - 1) * limit, limit)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let feed_responses = feed_responses(
+        liked,
+        viewer_id,
+        likes.get_ref(),
+        reactions.get_ref(),
+        bookmarks.get_ref(),
+        &mongo_db,
+        &config,
+    )
+    .await?;
+
+    let mut response = HttpResponse::Ok();
+    insert_pagination_headers(
+        &mut response,
+        &http_req,
+        &config,
+        &PageInfo {
+            page,
+            limit,
+            total,
+            returned: feed_responses.len(),
+            max_depth: None,
+        },
+    );
+    Ok(response.json(feed_responses))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/me/bookmarks",
+    params(
+        ("page" = Option<u64>, Query, description = "Page number (default: 1)"),
+        ("limit" = Option<u64>, Query, description = "Items per page (default: 20, max: 50)")
+    ),
+    responses(
+        (status = 200, description = "Feeds bookmarked by the caller, most recently bookmarked first; Link and X-Total-Count headers when enabled", body = Vec<FeedResponse>),
+        (status = 401, description = "Unauthorized"),
+        (status = 503, description = "Comment counts unavailable and MONGODB_DEGRADE_COUNTS is off")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "users"
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn get_my_bookmarks(
+    http_req: HttpRequest,
+    user: AuthenticatedUser,
+    likes: web::Data<dyn LikeRepository>,
+    reactions: web::Data<dyn ReactionRepository>,
+    bookmarks: web::Data<dyn BookmarkRepository>,
+    mongo_db: web::Data<MongoDatabase>,
+    config: web::Data<Config>,
+    query: web::Query<BookmarksQuery>,
+) -> ActixResult<HttpResponse> {
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(20).clamp(1, MAX_BOOKMARKS_LIMIT);
+
+    // Only counted when the X-Total-Count header will be sent
+    let total = if config.server.pagination_headers {
+        Some(
+            bookmarks
+                .count_bookmarked(user.user_id)
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?,
+        )
+    } else {
+        None
+    };
+    let bookmarked = bookmarks
+        .list_bookmarked(user.user_id, (page - 1) * limit, limit)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let feed_responses = feed_responses(
+        bookmarked,
+        Some(user.user_id),
+        likes.get_ref(),
+        reactions.get_ref(),
+        bookmarks.get_ref(),
+        &mongo_db,
+        &config,
+    )
+    .await?;
+
+    let mut response = HttpResponse::Ok();
+    insert_pagination_headers(
+        &mut response,
+        &http_req,
+        &config,
+        &PageInfo {
+            page,
+            limit,
+            total,
+            returned: feed_responses.len(),
+            max_depth: None,
+        },
+    );
+    Ok(response.json(feed_responses))
+}
@@ -0,0 +1,152 @@
+use crate::config::{ApiVersion, Config};
+use crate::models::{FeedAuthor, FeedResponse, FeedResponseV2, VersionedFeed};
+use crate::repositories::UserRepository;
+use actix_web::{
+    dev::Payload, error::InternalError, http::header, web, Error, FromRequest, HttpRequest,
+    HttpResponse, HttpResponseBuilder, Result as ActixResult,
+};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::future::{ready, Ready};
+
+/// Response header naming the version a body was serialized in
+pub const API_VERSION_HEADER: &str = "x-api-version";
+
+/// Version an `Accept` header asks for, or `None` if it only accepts versioned media types
+/// this server does not serve (`406`).
+///
+/// Media ranges are comma-separated and may carry parameters; `q=0` rules a range out.
+/// Among `application/vnd.{vendor}.v{N}+json` ranges with a known `N`, the highest `q`
+/// wins, then the highest version. Without one, `default` is served, including for
+/// headers like `application/json`, `*/*` or `text/html` that predate versioning.
+pub fn negotiate(accept: Option<&str>, vendor: &str, default: ApiVersion) -> Option<ApiVersion> {
+    let prefix = format!("application/vnd.{}.v", vendor.to_ascii_lowercase());
+    let mut best: Option<(f32, ApiVersion)> = None;
+    let mut unknown_versions = false;
+    let mut accepts_plain_json = false;
+
+    for range in accept.unwrap_or("").split(',') {
+        let mut parts = range.split(';');
+        let media_type = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        if media_type.is_empty() {
+            continue;
+        }
+        let quality = parts
+            .filter_map(|param| param.split_once('='))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+            .map_or(1.0, |(_, q)| q.trim().parse::<f32>().unwrap_or(1.0));
+        if quality <= 0.0 {
+            continue;
+        }
+
+        let number = media_type
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix("+json"));
+        match number.map(|n| n.parse().ok().and_then(ApiVersion::from_number)) {
+            Some(Some(version)) => {
+                let better = best.is_none_or(|(best_quality, best_version)| {
+                    quality > best_quality || (quality == best_quality && version > best_version)
+                });
+                if better {
+                    best = Some((quality, version));
+                }
+            }
+            Some(None) => unknown_versions = true,
+            None => {
+                accepts_plain_json |= matches!(
+                    media_type.as_str(),
+                    "application/json" | "application/*" | "*/*"
+                )
+            }
+        }
+    }
+
+    match best {
+        Some((_, version)) => Some(version),
+        None if unknown_versions && !accepts_plain_json => None,
+        None => Some(default),
+    }
+}
+
+/// Versioned media type, e.g. `application/vnd.example.v2+json`
+pub fn media_type(vendor: &str, version: ApiVersion) -> String {
+    format!("application/vnd.{}.v{}+json", vendor, version.number())
+}
+
+impl FromRequest for ApiVersion {
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let (vendor, default) = match req.app_data::<web::Data<Config>>() {
+            Some(config) => (
+                config.server.media_type_vendor.clone(),
+                config.server.default_api_version,
+            ),
+            None => ("example".to_string(), ApiVersion::V1),
+        };
+        let accept = req
+            .headers()
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok());
+
+        ready(negotiate(accept, &vendor, default).ok_or_else(|| {
+            let supported: Vec<String> = ApiVersion::ALL
+                .into_iter()
+                .map(|version| media_type(&vendor, version))
+                .collect();
+            let response = HttpResponse::NotAcceptable().json(json!({
+                "error": "None of the requested API versions is supported",
+                "supported": supported
+            }));
+            InternalError::from_response("Unsupported API version", response).into()
+        }))
+    }
+}
+
+/// Mark a response as serialized in `version`. The body stays `application/json`;
+/// `Vary: Accept` keeps caches from serving one version to clients asking for another.
+pub fn insert_version_headers(response: &mut HttpResponseBuilder, version: ApiVersion) {
+    response
+        .insert_header((API_VERSION_HEADER, version.number().to_string()))
+        .append_header((header::VARY, "Accept"));
+}
+
+/// `feeds` in the shape of `version`. v2 looks up all authors in one query.
+pub async fn versioned_feeds(
+    version: ApiVersion,
+    feeds: Vec<FeedResponse>,
+    users: &dyn UserRepository,
+) -> ActixResult<Vec<VersionedFeed>> {
+    if version == ApiVersion::V1 {
+        return Ok(feeds.into_iter().map(VersionedFeed::V1).collect());
+    }
+
+    let author_ids: Vec<i64> = feeds
+        .iter()
+        .map(|feed| feed.user_id)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let authors: HashMap<i64, FeedAuthor> = users
+        .find_by_ids(&author_ids)
+        .await
+        .map_err(actix_web::error::ErrorInternalServerError)?
+        .into_iter()
+        .map(|user| {
+            let author = FeedAuthor {
+                id: user.id,
+                username: user.username,
+            };
+            (user.id, author)
+        })
+        .collect();
+
+    Ok(feeds
+        .into_iter()
+        .map(|feed| {
+            let author = authors.get(&feed.user_id).cloned();
+            VersionedFeed::V2(FeedResponseV2 { feed, author })
+        })
+        .collect())
+}
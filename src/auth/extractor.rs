@@ -1,48 +1,125 @@
-use crate::auth::verify_token;
+use crate::auth::{is_revoked, verify_token};
 use crate::config::Config;
-use actix_web::{web, Error, FromRequest, HttpRequest};
-use std::future::{ready, Ready};
+use crate::db::DbPool;
+use crate::entities::user;
+use actix_web::{web, Error, FromRequest, HttpMessage, HttpRequest};
+use redis::Client as RedisClient;
+use sea_orm::EntityTrait;
+use std::future::Future;
+use std::pin::Pin;
 
+/// Caller identified by a valid, unrevoked bearer token. Extracting it also stores the
+/// token's `Claims` in the request extensions, for middleware such as sliding sessions.
 pub struct AuthenticatedUser {
     pub user_id: i64,
     #[allow(dead_code)]
     pub email: String,
+    /// Token id of the current session (empty for tokens issued before session tracking)
+    pub jti: String,
 }
 
 impl FromRequest for AuthenticatedUser {
     type Error = Error;
-    type Future = Ready<Result<Self, Self::Error>>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
 
     fn from_request(req: &HttpRequest, _payload: &mut actix_web::dev::Payload) -> Self::Future {
-        let auth_header = req.headers().get("Authorization");
-
-        if let Some(header_value) = auth_header {
-            if let Ok(header_str) = header_value.to_str() {
-                if let Some(token) = header_str.strip_prefix("Bearer ") {
-                    let config = req.app_data::<web::Data<Config>>();
-                    if let Some(config) = config {
-                        match verify_token(token, &config.jwt.secret) {
-                            Ok(claims) => {
-                                if let Ok(user_id) = claims.sub.parse::<i64>() {
-                                    return ready(Ok(AuthenticatedUser {
-                                        user_id,
-                                        email: claims.email,
-                                    }));
-                                }
-                            }
-                            Err(_) => {
-                                return ready(Err(actix_web::error::ErrorUnauthorized(
-                                    "Invalid token",
-                                )));
-                            }
-                        }
+        let config = req.app_data::<web::Data<Config>>().cloned();
+        // Expect "<scheme> <token>"; the scheme (AUTH_HEADER_SCHEME) is matched case-sensitively
+        let claims = config.as_ref().and_then(|config| {
+            let token = req
+                .headers()
+                .get("Authorization")
+                .and_then(|header_value| header_value.to_str().ok())
+                .and_then(|header_str| header_str.strip_prefix(config.jwt.header_scheme.as_str()))
+                .and_then(|rest| rest.strip_prefix(' '))?;
+            // Checked before decoding, so an oversized header costs no base64/JSON work
+            if token.len() > config.jwt.max_token_length {
+                return Some(Err("Token too long"));
+            }
+            Some(
+                verify_token(token, &config.jwt.secret, &config.jwt.audiences())
+                    .map_err(|_| "Invalid token"),
+            )
+        });
+        let redis_client = req.app_data::<web::Data<RedisClient>>().cloned();
+        let req = req.clone();
+
+        Box::pin(async move {
+            let claims = match claims {
+                Some(Ok(claims)) => claims,
+                Some(Err(message)) => {
+                    return Err(actix_web::error::ErrorUnauthorized(message));
+                }
+                None => {
+                    return Err(actix_web::error::ErrorUnauthorized(
+                        "Missing or invalid authorization header",
+                    ));
+                }
+            };
+
+            if let (Some(redis_client), Some(config)) =
+                (redis_client.filter(|_| !claims.jti.is_empty()), config)
+            {
+                match is_revoked(&redis_client, &config, &claims.jti).await {
+                    Ok(true) => {
+                        return Err(actix_web::error::ErrorUnauthorized(
+                            "Token has been revoked",
+                        ));
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        // Fail open: a Redis outage should not log every user out
+                        log::error!("Failed to check token revocation: {:?}", e);
                     }
                 }
             }
-        }
 
-        ready(Err(actix_web::error::ErrorUnauthorized(
-            "Missing or invalid authorization header",
-        )))
+            let user_id = claims.sub.parse::<i64>().map_err(|_| {
+                actix_web::error::ErrorUnauthorized("Missing or invalid authorization header")
+            })?;
+            req.extensions_mut().insert(claims.clone());
+
+            Ok(AuthenticatedUser {
+                user_id,
+                email: claims.email,
+                jti: claims.jti,
+            })
+        })
+    }
+}
+
+/// Authenticated user whose `users.is_admin` flag is set.
+/// The flag is read from MySQL on every request, so revoking admin takes effect immediately.
+pub struct AdminUser {
+    pub user_id: i64,
+}
+
+impl FromRequest for AdminUser {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut actix_web::dev::Payload) -> Self::Future {
+        let authenticated = AuthenticatedUser::from_request(req, payload);
+        let pool = req.app_data::<web::Data<DbPool>>().cloned();
+
+        Box::pin(async move {
+            let user = authenticated.await?;
+            let pool = pool.ok_or_else(|| {
+                actix_web::error::ErrorInternalServerError("Database pool not configured")
+            })?;
+
+            let is_admin = user::Entity::find_by_id(user.user_id)
+                .one(pool.get_ref())
+                .await
+                .map_err(actix_web::error::ErrorInternalServerError)?
+                .is_some_and(|u| u.is_admin);
+            if !is_admin {
+                return Err(actix_web::error::ErrorForbidden("Admin access required"));
+            }
+
+            Ok(AdminUser {
+                user_id: user.user_id,
+            })
+        })
     }
 }
@@ -1,13 +1,20 @@
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // user id
     pub email: String,
     pub exp: i64,
     pub iat: i64,
+    /// Unique token id, used to track sessions and revoke individual tokens
+    #[serde(default)]
+    pub jti: String,
+    /// Client type the token was issued for (`JWT_CLIENT_TYPES`); absent on older tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
 }
 
 impl Claims {
@@ -18,8 +25,15 @@ impl Claims {
             email,
             exp: (now + Duration::hours(expiration_hours)).timestamp(),
             iat: now.timestamp(),
+            jti: Uuid::new_v4().to_string(),
+            aud: None,
         }
     }
+
+    pub fn with_audience(mut self, audience: &str) -> Self {
+        self.aud = Some(audience.to_string());
+        self
+    }
 }
 
 pub fn create_token(claims: &Claims, secret: &str) -> Result<String, anyhow::Error> {
@@ -31,8 +45,14 @@ pub fn create_token(claims: &Claims, secret: &str) -> Result<String, anyhow::Err
     Ok(token)
 }
 
-pub fn verify_token(token: &str, secret: &str) -> Result<Claims, anyhow::Error> {
-    let validation = Validation::default();
+/// Tokens carrying an `aud` must name one of `audiences`; tokens without one are accepted
+pub fn verify_token(
+    token: &str,
+    secret: &str,
+    audiences: &[String],
+) -> Result<Claims, anyhow::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(audiences);
     let token_data = decode::<Claims>(
         token,
         &DecodingKey::from_secret(secret.as_ref()),
@@ -1,7 +1,11 @@
 pub mod extractor;
 pub mod jwt;
+pub mod nonce;
 pub mod password;
+pub mod session;
 
 pub use extractor::*;
 pub use jwt::*;
+pub use nonce::*;
 pub use password::*;
+pub use session::*;
@@ -0,0 +1,43 @@
+use crate::config::Config;
+use crate::db::redis_key;
+use redis::Client as RedisClient;
+use uuid::Uuid;
+
+fn nonce_key(config: &Config, user_id: i64, nonce: &str) -> String {
+    redis_key(config, &format!("auth:nonce:{}:{}", user_id, nonce))
+}
+
+/// Issue a one-time nonce for a user's next sensitive request, valid for
+/// `AUTH_NONCE_TTL_SECONDS`
+pub async fn issue_nonce(
+    redis_client: &RedisClient,
+    config: &Config,
+    user_id: i64,
+) -> Result<String, anyhow::Error> {
+    let nonce = Uuid::new_v4().to_string();
+    let mut conn = redis_client.get_async_connection().await?;
+    redis::cmd("SET")
+        .arg(nonce_key(config, user_id, &nonce))
+        .arg(1)
+        .arg("EX")
+        .arg(config.jwt.nonce_ttl_seconds)
+        .query_async::<_, ()>(&mut conn)
+        .await?;
+    Ok(nonce)
+}
+
+/// Consume a nonce. Returns `false` if it was never issued to this user, has expired,
+/// or was already used; `DEL` makes this atomic so a nonce is accepted at most once.
+pub async fn consume_nonce(
+    redis_client: &RedisClient,
+    config: &Config,
+    user_id: i64,
+    nonce: &str,
+) -> Result<bool, anyhow::Error> {
+    let mut conn = redis_client.get_async_connection().await?;
+    let deleted: i64 = redis::cmd("DEL")
+        .arg(nonce_key(config, user_id, nonce))
+        .query_async(&mut conn)
+        .await?;
+    Ok(deleted == 1)
+}
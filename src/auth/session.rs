@@ -0,0 +1,196 @@
+use crate::auth::Claims;
+use crate::config::Config;
+use crate::db::redis_key;
+use crate::models::SessionResponse;
+use chrono::{TimeZone, Utc};
+use redis::Client as RedisClient;
+use serde::{Deserialize, Serialize};
+
+/// Session metadata stored in Redis under `session:{jti}`
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionMetadata {
+    issued_at: i64,
+    expires_at: i64,
+    user_agent: Option<String>,
+}
+
+fn sessions_key(config: &Config, user_id: i64) -> String {
+    redis_key(config, &format!("sessions:{}", user_id))
+}
+
+fn session_key(config: &Config, jti: &str) -> String {
+    redis_key(config, &format!("session:{}", jti))
+}
+
+fn revoked_key(config: &Config, jti: &str) -> String {
+    redis_key(config, &format!("revoked:{}", jti))
+}
+
+/// Record a newly issued token as an active session for its user.
+/// `sessions:{user_id}` is a set of jtis; each jti's metadata lives in `session:{jti}`
+/// with a TTL matching the token expiration. The set's TTL is reset to the longest token
+/// lifetime on every new session, so it outlives all its tokens but not an idle user.
+pub async fn record_session(
+    redis_client: &RedisClient,
+    config: &Config,
+    user_id: i64,
+    claims: &Claims,
+    user_agent: Option<String>,
+) -> Result<(), anyhow::Error> {
+    let ttl = (claims.exp - Utc::now().timestamp()).max(1);
+    let metadata = serde_json::to_string(&SessionMetadata {
+        issued_at: claims.iat,
+        expires_at: claims.exp,
+        user_agent,
+    })?;
+
+    let mut conn = redis_client.get_async_connection().await?;
+    redis::pipe()
+        .cmd("SADD")
+        .arg(sessions_key(config, user_id))
+        .arg(&claims.jti)
+        .ignore()
+        .cmd("EXPIRE")
+        .arg(sessions_key(config, user_id))
+        .arg(config.jwt.max_lifetime_hours() * 3600)
+        .ignore()
+        .cmd("SET")
+        .arg(session_key(config, &claims.jti))
+        .arg(metadata)
+        .arg("EX")
+        .arg(ttl)
+        .ignore()
+        .query_async::<_, ()>(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// List a user's active sessions, pruning jtis whose metadata has expired
+pub async fn list_sessions(
+    redis_client: &RedisClient,
+    config: &Config,
+    user_id: i64,
+    current_jti: &str,
+) -> Result<Vec<SessionResponse>, anyhow::Error> {
+    let mut conn = redis_client.get_async_connection().await?;
+    let jtis: Vec<String> = redis::cmd("SMEMBERS")
+        .arg(sessions_key(config, user_id))
+        .query_async(&mut conn)
+        .await?;
+
+    let mut sessions = Vec::new();
+    for jti in jtis {
+        let metadata: Option<String> = redis::cmd("GET")
+            .arg(session_key(config, &jti))
+            .query_async(&mut conn)
+            .await?;
+
+        let metadata = match metadata.and_then(|m| serde_json::from_str::<SessionMetadata>(&m).ok())
+        {
+            Some(metadata) => metadata,
+            None => {
+                // Token expired: drop it from the active set
+                let _: Result<(), _> = redis::cmd("SREM")
+                    .arg(sessions_key(config, user_id))
+                    .arg(&jti)
+                    .query_async(&mut conn)
+                    .await;
+                continue;
+            }
+        };
+
+        sessions.push(SessionResponse {
+            is_current: jti == current_jti,
+            jti,
+            issued_at: Utc
+                .timestamp_opt(metadata.issued_at, 0)
+                .single()
+                .unwrap_or_default(),
+            expires_at: Utc
+                .timestamp_opt(metadata.expires_at, 0)
+                .single()
+                .unwrap_or_default(),
+            user_agent: metadata.user_agent,
+        });
+    }
+
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.issued_at));
+    Ok(sessions)
+}
+
+/// Revoke one of a user's sessions. Returns `false` if the jti is not an active
+/// session of that user.
+pub async fn revoke_session(
+    redis_client: &RedisClient,
+    config: &Config,
+    user_id: i64,
+    jti: &str,
+) -> Result<bool, anyhow::Error> {
+    let mut conn = redis_client.get_async_connection().await?;
+    let metadata: Option<String> = redis::cmd("GET")
+        .arg(session_key(config, jti))
+        .query_async(&mut conn)
+        .await?;
+    let removed: i64 = redis::cmd("SREM")
+        .arg(sessions_key(config, user_id))
+        .arg(jti)
+        .query_async(&mut conn)
+        .await?;
+
+    if removed == 0 {
+        return Ok(false);
+    }
+
+    let ttl = metadata
+        .and_then(|m| serde_json::from_str::<SessionMetadata>(&m).ok())
+        .map(|m| (m.expires_at - Utc::now().timestamp()).max(1))
+        .unwrap_or(1);
+
+    // Blocklist the jti until the token would have expired anyway
+    redis::pipe()
+        .cmd("SET")
+        .arg(revoked_key(config, jti))
+        .arg(1)
+        .arg("EX")
+        .arg(ttl)
+        .ignore()
+        .cmd("DEL")
+        .arg(session_key(config, jti))
+        .ignore()
+        .query_async::<_, ()>(&mut conn)
+        .await?;
+    Ok(true)
+}
+
+/// Revoke every active session of a user (e.g. when the account is deleted)
+pub async fn revoke_all_sessions(
+    redis_client: &RedisClient,
+    config: &Config,
+    user_id: i64,
+) -> Result<(), anyhow::Error> {
+    let jtis: Vec<String> = {
+        let mut conn = redis_client.get_async_connection().await?;
+        redis::cmd("SMEMBERS")
+            .arg(sessions_key(config, user_id))
+            .query_async(&mut conn)
+            .await?
+    };
+    for jti in jtis {
+        revoke_session(redis_client, config, user_id, &jti).await?;
+    }
+    Ok(())
+}
+
+/// Check whether a token id has been revoked
+pub async fn is_revoked(
+    redis_client: &RedisClient,
+    config: &Config,
+    jti: &str,
+) -> Result<bool, anyhow::Error> {
+    let mut conn = redis_client.get_async_connection().await?;
+    let exists: bool = redis::cmd("EXISTS")
+        .arg(revoked_key(config, jti))
+        .query_async(&mut conn)
+        .await?;
+    Ok(exists)
+}
@@ -1,26 +1,164 @@
+use crate::db::validate_write_concern;
+use crate::services::locale::{localized_template, normalize_language};
+use crate::services::notification::render_template;
+use actix_web::http::{header::HeaderName, Uri};
+use ipnet::IpNet;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::env;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// JWT secrets shipped as defaults/examples; never acceptable in production
+const INSECURE_JWT_SECRETS: [&str; 2] = [
+    "your-secret-key-change-this",
+    "your-secret-key-change-this-in-production",
+];
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
+    /// Deployment environment (`APP_ENV`), e.g. "development" or "production"
+    pub app_env: String,
     pub server: ServerConfig,
     pub jwt: JwtConfig,
     pub mysql: MysqlConfig,
     pub mongodb: MongodbConfig,
     pub redis: RedisConfig,
     pub kafka: KafkaConfig,
+    pub rate_limit: RateLimitConfig,
+    pub notifications: NotificationsConfig,
+    pub cors: CorsConfig,
+    pub usernames: UsernamesConfig,
+    pub content: ContentConfig,
+    pub search: SearchConfig,
+    pub top_stats: TopStatsConfig,
+    pub feed_cache: FeedCacheConfig,
+    pub feed_marker: FeedMarkerConfig,
+    pub like_counts: LikeCountsConfig,
+    pub digests: DigestsConfig,
+    pub audit_log: AuditLogConfig,
+    pub moderation: ModerationConfig,
+    pub feed_expiry: FeedExpiryConfig,
+    pub feed_history: FeedHistoryConfig,
+    pub startup: StartupConfig,
+    /// Env vars that were set but could not be parsed; reported by `validate()`
+    #[serde(default)]
+    pub invalid_vars: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// Compress responses according to `Accept-Encoding` (disable for debugging)
+    pub compression: bool,
+    /// Add `Link` and `X-Total-Count` headers to paginated listings
+    pub pagination_headers: bool,
+    /// Log the number of SeaORM queries each request ran (default on in debug builds)
+    pub query_count_logging: bool,
+    /// Log JSON request and response bodies at debug level
+    pub body_logging: bool,
+    /// Fields masked in logged bodies and query strings (also matches `*_<field>`)
+    pub log_redacted_fields: Vec<String>,
+    /// Header carrying the request ID, read from the request (or generated) and echoed in the response
+    pub request_id_header: String,
+    /// Proxies whose `X-Forwarded-For` is trusted for the client IP (`TRUSTED_PROXIES`)
+    pub trusted_proxies: Vec<IpNet>,
+    /// Deepest `offset + limit` anonymous callers may page to in `GET /api/feed` (0 = no cap)
+    pub feed_max_depth_anonymous: u64,
+    /// Same cap for authenticated callers (0 = no cap)
+    pub feed_max_depth_authenticated: u64,
+    /// Reject unknown fields in `RequestJson` bodies instead of ignoring them
+    pub strict_json: bool,
+    /// Serve `POST /api/admin/seed`, which fills the databases with demo data
+    pub seed_endpoint: bool,
+    /// Feed response version for requests whose `Accept` header names none
+    pub default_api_version: ApiVersion,
+    /// Vendor in versioned media types, `application/vnd.{vendor}.v{N}+json`
+    pub media_type_vendor: String,
+}
+
+/// Shape of feed responses, negotiated per request through the `Accept` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+pub enum ApiVersion {
+    /// `FeedResponse` with the author as `user_id`
+    V1,
+    /// `FeedResponseV2`: v1 plus the embedded `author`
+    V2,
+}
+
+impl ApiVersion {
+    pub const ALL: [ApiVersion; 2] = [ApiVersion::V1, ApiVersion::V2];
+
+    pub fn number(self) -> u32 {
+        match self {
+            ApiVersion::V1 => 1,
+            ApiVersion::V2 => 2,
+        }
+    }
+
+    pub fn from_number(number: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.number() == number)
+    }
+}
+
+impl FromStr for ApiVersion {
+    type Err = ();
+
+    /// `2` or `v2`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_ascii_lowercase();
+        let number = value.strip_prefix('v').unwrap_or(&value);
+        number
+            .parse()
+            .ok()
+            .and_then(ApiVersion::from_number)
+            .ok_or(())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct JwtConfig {
     pub secret: String,
     pub expiration_hours: i64,
+    /// Authorization header scheme preceding the token, matched case-sensitively (default "Bearer")
+    pub header_scheme: String,
+    /// Require a one-time nonce from `GET /api/auth/nonce` for password change and account deletion
+    pub require_nonce: bool,
+    /// Seconds an issued nonce stays valid
+    pub nonce_ttl_seconds: u64,
+    /// Token lifetime in hours per client type; the client type is the token's `aud`
+    pub client_types: HashMap<String, i64>,
+    /// Client type of tokens issued without an explicit `client_type`
+    pub default_client_type: String,
+    /// Whether tokens keep their issued expiry or are re-issued as they near it
+    pub expiry_mode: JwtExpiryMode,
+    /// In sliding mode, a token with fewer minutes than this left is re-issued
+    pub sliding_threshold_minutes: i64,
+    /// Longest bearer token, in bytes, that is decoded at all; longer ones get `401`
+    pub max_token_length: usize,
+}
+
+/// How token expiry is handled for active users
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum JwtExpiryMode {
+    /// Tokens expire at their issued `exp`; users log in again afterwards
+    Fixed,
+    /// Responses to requests whose token is close to expiry carry a fresh token in
+    /// `X-Refreshed-Token`, so active sessions never lapse
+    Sliding,
+}
+
+impl FromStr for JwtExpiryMode {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "fixed" => Ok(JwtExpiryMode::Fixed),
+            "sliding" => Ok(JwtExpiryMode::Sliding),
+            _ => Err(()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -36,6 +174,16 @@ pub struct MysqlConfig {
 pub struct MongodbConfig {
     pub uri: String,
     pub database: String,
+    /// Default write concern for the client (e.g. "majority", "1")
+    pub write_concern: Option<String>,
+    /// Per-collection write concern overrides (collection name -> write concern)
+    pub collection_write_concerns: HashMap<String, String>,
+    /// Custom tag set names (`getLastErrorModes`) write concerns may use
+    pub write_concern_tags: Vec<String>,
+    /// Fraction of views stored in `feed_views`, in (0, 1]; Kafka view events are never sampled
+    pub feed_view_sample_rate: f64,
+    /// When comment counts cannot be read, serve feeds flagged `counts_degraded` instead of a 503
+    pub degrade_counts: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -43,40 +191,434 @@ pub struct RedisConfig {
     pub host: String,
     pub port: u16,
     pub password: Option<String>,
+    /// Prepended to every Redis key, e.g. "staging:" (empty by default)
+    pub key_prefix: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct KafkaConfig {
     pub brokers: String,
     pub group_id: String,
+    /// Attempts per event for `send_message_with_retry` (at least 1)
+    pub send_max_attempts: u32,
+    /// Delay before the first retry; doubled after each failed attempt
+    pub send_retry_backoff_ms: u64,
+    /// How often each consumer loop records its heartbeat in Redis
+    pub consumer_heartbeat_interval_seconds: u64,
+    /// Heartbeats older than this make `/ready` report the consumer as down
+    pub consumer_heartbeat_max_age_seconds: u64,
+    /// What consumers do with a message that has no payload
+    pub empty_payload_action: EmptyPayloadAction,
+    /// How long `GET /api/admin/dlq` and replays wait for Kafka while reading a dead-letter topic
+    pub dlq_peek_timeout_ms: u64,
+}
+
+/// Handling of consumed messages without a payload. Either way the offset is committed,
+/// so the message is not redelivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum EmptyPayloadAction {
+    /// Log a warning and move on
+    Skip,
+    /// Forward the message's key to `{topic}_dlq` for inspection
+    DeadLetter,
+}
+
+impl FromStr for EmptyPayloadAction {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "skip" => Ok(EmptyPayloadAction::Skip),
+            "dlq" => Ok(EmptyPayloadAction::DeadLetter),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Retries for the datastore and Kafka connections made at startup, and the boot self-test
+#[derive(Debug, Clone, Deserialize)]
+pub struct StartupConfig {
+    /// Attempts per connection before startup fails (at least 1)
+    pub connect_max_attempts: u32,
+    /// Delay before the first retry; doubled after each failed attempt
+    pub connect_retry_backoff_ms: u64,
+    /// Upper bound for the doubled delay
+    pub connect_max_backoff_ms: u64,
+    /// Run a sample of every event through its handler before the consumers start
+    pub self_test: bool,
+    /// Exit if the self-test fails instead of only logging it. Only `viewed` and
+    /// `user_created` are checked for their writes; the other events fail only if they do
+    /// not parse or their handler panics, since those handlers log errors instead of
+    /// returning them
+    pub self_test_strict: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RateLimitConfig {
+    /// Seconds a user must wait between comments on the same feed (0 disables)
+    pub comment_cooldown_seconds: u64,
+    /// Like/unlike operations per minute per user, refilling a token bucket (0 disables)
+    pub like_per_minute: u64,
+    /// Like/unlike operations a user may make in a burst before being limited
+    pub like_burst: u64,
+    /// Views per minute per client IP, refilling a token bucket (0 disables)
+    pub view_per_minute: u64,
+    /// Views a client IP may record in a burst before being limited
+    pub view_burst: u64,
+    /// Up to this many random seconds are added to a view 429's `Retry-After`
+    pub view_retry_jitter_seconds: u64,
+    /// Requests per minute per client IP across all of `/api`, refilling a token bucket
+    /// (0 disables)
+    pub ip_per_minute: u64,
+    /// Requests a client IP may make in a burst before being limited
+    pub ip_burst: u64,
+    /// Client IPs and networks exempt from the per-IP limit (`RATE_LIMIT_IP_ALLOWLIST`)
+    pub ip_allowlist: Vec<IpNet>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotificationsConfig {
+    /// Newest notifications kept per user; older ones are deleted by the trim job (0 disables)
+    pub max_per_user: u64,
+    /// Seconds between runs of the notification trim job
+    pub trim_interval_seconds: u64,
+    /// Text of like notifications; placeholders `{username}` and `{feed_id}`
+    pub like_template: String,
+    /// Text of comment notifications; placeholders `{username}`, `{feed_id}` and `{content}`
+    pub comment_template: String,
+    /// Like templates per recipient language (lowercase tag, e.g. "fr" or "pt-br");
+    /// recipients without a match get `like_template`
+    pub like_templates: HashMap<String, String>,
+    /// Comment templates per recipient language, same lookup as `like_templates`
+    pub comment_templates: HashMap<String, String>,
+    /// Characters of the comment kept in `{content}` before it is cut with `…` (0 keeps all)
+    pub content_preview_chars: usize,
+    /// Fold likes of the same feed into one notification per recipient instead of one each
+    pub aggregate_likes: bool,
+    /// Minutes after its first like that an aggregate notification keeps taking likes
+    pub aggregate_window_minutes: u64,
+    /// Usernames of the latest likers kept on an aggregate notification
+    pub aggregate_max_actors: usize,
+    /// Text of aggregate like notifications with more than one like; placeholders
+    /// `{username}`, `{usernames}`, `{count}` and `{feed_id}`
+    pub like_aggregate_template: String,
+    /// Aggregate like templates per recipient language, same lookup as `like_templates`
+    pub like_aggregate_templates: HashMap<String, String>,
+}
+
+impl NotificationsConfig {
+    /// Like template for a recipient's language (English `like_template` by default)
+    pub fn like_template_for(&self, language: Option<&str>) -> &str {
+        localized_template(&self.like_template, &self.like_templates, language)
+    }
+
+    /// Comment template for a recipient's language (`comment_template` by default)
+    pub fn comment_template_for(&self, language: Option<&str>) -> &str {
+        localized_template(&self.comment_template, &self.comment_templates, language)
+    }
+
+    /// Aggregate like template for a recipient's language (`like_aggregate_template` by default)
+    pub fn like_aggregate_template_for(&self, language: Option<&str>) -> &str {
+        localized_template(
+            &self.like_aggregate_template,
+            &self.like_aggregate_templates,
+            language,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CorsConfig {
+    /// Origins allowed to call `/api/*`; `*` allows any origin
+    pub allowed_origins: Vec<String>,
+    /// Request headers allowed on every `/api/*` route
+    pub allowed_headers: Vec<String>,
+    /// Extra allowed request headers per `/api` scope, keyed by scope name (e.g. "feed")
+    pub route_allowed_headers: HashMap<String, Vec<String>>,
+    /// Seconds browsers may cache a preflight response (`Access-Control-Max-Age`)
+    pub max_age_seconds: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UsernamesConfig {
+    /// Lowercase usernames at signup and match them case-insensitively (mentions, search)
+    pub case_insensitive: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContentConfig {
+    /// Detect the language of new feeds (costs CPU per post; feeds stay untagged when off)
+    pub language_detection: bool,
+    /// Feeds an author can pin to the top of their timeline at once
+    pub max_pinned_feeds: u64,
+    /// Distinct mentions processed per feed or comment; later ones are ignored (0 = no cap)
+    pub max_mentions: usize,
+    /// Reject content over `max_mentions` with `400` instead of ignoring the excess
+    pub reject_excess_mentions: bool,
+    /// Categories a feed can be filed under, lowercased (empty = feeds cannot be categorized)
+    pub feed_categories: Vec<String>,
+}
+
+/// Engine behind `GET /api/feed/search`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum SearchBackendKind {
+    /// `LIKE %q%` on `feeds.content`; no indexing needed
+    Mysql,
+    /// External engine (Meilisearch/Elasticsearch) fed from `FeedCreatedEvent`
+    External,
+}
+
+impl FromStr for SearchBackendKind {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "mysql" => Ok(SearchBackendKind::Mysql),
+            "external" => Ok(SearchBackendKind::External),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchConfig {
+    pub backend: SearchBackendKind,
+    /// Base URL of the external engine, required when `backend` is `External`
+    pub external_url: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TopStatsConfig {
+    /// Warn when a top stats run takes at least this many seconds (0 disables)
+    pub slow_job_warning_seconds: u64,
+    /// Ranks kept per `top:*` sorted set; deeper pages are ranked from the databases
+    pub cache_size: u64,
+    /// Largest `limit` served by the `/api/top/*` rankings; larger values are clamped
+    pub max_limit: u64,
+    /// Seconds between trims of the sorted sets back to `cache_size` (0 disables)
+    pub trim_interval_seconds: u64,
+    /// Seconds between sweeps removing feed ranking members whose feed no longer exists
+    /// (0 disables)
+    pub orphan_sweep_interval_seconds: u64,
+    /// Characters of feed content kept in top feed responses before it is cut with `…`
+    /// (0 keeps all)
+    pub content_preview_chars: usize,
+    /// Seconds between checks of `top:feeds_liked` by SSE streams (0 disables the stream)
+    pub stream_interval_seconds: u64,
+    /// Score a like by a verified user adds to `top:feeds_liked` (others add 1.0)
+    pub like_weight_verified: f64,
+    /// Score a like by an admin adds to `top:feeds_liked`; takes precedence over verified
+    pub like_weight_admin: f64,
+}
+
+impl TopStatsConfig {
+    /// Score a like adds to `top:feeds_liked`, by the liker's account flags
+    pub fn like_weight(&self, is_admin: bool, is_verified: bool) -> f64 {
+        if is_admin {
+            self.like_weight_admin
+        } else if is_verified {
+            self.like_weight_verified
+        } else {
+            1.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeedCacheConfig {
+    /// Serve anonymous first pages of `GET /api/feed` from a Redis snapshot
+    pub enabled: bool,
+    /// Upper bound on staleness for changes that do not invalidate the cache
+    pub ttl_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeedMarkerConfig {
+    /// Remember the newest feed each user has seen and serve `GET /api/feed/unseen-count`
+    pub enabled: bool,
+    /// Days a marker is kept after the user last loaded the timeline (0 keeps it forever)
+    pub ttl_days: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LikeCountsConfig {
+    /// Maintain `feeds.like_count` on like/unlike and read it instead of counting
+    /// `feed_likes` per feed
+    pub denormalized: bool,
+    /// Seconds between runs of the job that corrects drift in `feeds.like_count`
+    pub reconcile_interval_seconds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DigestsConfig {
+    /// Build a daily digest of each active user's unread notifications
+    pub enabled: bool,
+    /// Hour of the day (UTC, 0-23) the digest job runs at
+    pub hour_utc: u32,
+    /// Users count as active when they viewed or commented on a feed within this many
+    /// days (0 counts every user with unread notifications)
+    pub active_within_days: u64,
+    /// Unread notifications a user needs to get a digest
+    pub min_unread: u64,
+    /// Most frequent actors listed per digest
+    pub top_actors: usize,
+    /// Also publish each digest to the `digests` Kafka topic
+    pub publish: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuditLogConfig {
+    /// Record every authenticated request to `/api/admin` in the `audit_log` collection
+    pub enabled: bool,
+    /// Also record read-only (`GET`) admin requests, such as listing users
+    pub include_reads: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModerationConfig {
+    /// Serve `POST /api/feed/{feed_id}/report`; the admin review endpoints stay available
+    pub reports_enabled: bool,
+    /// Longest report reason accepted, in characters
+    pub max_reason_chars: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeedExpiryConfig {
+    /// Seconds between runs of the job announcing expired feeds and taking them out of the
+    /// rankings (0 disables it; expired feeds are still left out of reads)
+    pub check_interval_seconds: u64,
+    /// Longest `expires_at` accepted, in hours after the feed goes live (0 = no limit)
+    pub max_lifetime_hours: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeedHistoryConfig {
+    /// Keep the previous content of edited feeds in `feed_edits` and serve
+    /// `GET /api/feed/{feed_id}/history`
+    pub enabled: bool,
+    /// Revisions kept per feed; older ones are deleted as new edits come in
+    pub max_revisions: u64,
+}
+
+impl JwtConfig {
+    /// Audiences `verify_token` accepts
+    pub fn audiences(&self) -> Vec<String> {
+        self.client_types.keys().cloned().collect()
+    }
+
+    /// Token lifetime in hours for a token's `aud`, falling back to `JWT_EXPIRATION_HOURS`
+    pub fn lifetime_hours(&self, audience: Option<&str>) -> i64 {
+        audience
+            .and_then(|audience| self.client_types.get(audience))
+            .copied()
+            .unwrap_or(self.expiration_hours)
+    }
+
+    /// Longest lifetime any token can be issued with, in hours
+    pub fn max_lifetime_hours(&self) -> i64 {
+        self.client_types
+            .values()
+            .copied()
+            .fold(self.expiration_hours, i64::max)
+    }
+}
+
+impl UsernamesConfig {
+    /// Username as stored and looked up: trimmed, and lowercased in case-insensitive mode
+    pub fn normalize(&self, username: &str) -> String {
+        let username = username.trim();
+        if self.case_insensitive {
+            username.to_lowercase()
+        } else {
+            username.to_string()
+        }
+    }
+
+    /// Whether two usernames refer to the same user
+    pub fn matches(&self, a: &str, b: &str) -> bool {
+        self.normalize(a) == self.normalize(b)
+    }
 }
 
 impl Config {
     pub fn from_env() -> Result<Self, anyhow::Error> {
         dotenv::dotenv().ok();
 
+        let mut invalid_vars = Vec::new();
+        let jwt_expiration_hours = env_or("JWT_EXPIRATION_HOURS", 24, &mut invalid_vars);
+
         Ok(Config {
+            app_env: env::var("APP_ENV").unwrap_or_else(|_| "development".to_string()),
             server: ServerConfig {
                 host: env::var("SERVER_HOST").unwrap_or_else(|_| "0.0.0.0".to_string()),
-                port: env::var("SERVER_PORT")
-                    .unwrap_or_else(|_| "8080".to_string())
-                    .parse()
-                    .unwrap_or(8080),
+                port: env_or("SERVER_PORT", 8080, &mut invalid_vars),
+                compression: env_or("SERVER_COMPRESSION", true, &mut invalid_vars),
+                pagination_headers: env_or("SERVER_PAGINATION_HEADERS", true, &mut invalid_vars),
+                query_count_logging: env_or(
+                    "SERVER_QUERY_COUNT_LOGGING",
+                    cfg!(debug_assertions),
+                    &mut invalid_vars,
+                ),
+                body_logging: env_or("SERVER_BODY_LOGGING", false, &mut invalid_vars),
+                log_redacted_fields: env_list("SERVER_LOG_REDACTED_FIELDS", "password,token,email"),
+                request_id_header: env::var("SERVER_REQUEST_ID_HEADER")
+                    .unwrap_or_else(|_| "X-Request-Id".to_string()),
+                trusted_proxies: env_networks("TRUSTED_PROXIES", &mut invalid_vars),
+                feed_max_depth_anonymous: env_or(
+                    "SERVER_FEED_MAX_DEPTH_ANONYMOUS",
+                    1000,
+                    &mut invalid_vars,
+                ),
+                feed_max_depth_authenticated: env_or(
+                    "SERVER_FEED_MAX_DEPTH_AUTHENTICATED",
+                    0,
+                    &mut invalid_vars,
+                ),
+                strict_json: env_or("SERVER_STRICT_JSON", false, &mut invalid_vars),
+                seed_endpoint: env_or("ENABLE_SEED_ENDPOINT", false, &mut invalid_vars),
+                default_api_version: env_or(
+                    "SERVER_DEFAULT_API_VERSION",
+                    ApiVersion::V1,
+                    &mut invalid_vars,
+                ),
+                media_type_vendor: env::var("SERVER_MEDIA_TYPE_VENDOR")
+                    .unwrap_or_else(|_| "example".to_string()),
             },
             jwt: JwtConfig {
                 secret: env::var("JWT_SECRET")
                     .unwrap_or_else(|_| "your-secret-key-change-this".to_string()),
-                expiration_hours: env::var("JWT_EXPIRATION_HOURS")
-                    .unwrap_or_else(|_| "24".to_string())
-                    .parse()
-                    .unwrap_or(24),
+                expiration_hours: jwt_expiration_hours,
+                header_scheme: env::var("AUTH_HEADER_SCHEME")
+                    .unwrap_or_else(|_| "Bearer".to_string()),
+                require_nonce: env_or("AUTH_REQUIRE_NONCE", false, &mut invalid_vars),
+                nonce_ttl_seconds: env_or("AUTH_NONCE_TTL_SECONDS", 300, &mut invalid_vars),
+                // Format: "web,mobile=720,integration=1"; types without hours use JWT_EXPIRATION_HOURS
+                client_types: env_list("JWT_CLIENT_TYPES", "web,mobile,integration")
+                    .into_iter()
+                    .map(|entry| match entry.split_once('=') {
+                        Some((name, hours)) => {
+                            let hours = hours.trim().parse().unwrap_or_else(|_| {
+                                invalid_vars.push("JWT_CLIENT_TYPES".to_string());
+                                jwt_expiration_hours
+                            });
+                            (name.trim().to_string(), hours)
+                        }
+                        None => (entry, jwt_expiration_hours),
+                    })
+                    .collect(),
+                default_client_type: env::var("JWT_DEFAULT_CLIENT_TYPE")
+                    .unwrap_or_else(|_| "web".to_string()),
+                expiry_mode: env_or("JWT_EXPIRY_MODE", JwtExpiryMode::Fixed, &mut invalid_vars),
+                sliding_threshold_minutes: env_or(
+                    "JWT_SLIDING_THRESHOLD_MINUTES",
+                    60,
+                    &mut invalid_vars,
+                ),
+                max_token_length: env_or("JWT_MAX_TOKEN_LENGTH", 4096, &mut invalid_vars),
             },
             mysql: MysqlConfig {
                 host: env::var("MYSQL_HOST").unwrap_or_else(|_| "localhost".to_string()),
-                port: env::var("MYSQL_PORT")
-                    .unwrap_or_else(|_| "3306".to_string())
-                    .parse()
-                    .unwrap_or(3306),
+                port: env_or("MYSQL_PORT", 3306, &mut invalid_vars),
                 user: env::var("MYSQL_USER").unwrap_or_else(|_| "root".to_string()),
                 password: env::var("MYSQL_PASSWORD").unwrap_or_else(|_| "password".to_string()),
                 database: env::var("MYSQL_DATABASE").unwrap_or_else(|_| "example_db".to_string()),
@@ -85,23 +627,632 @@ impl Config {
                 uri: env::var("MONGODB_URI")
                     .unwrap_or_else(|_| "mongodb://localhost:27017".to_string()),
                 database: env::var("MONGODB_DATABASE").unwrap_or_else(|_| "example_db".to_string()),
+                write_concern: env::var("MONGODB_WRITE_CONCERN")
+                    .ok()
+                    .filter(|s| !s.is_empty()),
+                // Format: "feed_views=1,other_collection=majority"
+                collection_write_concerns: env::var("MONGODB_COLLECTION_WRITE_CONCERNS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .filter_map(|pair| {
+                        let (name, value) = pair.split_once('=')?;
+                        Some((name.trim().to_string(), value.trim().to_string()))
+                    })
+                    .collect(),
+                write_concern_tags: env_list("MONGODB_WRITE_CONCERN_TAGS", ""),
+                feed_view_sample_rate: env_or(
+                    "MONGODB_FEED_VIEW_SAMPLE_RATE",
+                    1.0,
+                    &mut invalid_vars,
+                ),
+                degrade_counts: env_or("MONGODB_DEGRADE_COUNTS", true, &mut invalid_vars),
             },
             redis: RedisConfig {
                 host: env::var("REDIS_HOST").unwrap_or_else(|_| "localhost".to_string()),
-                port: env::var("REDIS_PORT")
-                    .unwrap_or_else(|_| "6379".to_string())
-                    .parse()
-                    .unwrap_or(6379),
+                port: env_or("REDIS_PORT", 6379, &mut invalid_vars),
                 password: env::var("REDIS_PASSWORD").ok(),
+                key_prefix: env::var("REDIS_KEY_PREFIX").unwrap_or_default(),
             },
             kafka: KafkaConfig {
                 brokers: env::var("KAFKA_BROKERS").unwrap_or_else(|_| "localhost:9092".to_string()),
                 group_id: env::var("KAFKA_GROUP_ID")
                     .unwrap_or_else(|_| "example_rust_service".to_string()),
+                send_max_attempts: env_or("KAFKA_SEND_MAX_ATTEMPTS", 3, &mut invalid_vars),
+                send_retry_backoff_ms: env_or(
+                    "KAFKA_SEND_RETRY_BACKOFF_MS",
+                    100,
+                    &mut invalid_vars,
+                ),
+                consumer_heartbeat_interval_seconds: env_or(
+                    "KAFKA_CONSUMER_HEARTBEAT_INTERVAL_SECONDS",
+                    10,
+                    &mut invalid_vars,
+                ),
+                consumer_heartbeat_max_age_seconds: env_or(
+                    "KAFKA_CONSUMER_HEARTBEAT_MAX_AGE_SECONDS",
+                    60,
+                    &mut invalid_vars,
+                ),
+                empty_payload_action: env_or(
+                    "KAFKA_EMPTY_PAYLOAD_ACTION",
+                    EmptyPayloadAction::Skip,
+                    &mut invalid_vars,
+                ),
+                dlq_peek_timeout_ms: env_or("KAFKA_DLQ_PEEK_TIMEOUT_MS", 3000, &mut invalid_vars),
             },
+            rate_limit: RateLimitConfig {
+                comment_cooldown_seconds: env_or(
+                    "RATE_LIMIT_COMMENT_COOLDOWN_SECONDS",
+                    10,
+                    &mut invalid_vars,
+                ),
+                like_per_minute: env_or("RATE_LIMIT_LIKE_PER_MINUTE", 30, &mut invalid_vars),
+                like_burst: env_or("RATE_LIMIT_LIKE_BURST", 10, &mut invalid_vars),
+                view_per_minute: env_or("RATE_LIMIT_VIEW_PER_MINUTE", 120, &mut invalid_vars),
+                view_burst: env_or("RATE_LIMIT_VIEW_BURST", 30, &mut invalid_vars),
+                view_retry_jitter_seconds: env_or(
+                    "RATE_LIMIT_VIEW_RETRY_JITTER_SECONDS",
+                    5,
+                    &mut invalid_vars,
+                ),
+                ip_per_minute: env_or("RATE_LIMIT_IP_PER_MINUTE", 600, &mut invalid_vars),
+                ip_burst: env_or("RATE_LIMIT_IP_BURST", 100, &mut invalid_vars),
+                ip_allowlist: env_networks("RATE_LIMIT_IP_ALLOWLIST", &mut invalid_vars),
+            },
+            notifications: NotificationsConfig {
+                max_per_user: env_or("NOTIFICATIONS_MAX_PER_USER", 500, &mut invalid_vars),
+                trim_interval_seconds: env_or(
+                    "NOTIFICATIONS_TRIM_INTERVAL_SECONDS",
+                    3600,
+                    &mut invalid_vars,
+                ),
+                like_template: env::var("NOTIFICATIONS_LIKE_TEMPLATE")
+                    .unwrap_or_else(|_| "{username} liked your feed".to_string()),
+                comment_template: env::var("NOTIFICATIONS_COMMENT_TEMPLATE")
+                    .unwrap_or_else(|_| "{content}".to_string()),
+                // Format: "fr={username} a aimé votre publication;de={username} gefällt dein Beitrag"
+                like_templates: env_templates("NOTIFICATIONS_LIKE_TEMPLATES"),
+                comment_templates: env_templates("NOTIFICATIONS_COMMENT_TEMPLATES"),
+                content_preview_chars: env_or(
+                    "NOTIFICATIONS_CONTENT_PREVIEW_CHARS",
+                    100,
+                    &mut invalid_vars,
+                ),
+                aggregate_likes: env_or("NOTIFICATIONS_AGGREGATE_LIKES", false, &mut invalid_vars),
+                aggregate_window_minutes: env_or(
+                    "NOTIFICATIONS_AGGREGATE_WINDOW_MINUTES",
+                    60,
+                    &mut invalid_vars,
+                ),
+                aggregate_max_actors: env_or(
+                    "NOTIFICATIONS_AGGREGATE_MAX_ACTORS",
+                    3,
+                    &mut invalid_vars,
+                ),
+                like_aggregate_template: env::var("NOTIFICATIONS_LIKE_AGGREGATE_TEMPLATE")
+                    .unwrap_or_else(|_| "{count} people liked your feed".to_string()),
+                like_aggregate_templates: env_templates("NOTIFICATIONS_LIKE_AGGREGATE_TEMPLATES"),
+            },
+            cors: CorsConfig {
+                allowed_origins: env_list("CORS_ALLOWED_ORIGINS", "*"),
+                allowed_headers: env_list(
+                    "CORS_ALLOWED_HEADERS",
+                    "Authorization,Content-Type,Idempotency-Key,X-Request-Id",
+                ),
+                // Format: "feed=Idempotency-Key,X-Client-Version;admin=X-Admin-Reason"
+                route_allowed_headers: env::var("CORS_ROUTE_ALLOWED_HEADERS")
+                    .unwrap_or_default()
+                    .split(';')
+                    .filter_map(|entry| {
+                        let (scope, headers) = entry.split_once('=')?;
+                        Some((scope.trim().to_string(), split_list(headers)))
+                    })
+                    .collect(),
+                max_age_seconds: env_or("CORS_MAX_AGE_SECONDS", 3600, &mut invalid_vars),
+            },
+            usernames: UsernamesConfig {
+                case_insensitive: env_or("USERNAMES_CASE_INSENSITIVE", true, &mut invalid_vars),
+            },
+            content: ContentConfig {
+                language_detection: env_or("CONTENT_LANGUAGE_DETECTION", false, &mut invalid_vars),
+                max_pinned_feeds: env_or("CONTENT_MAX_PINNED_FEEDS", 3, &mut invalid_vars),
+                max_mentions: env_or("CONTENT_MAX_MENTIONS", 10, &mut invalid_vars),
+                reject_excess_mentions: env_or(
+                    "CONTENT_REJECT_EXCESS_MENTIONS",
+                    false,
+                    &mut invalid_vars,
+                ),
+                feed_categories: env_list(
+                    "CONTENT_FEED_CATEGORIES",
+                    "general,news,sports,technology,entertainment",
+                )
+                .into_iter()
+                .map(|category| category.to_lowercase())
+                .collect(),
+            },
+            search: SearchConfig {
+                backend: env_or(
+                    "SEARCH_BACKEND",
+                    SearchBackendKind::Mysql,
+                    &mut invalid_vars,
+                ),
+                external_url: env::var("SEARCH_EXTERNAL_URL").unwrap_or_default(),
+            },
+            top_stats: TopStatsConfig {
+                slow_job_warning_seconds: env_or(
+                    "TOP_STATS_SLOW_JOB_WARNING_SECONDS",
+                    600,
+                    &mut invalid_vars,
+                ),
+                cache_size: env_or("TOP_STATS_CACHE_SIZE", 1000, &mut invalid_vars),
+                max_limit: env_or("TOP_STATS_MAX_LIMIT", 100, &mut invalid_vars),
+                trim_interval_seconds: env_or(
+                    "TOP_STATS_TRIM_INTERVAL_SECONDS",
+                    300,
+                    &mut invalid_vars,
+                ),
+                orphan_sweep_interval_seconds: env_or(
+                    "TOP_STATS_ORPHAN_SWEEP_INTERVAL_SECONDS",
+                    3600,
+                    &mut invalid_vars,
+                ),
+                content_preview_chars: env_or(
+                    "TOP_STATS_CONTENT_PREVIEW_CHARS",
+                    200,
+                    &mut invalid_vars,
+                ),
+                stream_interval_seconds: env_or(
+                    "TOP_STATS_STREAM_INTERVAL_SECONDS",
+                    5,
+                    &mut invalid_vars,
+                ),
+                like_weight_verified: env_or(
+                    "TOP_STATS_LIKE_WEIGHT_VERIFIED",
+                    1.0,
+                    &mut invalid_vars,
+                ),
+                like_weight_admin: env_or("TOP_STATS_LIKE_WEIGHT_ADMIN", 1.0, &mut invalid_vars),
+            },
+            feed_cache: FeedCacheConfig {
+                enabled: env_or("FEED_CACHE_ENABLED", false, &mut invalid_vars),
+                ttl_seconds: env_or("FEED_CACHE_TTL_SECONDS", 10, &mut invalid_vars),
+            },
+            feed_marker: FeedMarkerConfig {
+                enabled: env_or("FEED_MARKER_ENABLED", true, &mut invalid_vars),
+                ttl_days: env_or("FEED_MARKER_TTL_DAYS", 30, &mut invalid_vars),
+            },
+            like_counts: LikeCountsConfig {
+                denormalized: env_or("LIKE_COUNT_DENORMALIZED", false, &mut invalid_vars),
+                reconcile_interval_seconds: env_or(
+                    "LIKE_COUNT_RECONCILE_INTERVAL_SECONDS",
+                    3600,
+                    &mut invalid_vars,
+                ),
+            },
+            digests: DigestsConfig {
+                enabled: env_or("DIGESTS_ENABLED", false, &mut invalid_vars),
+                hour_utc: env_or("DIGESTS_HOUR_UTC", 8, &mut invalid_vars),
+                active_within_days: env_or("DIGESTS_ACTIVE_WITHIN_DAYS", 30, &mut invalid_vars),
+                min_unread: env_or("DIGESTS_MIN_UNREAD", 1, &mut invalid_vars),
+                top_actors: env_or("DIGESTS_TOP_ACTORS", 3, &mut invalid_vars),
+                publish: env_or("DIGESTS_PUBLISH", false, &mut invalid_vars),
+            },
+            audit_log: AuditLogConfig {
+                enabled: env_or("AUDIT_LOG_ENABLED", true, &mut invalid_vars),
+                include_reads: env_or("AUDIT_LOG_INCLUDE_READS", true, &mut invalid_vars),
+            },
+            moderation: ModerationConfig {
+                reports_enabled: env_or("MODERATION_REPORTS_ENABLED", true, &mut invalid_vars),
+                max_reason_chars: env_or("MODERATION_MAX_REASON_CHARS", 500, &mut invalid_vars),
+            },
+            feed_expiry: FeedExpiryConfig {
+                check_interval_seconds: env_or(
+                    "FEED_EXPIRY_CHECK_INTERVAL_SECONDS",
+                    60,
+                    &mut invalid_vars,
+                ),
+                max_lifetime_hours: env_or("FEED_EXPIRY_MAX_LIFETIME_HOURS", 0, &mut invalid_vars),
+            },
+            feed_history: FeedHistoryConfig {
+                enabled: env_or("FEED_HISTORY_ENABLED", false, &mut invalid_vars),
+                max_revisions: env_or("FEED_HISTORY_MAX_REVISIONS", 20, &mut invalid_vars),
+            },
+            startup: StartupConfig {
+                connect_max_attempts: env_or("STARTUP_CONNECT_MAX_ATTEMPTS", 10, &mut invalid_vars),
+                connect_retry_backoff_ms: env_or(
+                    "STARTUP_CONNECT_RETRY_BACKOFF_MS",
+                    500,
+                    &mut invalid_vars,
+                ),
+                connect_max_backoff_ms: env_or(
+                    "STARTUP_CONNECT_MAX_BACKOFF_MS",
+                    10_000,
+                    &mut invalid_vars,
+                ),
+                self_test: env_or("SELF_TEST_ON_BOOT", false, &mut invalid_vars),
+                self_test_strict: env_or("SELF_TEST_STRICT", false, &mut invalid_vars),
+            },
+            invalid_vars,
         })
     }
 
+    pub fn is_production(&self) -> bool {
+        self.app_env.eq_ignore_ascii_case("production")
+    }
+
+    /// Validate the loaded configuration, collecting every problem into one error:
+    /// malformed numeric env vars, empty required fields, and (in production)
+    /// an insecure JWT secret
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        let mut errors: Vec<String> = self
+            .invalid_vars
+            .iter()
+            .map(|var| format!("{} has an invalid value", var))
+            .collect();
+
+        let required = [
+            ("SERVER_HOST", &self.server.host),
+            ("JWT_SECRET", &self.jwt.secret),
+            ("AUTH_HEADER_SCHEME", &self.jwt.header_scheme),
+            ("MYSQL_HOST", &self.mysql.host),
+            ("MYSQL_USER", &self.mysql.user),
+            ("MYSQL_DATABASE", &self.mysql.database),
+            ("MONGODB_URI", &self.mongodb.uri),
+            ("MONGODB_DATABASE", &self.mongodb.database),
+            ("REDIS_HOST", &self.redis.host),
+            ("KAFKA_BROKERS", &self.kafka.brokers),
+            ("KAFKA_GROUP_ID", &self.kafka.group_id),
+        ];
+        for (var, value) in required {
+            if value.trim().is_empty() {
+                errors.push(format!("{} must not be empty", var));
+            }
+        }
+
+        if self.jwt.header_scheme.contains(char::is_whitespace) {
+            errors.push("AUTH_HEADER_SCHEME must not contain whitespace".to_string());
+        }
+
+        if self.jwt.nonce_ttl_seconds == 0 {
+            errors.push("AUTH_NONCE_TTL_SECONDS must be at least 1".to_string());
+        }
+
+        if self.startup.connect_max_attempts == 0 {
+            errors.push("STARTUP_CONNECT_MAX_ATTEMPTS must be at least 1".to_string());
+        }
+        if self.startup.connect_max_backoff_ms < self.startup.connect_retry_backoff_ms {
+            errors.push(
+                "STARTUP_CONNECT_MAX_BACKOFF_MS must not be below STARTUP_CONNECT_RETRY_BACKOFF_MS"
+                    .to_string(),
+            );
+        }
+        if self.startup.self_test_strict && !self.startup.self_test {
+            errors.push("SELF_TEST_STRICT requires SELF_TEST_ON_BOOT=true".to_string());
+        }
+
+        if self.kafka.send_max_attempts == 0 {
+            errors.push("KAFKA_SEND_MAX_ATTEMPTS must be at least 1".to_string());
+        }
+
+        if self.kafka.consumer_heartbeat_interval_seconds == 0 {
+            errors.push("KAFKA_CONSUMER_HEARTBEAT_INTERVAL_SECONDS must be at least 1".to_string());
+        } else if self.kafka.consumer_heartbeat_max_age_seconds
+            <= self.kafka.consumer_heartbeat_interval_seconds
+        {
+            errors.push(
+                "KAFKA_CONSUMER_HEARTBEAT_MAX_AGE_SECONDS must be greater than the heartbeat interval"
+                    .to_string(),
+            );
+        }
+
+        // A typo would otherwise become a custom tag set and fail every write
+        let tags = &self.mongodb.write_concern_tags;
+        if let Some(write_concern) = &self.mongodb.write_concern {
+            if let Err(e) = validate_write_concern(write_concern, tags) {
+                errors.push(format!("MONGODB_WRITE_CONCERN: {}", e));
+            }
+        }
+        let mut overrides: Vec<_> = self.mongodb.collection_write_concerns.iter().collect();
+        overrides.sort();
+        for (collection, write_concern) in overrides {
+            if let Err(e) = validate_write_concern(write_concern, tags) {
+                errors.push(format!(
+                    "MONGODB_COLLECTION_WRITE_CONCERNS ({}): {}",
+                    collection, e
+                ));
+            }
+        }
+
+        let sample_rate = self.mongodb.feed_view_sample_rate;
+        if !(sample_rate > 0.0 && sample_rate <= 1.0) {
+            errors.push(
+                "MONGODB_FEED_VIEW_SAMPLE_RATE must be greater than 0 and at most 1".to_string(),
+            );
+        }
+
+        if self.search.backend == SearchBackendKind::External
+            && self.search.external_url.trim().is_empty()
+        {
+            errors.push("SEARCH_EXTERNAL_URL must be set when SEARCH_BACKEND=external".to_string());
+        }
+
+        if self.rate_limit.like_per_minute > 0 && self.rate_limit.like_burst == 0 {
+            errors.push("RATE_LIMIT_LIKE_BURST must be at least 1".to_string());
+        }
+
+        if self.rate_limit.view_per_minute > 0 && self.rate_limit.view_burst == 0 {
+            errors.push("RATE_LIMIT_VIEW_BURST must be at least 1".to_string());
+        }
+
+        if self.rate_limit.ip_per_minute > 0 && self.rate_limit.ip_burst == 0 {
+            errors.push("RATE_LIMIT_IP_BURST must be at least 1".to_string());
+        }
+
+        if self.feed_cache.enabled && self.feed_cache.ttl_seconds == 0 {
+            errors.push("FEED_CACHE_TTL_SECONDS must be at least 1".to_string());
+        }
+
+        if self.top_stats.cache_size == 0 {
+            errors.push("TOP_STATS_CACHE_SIZE must be at least 1".to_string());
+        }
+        if self.top_stats.max_limit == 0 {
+            errors.push("TOP_STATS_MAX_LIMIT must be at least 1".to_string());
+        }
+
+        for (var, weight) in [
+            (
+                "TOP_STATS_LIKE_WEIGHT_VERIFIED",
+                self.top_stats.like_weight_verified,
+            ),
+            (
+                "TOP_STATS_LIKE_WEIGHT_ADMIN",
+                self.top_stats.like_weight_admin,
+            ),
+        ] {
+            if !(weight.is_finite() && weight > 0.0) {
+                errors.push(format!("{} must be a positive number", var));
+            }
+        }
+
+        if self.like_counts.denormalized && self.like_counts.reconcile_interval_seconds == 0 {
+            errors.push("LIKE_COUNT_RECONCILE_INTERVAL_SECONDS must be at least 1".to_string());
+        }
+
+        if self.feed_history.enabled && self.feed_history.max_revisions == 0 {
+            errors.push("FEED_HISTORY_MAX_REVISIONS must be at least 1".to_string());
+        }
+
+        if self.content.max_pinned_feeds == 0 {
+            errors.push("CONTENT_MAX_PINNED_FEEDS must be at least 1".to_string());
+        }
+        // Categories end up in the column, query strings and feed cache keys; GET /api/feed
+        // rejects any category filter not in this list
+        let invalid_categories: Vec<&str> = self
+            .content
+            .feed_categories
+            .iter()
+            .filter(|category| {
+                category.len() > 32
+                    || !category
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            })
+            .map(String::as_str)
+            .collect();
+        if !invalid_categories.is_empty() {
+            errors.push(format!(
+                "CONTENT_FEED_CATEGORIES entries must be at most 32 letters, digits, '-' or '_': {}",
+                invalid_categories.join(", ")
+            ));
+        }
+
+        if self.moderation.max_reason_chars == 0 {
+            errors.push("MODERATION_MAX_REASON_CHARS must be at least 1".to_string());
+        }
+
+        if self.digests.hour_utc > 23 {
+            errors.push("DIGESTS_HOUR_UTC must be between 0 and 23".to_string());
+        }
+        if self.digests.min_unread == 0 {
+            errors.push("DIGESTS_MIN_UNREAD must be at least 1".to_string());
+        }
+
+        if self.notifications.trim_interval_seconds == 0 {
+            errors.push("NOTIFICATIONS_TRIM_INTERVAL_SECONDS must be at least 1".to_string());
+        }
+        if self.notifications.aggregate_likes {
+            if self.notifications.aggregate_window_minutes == 0 {
+                errors
+                    .push("NOTIFICATIONS_AGGREGATE_WINDOW_MINUTES must be at least 1".to_string());
+            }
+            if self.notifications.aggregate_max_actors == 0 {
+                errors.push("NOTIFICATIONS_AGGREGATE_MAX_ACTORS must be at least 1".to_string());
+            }
+        }
+
+        let like_placeholders = &["username", "feed_id"][..];
+        let comment_placeholders = &["username", "feed_id", "content"][..];
+        let aggregate_placeholders = &["username", "usernames", "count", "feed_id"][..];
+        let mut templates = vec![
+            (
+                "NOTIFICATIONS_LIKE_TEMPLATE".to_string(),
+                &self.notifications.like_template,
+                like_placeholders,
+            ),
+            (
+                "NOTIFICATIONS_COMMENT_TEMPLATE".to_string(),
+                &self.notifications.comment_template,
+                comment_placeholders,
+            ),
+            (
+                "NOTIFICATIONS_LIKE_AGGREGATE_TEMPLATE".to_string(),
+                &self.notifications.like_aggregate_template,
+                aggregate_placeholders,
+            ),
+        ];
+        let localized = [
+            (
+                "NOTIFICATIONS_LIKE_TEMPLATES",
+                &self.notifications.like_templates,
+                like_placeholders,
+            ),
+            (
+                "NOTIFICATIONS_COMMENT_TEMPLATES",
+                &self.notifications.comment_templates,
+                comment_placeholders,
+            ),
+            (
+                "NOTIFICATIONS_LIKE_AGGREGATE_TEMPLATES",
+                &self.notifications.like_aggregate_templates,
+                aggregate_placeholders,
+            ),
+        ];
+        for (key, by_language, placeholders) in localized {
+            for (language, template) in by_language {
+                if normalize_language(language).as_deref() != Some(language.as_str()) {
+                    errors.push(format!(
+                        "{} entry {:?} is not a valid language tag",
+                        key, language
+                    ));
+                }
+                templates.push((format!("{} ({})", key, language), template, placeholders));
+            }
+        }
+        for (key, template, placeholders) in templates {
+            let values: Vec<(&str, &str)> = placeholders.iter().map(|name| (*name, "")).collect();
+            if template.trim().is_empty() {
+                errors.push(format!("{} must not be empty", key));
+            } else if let Err(name) = render_template(template, &values) {
+                errors.push(format!(
+                    "{} uses unknown placeholder {{{}}}; available: {}",
+                    key,
+                    name,
+                    placeholders.join(", ")
+                ));
+            }
+        }
+
+        // Signing in must never lower the cap (0 = no cap)
+        let (anonymous_depth, authenticated_depth) = (
+            self.server.feed_max_depth_anonymous,
+            self.server.feed_max_depth_authenticated,
+        );
+        if authenticated_depth > 0
+            && (anonymous_depth == 0 || authenticated_depth < anonymous_depth)
+        {
+            errors.push(format!(
+                "SERVER_FEED_MAX_DEPTH_AUTHENTICATED ({}) must be 0 or at least SERVER_FEED_MAX_DEPTH_ANONYMOUS ({})",
+                authenticated_depth, anonymous_depth
+            ));
+        }
+
+        if HeaderName::from_bytes(self.server.request_id_header.as_bytes()).is_err() {
+            errors.push(format!(
+                "SERVER_REQUEST_ID_HEADER {:?} is not a valid header name",
+                self.server.request_id_header
+            ));
+        }
+
+        let vendor = &self.server.media_type_vendor;
+        if vendor.is_empty()
+            || !vendor
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.')
+        {
+            errors.push(format!(
+                "SERVER_MEDIA_TYPE_VENDOR {:?} may only contain letters, digits, '-' and '.'",
+                vendor
+            ));
+        }
+
+        let cors_headers = self
+            .cors
+            .allowed_headers
+            .iter()
+            .chain(self.cors.route_allowed_headers.values().flatten());
+        for header in cors_headers {
+            if HeaderName::from_bytes(header.as_bytes()).is_err() {
+                errors.push(format!(
+                    "CORS header {:?} is not a valid header name",
+                    header
+                ));
+            }
+        }
+        for origin in &self.cors.allowed_origins {
+            if origin != "*" && origin.parse::<Uri>().is_err() {
+                errors.push(format!(
+                    "CORS_ALLOWED_ORIGINS entry {:?} is not a valid origin",
+                    origin
+                ));
+            }
+        }
+
+        if self.jwt.expiration_hours <= 0 {
+            errors.push("JWT_EXPIRATION_HOURS must be positive".to_string());
+        }
+
+        for (client_type, hours) in &self.jwt.client_types {
+            if *hours <= 0 {
+                errors.push(format!(
+                    "JWT_CLIENT_TYPES lifetime of {:?} must be positive",
+                    client_type
+                ));
+            }
+        }
+        if !self
+            .jwt
+            .client_types
+            .contains_key(&self.jwt.default_client_type)
+        {
+            errors.push(format!(
+                "JWT_DEFAULT_CLIENT_TYPE {:?} is not listed in JWT_CLIENT_TYPES",
+                self.jwt.default_client_type
+            ));
+        }
+
+        if self.jwt.max_token_length == 0 {
+            errors.push("JWT_MAX_TOKEN_LENGTH must be at least 1".to_string());
+        }
+
+        if self.jwt.expiry_mode == JwtExpiryMode::Sliding {
+            if self.jwt.sliding_threshold_minutes <= 0 {
+                errors.push("JWT_SLIDING_THRESHOLD_MINUTES must be positive".to_string());
+            }
+            // Otherwise every request would be answered with a new token
+            for (client_type, hours) in &self.jwt.client_types {
+                if self.jwt.sliding_threshold_minutes >= hours * 60 {
+                    errors.push(format!(
+                        "JWT_SLIDING_THRESHOLD_MINUTES must be shorter than the lifetime of {:?}",
+                        client_type
+                    ));
+                }
+            }
+        }
+
+        if self.is_production() && self.server.seed_endpoint {
+            errors.push("ENABLE_SEED_ENDPOINT must not be set in production".to_string());
+        }
+
+        if self.is_production()
+            && (INSECURE_JWT_SECRETS.contains(&self.jwt.secret.as_str())
+                || self.jwt.secret.len() < 32)
+        {
+            errors.push(
+                "JWT_SECRET must be set to a random value of at least 32 characters in production"
+                    .to_string(),
+            );
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Invalid configuration:\n  - {}",
+                errors.join("\n  - ")
+            ))
+        }
+    }
+
     pub fn mysql_url(&self) -> String {
         format!(
             "mysql://{}:{}@{}:{}/{}",
@@ -124,3 +1275,61 @@ impl Config {
         }
     }
 }
+
+/// Comma-separated values, trimmed, without empty entries
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `language=template` pairs separated by `;`; language tags are lowercased
+fn env_templates(key: &str) -> HashMap<String, String> {
+    env::var(key)
+        .unwrap_or_default()
+        .split(';')
+        .filter_map(|entry| {
+            let (language, template) = entry.split_once('=')?;
+            Some((
+                language.trim().replace('_', "-").to_ascii_lowercase(),
+                template.trim().to_string(),
+            ))
+        })
+        .collect()
+}
+
+fn env_list(key: &str, default: &str) -> Vec<String> {
+    split_list(&env::var(key).unwrap_or_else(|_| default.to_string()))
+}
+
+/// Comma-separated CIDRs; a bare address is a single-host network
+fn env_networks(key: &str, invalid: &mut Vec<String>) -> Vec<IpNet> {
+    let mut networks = Vec::new();
+    for item in env_list(key, "") {
+        match item
+            .parse::<IpNet>()
+            .or_else(|_| item.parse::<IpAddr>().map(IpNet::from))
+        {
+            Ok(network) => networks.push(network),
+            Err(_) if !invalid.iter().any(|var| var == key) => invalid.push(key.to_string()),
+            Err(_) => {}
+        }
+    }
+    networks
+}
+
+/// Read and parse an env var, falling back to `default` when it is unset.
+/// A set-but-malformed value also falls back, but is recorded in `invalid` so
+/// `Config::validate()` can reject it instead of silently using the default.
+fn env_or<T: FromStr>(key: &str, default: T, invalid: &mut Vec<String>) -> T {
+    match env::var(key) {
+        Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+            invalid.push(key.to_string());
+            default
+        }),
+        Err(_) => default,
+    }
+}
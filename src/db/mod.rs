@@ -1,7 +1,11 @@
 pub mod mongodb;
 pub mod mysql;
+pub mod query_count;
 pub mod redis;
+pub mod retry;
 
 pub use mongodb::*;
 pub use mysql::*;
+pub use query_count::*;
 pub use redis::*;
+pub use retry::*;
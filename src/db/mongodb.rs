@@ -1,8 +1,258 @@
 use crate::config::Config;
-use mongodb::{Client, Database};
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use mongodb::error::ErrorKind;
+use mongodb::options::{
+    Acknowledgment, ClientOptions, CollectionOptions, CreateCollectionOptions, WriteConcern,
+};
+use mongodb::{Client, Collection, Database};
+use std::collections::HashMap;
 
 pub async fn create_mongodb_client(config: &Config) -> Result<Database, anyhow::Error> {
-    let client = Client::with_uri_str(&config.mongodb.uri).await?;
+    let mut options = ClientOptions::parse(&config.mongodb.uri).await?;
+    if let Some(write_concern) = &config.mongodb.write_concern {
+        options.write_concern = Some(parse_write_concern(write_concern));
+    }
+
+    let client = Client::with_options(options)?;
     let db = client.database(&config.mongodb.database);
+    // The client connects lazily; ping so an unreachable server fails here
+    db.run_command(doc! {"ping": 1}, None).await?;
     Ok(db)
 }
+
+/// Server error code for creating a collection that already exists
+const NAMESPACE_EXISTS: i32 = 48;
+
+/// `$jsonSchema` validators for the collections the app writes.
+/// Integers and timestamps must be `long`: `i64` fields and `ts_seconds` dates serialize
+/// that way, and a document written with another type would only fail when read back.
+fn collection_validators() -> Vec<(&'static str, Document)> {
+    vec![
+        (
+            "audit_log",
+            doc! {"$jsonSchema": {
+                "bsonType": "object",
+                "required": ["user_id", "action", "target", "status", "success", "created_at"],
+                "properties": {
+                    "user_id": {"bsonType": "long"},
+                    "action": {"bsonType": "string"},
+                    "target": {"bsonType": "object"},
+                    "status": {"bsonType": "int"},
+                    "success": {"bsonType": "bool"},
+                    "request_id": {"bsonType": "string"},
+                    "created_at": {"bsonType": "long"},
+                },
+            }},
+        ),
+        (
+            "feed_reports",
+            doc! {"$jsonSchema": {
+                "bsonType": "object",
+                "required": ["feed_id", "user_id", "reason", "status", "created_at"],
+                "properties": {
+                    "feed_id": {"bsonType": "long"},
+                    "user_id": {"bsonType": "long"},
+                    "reason": {"bsonType": "string"},
+                    "status": {"enum": ["open", "dismissed", "hidden"]},
+                    "created_at": {"bsonType": "long"},
+                    "resolved_by": {"bsonType": "long"},
+                    "resolved_at": {"bsonType": "long"},
+                },
+            }},
+        ),
+        (
+            "comments",
+            doc! {"$jsonSchema": {
+                "bsonType": "object",
+                "required": ["feed_id", "user_id", "content", "created_at"],
+                "properties": {
+                    "feed_id": {"bsonType": "long"},
+                    "user_id": {"bsonType": "long"},
+                    "content": {"bsonType": "string"},
+                    "created_at": {"bsonType": "long"},
+                    "updated_at": {"bsonType": "long"},
+                    "parent_comment_id": {"bsonType": "string"},
+                },
+            }},
+        ),
+        (
+            "feed_edits",
+            doc! {"$jsonSchema": {
+                "bsonType": "object",
+                "required": ["feed_id", "user_id", "content", "edited_at"],
+                "properties": {
+                    "feed_id": {"bsonType": "long"},
+                    "user_id": {"bsonType": "long"},
+                    "content": {"bsonType": "string"},
+                    "edited_at": {"bsonType": "long"},
+                },
+            }},
+        ),
+        (
+            "feed_views",
+            doc! {"$jsonSchema": {
+                "bsonType": "object",
+                "required": ["feed_id", "user_id", "viewed_at"],
+                "properties": {
+                    "feed_id": {"bsonType": "long"},
+                    "user_id": {"bsonType": "long"},
+                    "viewed_at": {"bsonType": "long"},
+                    "weight": {"bsonType": "double"},
+                },
+            }},
+        ),
+        (
+            "notifications",
+            doc! {"$jsonSchema": {
+                "bsonType": "object",
+                "required": [
+                    "user_id", "from_user_id", "from_username", "feed_id",
+                    "notification_type", "content", "created_at", "is_read",
+                ],
+                "properties": {
+                    "user_id": {"bsonType": "long"},
+                    "from_user_id": {"bsonType": "long"},
+                    "from_username": {"bsonType": "string"},
+                    "feed_id": {"bsonType": "long"},
+                    "notification_type": {"enum": ["like", "comment"]},
+                    "content": {"bsonType": "string"},
+                    "created_at": {"bsonType": "long"},
+                    "is_read": {"bsonType": "bool"},
+                    "actor_count": {"bsonType": "long"},
+                    "recent_actors": {"bsonType": "array", "items": {"bsonType": "string"}},
+                },
+            }},
+        ),
+    ]
+}
+
+/// Create the `audit_log`, `feed_reports`, `comments`, `feed_edits`, `feed_views` and
+/// `notifications` collections with JSON-schema validators, so malformed documents are rejected on insert.
+/// Idempotent: collections that already have a validator are left alone, and existing
+/// collections without one get it added via `collMod`.
+pub async fn ensure_mongodb_schema(db: &Database) -> Result<(), anyhow::Error> {
+    let mut cursor = db.list_collections(None, None).await?;
+    let mut existing: HashMap<String, bool> = HashMap::new();
+    while cursor.advance().await? {
+        let spec = cursor.deserialize_current()?;
+        existing.insert(spec.name, spec.options.validator.is_some());
+    }
+
+    for (name, validator) in collection_validators() {
+        match existing.get(name) {
+            Some(true) => continue,
+            Some(false) => add_validator(db, name, validator).await?,
+            None => {
+                let options = CreateCollectionOptions::builder()
+                    .validator(validator.clone())
+                    .build();
+                match db.create_collection(name, options).await {
+                    Ok(()) => log::info!("Created MongoDB collection {} with validator", name),
+                    // Another instance created it first
+                    Err(e) if matches!(*e.kind, ErrorKind::Command(ref c) if c.code == NAMESPACE_EXISTS) => {
+                        add_validator(db, name, validator).await?
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn add_validator(
+    db: &Database,
+    name: &str,
+    validator: Document,
+) -> Result<(), anyhow::Error> {
+    db.run_command(doc! {"collMod": name, "validator": validator}, None)
+        .await?;
+    log::info!("Added validator to MongoDB collection {}", name);
+    Ok(())
+}
+
+/// Check a configured write concern: "majority" (any case), a node count, or one of the
+/// custom tag set names in `tags`. Anything else would be sent to the server as a tag set
+/// and only fail on the first write.
+pub fn validate_write_concern(value: &str, tags: &[String]) -> Result<(), String> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("majority")
+        || value.parse::<u32>().is_ok()
+        || tags.iter().any(|tag| tag == value)
+    {
+        Ok(())
+    } else {
+        Err(format!(
+            "unknown write concern '{}': use majority, a node count, or a tag set listed in MONGODB_WRITE_CONCERN_TAGS",
+            value
+        ))
+    }
+}
+
+/// Parse a write concern string: "majority", a node count ("1", "2"), or a custom tag set name
+pub fn parse_write_concern(value: &str) -> WriteConcern {
+    let acknowledgment = match value.trim() {
+        majority if majority.eq_ignore_ascii_case("majority") => Acknowledgment::Majority,
+        other => match other.parse::<u32>() {
+            Ok(nodes) => Acknowledgment::Nodes(nodes),
+            Err(_) => Acknowledgment::Custom(other.to_string()),
+        },
+    };
+    WriteConcern::builder().w(acknowledgment).build()
+}
+
+/// Get a collection handle, applying the per-collection write concern override if configured.
+/// Collections without an override inherit the client's default write concern.
+pub fn mongo_collection<T>(db: &Database, config: &Config, name: &str) -> Collection<T> {
+    match config.mongodb.collection_write_concerns.get(name) {
+        Some(write_concern) => {
+            let options = CollectionOptions::builder()
+                .write_concern(parse_write_concern(write_concern))
+                .build();
+            db.collection_with_options::<T>(name, options)
+        }
+        None => db.collection::<T>(name),
+    }
+}
+
+/// Match a document `_id` given as a string.
+/// New documents always get a UUID string `_id`, but a hex string that parses as an
+/// ObjectId also matches documents inserted with a native ObjectId `_id`.
+pub fn id_filter(id: &str) -> Bson {
+    match ObjectId::parse_str(id) {
+        Ok(oid) => Bson::Document(doc! {"$in": [id, oid]}),
+        Err(_) => Bson::String(id.to_string()),
+    }
+}
+
+/// Like `id_filter`, for a list of IDs (`{"$in": [...]}`)
+pub fn ids_filter(ids: &[String]) -> Bson {
+    let mut values: Vec<Bson> = Vec::with_capacity(ids.len());
+    for id in ids {
+        values.push(Bson::String(id.clone()));
+        if let Ok(oid) = ObjectId::parse_str(id) {
+            values.push(Bson::ObjectId(oid));
+        }
+    }
+    Bson::Document(doc! {"$in": values})
+}
+
+/// Deserialize an optional `_id` that may be stored as a string or an ObjectId.
+/// ObjectIds are returned as their hex string.
+pub fn deserialize_string_id<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+    use serde::Deserialize;
+
+    match Option::<Bson>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Bson::String(id)) => Ok(Some(id)),
+        Some(Bson::ObjectId(oid)) => Ok(Some(oid.to_hex())),
+        Some(other) => Err(D::Error::custom(format!(
+            "unsupported _id type: {:?}",
+            other.element_type()
+        ))),
+    }
+}
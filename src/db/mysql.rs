@@ -1,3 +1,4 @@
+use super::query_count::record_query;
 use crate::config::Config;
 use sea_orm::{ConnectionTrait, Database, DatabaseConnection};
 
@@ -5,7 +6,10 @@ pub type DbPool = DatabaseConnection;
 
 pub async fn create_mysql_pool(config: &Config) -> Result<DbPool, anyhow::Error> {
     let url = config.mysql_url();
-    let db = Database::connect(&url).await?;
+    let mut db = Database::connect(&url).await?;
+    if config.server.query_count_logging {
+        db.set_metric_callback(record_query);
+    }
 
     // Create tables if not exists using SeaORM migrations or raw SQL
     // For now, we'll use raw SQL for schema creation
@@ -16,6 +20,8 @@ pub async fn create_mysql_pool(config: &Config) -> Result<DbPool, anyhow::Error>
             email VARCHAR(255) UNIQUE NOT NULL,
             username VARCHAR(255) UNIQUE NOT NULL,
             password_hash VARCHAR(255) NOT NULL,
+            is_admin BOOLEAN NOT NULL DEFAULT FALSE,
+            is_verified BOOLEAN NOT NULL DEFAULT FALSE,
             created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
             updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
         );
@@ -24,11 +30,24 @@ pub async fn create_mysql_pool(config: &Config) -> Result<DbPool, anyhow::Error>
             id BIGINT AUTO_INCREMENT PRIMARY KEY,
             user_id BIGINT NOT NULL,
             content TEXT NOT NULL,
+            language VARCHAR(8) NULL DEFAULT NULL,
+            category VARCHAR(32) NULL DEFAULT NULL,
+            publish_at TIMESTAMP NULL DEFAULT NULL,
+            is_published BOOLEAN NOT NULL DEFAULT TRUE,
+            like_count BIGINT NOT NULL DEFAULT 0,
+            pinned_at TIMESTAMP NULL DEFAULT NULL,
+            hidden_at TIMESTAMP NULL DEFAULT NULL,
+            expires_at TIMESTAMP NULL DEFAULT NULL,
+            is_expired BOOLEAN NOT NULL DEFAULT FALSE,
             created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
             updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
             FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
             INDEX idx_user_id (user_id),
-            INDEX idx_created_at (created_at)
+            INDEX idx_created_at (created_at),
+            INDEX idx_publish_at (publish_at),
+            INDEX idx_expires_at (expires_at),
+            INDEX idx_language (language),
+            INDEX idx_category (category)
         );
         
         CREATE TABLE IF NOT EXISTS feed_likes (
@@ -42,6 +61,29 @@ pub async fn create_mysql_pool(config: &Config) -> Result<DbPool, anyhow::Error>
             INDEX idx_feed_id (feed_id),
             INDEX idx_user_id (user_id)
         );
+        
+        CREATE TABLE IF NOT EXISTS feed_reactions (
+            id BIGINT AUTO_INCREMENT PRIMARY KEY,
+            feed_id BIGINT NOT NULL,
+            user_id BIGINT NOT NULL,
+            reaction_type VARCHAR(16) NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE KEY unique_feed_user_reaction (feed_id, user_id, reaction_type),
+            FOREIGN KEY (feed_id) REFERENCES feeds(id) ON DELETE CASCADE,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+            INDEX idx_user_id (user_id)
+        );
+        
+        CREATE TABLE IF NOT EXISTS bookmarks (
+            id BIGINT AUTO_INCREMENT PRIMARY KEY,
+            user_id BIGINT NOT NULL,
+            feed_id BIGINT NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE KEY unique_user_feed (user_id, feed_id),
+            FOREIGN KEY (feed_id) REFERENCES feeds(id) ON DELETE CASCADE,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+            INDEX idx_user_created_at (user_id, created_at)
+        );
     "#;
 
     // Execute schema creation
@@ -56,5 +98,56 @@ pub async fn create_mysql_pool(config: &Config) -> Result<DbPool, anyhow::Error>
         }
     }
 
+    // Columns added after the initial schema; CREATE TABLE IF NOT EXISTS
+    // does not add them to tables created by older versions
+    let columns = [
+        ("users", "is_admin", "BOOLEAN NOT NULL DEFAULT FALSE"),
+        ("users", "is_verified", "BOOLEAN NOT NULL DEFAULT FALSE"),
+        ("feeds", "publish_at", "TIMESTAMP NULL DEFAULT NULL"),
+        ("feeds", "is_published", "BOOLEAN NOT NULL DEFAULT TRUE"),
+        ("feeds", "language", "VARCHAR(8) NULL DEFAULT NULL"),
+        ("feeds", "like_count", "BIGINT NOT NULL DEFAULT 0"),
+        ("feeds", "pinned_at", "TIMESTAMP NULL DEFAULT NULL"),
+        ("feeds", "hidden_at", "TIMESTAMP NULL DEFAULT NULL"),
+        ("feeds", "expires_at", "TIMESTAMP NULL DEFAULT NULL"),
+        ("feeds", "category", "VARCHAR(32) NULL DEFAULT NULL"),
+        ("feeds", "is_expired", "BOOLEAN NOT NULL DEFAULT FALSE"),
+    ];
+    for (table, column, definition) in columns {
+        add_column_if_missing(&db, table, column, definition).await?;
+    }
+
     Ok(db)
 }
+
+async fn add_column_if_missing(
+    db: &DbPool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), anyhow::Error> {
+    let stmt = sea_orm::Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::MySql,
+        r#"
+            SELECT COUNT(*) AS column_count
+            FROM INFORMATION_SCHEMA.COLUMNS
+            WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND COLUMN_NAME = ?
+        "#,
+        [table.into(), column.into()],
+    );
+    let exists = match db.query_one(stmt).await? {
+        Some(row) => row.try_get::<i64>("", "column_count")? > 0,
+        None => false,
+    };
+
+    if !exists {
+        log::info!("Adding column {}.{}", table, column);
+        let stmt = sea_orm::Statement::from_string(
+            sea_orm::DatabaseBackend::MySql,
+            format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+        );
+        db.execute(stmt).await?;
+    }
+
+    Ok(())
+}
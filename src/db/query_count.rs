@@ -0,0 +1,37 @@
+use sea_orm::metric::Info;
+use std::cell::Cell;
+use std::future::Future;
+use std::time::Duration;
+
+/// SeaORM queries run while a future was being counted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    pub count: u64,
+    pub elapsed: Duration,
+}
+
+tokio::task_local! {
+    static QUERY_STATS: Cell<QueryStats>;
+}
+
+/// Metric callback for `DatabaseConnection::set_metric_callback`.
+/// Queries outside `count_queries` (background jobs, consumers) are ignored.
+pub fn record_query(info: &Info<'_>) {
+    let _ = QUERY_STATS.try_with(|stats| {
+        let mut current = stats.get();
+        current.count += 1;
+        current.elapsed += info.elapsed;
+        stats.set(current);
+    });
+}
+
+/// Run `fut`, returning its output with the queries it ran on a connection that has
+/// `record_query` installed. Queries must run on the same task, which holds for handlers.
+pub async fn count_queries<F: Future>(fut: F) -> (F::Output, QueryStats) {
+    QUERY_STATS
+        .scope(Cell::new(QueryStats::default()), async move {
+            let output = fut.await;
+            (output, QUERY_STATS.with(Cell::get))
+        })
+        .await
+}
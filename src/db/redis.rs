@@ -6,3 +6,16 @@ pub fn create_redis_client(config: &Config) -> Result<RedisClient, anyhow::Error
     let client = RedisClient::open(url)?;
     Ok(client)
 }
+
+/// `create_redis_client`, checked with a `PING` since opening the client does not connect
+pub async fn connect_redis(config: &Config) -> Result<RedisClient, anyhow::Error> {
+    let client = create_redis_client(config)?;
+    let mut conn = client.get_async_connection().await?;
+    redis::cmd("PING").query_async::<_, ()>(&mut conn).await?;
+    Ok(client)
+}
+
+/// Build a Redis key with the configured environment prefix (`REDIS_KEY_PREFIX`)
+pub fn redis_key(config: &Config, key: &str) -> String {
+    format!("{}{}", config.redis.key_prefix, key)
+}
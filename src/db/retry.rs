@@ -0,0 +1,43 @@
+use crate::config::Config;
+use std::future::Future;
+use std::time::Duration;
+
+/// Run `connect` until it succeeds, up to `STARTUP_CONNECT_MAX_ATTEMPTS` times.
+/// Waits `STARTUP_CONNECT_RETRY_BACKOFF_MS` after the first failure, doubling up to
+/// `STARTUP_CONNECT_MAX_BACKOFF_MS`, so the service can start before its dependencies
+/// are ready. Only the final failure is returned.
+pub async fn connect_with_retry<T, F, Fut>(
+    config: &Config,
+    name: &str,
+    mut connect: F,
+) -> Result<T, anyhow::Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, anyhow::Error>>,
+{
+    let max_attempts = config.startup.connect_max_attempts.max(1);
+    let max_backoff = Duration::from_millis(config.startup.connect_max_backoff_ms);
+    let mut backoff = Duration::from_millis(config.startup.connect_retry_backoff_ms);
+    let mut attempt = 1;
+    loop {
+        match connect().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < max_attempts => {
+                log::warn!(
+                    "{} connection attempt {}/{} failed, retrying in {:?}: {:?}",
+                    name,
+                    attempt,
+                    max_attempts,
+                    backoff,
+                    e
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(max_backoff);
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(e.context(format!("{} unreachable after {} attempts", name, attempt)))
+            }
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use sea_orm::entity::prelude::*;
+use sea_orm::Condition;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
@@ -8,6 +9,23 @@ pub struct Model {
     pub id: i64,
     pub user_id: i64,
     pub content: String,
+    /// ISO 639-3 code detected at creation, e.g. "eng" (`None` if detection is off or unsure)
+    pub language: Option<String>,
+    /// One of `CONTENT_FEED_CATEGORIES`, chosen by the author (`None` if uncategorized)
+    pub category: Option<String>,
+    pub publish_at: Option<DateTimeUtc>,
+    pub is_published: bool,
+    /// Denormalized number of likes, maintained only with `LIKE_COUNT_DENORMALIZED`
+    /// (otherwise counted from `feed_likes` on read)
+    pub like_count: i64,
+    /// When the author pinned the feed to the top of their timeline (`None` if not pinned)
+    pub pinned_at: Option<DateTimeUtc>,
+    /// When a moderator hid the feed after a report; hidden feeds are left out of listings
+    pub hidden_at: Option<DateTimeUtc>,
+    /// When the feed stops being served, like a story (`None` if it never expires)
+    pub expires_at: Option<DateTimeUtc>,
+    /// Set by the expiry job once it announced the expiry and cleaned up the rankings
+    pub is_expired: bool,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
@@ -37,3 +55,29 @@ impl Related<super::feed_like::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// Whether `expires_at` has been reached at `now`
+    pub fn has_expired(&self, now: DateTimeUtc) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Whether the feed has gone live at `now`: it has no `publish_at`, or that has passed
+    pub fn is_live(&self, now: DateTimeUtc) -> bool {
+        self.publish_at.is_none_or(|publish_at| publish_at <= now)
+    }
+}
+
+/// Feeds without a `publish_at` after `now`
+pub fn live(now: DateTimeUtc) -> Condition {
+    Condition::any()
+        .add(Column::PublishAt.is_null())
+        .add(Column::PublishAt.lte(now))
+}
+
+/// Feeds without an `expires_at` at or before `now`
+pub fn unexpired(now: DateTimeUtc) -> Condition {
+    Condition::any()
+        .add(Column::ExpiresAt.is_null())
+        .add(Column::ExpiresAt.gt(now))
+}
@@ -1,3 +1,5 @@
+pub mod bookmark;
 pub mod feed;
 pub mod feed_like;
+pub mod feed_reaction;
 pub mod user;
@@ -12,6 +12,9 @@ pub struct Model {
     pub username: String,
     #[serde(skip_serializing)]
     pub password_hash: String,
+    pub is_admin: bool,
+    /// Verified account; its likes count `TOP_STATS_LIKE_WEIGHT_VERIFIED` in the rankings
+    pub is_verified: bool,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
@@ -0,0 +1,206 @@
+use crate::config::Config;
+use crate::db::mongo_collection;
+use crate::kafka::{DigestReadyEvent, KafkaProducer};
+use crate::models::{Digest, DigestActor};
+use chrono::{DateTime, Duration, NaiveTime, Utc};
+use log::{error, info, warn};
+use mongodb::bson::{doc, from_document, Bson, Document};
+use mongodb::options::{AggregateOptions, ReplaceOptions};
+use mongodb::Database as MongoDatabase;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// User IDs per `$in` lookup when checking who is active
+const ACTIVITY_BATCH_SIZE: usize = 1000;
+
+/// Unread notifications of one recipient, from one actor, of one type
+#[derive(Deserialize)]
+struct UnreadGroup {
+    #[serde(rename = "_id")]
+    key: UnreadKey,
+    username: String,
+    count: i64,
+}
+
+#[derive(Deserialize)]
+struct UnreadKey {
+    user_id: i64,
+    from_user_id: i64,
+    notification_type: String,
+}
+
+#[derive(Default)]
+struct UnreadSummary {
+    total: i64,
+    counts: BTreeMap<String, i64>,
+    actors: HashMap<i64, (String, i64)>,
+}
+
+/// Next time the digest job runs: the first `hour_utc`:00 strictly after `now`
+pub fn next_digest_run(now: DateTime<Utc>, hour_utc: u32) -> DateTime<Utc> {
+    let time = NaiveTime::from_hms_opt(hour_utc, 0, 0).unwrap_or(NaiveTime::MIN);
+    let today = now.date_naive().and_time(time).and_utc();
+    if today > now {
+        today
+    } else {
+        today + Duration::days(1)
+    }
+}
+
+/// Build the day's digest for every active user with at least `DIGESTS_MIN_UNREAD` unread
+/// notifications and store it in `digests`, publishing it to the `digests` topic when
+/// `DIGESTS_PUBLISH` is set. Returns the number of digests written.
+///
+/// Digest IDs are `{user_id}:{date}`, so running the job twice on one day replaces the
+/// first run's digests instead of duplicating them.
+pub async fn build_digests(
+    mongo_db: &MongoDatabase,
+    kafka_producer: &KafkaProducer,
+    config: &Config,
+    now: DateTime<Utc>,
+) -> u64 {
+    let mut summaries = match unread_summaries(mongo_db).await {
+        Ok(summaries) => summaries,
+        Err(e) => {
+            error!("Error aggregating unread notifications: {:?}", e);
+            return 0;
+        }
+    };
+    summaries.retain(|_, summary| summary.total >= config.digests.min_unread as i64);
+
+    if config.digests.active_within_days > 0 && !summaries.is_empty() {
+        let since = now - Duration::days(config.digests.active_within_days as i64);
+        let user_ids: Vec<i64> = summaries.keys().copied().collect();
+        match active_users(mongo_db, &user_ids, since).await {
+            Ok(active) => summaries.retain(|user_id, _| active.contains(user_id)),
+            Err(e) => {
+                error!("Error looking up active users for digests: {:?}", e);
+                return 0;
+            }
+        }
+    }
+
+    let collection = mongo_collection::<Digest>(mongo_db, config, "digests");
+    let date = now.format("%Y-%m-%d").to_string();
+    let mut written = 0;
+    for (user_id, summary) in summaries {
+        let mut top_actors: Vec<DigestActor> = summary
+            .actors
+            .into_iter()
+            .map(|(actor_id, (username, count))| DigestActor {
+                user_id: actor_id,
+                username,
+                count,
+            })
+            .collect();
+        top_actors.sort_by(|a, b| b.count.cmp(&a.count).then(a.user_id.cmp(&b.user_id)));
+        top_actors.truncate(config.digests.top_actors);
+
+        let digest = Digest {
+            id: format!("{}:{}", user_id, date),
+            user_id,
+            date: date.clone(),
+            unread_total: summary.total,
+            counts: summary.counts,
+            top_actors,
+            created_at: now,
+        };
+        let options = ReplaceOptions::builder().upsert(true).build();
+        if let Err(e) = collection
+            .replace_one(doc! {"_id": &digest.id}, &digest, options)
+            .await
+        {
+            error!("Error storing digest for user {}: {:?}", user_id, e);
+            continue;
+        }
+        written += 1;
+
+        if config.digests.publish {
+            if let Ok(event_json) = serde_json::to_string(&DigestReadyEvent::new(digest)) {
+                if let Err(e) = kafka_producer
+                    .send_message("digests", &user_id.to_string(), &event_json)
+                    .await
+                {
+                    warn!("Failed to send Kafka event: {:?}", e);
+                }
+            }
+        }
+    }
+
+    info!("Built {} notification digests for {}", written, date);
+    written
+}
+
+/// Unread notifications per recipient, counted by type and by actor
+async fn unread_summaries(
+    mongo_db: &MongoDatabase,
+) -> mongodb::error::Result<HashMap<i64, UnreadSummary>> {
+    let pipeline = vec![
+        doc! {"$match": {"is_read": false}},
+        // `$last` below then picks each actor's most recent username
+        doc! {"$sort": {"created_at": 1}},
+        doc! {"$group": {
+            "_id": {
+                "user_id": "$user_id",
+                "from_user_id": "$from_user_id",
+                "notification_type": "$notification_type",
+            },
+            "username": {"$last": "$from_username"},
+            // An aggregate notification counts once per like, all credited to its latest liker
+            "count": {"$sum": {"$ifNull": ["$actor_count", 1]}},
+        }},
+    ];
+    let options = AggregateOptions::builder().allow_disk_use(true).build();
+    let mut cursor = mongo_db
+        .collection::<Document>("notifications")
+        .aggregate(pipeline, options)
+        .await?;
+
+    let mut summaries: HashMap<i64, UnreadSummary> = HashMap::new();
+    while cursor.advance().await? {
+        let group: UnreadGroup = match from_document(cursor.deserialize_current()?) {
+            Ok(group) => group,
+            Err(e) => {
+                warn!("Skipping malformed unread notification group: {:?}", e);
+                continue;
+            }
+        };
+        let summary = summaries.entry(group.key.user_id).or_default();
+        summary.total += group.count;
+        *summary
+            .counts
+            .entry(group.key.notification_type)
+            .or_insert(0) += group.count;
+        let actor = summary
+            .actors
+            .entry(group.key.from_user_id)
+            .or_insert((group.username, 0));
+        actor.1 += group.count;
+    }
+    Ok(summaries)
+}
+
+/// Those of `user_ids` who viewed or commented on a feed since `since`
+async fn active_users(
+    mongo_db: &MongoDatabase,
+    user_ids: &[i64],
+    since: DateTime<Utc>,
+) -> mongodb::error::Result<HashSet<i64>> {
+    let since = since.timestamp();
+    let mut active = HashSet::new();
+    for batch in user_ids.chunks(ACTIVITY_BATCH_SIZE) {
+        let activity = [("feed_views", "viewed_at"), ("comments", "created_at")];
+        for (collection, timestamp_field) in activity {
+            let ids = mongo_db
+                .collection::<Document>(collection)
+                .distinct(
+                    "user_id",
+                    doc! {"user_id": {"$in": batch}, timestamp_field: {"$gte": since}},
+                    None,
+                )
+                .await?;
+            active.extend(ids.iter().filter_map(Bson::as_i64));
+        }
+    }
+    Ok(active)
+}
@@ -0,0 +1,97 @@
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::entities::feed;
+use crate::jobs::remove_feeds_from_rankings;
+use crate::kafka::{FeedExpiredEvent, KafkaProducer};
+use crate::services::feed_cache;
+use chrono::Utc;
+use log::{error, info, warn};
+use redis::Client as RedisClient;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+
+/// Handle feeds whose `expires_at` has arrived: take them out of the feed rankings,
+/// mark them expired and emit `FeedExpiredEvent` for each feed this run marked, so
+/// every expiry is announced once even with several instances. Reads filter on
+/// `expires_at` directly, so an expired feed is no longer served even before this runs.
+/// Returns the number of feeds expired.
+pub async fn expire_feeds(
+    mysql_pool: &DbPool,
+    redis_client: &RedisClient,
+    kafka_producer: &KafkaProducer,
+    config: &Config,
+) -> u64 {
+    let expired_feeds = match feed::Entity::find()
+        .filter(feed::Column::IsExpired.eq(false))
+        .filter(feed::Column::ExpiresAt.lte(Utc::now()))
+        .order_by_asc(feed::Column::ExpiresAt)
+        .all(mysql_pool)
+        .await
+    {
+        Ok(feeds) => feeds,
+        Err(e) => {
+            error!("Error fetching expired feeds: {:?}", e);
+            return 0;
+        }
+    };
+    if expired_feeds.is_empty() {
+        return 0;
+    }
+    let feed_ids: Vec<i64> = expired_feeds.iter().map(|f| f.id).collect();
+
+    // Rankings first: if Redis is down the feeds stay unmarked and the next run retries
+    let mut pipe = redis::pipe();
+    remove_feeds_from_rankings(&mut pipe, config, &feed_ids);
+    let removed = match redis_client.get_async_connection().await {
+        Ok(mut conn) => pipe.query_async::<_, ()>(&mut conn).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = removed {
+        error!("Failed to remove expired feeds from the rankings: {:?}", e);
+        return 0;
+    }
+
+    // Claimed one by one, only while still unexpired, so when several instances run
+    // the job only the one that marks a feed announces it
+    let mut claimed_feeds = Vec::with_capacity(expired_feeds.len());
+    for feed_model in expired_feeds {
+        let claimed = feed::Entity::update_many()
+            .col_expr(feed::Column::IsExpired, Expr::value(true))
+            .filter(feed::Column::Id.eq(feed_model.id))
+            .filter(feed::Column::IsExpired.eq(false))
+            .exec(mysql_pool)
+            .await;
+        match claimed {
+            Ok(result) if result.rows_affected == 1 => claimed_feeds.push(feed_model),
+            Ok(_) => {}
+            Err(e) => error!("Failed to mark feed {} as expired: {:?}", feed_model.id, e),
+        }
+    }
+    if claimed_feeds.is_empty() {
+        return 0;
+    }
+
+    if config.feed_cache.enabled {
+        if let Err(e) = feed_cache::invalidate(redis_client, config).await {
+            warn!("Failed to invalidate feed cache: {:?}", e);
+        }
+    }
+
+    for feed_model in &claimed_feeds {
+        let Some(expires_at) = feed_model.expires_at else {
+            continue;
+        };
+        let event = FeedExpiredEvent::new(feed_model.id, feed_model.user_id, expires_at);
+        if let Ok(event_json) = serde_json::to_string(&event) {
+            if let Err(e) = kafka_producer
+                .send_message("feed_events", &feed_model.id.to_string(), &event_json)
+                .await
+            {
+                warn!("Failed to send Kafka event: {:?}", e);
+            }
+        }
+    }
+
+    info!("Expired {} feeds", claimed_feeds.len());
+    claimed_feeds.len() as u64
+}
@@ -1,27 +1,127 @@
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::kafka::{FeedEventType, UserCreatedEvent, UserDeletedEvent, UserEventType};
+use crate::models::UserProfile;
+use crate::services::notification::{
+    handle_feed_commented_event, handle_feed_liked_event, handle_feed_unliked_event,
+    handle_feed_viewed_event,
+};
+use crate::services::search::{handle_feed_created_event, SearchBackend};
+use chrono::{DateTime, Utc};
 use log::{error, info};
+use mongodb::bson::doc;
+use mongodb::options::UpdateOptions;
+use mongodb::Database as MongoDatabase;
+use redis::Client as RedisClient;
 use serde_json::Value;
 
-pub fn handle_user_created_event(topic: String, key: String, payload: Vec<u8>) {
+/// Handle a `user_events` message. Payloads that cannot be decoded or parsed are
+/// returned as `Err` with the reason, so the consumer can dead-letter them.
+pub async fn handle_user_created_event(
+    topic: String,
+    key: String,
+    payload: Vec<u8>,
+    mongo_db: &MongoDatabase,
+) -> Result<(), String> {
     info!(
         "Handling user_created event from topic: {}, key: {}",
         topic, key
     );
 
-    match std::str::from_utf8(&payload) {
-        Ok(payload_str) => {
-            match serde_json::from_str::<Value>(payload_str) {
-                Ok(data) => {
-                    info!("User created event data: {:?}", data);
-                    // Process the event here
-                    // Example: send welcome email, create user profile, etc.
-                }
-                Err(e) => {
-                    error!("Failed to parse event payload: {:?}", e);
-                }
-            }
-        }
+    let payload_str = match std::str::from_utf8(&payload) {
+        Ok(payload_str) => payload_str,
         Err(e) => {
             error!("Failed to decode event payload: {:?}", e);
+            return Err(format!("invalid UTF-8 payload: {}", e));
+        }
+    };
+
+    // Data of purged users is removed synchronously by the API; nothing to do here
+    if let Ok(event) = serde_json::from_str::<UserDeletedEvent>(payload_str) {
+        if event.event_type == UserEventType::UserDeleted {
+            info!("User {} was deleted", event.user_id);
+            return Ok(());
+        }
+    }
+
+    match serde_json::from_str::<UserCreatedEvent>(payload_str) {
+        Ok(event) => {
+            info!("User created event data: {:?}", event);
+            create_user_profile(&event, mongo_db).await;
+            Ok(())
+        }
+        Err(e) => {
+            error!("Failed to parse event payload: {:?}", e);
+            Err(format!("invalid user event: {}", e))
+        }
+    }
+}
+
+/// Seed the `user_profiles` document for a new user.
+/// Uses an upsert with `$setOnInsert` so a redelivered event never overwrites
+/// a profile the user has already edited.
+async fn create_user_profile(event: &UserCreatedEvent, mongo_db: &MongoDatabase) {
+    let user_id = event.user_id as i64;
+    let joined_at = DateTime::parse_from_rfc3339(&event.timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now());
+
+    let collection = mongo_db.collection::<UserProfile>("user_profiles");
+    let filter = doc! {"_id": user_id};
+    let update = doc! {
+        "$setOnInsert": {
+            "bio": "",
+            "avatar_url": mongodb::bson::Bson::Null,
+            "joined_at": joined_at.timestamp(),
+            "language": event.language.clone(),
+        }
+    };
+    let options = UpdateOptions::builder().upsert(true).build();
+
+    match collection.update_one(filter, update, options).await {
+        Ok(result) if result.upserted_id.is_some() => {
+            info!("Created profile for user {}", user_id);
+        }
+        Ok(_) => {
+            info!("Profile for user {} already exists", user_id);
+        }
+        Err(e) => {
+            error!("Failed to create profile for user {}: {:?}", user_id, e);
+        }
+    }
+}
+
+/// Run the handler for a parsed `feed_events` message
+pub async fn handle_feed_event(
+    event_type: FeedEventType,
+    event_data: &Value,
+    mongo_db: &MongoDatabase,
+    mysql_pool: &DbPool,
+    redis_client: &RedisClient,
+    config: &Config,
+    search_backend: &dyn SearchBackend,
+) {
+    match event_type {
+        FeedEventType::Liked => {
+            handle_feed_liked_event(event_data, mongo_db, mysql_pool, redis_client, config).await;
+        }
+        FeedEventType::Commented => {
+            info!("Received commented event, processing...");
+            handle_feed_commented_event(event_data, mongo_db, mysql_pool, redis_client, config)
+                .await;
+            info!("Finished processing commented event");
+        }
+        FeedEventType::Unliked => {
+            handle_feed_unliked_event(event_data, mysql_pool, redis_client, config).await;
+        }
+        FeedEventType::Viewed => {
+            handle_feed_viewed_event(event_data, mysql_pool, redis_client, config).await;
+        }
+        FeedEventType::Created => {
+            handle_feed_created_event(event_data, search_backend).await;
+        }
+        FeedEventType::Expired => {
+            // Emitted after the expiry job cleaned up; it is for downstream consumers
         }
     }
 }
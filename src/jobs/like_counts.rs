@@ -0,0 +1,36 @@
+use crate::db::DbPool;
+use log::{error, info};
+use sea_orm::{ConnectionTrait, DbErr, Statement};
+
+/// Set `feeds.like_count` to the number of rows in `feed_likes` wherever they differ.
+/// Likes removed outside `LikeRepository` (account purges, cascades) and counters left
+/// stale while `LIKE_COUNT_DENORMALIZED` was off are corrected here.
+/// Returns the number of feeds that were corrected.
+pub async fn reconcile_like_counts(mysql_pool: &DbPool) -> Result<u64, DbErr> {
+    let sql = r#"
+        UPDATE feeds f
+        LEFT JOIN (
+            SELECT feed_id, COUNT(id) AS like_count
+            FROM feed_likes
+            GROUP BY feed_id
+        ) fl ON fl.feed_id = f.id
+        SET f.like_count = COALESCE(fl.like_count, 0)
+        WHERE f.like_count <> COALESCE(fl.like_count, 0)
+    "#;
+    let result = mysql_pool
+        .execute(Statement::from_string(
+            sea_orm::DatabaseBackend::MySql,
+            sql.to_string(),
+        ))
+        .await?;
+    Ok(result.rows_affected())
+}
+
+/// Scheduled entry point: reconcile and log the outcome
+pub async fn run_like_count_reconciliation(mysql_pool: &DbPool) {
+    match reconcile_like_counts(mysql_pool).await {
+        Ok(0) => {}
+        Ok(corrected) => info!("Corrected like_count of {} feeds", corrected),
+        Err(e) => error!("Failed to reconcile like counts: {:?}", e),
+    }
+}
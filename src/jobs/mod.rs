@@ -1,5 +1,17 @@
+pub mod digests;
+pub mod feed_expiry;
 pub mod handlers;
+pub mod like_counts;
+pub mod notification_retention;
+pub mod scheduled_feeds;
+pub mod self_test;
 pub mod top_stats;
 
+pub use digests::*;
+pub use feed_expiry::*;
 pub use handlers::*;
+pub use like_counts::*;
+pub use notification_retention::*;
+pub use scheduled_feeds::*;
+pub use self_test::*;
 pub use top_stats::*;
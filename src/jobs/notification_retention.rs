@@ -0,0 +1,82 @@
+use crate::config::Config;
+use log::{error, info};
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::options::{AggregateOptions, FindOptions};
+use mongodb::Database as MongoDatabase;
+
+/// Delete each user's notifications beyond the newest `NOTIFICATIONS_MAX_PER_USER`.
+/// Only users over the cap are visited, so a sweep is cheap when most users are below it.
+pub async fn trim_notifications(mongo_db: &MongoDatabase, config: &Config) {
+    let max_per_user = config.notifications.max_per_user;
+    if max_per_user == 0 {
+        return;
+    }
+
+    let collection = mongo_db.collection::<Document>("notifications");
+
+    let pipeline = vec![
+        doc! {"$group": {"_id": "$user_id", "count": {"$sum": 1}}},
+        doc! {"$match": {"count": {"$gt": max_per_user as i64}}},
+    ];
+    let options = AggregateOptions::builder().allow_disk_use(true).build();
+    let mut cursor = match collection.aggregate(pipeline, options).await {
+        Ok(c) => c,
+        Err(e) => {
+            error!("Error finding users over the notification cap: {:?}", e);
+            return;
+        }
+    };
+
+    let mut user_ids: Vec<Bson> = Vec::new();
+    while let Ok(true) = cursor.advance().await {
+        if let Ok(group) = cursor.deserialize_current() {
+            if let Some(user_id) = group.get("_id") {
+                user_ids.push(user_id.clone());
+            }
+        }
+    }
+
+    let mut total_deleted = 0;
+    for user_id in user_ids {
+        // Everything after the newest `max_per_user` (ties broken by _id) is removed
+        let options = FindOptions::builder()
+            .sort(doc! {"created_at": -1, "_id": -1})
+            .skip(max_per_user)
+            .projection(doc! {"_id": 1})
+            .build();
+        let mut cursor = match collection
+            .find(doc! {"user_id": user_id.clone()}, options)
+            .await
+        {
+            Ok(c) => c,
+            Err(e) => {
+                error!("Error listing notifications for user {}: {:?}", user_id, e);
+                continue;
+            }
+        };
+
+        let mut stale_ids: Vec<Bson> = Vec::new();
+        while let Ok(true) = cursor.advance().await {
+            if let Ok(notification) = cursor.deserialize_current() {
+                if let Some(id) = notification.get("_id") {
+                    stale_ids.push(id.clone());
+                }
+            }
+        }
+
+        match collection
+            .delete_many(doc! {"_id": {"$in": stale_ids}}, None)
+            .await
+        {
+            Ok(result) => total_deleted += result.deleted_count,
+            Err(e) => error!("Error trimming notifications for user {}: {:?}", user_id, e),
+        }
+    }
+
+    if total_deleted > 0 {
+        info!(
+            "Trimmed {} notifications beyond {} per user",
+            total_deleted, max_per_user
+        );
+    }
+}
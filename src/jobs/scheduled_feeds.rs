@@ -0,0 +1,80 @@
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::entities::feed;
+use crate::kafka::{FeedCreatedEvent, KafkaProducer};
+use crate::services::feed_cache;
+use chrono::Utc;
+use log::{error, info, warn};
+use redis::Client as RedisClient;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+
+/// Publish scheduled feeds whose `publish_at` has arrived: mark each one as published
+/// and emit `FeedCreatedEvent` for it. The mark only applies while the feed is still
+/// unpublished, so when several instances run the job only the one that claims a feed
+/// announces it. The feed cache is invalidated once anything was published, so cached
+/// first pages pick the new feeds up.
+pub async fn publish_scheduled_feeds(
+    mysql_pool: &DbPool,
+    redis_client: &RedisClient,
+    kafka_producer: &KafkaProducer,
+    config: &Config,
+) {
+    let due_feeds = match feed::Entity::find()
+        .filter(feed::Column::IsPublished.eq(false))
+        .filter(feed::Column::PublishAt.lte(Utc::now()))
+        .order_by_asc(feed::Column::PublishAt)
+        .all(mysql_pool)
+        .await
+    {
+        Ok(feeds) => feeds,
+        Err(e) => {
+            error!("Error fetching scheduled feeds: {:?}", e);
+            return;
+        }
+    };
+
+    let mut published = 0;
+    for feed_model in due_feeds {
+        let feed_id = feed_model.id;
+        let event = FeedCreatedEvent::new(
+            feed_id as u64,
+            feed_model.user_id,
+            feed_model.content.clone(),
+        );
+
+        let claimed = feed::Entity::update_many()
+            .col_expr(feed::Column::IsPublished, Expr::value(true))
+            .filter(feed::Column::Id.eq(feed_id))
+            .filter(feed::Column::IsPublished.eq(false))
+            .exec(mysql_pool)
+            .await;
+        match claimed {
+            Ok(result) if result.rows_affected == 1 => {}
+            // Another instance published it first
+            Ok(_) => continue,
+            Err(e) => {
+                error!("Failed to mark feed {} as published: {:?}", feed_id, e);
+                continue;
+            }
+        }
+
+        if let Ok(event_json) = serde_json::to_string(&event) {
+            if let Err(e) = kafka_producer
+                .send_message("feed_events", &feed_id.to_string(), &event_json)
+                .await
+            {
+                warn!("Failed to send Kafka event: {:?}", e);
+            }
+        }
+
+        info!("Published scheduled feed {}", feed_id);
+        published += 1;
+    }
+
+    if published > 0 && config.feed_cache.enabled {
+        if let Err(e) = feed_cache::invalidate(redis_client, config).await {
+            warn!("Failed to invalidate feed cache: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use crate::config::Config;
+use crate::db::{redis_key, DbPool};
+use crate::jobs::{handle_feed_event, handle_user_created_event, remove_feeds_from_rankings};
+use crate::kafka::{
+    parse_feed_event, FeedCommentedEvent, FeedCreatedEvent, FeedExpiredEvent, FeedLikedEvent,
+    FeedUnlikedEvent, FeedViewedEvent, UserCreatedEvent, UserDeletedEvent,
+};
+use crate::models::UserProfile;
+use crate::services::search::SearchBackend;
+use chrono::Utc;
+use log::{error, info, warn};
+use mongodb::bson::doc;
+use mongodb::Database as MongoDatabase;
+use redis::Client as RedisClient;
+use std::sync::Arc;
+
+/// User and feed ID of the sample events. Auto-increment IDs start at 1, so no real
+/// user or feed is touched, and what the handlers write for it is removed afterwards.
+pub const SELF_TEST_ID: i64 = 0;
+
+/// Result of one sample event
+#[derive(Debug, Clone)]
+pub struct SelfTestCheck {
+    pub event: &'static str,
+    /// Why the event failed, `None` if it passed
+    pub failure: Option<String>,
+}
+
+/// Send a sample of every event type through the same parsing and handlers as the Kafka
+/// consumers (without going through Kafka), against the live datastores. An event fails
+/// if it does not parse, its handler panics or returns an error, or, for `user_created`
+/// and `viewed`, the write it should make is missing afterwards. The other feed handlers
+/// log their own errors rather than returning them, so `liked`, `unliked`, `commented`,
+/// `created`, `expired` and `user_deleted` pass as long as they do not panic; the logs
+/// around them show what was misconfigured.
+pub async fn run_self_test(
+    mysql_pool: &DbPool,
+    mongo_db: &MongoDatabase,
+    redis_client: &RedisClient,
+    config: &Config,
+    search_backend: Arc<dyn SearchBackend>,
+) -> Vec<SelfTestCheck> {
+    let feed_id = SELF_TEST_ID;
+    let user_id = SELF_TEST_ID;
+    let feed_events = [
+        (
+            "created",
+            serde_json::to_string(&FeedCreatedEvent::new(
+                feed_id as u64,
+                user_id,
+                "self-test".to_string(),
+            )),
+        ),
+        (
+            "liked",
+            serde_json::to_string(&FeedLikedEvent::new(feed_id, user_id)),
+        ),
+        (
+            "unliked",
+            serde_json::to_string(&FeedUnlikedEvent::new(feed_id, user_id)),
+        ),
+        (
+            "commented",
+            serde_json::to_string(&FeedCommentedEvent::new(
+                feed_id,
+                user_id,
+                "self-test".to_string(),
+                "self-test".to_string(),
+            )),
+        ),
+        (
+            "viewed",
+            serde_json::to_string(&FeedViewedEvent::new(feed_id, user_id)),
+        ),
+        (
+            "expired",
+            serde_json::to_string(&FeedExpiredEvent::new(feed_id, user_id, Utc::now())),
+        ),
+    ];
+
+    let mut checks = Vec::new();
+    for (event, payload) in feed_events {
+        let failure = match payload {
+            Ok(payload) => {
+                run_feed_event(
+                    &payload,
+                    mysql_pool,
+                    mongo_db,
+                    redis_client,
+                    config,
+                    search_backend.clone(),
+                )
+                .await
+            }
+            Err(e) => Some(format!("failed to serialize: {}", e)),
+        };
+        let failure = match failure {
+            None if event == "viewed" => check_viewed(redis_client, config, feed_id).await,
+            failure => failure,
+        };
+        checks.push(SelfTestCheck { event, failure });
+    }
+
+    let user_events = [
+        (
+            "user_created",
+            serde_json::to_string(&UserCreatedEvent::new(
+                user_id as u64,
+                "self-test@example.com".to_string(),
+                "self-test".to_string(),
+            )),
+        ),
+        (
+            "user_deleted",
+            serde_json::to_string(&UserDeletedEvent::new(user_id)),
+        ),
+    ];
+    for (event, payload) in user_events {
+        let failure = match payload {
+            Ok(payload) => run_user_event(payload, mongo_db).await,
+            Err(e) => Some(format!("failed to serialize: {}", e)),
+        };
+        let failure = match failure {
+            None if event == "user_created" => check_profile(mongo_db, user_id).await,
+            failure => failure,
+        };
+        checks.push(SelfTestCheck { event, failure });
+    }
+
+    clean_up(mongo_db, redis_client, config, search_backend.as_ref()).await;
+
+    for check in &checks {
+        match &check.failure {
+            None => info!("Self-test: {} event passed", check.event),
+            Some(failure) => error!("Self-test: {} event failed: {}", check.event, failure),
+        }
+    }
+    checks
+}
+
+/// Parse `payload` like the `feed_events` consumer and run its handler on its own task,
+/// so a panic is reported instead of taking startup down
+async fn run_feed_event(
+    payload: &str,
+    mysql_pool: &DbPool,
+    mongo_db: &MongoDatabase,
+    redis_client: &RedisClient,
+    config: &Config,
+    search_backend: Arc<dyn SearchBackend>,
+) -> Option<String> {
+    let (event_type, event_data) = match parse_feed_event(payload) {
+        Ok(parsed) => parsed,
+        Err(e) => return Some(format!("failed to parse: {}", e)),
+    };
+    let mysql_pool = mysql_pool.clone();
+    let mongo_db = mongo_db.clone();
+    let redis_client = redis_client.clone();
+    let config = config.clone();
+    let handled = tokio::spawn(async move {
+        handle_feed_event(
+            event_type,
+            &event_data,
+            &mongo_db,
+            &mysql_pool,
+            &redis_client,
+            &config,
+            search_backend.as_ref(),
+        )
+        .await;
+    })
+    .await;
+    handled.err().map(|e| format!("handler failed: {}", e))
+}
+
+/// Run `payload` through the `user_events` handler on its own task
+async fn run_user_event(payload: String, mongo_db: &MongoDatabase) -> Option<String> {
+    let mongo_db = mongo_db.clone();
+    let handled = tokio::spawn(async move {
+        handle_user_created_event(
+            "user_events".to_string(),
+            SELF_TEST_ID.to_string(),
+            payload.into_bytes(),
+            &mongo_db,
+        )
+        .await
+    })
+    .await;
+    match handled {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e),
+        Err(e) => Some(format!("handler failed: {}", e)),
+    }
+}
+
+async fn check_viewed(redis_client: &RedisClient, config: &Config, feed_id: i64) -> Option<String> {
+    let key = redis_key(config, "top:feeds_viewed");
+    let score: redis::RedisResult<Option<f64>> = match redis_client.get_async_connection().await {
+        Ok(mut conn) => {
+            redis::cmd("ZSCORE")
+                .arg(&key)
+                .arg(feed_id)
+                .query_async(&mut conn)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    match score {
+        Ok(Some(_)) => None,
+        Ok(None) => Some(format!("feed {} was not counted in {}", feed_id, key)),
+        Err(e) => Some(format!("failed to read {}: {}", key, e)),
+    }
+}
+
+async fn check_profile(mongo_db: &MongoDatabase, user_id: i64) -> Option<String> {
+    match mongo_db
+        .collection::<UserProfile>("user_profiles")
+        .find_one(doc! {"_id": user_id}, None)
+        .await
+    {
+        Ok(Some(_)) => None,
+        Ok(None) => Some(format!("no user_profiles document for user {}", user_id)),
+        Err(e) => Some(format!("failed to read user_profiles: {}", e)),
+    }
+}
+
+/// Remove what the sample events wrote: the ranking members, the search index entry
+/// and the seeded profile
+async fn clean_up(
+    mongo_db: &MongoDatabase,
+    redis_client: &RedisClient,
+    config: &Config,
+    search_backend: &dyn SearchBackend,
+) {
+    let mut pipe = redis::pipe();
+    remove_feeds_from_rankings(&mut pipe, config, &[SELF_TEST_ID]);
+    let removed = match redis_client.get_async_connection().await {
+        Ok(mut conn) => pipe.query_async::<_, ()>(&mut conn).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = removed {
+        warn!(
+            "Self-test: failed to remove sample feed from the rankings: {:?}",
+            e
+        );
+    }
+
+    if search_backend.needs_indexing() {
+        if let Err(e) = search_backend.remove_feed(SELF_TEST_ID).await {
+            warn!(
+                "Self-test: failed to remove sample feed from the search index: {:?}",
+                e
+            );
+        }
+    }
+
+    if let Err(e) = mongo_db
+        .collection::<UserProfile>("user_profiles")
+        .delete_one(doc! {"_id": SELF_TEST_ID}, None)
+        .await
+    {
+        warn!("Self-test: failed to remove sample profile: {:?}", e);
+    }
+}
@@ -1,97 +1,342 @@
-use crate::db::DbPool;
+use crate::config::{Config, TopStatsConfig};
+use crate::db::{redis_key, DbPool};
 use crate::entities::{feed, user};
+use crate::metrics;
 use crate::models::{Comment, FeedView, TopFeed, TopUser};
-use chrono::{Duration, Utc};
-use log::{error, info};
+use chrono::{DateTime, Duration, Utc};
+use log::{error, info, warn};
 use mongodb::bson::doc;
 use mongodb::Database as MongoDatabase;
 use redis::Client as RedisClient;
-use sea_orm::{ConnectionTrait, EntityTrait};
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QuerySelect};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use utoipa::ToSchema;
+
+/// Start of the window top statistics are computed over (last 7 days)
+pub fn top_stats_window_start() -> DateTime<Utc> {
+    Utc::now() - Duration::days(7)
+}
+
+/// Weights of each interaction in the trending score
+pub const TRENDING_LIKE_WEIGHT: i64 = 3;
+pub const TRENDING_COMMENT_WEIGHT: i64 = 5;
+pub const TRENDING_VIEW_WEIGHT: i64 = 1;
+
+/// Time window for trending rankings ("hot today" / "hot this week")
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendingWindow {
+    #[default]
+    Day,
+    Week,
+}
+
+impl TrendingWindow {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrendingWindow::Day => "day",
+            TrendingWindow::Week => "week",
+        }
+    }
+
+    pub fn start(&self) -> DateTime<Utc> {
+        match self {
+            TrendingWindow::Day => Utc::now() - Duration::days(1),
+            TrendingWindow::Week => Utc::now() - Duration::days(7),
+        }
+    }
+}
 
 pub async fn calculate_top_stats(
     mysql_pool: &DbPool,
     mongo_db: &MongoDatabase,
     redis_client: &RedisClient,
+    config: &Config,
 ) {
-    let seven_days_ago = Utc::now() - Duration::days(7);
-
-    let top_users = calculate_top_users_liked(mysql_pool, seven_days_ago).await;
-    let top_feeds_commented = calculate_top_comments(mongo_db, mysql_pool, seven_days_ago).await;
-    let top_feeds_viewed = calculate_top_feeds_viewed(mongo_db, mysql_pool, seven_days_ago).await;
-    let top_feeds_liked = calculate_top_feeds_liked(mysql_pool, seven_days_ago).await;
-    let mut conn = redis_client.get_async_connection().await;
-    if let Ok(ref mut conn) = conn {
-        let _: Result<(), _> = redis::cmd("DEL")
-            .arg("top:users_liked")
-            .query_async(conn)
-            .await;
-
-        for user in top_users {
-            let user_id_str = user.user_id.to_string();
-            let score = user.total_likes as f64;
-            let _: Result<(), _> = redis::cmd("ZADD")
-                .arg("top:users_liked")
-                .arg(score)
-                .arg(&user_id_str)
-                .query_async(conn)
-                .await;
-        }
+    let started = Instant::now();
+    rebuild_rankings(
+        mysql_pool,
+        mongo_db,
+        redis_client,
+        config,
+        top_stats_window_start(),
+    )
+    .await;
+
+    let elapsed = started.elapsed();
+    metrics::record_top_stats_duration(elapsed);
+    let threshold = config.top_stats.slow_job_warning_seconds;
+    if threshold > 0 && elapsed.as_secs() >= threshold {
+        warn!(
+            "Top stats job took {:?}, over the {}s threshold; it may fall behind its interval",
+            elapsed, threshold
+        );
+    }
+
+    info!("Top stats calculated and stored in Redis in {:?}", elapsed);
+}
 
-        let _: Result<(), _> = redis::cmd("DEL")
-            .arg("top:comments")
-            .query_async(conn)
-            .await;
+/// One-shot seeding of the `top:*` rankings from the complete history, e.g. for a new
+/// deployment or after a Redis flush. The next hourly run narrows them to the 7-day window.
+pub async fn backfill_top_stats(
+    mysql_pool: &DbPool,
+    mongo_db: &MongoDatabase,
+    redis_client: &RedisClient,
+    config: &Config,
+) {
+    let started = Instant::now();
+    info!("Top stats backfill started over the complete history");
+    rebuild_rankings(
+        mysql_pool,
+        mongo_db,
+        redis_client,
+        config,
+        DateTime::<Utc>::UNIX_EPOCH,
+    )
+    .await;
+    info!("Top stats backfill finished in {:?}", started.elapsed());
+}
 
-        for feed in top_feeds_commented {
-            let feed_id_str = feed.feed_id.to_string();
-            let score = feed.count as f64;
+/// Replace each `top:*` ranking with the first `TOP_STATS_CACHE_SIZE` ranks of activity
+/// since `since`, logging progress per ranking
+async fn rebuild_rankings(
+    mysql_pool: &DbPool,
+    mongo_db: &MongoDatabase,
+    redis_client: &RedisClient,
+    config: &Config,
+    since: DateTime<Utc>,
+) {
+    let size = config.top_stats.cache_size;
+
+    let top_users = calculate_top_users_liked(mysql_pool, since, 0, size).await;
+    info!("Ranked {} users by likes since {}", top_users.len(), since);
+    let top_feeds_commented = calculate_top_comments(mongo_db, mysql_pool, since, 0, size).await;
+    info!(
+        "Ranked {} feeds by comments since {}",
+        top_feeds_commented.len(),
+        since
+    );
+    let top_feeds_viewed = calculate_top_feeds_viewed(mongo_db, mysql_pool, since, 0, size).await;
+    info!(
+        "Ranked {} feeds by views since {}",
+        top_feeds_viewed.len(),
+        since
+    );
+    let top_feeds_liked =
+        calculate_top_feeds_liked(mysql_pool, &config.top_stats, since, 0, size).await;
+    info!(
+        "Ranked {} feeds by likes since {}",
+        top_feeds_liked.len(),
+        since
+    );
+
+    let rankings: [(&str, Vec<(i64, i64)>); 4] = [
+        (
+            "users_liked",
+            top_users
+                .iter()
+                .map(|u| (u.user_id, u.total_likes))
+                .collect(),
+        ),
+        (
+            "comments",
+            top_feeds_commented
+                .iter()
+                .map(|f| (f.feed_id, f.count))
+                .collect(),
+        ),
+        (
+            "feeds_viewed",
+            top_feeds_viewed
+                .iter()
+                .map(|f| (f.feed_id, f.count))
+                .collect(),
+        ),
+        (
+            "feeds_liked",
+            top_feeds_liked
+                .iter()
+                .map(|f| (f.feed_id, f.count))
+                .collect(),
+        ),
+    ];
+
+    let mut conn = match redis_client.get_async_connection().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("Failed to connect to Redis to store top stats: {:?}", e);
+            return;
+        }
+    };
+    for (ranking, entries) in rankings {
+        let key = redis_key(config, &format!("top:{}", ranking));
+        let _: Result<(), _> = redis::cmd("DEL").arg(&key).query_async(&mut conn).await;
+
+        for (id, score) in &entries {
             let _: Result<(), _> = redis::cmd("ZADD")
-                .arg("top:comments")
-                .arg(score)
-                .arg(&feed_id_str)
-                .query_async(conn)
+                .arg(&key)
+                .arg(*score as f64)
+                .arg(id.to_string())
+                .query_async(&mut conn)
                 .await;
         }
+        metrics::set_top_stats_ranking_size(ranking, entries.len());
+    }
+}
 
-        let _: Result<(), _> = redis::cmd("DEL")
-            .arg("top:feeds_viewed")
-            .query_async(conn)
-            .await;
+/// Cut every `top:*` sorted set back to its `TOP_STATS_CACHE_SIZE` highest-scored members.
+/// The consumer's realtime `ZINCRBY`s add a member for every user or feed that gets any
+/// activity, so between hourly rebuilds the sets would otherwise grow with the long tail.
+/// Returns the number of members removed.
+pub async fn trim_rankings(redis_client: &RedisClient, config: &Config) -> u64 {
+    let mut conn = match redis_client.get_async_connection().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("Failed to connect to Redis to trim top stats: {:?}", e);
+            return 0;
+        }
+    };
 
-        for feed in top_feeds_viewed {
-            let feed_id_str = feed.feed_id.to_string();
-            let score = feed.count as f64;
-            let _: Result<(), _> = redis::cmd("ZADD")
-                .arg("top:feeds_viewed")
-                .arg(score)
-                .arg(&feed_id_str)
-                .query_async(conn)
-                .await;
+    // Ranks are ascending by score, so everything below the top `size` is 0..=-(size + 1)
+    let keep = config.top_stats.cache_size as i64;
+    let mut removed = 0;
+    for ranking in metrics::TOP_STATS_RANKINGS {
+        let key = redis_key(config, &format!("top:{}", ranking));
+        match redis::cmd("ZREMRANGEBYRANK")
+            .arg(&key)
+            .arg(0)
+            .arg(-(keep + 1))
+            .query_async::<_, u64>(&mut conn)
+            .await
+        {
+            Ok(count) => removed += count,
+            Err(e) => error!("Failed to trim {}: {:?}", key, e),
         }
+    }
+    if removed > 0 {
+        info!(
+            "Trimmed {} members beyond the top {} from the rankings",
+            removed, keep
+        );
+    }
+    removed
+}
 
-        let _: Result<(), _> = redis::cmd("DEL")
-            .arg("top:feeds_liked")
-            .query_async(conn)
-            .await;
+/// Rankings whose members are feed IDs
+pub const FEED_RANKINGS: [&str; 3] = ["feeds_liked", "comments", "feeds_viewed"];
 
-        for feed in top_feeds_liked {
-            let feed_id_str = feed.feed_id.to_string();
-            let score = feed.count as f64;
-            let _: Result<(), _> = redis::cmd("ZADD")
-                .arg("top:feeds_liked")
-                .arg(score)
-                .arg(&feed_id_str)
-                .query_async(conn)
-                .await;
+/// Queue on `pipe` the `ZREM`s taking `feed_ids` out of every feed ranking. Every path
+/// that deletes feeds runs it, so deleted feeds do not keep leaderboard slots that the
+/// top endpoints then drop during enrichment.
+pub fn remove_feeds_from_rankings(pipe: &mut redis::Pipeline, config: &Config, feed_ids: &[i64]) {
+    if feed_ids.is_empty() {
+        return;
+    }
+    for ranking in FEED_RANKINGS {
+        pipe.cmd("ZREM")
+            .arg(redis_key(config, &format!("top:{}", ranking)))
+            .arg(feed_ids)
+            .ignore();
+    }
+}
+
+/// Remove feed ranking members whose feed no longer exists in MySQL, has expired or was
+/// hidden by a moderator (or that are not feed IDs at all). Catches what deletion, expiry
+/// and moderation paths missed, e.g. when Redis was down after MySQL had already deleted
+/// the feed. Returns the number of members removed.
+pub async fn sweep_orphaned_rankings(
+    pool: &DbPool,
+    redis_client: &RedisClient,
+    config: &Config,
+) -> u64 {
+    let mut conn = match redis_client.get_async_connection().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("Failed to connect to Redis to sweep top stats: {:?}", e);
+            return 0;
+        }
+    };
+
+    let mut removed = 0;
+    for ranking in FEED_RANKINGS {
+        let key = redis_key(config, &format!("top:{}", ranking));
+        let members: Vec<String> = match redis::cmd("ZRANGE")
+            .arg(&key)
+            .arg(0)
+            .arg(-1)
+            .query_async(&mut conn)
+            .await
+        {
+            Ok(members) => members,
+            Err(e) => {
+                error!("Failed to read {}: {:?}", key, e);
+                continue;
+            }
+        };
+        let orphans = match orphaned_feed_members(pool, &members).await {
+            Ok(orphans) => orphans,
+            Err(e) => {
+                error!("Failed to look up feeds ranked in {}: {:?}", key, e);
+                continue;
+            }
+        };
+        if orphans.is_empty() {
+            continue;
+        }
+        match redis::cmd("ZREM")
+            .arg(&key)
+            .arg(&orphans)
+            .query_async::<_, u64>(&mut conn)
+            .await
+        {
+            Ok(count) => removed += count,
+            Err(e) => error!("Failed to remove orphans from {}: {:?}", key, e),
         }
     }
+    if removed > 0 {
+        info!(
+            "Removed {} ranking members whose feed no longer exists",
+            removed
+        );
+    }
+    removed
+}
 
-    info!("Top stats calculated and stored in Redis");
+/// Members that are not the ID of an existing, live, unexpired feed that is not hidden
+async fn orphaned_feed_members(
+    pool: &DbPool,
+    members: &[String],
+) -> Result<Vec<String>, sea_orm::DbErr> {
+    let ids: Vec<i64> = members.iter().filter_map(|m| m.parse().ok()).collect();
+    let mut existing = HashSet::new();
+    for chunk in ids.chunks(1000) {
+        let found: Vec<i64> = feed::Entity::find()
+            .select_only()
+            .column(feed::Column::Id)
+            .filter(feed::Column::Id.is_in(chunk.to_vec()))
+            .filter(feed::live(Utc::now()))
+            .filter(feed::unexpired(Utc::now()))
+            .filter(feed::Column::HiddenAt.is_null())
+            .into_tuple()
+            .all(pool)
+            .await?;
+        existing.extend(found);
+    }
+    Ok(members
+        .iter()
+        .filter(|m| m.parse::<i64>().map_or(true, |id| !existing.contains(&id)))
+        .cloned()
+        .collect())
 }
 
-async fn calculate_top_users_liked(
+/// Ranks `limit` entries starting at rank `offset` (0-based)
+pub async fn calculate_top_users_liked(
     pool: &DbPool,
-    since: chrono::DateTime<chrono::Utc>,
+    since: DateTime<Utc>,
+    offset: u64,
+    limit: u64,
 ) -> Vec<TopUser> {
     let query = r#"
         SELECT 
@@ -104,14 +349,18 @@ async fn calculate_top_users_liked(
         WHERE fl.created_at >= ?
         GROUP BY f.user_id, u.username
         ORDER BY total_likes DESC
-        LIMIT 1000
+        LIMIT ? OFFSET ?
     "#;
 
     // Use raw SQL for complex aggregation with SeaORM
     let stmt = sea_orm::Statement::from_sql_and_values(
         sea_orm::DatabaseBackend::MySql,
         query,
-        [sea_orm::Value::ChronoDateTimeUtc(Some(since.into()))],
+        [
+            sea_orm::Value::ChronoDateTimeUtc(Some(since.into())),
+            sea_orm::Value::BigUnsigned(Some(limit)),
+            sea_orm::Value::BigUnsigned(Some(offset)),
+        ],
     );
 
     match pool.query_all(stmt).await {
@@ -132,27 +381,141 @@ async fn calculate_top_users_liked(
     }
 }
 
-async fn calculate_top_comments(
+/// Ranks `limit` entries starting at rank `offset` (0-based)
+pub async fn calculate_top_comments(
     mongo_db: &MongoDatabase,
     mysql_pool: &DbPool,
-    since: chrono::DateTime<chrono::Utc>,
+    since: DateTime<Utc>,
+    offset: u64,
+    limit: u64,
 ) -> Vec<TopFeed> {
+    let comment_counts = count_comments_by_feed(mongo_db, since, None).await;
+    top_feeds_from_counts(mysql_pool, &comment_counts, offset, limit).await
+}
+
+/// Like `calculate_top_comments`, restricted to feeds posted by `user_id`.
+/// The Redis ranking only holds the global top, so this always counts from MongoDB.
+pub async fn calculate_top_comments_by_user(
+    mongo_db: &MongoDatabase,
+    mysql_pool: &DbPool,
+    user_id: i64,
+    since: DateTime<Utc>,
+    offset: u64,
+    limit: u64,
+) -> Vec<TopFeed> {
+    let feed_ids: Vec<i64> = match feed::Entity::find()
+        .select_only()
+        .column(feed::Column::Id)
+        .filter(feed::Column::UserId.eq(user_id))
+        .into_tuple()
+        .all(mysql_pool)
+        .await
+    {
+        Ok(ids) => ids,
+        Err(e) => {
+            error!("Error fetching feeds of user {}: {:?}", user_id, e);
+            return Vec::new();
+        }
+    };
+    if feed_ids.is_empty() {
+        return Vec::new();
+    }
+
+    let comment_counts = count_comments_by_feed(mongo_db, since, Some(&feed_ids)).await;
+    top_feeds_from_counts(mysql_pool, &comment_counts, offset, limit).await
+}
+
+/// Ranks `limit` entries starting at rank `offset` (0-based)
+pub async fn calculate_top_feeds_viewed(
+    mongo_db: &MongoDatabase,
+    mysql_pool: &DbPool,
+    since: DateTime<Utc>,
+    offset: u64,
+    limit: u64,
+) -> Vec<TopFeed> {
+    let view_counts = count_views_by_feed(mongo_db, since).await;
+    top_feeds_from_counts(mysql_pool, &view_counts, offset, limit).await
+}
+
+/// Ranks feeds by `TRENDING_*_WEIGHT`-weighted likes, comments and views since `since`.
+/// `TopFeed.count` holds the composite score.
+pub async fn calculate_trending_feeds(
+    mongo_db: &MongoDatabase,
+    mysql_pool: &DbPool,
+    since: DateTime<Utc>,
+    offset: u64,
+    limit: u64,
+) -> Vec<TopFeed> {
+    let mut scores: HashMap<i64, i64> = HashMap::new();
+    for (feed_id, likes) in count_likes_by_feed(mysql_pool, since).await {
+        *scores.entry(feed_id).or_insert(0) += likes * TRENDING_LIKE_WEIGHT;
+    }
+    for (feed_id, comments) in count_comments_by_feed(mongo_db, since, None).await {
+        *scores.entry(feed_id).or_insert(0) += comments * TRENDING_COMMENT_WEIGHT;
+    }
+    for (feed_id, views) in count_views_by_feed(mongo_db, since).await {
+        *scores.entry(feed_id).or_insert(0) += views * TRENDING_VIEW_WEIGHT;
+    }
+
+    top_feeds_from_counts(mysql_pool, &scores, offset, limit).await
+}
+
+async fn count_likes_by_feed(pool: &DbPool, since: DateTime<Utc>) -> HashMap<i64, i64> {
+    let query = r#"
+        SELECT feed_id, COUNT(id) as like_count
+        FROM feed_likes
+        WHERE created_at >= ?
+        GROUP BY feed_id
+    "#;
+
+    let stmt = sea_orm::Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::MySql,
+        query,
+        [sea_orm::Value::ChronoDateTimeUtc(Some(since.into()))],
+    );
+
+    match pool.query_all(stmt).await {
+        Ok(rows) => rows
+            .iter()
+            .filter_map(|row| {
+                Some((
+                    row.try_get::<i64>("", "feed_id").ok()?,
+                    row.try_get::<i64>("", "like_count").ok()?,
+                ))
+            })
+            .collect(),
+        Err(e) => {
+            error!("Error counting likes: {:?}", e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Comments per feed since `since`, optionally only for `feed_ids`
+async fn count_comments_by_feed(
+    mongo_db: &MongoDatabase,
+    since: DateTime<Utc>,
+    feed_ids: Option<&[i64]>,
+) -> HashMap<i64, i64> {
     let collection = mongo_db.collection::<Comment>("comments");
-    let filter = doc! {
+    let mut filter = doc! {
         "created_at": {
             "$gte": since.timestamp()
         }
     };
+    if let Some(feed_ids) = feed_ids {
+        filter.insert("feed_id", doc! {"$in": feed_ids});
+    }
 
     let mut cursor = match collection.find(filter, None).await {
         Ok(c) => c,
         Err(e) => {
             error!("Error fetching comments: {:?}", e);
-            return Vec::new();
+            return HashMap::new();
         }
     };
 
-    let mut comment_counts: std::collections::HashMap<i64, i64> = std::collections::HashMap::new();
+    let mut comment_counts: HashMap<i64, i64> = HashMap::new();
 
     while let Ok(true) = cursor.advance().await {
         match cursor.deserialize_current() {
@@ -163,51 +526,10 @@ async fn calculate_top_comments(
         }
     }
 
-    let mut top_feeds = Vec::new();
-    let mut sorted: Vec<_> = comment_counts.iter().collect();
-    sorted.sort_by(|a, b| b.1.cmp(a.1));
-
-    for (feed_id, count) in sorted.iter().take(1000) {
-        // Get feed info using SeaORM
-        let feed_info = if let Ok(Some(feed_model)) =
-            feed::Entity::find_by_id(**feed_id).one(mysql_pool).await
-        {
-            if let Ok(Some(user_model)) = user::Entity::find_by_id(feed_model.user_id)
-                .one(mysql_pool)
-                .await
-            {
-                Some((
-                    feed_model.id,
-                    feed_model.user_id,
-                    user_model.username,
-                    feed_model.content,
-                ))
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        if let Some((feed_id_val, user_id, username, content)) = feed_info {
-            top_feeds.push(TopFeed {
-                feed_id: feed_id_val,
-                user_id,
-                username,
-                content,
-                count: **count,
-            });
-        }
-    }
-
-    top_feeds
+    comment_counts
 }
 
-async fn calculate_top_feeds_viewed(
-    mongo_db: &MongoDatabase,
-    mysql_pool: &DbPool,
-    since: chrono::DateTime<chrono::Utc>,
-) -> Vec<TopFeed> {
+async fn count_views_by_feed(mongo_db: &MongoDatabase, since: DateTime<Utc>) -> HashMap<i64, i64> {
     let collection = mongo_db.collection::<FeedView>("feed_views");
     let filter = doc! {
         "viewed_at": {
@@ -219,32 +541,58 @@ async fn calculate_top_feeds_viewed(
         Ok(c) => c,
         Err(e) => {
             error!("Error fetching feed views: {:?}", e);
-            return Vec::new();
+            return HashMap::new();
         }
     };
 
-    let mut view_counts: std::collections::HashMap<i64, i64> = std::collections::HashMap::new();
+    // Sampled views carry a weight above 1, so sum weights instead of counting documents
+    let mut view_counts: HashMap<i64, f64> = HashMap::new();
 
     while let Ok(true) = cursor.advance().await {
         match cursor.deserialize_current() {
             Ok(view) => {
-                *view_counts.entry(view.feed_id).or_insert(0) += 1;
+                *view_counts.entry(view.feed_id).or_insert(0.0) += view.weight;
             }
             Err(_) => continue,
         }
     }
 
+    view_counts
+        .into_iter()
+        .map(|(feed_id, views)| (feed_id, views.round() as i64))
+        .collect()
+}
+
+/// Sort per-feed counts (highest first, newest feed on ties) and resolve feed and user info
+/// for ranks `offset..offset + limit`. Feeds that no longer exist, are not live yet, have
+/// expired or are hidden are dropped.
+async fn top_feeds_from_counts(
+    mysql_pool: &DbPool,
+    counts: &HashMap<i64, i64>,
+    offset: u64,
+    limit: u64,
+) -> Vec<TopFeed> {
+    let mut sorted: Vec<(i64, i64)> = counts.iter().map(|(id, count)| (*id, *count)).collect();
+    sorted.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+
     let mut top_feeds = Vec::new();
-    let mut sorted: Vec<_> = view_counts.iter().collect();
-    sorted.sort_by(|a, b| b.1.cmp(a.1));
 
-    for (feed_id, count) in sorted.iter().take(1000) {
+    for (feed_id, count) in sorted
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+    {
         // Get feed and user info using SeaORM
-        let feed: Option<(i64, String)> = feed::Entity::find_by_id(**feed_id)
+        let feed: Option<(i64, String)> = feed::Entity::find_by_id(feed_id)
             .one(mysql_pool)
             .await
             .ok()
             .flatten()
+            .filter(|feed_model| {
+                feed_model.is_live(Utc::now())
+                    && !feed_model.has_expired(Utc::now())
+                    && feed_model.hidden_at.is_none()
+            })
             .map(|feed_model| (feed_model.user_id, feed_model.content));
 
         if let Some((user_id, content)) = feed {
@@ -257,11 +605,11 @@ async fn calculate_top_feeds_viewed(
 
             if let Some(username) = username {
                 top_feeds.push(TopFeed {
-                    feed_id: **feed_id,
+                    feed_id,
                     user_id,
                     username,
                     content,
-                    count: **count,
+                    count,
                 });
             }
         }
@@ -270,9 +618,14 @@ async fn calculate_top_feeds_viewed(
     top_feeds
 }
 
-async fn calculate_top_feeds_liked(
+/// Ranks `limit` entries starting at rank `offset` (0-based). Each like counts the
+/// liker's `TopStatsConfig::like_weight`, summed and rounded to a whole count.
+pub async fn calculate_top_feeds_liked(
     pool: &DbPool,
-    since: chrono::DateTime<chrono::Utc>,
+    top_stats: &TopStatsConfig,
+    since: DateTime<Utc>,
+    offset: u64,
+    limit: u64,
 ) -> Vec<TopFeed> {
     let query = r#"
         SELECT 
@@ -280,21 +633,33 @@ async fn calculate_top_feeds_liked(
             f.user_id,
             u.username,
             f.content,
-            COUNT(fl.id) as like_count
+            CAST(ROUND(SUM(
+                CASE WHEN lu.is_admin THEN ? WHEN lu.is_verified THEN ? ELSE 1e0 END
+            )) AS SIGNED) as like_count
         FROM feeds f
         INNER JOIN feed_likes fl ON f.id = fl.feed_id
         INNER JOIN users u ON f.user_id = u.id
-        WHERE fl.created_at >= ?
+        LEFT JOIN users lu ON fl.user_id = lu.id
+        WHERE fl.created_at >= ? AND (f.expires_at IS NULL OR f.expires_at > ?) AND f.hidden_at IS NULL
+          AND (f.publish_at IS NULL OR f.publish_at <= ?)
         GROUP BY f.id, f.user_id, u.username, f.content
         ORDER BY like_count DESC
-        LIMIT 1000
+        LIMIT ? OFFSET ?
     "#;
 
     // Use raw SQL for complex aggregation with SeaORM
     let stmt = sea_orm::Statement::from_sql_and_values(
         sea_orm::DatabaseBackend::MySql,
         query,
-        [sea_orm::Value::ChronoDateTimeUtc(Some(since.into()))],
+        [
+            sea_orm::Value::Double(Some(top_stats.like_weight_admin)),
+            sea_orm::Value::Double(Some(top_stats.like_weight_verified)),
+            sea_orm::Value::ChronoDateTimeUtc(Some(since.into())),
+            sea_orm::Value::ChronoDateTimeUtc(Some(Utc::now().into())),
+            sea_orm::Value::ChronoDateTimeUtc(Some(Utc::now().into())),
+            sea_orm::Value::BigUnsigned(Some(limit)),
+            sea_orm::Value::BigUnsigned(Some(offset)),
+        ],
     );
 
     match pool.query_all(stmt).await {
@@ -316,3 +681,71 @@ async fn calculate_top_feeds_liked(
         }
     }
 }
+
+/// Like `calculate_top_feeds_liked`, restricted to feeds posted by `user_id`.
+/// The Redis ranking only holds the global top, so this always queries MySQL.
+pub async fn calculate_top_feeds_liked_by_user(
+    pool: &DbPool,
+    top_stats: &TopStatsConfig,
+    user_id: i64,
+    since: DateTime<Utc>,
+    offset: u64,
+    limit: u64,
+) -> Vec<TopFeed> {
+    let query = r#"
+        SELECT 
+            f.id,
+            f.user_id,
+            u.username,
+            f.content,
+            CAST(ROUND(SUM(
+                CASE WHEN lu.is_admin THEN ? WHEN lu.is_verified THEN ? ELSE 1e0 END
+            )) AS SIGNED) as like_count
+        FROM feeds f
+        INNER JOIN feed_likes fl ON f.id = fl.feed_id
+        INNER JOIN users u ON f.user_id = u.id
+        LEFT JOIN users lu ON fl.user_id = lu.id
+        WHERE f.user_id = ? AND fl.created_at >= ? AND (f.expires_at IS NULL OR f.expires_at > ?)
+          AND f.hidden_at IS NULL AND (f.publish_at IS NULL OR f.publish_at <= ?)
+        GROUP BY f.id, f.user_id, u.username, f.content
+        ORDER BY like_count DESC, f.id DESC
+        LIMIT ? OFFSET ?
+    "#;
+
+    let stmt = sea_orm::Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::MySql,
+        query,
+        [
+            sea_orm::Value::Double(Some(top_stats.like_weight_admin)),
+            sea_orm::Value::Double(Some(top_stats.like_weight_verified)),
+            sea_orm::Value::BigInt(Some(user_id)),
+            sea_orm::Value::ChronoDateTimeUtc(Some(since.into())),
+            sea_orm::Value::ChronoDateTimeUtc(Some(Utc::now().into())),
+            sea_orm::Value::ChronoDateTimeUtc(Some(Utc::now().into())),
+            sea_orm::Value::BigUnsigned(Some(limit)),
+            sea_orm::Value::BigUnsigned(Some(offset)),
+        ],
+    );
+
+    match pool.query_all(stmt).await {
+        Ok(rows) => rows
+            .iter()
+            .filter_map(|row| {
+                Some(TopFeed {
+                    feed_id: row.try_get::<i64>("", "id").ok()?,
+                    user_id: row.try_get::<i64>("", "user_id").ok()?,
+                    username: row.try_get::<String>("", "username").ok()?,
+                    content: row.try_get::<String>("", "content").ok()?,
+                    count: row.try_get::<i64>("", "like_count").ok()?,
+                })
+            })
+            .collect(),
+        Err(e) => {
+            error!(
+                "Error calculating top feeds liked of user {}: {:?}",
+                user_id, e
+            );
+            Vec::new()
+        }
+    }
+}
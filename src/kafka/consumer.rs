@@ -1,36 +1,269 @@
-use crate::config::Config;
-use log::{error, info};
+use super::KafkaProducer;
+use crate::config::{Config, EmptyPayloadAction};
+use crate::db::redis_key;
+use crate::metrics;
+use log::{error, info, warn};
 use rdkafka::config::ClientConfig;
-use rdkafka::consumer::{stream_consumer::StreamConsumer, Consumer};
-use rdkafka::Message;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use rdkafka::consumer::{
+    stream_consumer::StreamConsumer, CommitMode, Consumer, ConsumerContext, Rebalance,
+};
+use rdkafka::error::KafkaResult;
+use rdkafka::{ClientContext, Message, TopicPartitionList};
+use redis::Client as RedisClient;
+use std::sync::{Arc, OnceLock, Weak};
+use std::time::{Duration, Instant};
+
+type RebalanceAwareConsumer = StreamConsumer<RebalanceContext>;
+
+/// Consumer context that logs rebalance events and commits offsets synchronously
+/// before partitions are revoked, so the consumer that gets the partitions next
+/// resumes after the last message processed here instead of replaying it.
+#[derive(Default)]
+pub struct RebalanceContext {
+    // Set right after the consumer is created; rebalance callbacks need it to commit
+    consumer: OnceLock<Weak<RebalanceAwareConsumer>>,
+}
+
+fn format_partitions(tpl: &TopicPartitionList) -> String {
+    tpl.elements()
+        .iter()
+        .map(|e| format!("{}[{}]", e.topic(), e.partition()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl ClientContext for RebalanceContext {}
+
+impl ConsumerContext for RebalanceContext {
+    fn pre_rebalance(&self, rebalance: &Rebalance) {
+        match rebalance {
+            Rebalance::Revoke(tpl) => {
+                info!(
+                    "Kafka rebalance: revoking partitions {}",
+                    format_partitions(tpl)
+                );
+                if let Some(consumer) = self.consumer.get().and_then(Weak::upgrade) {
+                    if let Err(e) = consumer.commit_consumer_state(CommitMode::Sync) {
+                        // Nothing consumed yet is reported as an error; safe to ignore
+                        warn!("Failed to commit offsets before revoke: {:?}", e);
+                    }
+                }
+            }
+            Rebalance::Assign(tpl) => {
+                info!(
+                    "Kafka rebalance: assigning partitions {}",
+                    format_partitions(tpl)
+                );
+            }
+            Rebalance::Error(e) => {
+                error!("Kafka rebalance error: {:?}", e);
+            }
+        }
+    }
+
+    fn post_rebalance(&self, rebalance: &Rebalance) {
+        match rebalance {
+            Rebalance::Assign(tpl) => {
+                info!(
+                    "Kafka rebalance complete: {} partitions assigned",
+                    tpl.count()
+                );
+            }
+            Rebalance::Revoke(tpl) => {
+                info!(
+                    "Kafka rebalance complete: {} partitions revoked",
+                    tpl.count()
+                );
+            }
+            Rebalance::Error(_) => {}
+        }
+    }
+
+    fn commit_callback(&self, result: KafkaResult<()>, _offsets: &TopicPartitionList) {
+        if let Err(e) = result {
+            warn!("Kafka offset commit failed: {:?}", e);
+        }
+    }
+}
+
+/// Redis hash holding consumer heartbeats for the configured group.
+/// Each consumer writes its own field (its topics, comma-separated) with a Unix timestamp.
+pub fn consumer_heartbeat_key(config: &Config) -> String {
+    redis_key(
+        config,
+        &format!("consumer:heartbeat:{}", config.kafka.group_id),
+    )
+}
+
+/// Record that the consumer loop is alive
+async fn record_heartbeat(redis_client: &RedisClient, key: &str, field: &str) {
+    let result: redis::RedisResult<()> = async {
+        let mut conn = redis_client.get_async_connection().await?;
+        redis::cmd("HSET")
+            .arg(key)
+            .arg(field)
+            .arg(chrono::Utc::now().timestamp())
+            .query_async(&mut conn)
+            .await
+    }
+    .await;
+
+    if let Err(e) = result {
+        warn!("Failed to record consumer heartbeat: {:?}", e);
+    }
+}
+
+/// What happened to one received message
+#[derive(Debug, PartialEq, Eq)]
+pub enum Dispatch {
+    /// Passed to the handler
+    Handled,
+    /// No payload, and `KAFKA_EMPTY_PAYLOAD_ACTION=skip`
+    SkippedEmpty,
+    /// No payload with `KAFKA_EMPTY_PAYLOAD_ACTION=dlq`, or a payload that is not valid
+    /// UTF-8: forward `key` and `payload` to `topic`, with `error` as the reason
+    DeadLetter {
+        topic: String,
+        key: String,
+        payload: String,
+        error: String,
+    },
+}
+
+/// `dlq-error` header of messages dead-lettered for having no payload
+pub const EMPTY_PAYLOAD_ERROR: &str = "empty payload";
+
+/// Dead-letter topic for messages consumed from `topic`, e.g. `feed_events_dlq`
+pub fn dead_letter_topic(topic: &str) -> String {
+    format!("{}_dlq", topic)
+}
+
+/// Forward a message consumed from `source_topic` that could not be handled to its
+/// dead-letter topic, with `error` in the `dlq-error` header. Offsets are already
+/// committed, so a failed forward loses the message; both outcomes are counted.
+pub async fn forward_dead_letter(
+    producer: &KafkaProducer,
+    source_topic: &str,
+    key: &str,
+    payload: &str,
+    error: &str,
+) {
+    let topic = dead_letter_topic(source_topic);
+    let sent = producer
+        .send_dead_letter(&topic, key, payload, source_topic, error)
+        .await;
+    if let Err(e) = &sent {
+        error!("Failed to forward message to {}: {:?}", topic, e);
+    }
+    metrics::record_dead_letter(source_topic, sent.is_ok());
+}
+
+/// Hand a received message to `handler`, or decide what happens to it when it has no
+/// usable payload. Offsets are auto-committed, so whatever is not handled or
+/// dead-lettered here is gone from the topic once the next commit runs.
+pub fn dispatch_message<M, F>(
+    message: &M,
+    empty_payload_action: EmptyPayloadAction,
+    handler: &F,
+) -> Dispatch
+where
+    M: Message,
+    F: Fn(String, String, Vec<u8>),
+{
+    let topic = message.topic().to_string();
+    let key = message
+        .key()
+        .and_then(|k| std::str::from_utf8(k).ok())
+        .unwrap_or("")
+        .to_string();
+
+    match message.payload_view::<str>() {
+        // A zero-length payload is as empty as a missing one
+        None | Some(Ok("")) => match empty_payload_action {
+            EmptyPayloadAction::Skip => {
+                warn!("Skipping empty message from topic: {}, key: {}", topic, key);
+                Dispatch::SkippedEmpty
+            }
+            EmptyPayloadAction::DeadLetter => {
+                let dead_letter = dead_letter_topic(&topic);
+                warn!(
+                    "Forwarding empty message from topic: {}, key: {} to {}",
+                    topic, key, dead_letter
+                );
+                Dispatch::DeadLetter {
+                    topic: dead_letter,
+                    key,
+                    payload: String::new(),
+                    error: EMPTY_PAYLOAD_ERROR.to_string(),
+                }
+            }
+        },
+        Some(Ok(payload)) => {
+            info!("Received message from topic: {}, key: {}", topic, key);
+            handler(topic, key, payload.as_bytes().to_vec());
+            Dispatch::Handled
+        }
+        // Kept (lossily) for inspection; the error names the first invalid byte
+        Some(Err(e)) => {
+            error!("Error while deserializing message payload: {:?}", e);
+            Dispatch::DeadLetter {
+                topic: dead_letter_topic(&topic),
+                key,
+                payload: String::from_utf8_lossy(message.payload().unwrap_or_default())
+                    .into_owned(),
+                error: format!("invalid UTF-8 payload: {}", e),
+            }
+        }
+    }
+}
 
 pub struct KafkaConsumer {
-    consumer: Arc<Mutex<StreamConsumer>>,
+    consumer: Arc<RebalanceAwareConsumer>,
     topics: Vec<String>,
+    redis_client: RedisClient,
+    heartbeat_key: String,
+    heartbeat_interval: Duration,
+    empty_payload_action: EmptyPayloadAction,
+    dead_letter_producer: KafkaProducer,
 }
 
 impl KafkaConsumer {
-    pub fn new(config: &Config, topics: Vec<String>) -> Result<Self, anyhow::Error> {
-        let consumer: StreamConsumer = ClientConfig::new()
+    pub fn new(
+        config: &Config,
+        redis_client: &RedisClient,
+        topics: Vec<String>,
+    ) -> Result<Self, anyhow::Error> {
+        let consumer: RebalanceAwareConsumer = ClientConfig::new()
             .set("group.id", &config.kafka.group_id)
             .set("bootstrap.servers", &config.kafka.brokers)
             .set("enable.partition.eof", "false")
             .set("session.timeout.ms", "6000")
             .set("enable.auto.commit", "true")
             .set("auto.offset.reset", "earliest")
-            .create()?;
+            .create_with_context(RebalanceContext::default())?;
+
+        let consumer = Arc::new(consumer);
+        let _ = consumer.context().consumer.set(Arc::downgrade(&consumer));
+
+        let empty_payload_action = config.kafka.empty_payload_action;
+        let dead_letter_producer = KafkaProducer::new(config)?;
 
         Ok(KafkaConsumer {
-            consumer: Arc::new(Mutex::new(consumer)),
+            consumer,
             topics,
+            redis_client: redis_client.clone(),
+            heartbeat_key: consumer_heartbeat_key(config),
+            heartbeat_interval: Duration::from_secs(
+                config.kafka.consumer_heartbeat_interval_seconds,
+            ),
+            empty_payload_action,
+            dead_letter_producer,
         })
     }
 
     pub async fn subscribe(&self) -> Result<(), anyhow::Error> {
-        let consumer = self.consumer.lock().await;
-        consumer.subscribe(&self.topics.iter().map(|s| s.as_str()).collect::<Vec<_>>())?;
+        self.consumer
+            .subscribe(&self.topics.iter().map(|s| s.as_str()).collect::<Vec<_>>())?;
         Ok(())
     }
 
@@ -39,30 +272,50 @@ impl KafkaConsumer {
         F: Fn(String, String, Vec<u8>) + Send + Sync + 'static,
     {
         let consumer = Arc::clone(&self.consumer);
+        let redis_client = self.redis_client.clone();
+        let heartbeat_key = self.heartbeat_key.clone();
+        let heartbeat_field = self.topics.join(",");
+        let heartbeat_interval = self.heartbeat_interval;
+        let empty_payload_action = self.empty_payload_action;
+        let dead_letter_producer = self.dead_letter_producer.clone();
 
         tokio::spawn(async move {
+            let mut last_heartbeat: Option<Instant> = None;
             loop {
-                match consumer.lock().await.recv().await {
-                    Ok(message) => match message.payload_view::<str>() {
-                        None => {
-                            error!("Received empty message");
-                        }
-                        Some(Ok(_payload)) => {
-                            let topic = message.topic().to_string();
-                            let key = message
-                                .key()
-                                .and_then(|k| std::str::from_utf8(k).ok())
-                                .unwrap_or("")
-                                .to_string();
-                            let payload_bytes = message.payload().unwrap_or(&[]).to_vec();
-
-                            info!("Received message from topic: {}, key: {}", topic, key);
-                            handler(topic, key, payload_bytes);
-                        }
-                        Some(Err(e)) => {
-                            error!("Error while deserializing message payload: {:?}", e);
+                if last_heartbeat.is_none_or(|at| at.elapsed() >= heartbeat_interval) {
+                    record_heartbeat(&redis_client, &heartbeat_key, &heartbeat_field).await;
+                    last_heartbeat = Some(Instant::now());
+                }
+
+                // Wake up periodically so an idle topic still produces heartbeats
+                let received = match tokio::time::timeout(heartbeat_interval, consumer.recv()).await
+                {
+                    Ok(received) => received,
+                    Err(_) => continue,
+                };
+
+                match received {
+                    Ok(message) => {
+                        let dispatched = dispatch_message(&message, empty_payload_action, &handler);
+                        let source_topic = message.topic().to_string();
+                        drop(message);
+                        if let Dispatch::DeadLetter {
+                            key,
+                            payload,
+                            error,
+                            ..
+                        } = dispatched
+                        {
+                            forward_dead_letter(
+                                &dead_letter_producer,
+                                &source_topic,
+                                &key,
+                                &payload,
+                                &error,
+                            )
+                            .await;
                         }
-                    },
+                    }
                     Err(e) => {
                         error!("Error receiving message: {:?}", e);
                         tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
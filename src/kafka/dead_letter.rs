@@ -0,0 +1,145 @@
+use super::producer::{DLQ_ERROR_HEADER, DLQ_ORIGINAL_TOPIC_HEADER};
+use crate::config::Config;
+use crate::models::DeadLetterMessage;
+use chrono::DateTime;
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{BaseConsumer, Consumer};
+use rdkafka::message::{Headers, Message};
+use rdkafka::{Offset, TopicPartitionList};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A dead letter as the admin API returns it
+pub fn dead_letter_from_message<M: Message>(message: &M) -> DeadLetterMessage {
+    let text = |bytes: &[u8]| String::from_utf8_lossy(bytes).into_owned();
+    let header = |name: &str| {
+        message
+            .headers()?
+            .iter()
+            .find(|header| header.key == name)?
+            .value
+            .map(text)
+    };
+    DeadLetterMessage {
+        topic: message.topic().to_string(),
+        partition: message.partition(),
+        offset: message.offset(),
+        key: message.key().map(text),
+        payload: message.payload().map(text).unwrap_or_default(),
+        original_topic: header(DLQ_ORIGINAL_TOPIC_HEADER),
+        error: header(DLQ_ERROR_HEADER),
+        timestamp: message
+            .timestamp()
+            .to_millis()
+            .and_then(DateTime::from_timestamp_millis),
+    }
+}
+
+/// Consumer that reads assigned partitions without joining the group or committing
+/// offsets, so inspecting a dead-letter topic never moves anyone's position in it
+fn inspector(config: &Config) -> Result<BaseConsumer, anyhow::Error> {
+    Ok(ClientConfig::new()
+        .set(
+            "group.id",
+            format!("{}.dlq-inspector", config.kafka.group_id),
+        )
+        .set("bootstrap.servers", &config.kafka.brokers)
+        .set("enable.auto.commit", "false")
+        .set("enable.auto.offset.store", "false")
+        .set("enable.partition.eof", "false")
+        .create()?)
+}
+
+/// The last `limit` messages of `topic`, newest first.
+///
+/// Reads up to `limit` messages from the end of every partition, then keeps the newest.
+/// Blocking: waits up to `KAFKA_DLQ_PEEK_TIMEOUT_MS`, and returns what it read by then.
+pub fn peek_dead_letters(
+    config: &Config,
+    topic: &str,
+    limit: usize,
+) -> Result<Vec<DeadLetterMessage>, anyhow::Error> {
+    let timeout = Duration::from_millis(config.kafka.dlq_peek_timeout_ms);
+    let deadline = Instant::now() + timeout;
+    let consumer = inspector(config)?;
+
+    // A topic nobody dead-lettered to yet has no partitions
+    let metadata = consumer.fetch_metadata(Some(topic), timeout)?;
+    let partitions: Vec<i32> = metadata
+        .topics()
+        .iter()
+        .filter(|t| t.name() == topic)
+        .flat_map(|t| t.partitions().iter().map(|p| p.id()))
+        .collect();
+
+    let mut assignment = TopicPartitionList::new();
+    // Offset after the last message of each partition still being read
+    let mut ends: HashMap<i32, i64> = HashMap::new();
+    for partition in partitions {
+        let (low, high) = consumer.fetch_watermarks(topic, partition, timeout)?;
+        if high > low {
+            let start = (high - limit as i64).max(low);
+            assignment.add_partition_offset(topic, partition, Offset::Offset(start))?;
+            ends.insert(partition, high);
+        }
+    }
+    if ends.is_empty() {
+        return Ok(Vec::new());
+    }
+    consumer.assign(&assignment)?;
+
+    let mut letters = Vec::new();
+    while !ends.is_empty() {
+        let Some(received) = consumer.poll(deadline.saturating_duration_since(Instant::now()))
+        else {
+            break;
+        };
+        let message = received?;
+        if ends
+            .get(&message.partition())
+            .is_some_and(|end| message.offset() + 1 >= *end)
+        {
+            ends.remove(&message.partition());
+        }
+        letters.push(dead_letter_from_message(&message));
+    }
+
+    letters.sort_by_key(|letter| std::cmp::Reverse((letter.timestamp, letter.offset)));
+    letters.truncate(limit);
+    Ok(letters)
+}
+
+/// The message at `offset` of `topic`/`partition`, or `None` if there is none (never
+/// written, or already removed by retention). Blocking, like `peek_dead_letters`.
+pub fn read_dead_letter(
+    config: &Config,
+    topic: &str,
+    partition: i32,
+    offset: i64,
+) -> Result<Option<DeadLetterMessage>, anyhow::Error> {
+    let timeout = Duration::from_millis(config.kafka.dlq_peek_timeout_ms);
+    let consumer = inspector(config)?;
+    let Ok((low, high)) = consumer.fetch_watermarks(topic, partition, timeout) else {
+        // Unknown topic or partition
+        return Ok(None);
+    };
+    if offset < low || offset >= high {
+        return Ok(None);
+    }
+
+    let mut assignment = TopicPartitionList::new();
+    assignment.add_partition_offset(topic, partition, Offset::Offset(offset))?;
+    consumer.assign(&assignment)?;
+    match consumer.poll(timeout) {
+        Some(received) => {
+            let message = received?;
+            Ok((message.offset() == offset).then(|| dead_letter_from_message(&message)))
+        }
+        None => Err(anyhow::anyhow!(
+            "Timed out reading {}[{}] at offset {}",
+            topic,
+            partition,
+            offset
+        )),
+    }
+}
@@ -1,16 +1,21 @@
-use chrono::Utc;
+use crate::models::{Digest, DigestActor};
+use chrono::{DateTime, Utc};
 use serde::de::Error;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Enum defining event types related to Feed
-/// Serializes/deserializes as snake_case: "created", "liked", "commented", "viewed"
+/// Serializes/deserializes as snake_case: "created", "liked", "unliked", "commented", "viewed",
+/// "expired"
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum FeedEventType {
     Created,
     Liked,
+    Unliked,
     Commented,
     Viewed,
+    Expired,
 }
 
 /// Enum defining event types related to User
@@ -18,6 +23,14 @@ pub enum FeedEventType {
 #[serde(rename_all = "snake_case")]
 pub enum UserEventType {
     UserCreated,
+    UserDeleted,
+}
+
+/// Enum defining event types published to the `digests` topic
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestEventType {
+    DigestReady,
 }
 
 /// Event when a feed is created
@@ -29,6 +42,9 @@ pub struct FeedCreatedEvent {
     pub user_id: i64,
     pub content: String,
     pub timestamp: String,
+    /// ID of the HTTP request that caused the event (absent for background jobs)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl FeedCreatedEvent {
@@ -39,8 +55,14 @@ impl FeedCreatedEvent {
             user_id,
             content,
             timestamp: Utc::now().to_rfc3339(),
+            request_id: None,
         }
     }
+
+    pub fn with_request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.to_string());
+        self
+    }
 }
 
 /// Event when a feed is liked
@@ -51,6 +73,9 @@ pub struct FeedLikedEvent {
     pub feed_id: i64,
     pub user_id: i64,
     pub timestamp: String,
+    /// See `FeedCreatedEvent::request_id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl FeedLikedEvent {
@@ -60,6 +85,33 @@ impl FeedLikedEvent {
             feed_id,
             user_id,
             timestamp: Utc::now().to_rfc3339(),
+            request_id: None,
+        }
+    }
+
+    pub fn with_request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.to_string());
+        self
+    }
+}
+
+/// Event when a like is removed from a feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedUnlikedEvent {
+    #[serde(rename = "event_type")]
+    pub event_type: FeedEventType,
+    pub feed_id: i64,
+    pub user_id: i64, // whose like was removed
+    pub timestamp: String,
+}
+
+impl FeedUnlikedEvent {
+    pub fn new(feed_id: i64, user_id: i64) -> Self {
+        Self {
+            event_type: FeedEventType::Unliked,
+            feed_id,
+            user_id,
+            timestamp: Utc::now().to_rfc3339(),
         }
     }
 }
@@ -74,6 +126,9 @@ pub struct FeedCommentedEvent {
     pub comment_id: String,
     pub content: String,
     pub timestamp: String,
+    /// See `FeedCreatedEvent::request_id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl FeedCommentedEvent {
@@ -85,8 +140,14 @@ impl FeedCommentedEvent {
             comment_id,
             content,
             timestamp: Utc::now().to_rfc3339(),
+            request_id: None,
         }
     }
+
+    pub fn with_request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.to_string());
+        self
+    }
 }
 
 /// Event when a feed is viewed
@@ -110,6 +171,29 @@ impl FeedViewedEvent {
     }
 }
 
+/// Event when a feed reached its `expires_at` and stopped being served
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedExpiredEvent {
+    #[serde(rename = "event_type")]
+    pub event_type: FeedEventType,
+    pub feed_id: i64,
+    pub user_id: i64, // author
+    pub expires_at: String,
+    pub timestamp: String,
+}
+
+impl FeedExpiredEvent {
+    pub fn new(feed_id: i64, user_id: i64, expires_at: DateTime<Utc>) -> Self {
+        Self {
+            event_type: FeedEventType::Expired,
+            feed_id,
+            user_id,
+            expires_at: expires_at.to_rfc3339(),
+            timestamp: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
 /// Event when a user is created
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserCreatedEvent {
@@ -119,6 +203,9 @@ pub struct UserCreatedEvent {
     pub email: String,
     pub username: String,
     pub timestamp: String,
+    /// Signup request's preferred `Accept-Language`, seeded into the profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
 }
 
 impl UserCreatedEvent {
@@ -129,6 +216,58 @@ impl UserCreatedEvent {
             email,
             username,
             timestamp: Utc::now().to_rfc3339(),
+            language: None,
+        }
+    }
+
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+}
+
+/// Event when a user and all their data have been purged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserDeletedEvent {
+    #[serde(rename = "event_type")]
+    pub event_type: UserEventType,
+    pub user_id: i64,
+    pub timestamp: String,
+}
+
+impl UserDeletedEvent {
+    pub fn new(user_id: i64) -> Self {
+        Self {
+            event_type: UserEventType::UserDeleted,
+            user_id,
+            timestamp: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Event when a user's daily digest has been stored, for an email service to send
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestReadyEvent {
+    #[serde(rename = "event_type")]
+    pub event_type: DigestEventType,
+    pub user_id: i64,
+    pub date: String,
+    pub unread_total: i64,
+    pub counts: BTreeMap<String, i64>,
+    pub top_actors: Vec<DigestActor>,
+    pub timestamp: String,
+}
+
+impl DigestReadyEvent {
+    pub fn new(digest: Digest) -> Self {
+        Self {
+            event_type: DigestEventType::DigestReady,
+            user_id: digest.user_id,
+            date: digest.date,
+            unread_total: digest.unread_total,
+            counts: digest.counts,
+            top_actors: digest.top_actors,
+            timestamp: Utc::now().to_rfc3339(),
         }
     }
 }
@@ -153,3 +292,11 @@ pub fn parse_feed_event(
 
     Ok((event_type, value))
 }
+
+/// `request_id` of a parsed event for log lines, or "-" if it has none
+pub fn event_request_id(event_data: &serde_json::Value) -> &str {
+    event_data
+        .get("request_id")
+        .and_then(|v| v.as_str())
+        .unwrap_or("-")
+}
@@ -1,7 +1,9 @@
 pub mod consumer;
+pub mod dead_letter;
 pub mod events;
 pub mod producer;
 
 pub use consumer::*;
+pub use dead_letter::*;
 pub use events::*;
 pub use producer::*;
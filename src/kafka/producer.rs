@@ -1,12 +1,21 @@
 use crate::config::Config;
 use rdkafka::config::ClientConfig;
-use rdkafka::producer::{BaseProducer, BaseRecord};
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{BaseProducer, BaseRecord, Producer};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
+/// Header of a dead letter naming the topic it was consumed from
+pub const DLQ_ORIGINAL_TOPIC_HEADER: &str = "dlq-original-topic";
+/// Header of a dead letter saying why it could not be processed
+pub const DLQ_ERROR_HEADER: &str = "dlq-error";
+
 #[derive(Clone)]
 pub struct KafkaProducer {
     producer: Arc<Mutex<BaseProducer>>,
+    max_attempts: u32,
+    retry_backoff: Duration,
 }
 
 impl KafkaProducer {
@@ -18,14 +27,120 @@ impl KafkaProducer {
 
         Ok(KafkaProducer {
             producer: Arc::new(Mutex::new(producer)),
+            max_attempts: config.kafka.send_max_attempts.max(1),
+            retry_backoff: Duration::from_millis(config.kafka.send_retry_backoff_ms),
         })
     }
 
+    /// Fetch cluster metadata, failing if no broker answers within 5 seconds.
+    /// Creating the producer does not connect, so startup checks with this instead.
+    pub async fn check_brokers(&self) -> Result<(), anyhow::Error> {
+        let producer = self.producer.lock().await;
+        producer
+            .client()
+            .fetch_metadata(None, Duration::from_secs(5))?;
+        Ok(())
+    }
+
     pub async fn send_message(
         &self,
         topic: &str,
         key: &str,
         payload: &str,
+    ) -> Result<(), anyhow::Error> {
+        self.enqueue(topic, key, payload, None)
+            .await
+            .inspect_err(|e| {
+                log::error!(
+                    "Failed to queue Kafka message: topic={}, key={}, error={:?}",
+                    topic,
+                    key,
+                    e
+                );
+            })
+    }
+
+    /// Like `send_message`, but retries transient failures (e.g. a full local queue)
+    /// up to `KAFKA_SEND_MAX_ATTEMPTS` times with exponential backoff.
+    /// Only the final failure is logged as an error and returned.
+    pub async fn send_message_with_retry(
+        &self,
+        topic: &str,
+        key: &str,
+        payload: &str,
+    ) -> Result<(), anyhow::Error> {
+        let mut backoff = self.retry_backoff;
+        let mut attempt = 1;
+        loop {
+            match self.enqueue(topic, key, payload, None).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < self.max_attempts => {
+                    crate::metrics::record_kafka_send_retry(topic);
+                    log::warn!(
+                        "Kafka send attempt {}/{} failed, retrying in {:?}: topic={}, key={}, error={:?}",
+                        attempt,
+                        self.max_attempts,
+                        backoff,
+                        topic,
+                        key,
+                        e
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    log::error!(
+                        "Failed to queue Kafka message after {} attempts: topic={}, key={}, error={:?}",
+                        attempt,
+                        topic,
+                        key,
+                        e
+                    );
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Forward a message consumed from `original_topic` to the dead-letter topic `topic`,
+    /// recording where it came from and why in the `DLQ_ORIGINAL_TOPIC_HEADER` and
+    /// `DLQ_ERROR_HEADER` headers
+    pub async fn send_dead_letter(
+        &self,
+        topic: &str,
+        key: &str,
+        payload: &str,
+        original_topic: &str,
+        error: &str,
+    ) -> Result<(), anyhow::Error> {
+        let headers = OwnedHeaders::new()
+            .insert(Header {
+                key: DLQ_ORIGINAL_TOPIC_HEADER,
+                value: Some(original_topic),
+            })
+            .insert(Header {
+                key: DLQ_ERROR_HEADER,
+                value: Some(error),
+            });
+        self.enqueue(topic, key, payload, Some(headers))
+            .await
+            .inspect_err(|e| {
+                log::error!(
+                    "Failed to queue dead letter: topic={}, key={}, error={:?}",
+                    topic,
+                    key,
+                    e
+                );
+            })
+    }
+
+    async fn enqueue(
+        &self,
+        topic: &str,
+        key: &str,
+        payload: &str,
+        headers: Option<OwnedHeaders>,
     ) -> Result<(), anyhow::Error> {
         let producer = self.producer.lock().await;
 
@@ -36,10 +151,14 @@ impl KafkaProducer {
             payload.len()
         );
 
-        match producer.send(BaseRecord::to(topic).key(key).payload(payload)) {
+        let mut record = BaseRecord::to(topic).key(key).payload(payload);
+        if let Some(headers) = headers {
+            record = record.headers(headers);
+        }
+        match producer.send(record) {
             Ok(_) => {
                 // Poll to ensure message is sent and handle delivery reports
-                producer.poll(std::time::Duration::from_millis(0));
+                producer.poll(Duration::from_millis(0));
 
                 log::info!(
                     "Kafka message queued successfully: topic={}, key={}, size={} bytes",
@@ -50,12 +169,8 @@ impl KafkaProducer {
                 Ok(())
             }
             Err((e, _)) => {
-                log::error!(
-                    "Failed to queue Kafka message: topic={}, key={}, error={:?}",
-                    topic,
-                    key,
-                    e
-                );
+                // Serve delivery reports so a full queue can drain before the next attempt
+                producer.poll(Duration::from_millis(0));
                 Err(anyhow::anyhow!("Kafka send error: {:?}", e))
             }
         }
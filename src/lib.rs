@@ -5,6 +5,8 @@ pub mod db;
 pub mod entities;
 pub mod jobs;
 pub mod kafka;
+pub mod metrics;
 pub mod models;
+pub mod repositories;
 pub mod services;
 
@@ -1,4 +1,7 @@
-use actix_web::{middleware::Logger, web, App, HttpServer};
+use actix_web::{
+    middleware::{from_fn, Compress, Condition},
+    web, App, HttpServer,
+};
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
@@ -9,53 +12,125 @@ mod db;
 mod entities;
 mod jobs;
 mod kafka;
+mod metrics;
 mod models;
+// The in-memory repositories are only used by tests through the library crate
+#[cfg_attr(feature = "test-support", allow(dead_code, unused_imports))]
+mod repositories;
 mod services;
 
-use config::Config;
-use db::{create_mongodb_client, create_mysql_pool, create_redis_client};
-use jobs::{calculate_top_stats, handle_user_created_event};
-use kafka::{parse_feed_event, FeedEventType, KafkaConsumer, KafkaProducer};
-use services::notification::{
-    handle_feed_commented_event, handle_feed_liked_event, handle_feed_viewed_event,
+use config::{Config, JwtExpiryMode};
+use db::{
+    connect_redis, connect_with_retry, create_mongodb_client, create_mysql_pool,
+    ensure_mongodb_schema,
+};
+use jobs::{
+    build_digests, calculate_top_stats, expire_feeds, handle_feed_event, handle_user_created_event,
+    next_digest_run, publish_scheduled_feeds, run_like_count_reconciliation, run_self_test,
+    sweep_orphaned_rankings, trim_notifications, trim_rankings,
 };
+use kafka::{forward_dead_letter, parse_feed_event, KafkaConsumer, KafkaProducer};
+use repositories::MysqlRepository;
+use services::search::create_search_backend;
+use std::future::ready;
+use std::sync::Arc;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
 
     let config = Config::from_env().expect("Failed to load configuration");
+    if let Err(e) = config.validate() {
+        log::error!("{}", e);
+        std::process::exit(1);
+    }
 
     log::info!(
         "Starting server on {}:{}",
         config.server.host,
         config.server.port
     );
-    let mysql_pool = create_mysql_pool(&config)
+    // Dependencies may still be starting (e.g. in docker compose), so each one is retried
+    let mysql_pool = connect_with_retry(&config, "MySQL", || create_mysql_pool(&config))
         .await
         .expect("Failed to create MySQL pool");
 
-    let mongodb_db = create_mongodb_client(&config)
+    let mongodb_db = connect_with_retry(&config, "MongoDB", || create_mongodb_client(&config))
         .await
         .expect("Failed to create MongoDB client");
 
-    let redis_client = create_redis_client(&config).expect("Failed to create Redis client");
+    // Validators need collMod/createCollection privileges; without them the app still works
+    if let Err(e) = ensure_mongodb_schema(&mongodb_db).await {
+        log::warn!("Failed to bootstrap MongoDB schema validators: {}", e);
+    }
+
+    let redis_client = connect_with_retry(&config, "Redis", || connect_redis(&config))
+        .await
+        .expect("Failed to create Redis client");
 
     log::info!("Database connections established");
 
-    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
-    let kafka_consumer_user = KafkaConsumer::new(&config, vec!["user_events".to_string()])
-        .expect("Failed to create Kafka consumer");
+    let kafka_producer = connect_with_retry(&config, "Kafka", || async {
+        let producer = KafkaProducer::new(&config)?;
+        producer.check_brokers().await?;
+        Ok(producer)
+    })
+    .await
+    .expect("Failed to create Kafka producer");
+
+    let search_backend = create_search_backend(&config, &mysql_pool);
+
+    // Before the consumers start, so a strict failure exits without consuming anything
+    if config.startup.self_test {
+        let checks = run_self_test(
+            &mysql_pool,
+            &mongodb_db,
+            &redis_client,
+            &config,
+            search_backend.clone(),
+        )
+        .await;
+        let failed = checks.iter().filter(|c| c.failure.is_some()).count();
+        if failed == 0 {
+            log::info!("Self-test passed for {} events", checks.len());
+        } else if config.startup.self_test_strict {
+            log::error!("Self-test failed for {} events, exiting", failed);
+            std::process::exit(1);
+        } else {
+            log::warn!("Self-test failed for {} events", failed);
+        }
+    }
+    let kafka_consumer_user = connect_with_retry(&config, "Kafka consumer", || {
+        ready(KafkaConsumer::new(
+            &config,
+            &redis_client,
+            vec!["user_events".to_string()],
+        ))
+    })
+    .await
+    .expect("Failed to create Kafka consumer");
 
     kafka_consumer_user
         .subscribe()
         .await
         .expect("Failed to subscribe to Kafka topics");
 
+    let mongodb_db_user = mongodb_db.clone();
+    let kafka_producer_user = kafka_producer.clone();
     kafka_consumer_user
-        .start_consuming(|topic, key, payload| match topic.as_str() {
+        .start_consuming(move |topic, key, payload| match topic.as_str() {
             "user_events" => {
-                handle_user_created_event(topic, key, payload);
+                let mongo_db = mongodb_db_user.clone();
+                let producer = kafka_producer_user.clone();
+                tokio::spawn(async move {
+                    let raw = String::from_utf8_lossy(&payload).into_owned();
+                    if let Err(e) =
+                        handle_user_created_event(topic.clone(), key.clone(), payload, &mongo_db)
+                            .await
+                    {
+                        forward_dead_letter(&producer, &topic, &key, &raw, &e).await;
+                    }
+                });
             }
             _ => {
                 log::warn!("Unknown topic: {}", topic);
@@ -67,8 +142,18 @@ async fn main() -> std::io::Result<()> {
     let mysql_pool_clone = mysql_pool.clone();
     let mongodb_db_clone = mongodb_db.clone();
     let redis_client_clone = redis_client.clone();
-    let kafka_consumer_feed = KafkaConsumer::new(&config, vec!["feed_events".to_string()])
-        .expect("Failed to create Kafka consumer for feed events");
+    let config_clone = config.clone();
+    let search_backend_clone = search_backend.clone();
+    let kafka_producer_feed = kafka_producer.clone();
+    let kafka_consumer_feed = connect_with_retry(&config, "Kafka consumer", || {
+        ready(KafkaConsumer::new(
+            &config,
+            &redis_client,
+            vec!["feed_events".to_string()],
+        ))
+    })
+    .await
+    .expect("Failed to create Kafka consumer for feed events");
 
     kafka_consumer_feed
         .subscribe()
@@ -76,7 +161,7 @@ async fn main() -> std::io::Result<()> {
         .expect("Failed to subscribe to feed events");
 
     kafka_consumer_feed
-        .start_consuming(move |topic, _key, payload| {
+        .start_consuming(move |topic, key, payload| {
             if topic == "feed_events" {
                 match std::str::from_utf8(&payload) {
                     Ok(payload_str) => {
@@ -91,46 +176,42 @@ async fn main() -> std::io::Result<()> {
                                 let mysql_pool = mysql_pool_clone.clone();
                                 let mongo_db = mongodb_db_clone.clone();
                                 let redis_client = redis_client_clone.clone();
+                                let config = config_clone.clone();
+                                let search_backend = search_backend_clone.clone();
 
                                 tokio::spawn(async move {
-                                    match event_type {
-                                        FeedEventType::Liked => {
-                                            handle_feed_liked_event(
-                                                &event_data,
-                                                &mongo_db,
-                                                &mysql_pool,
-                                                &redis_client,
-                                            )
-                                            .await;
-                                        }
-                                        FeedEventType::Commented => {
-                                            log::info!("Received commented event, processing...");
-                                            handle_feed_commented_event(
-                                                &event_data,
-                                                &mongo_db,
-                                                &mysql_pool,
-                                                &redis_client,
-                                            )
-                                            .await;
-                                            log::info!("Finished processing commented event");
-                                        }
-                                        FeedEventType::Viewed => {
-                                            handle_feed_viewed_event(&event_data, &redis_client)
-                                                .await;
-                                        }
-                                        FeedEventType::Created => {
-                                            log::info!("Feed created event received (no handler)");
-                                        }
-                                    }
+                                    handle_feed_event(
+                                        event_type,
+                                        &event_data,
+                                        &mongo_db,
+                                        &mysql_pool,
+                                        &redis_client,
+                                        &config,
+                                        search_backend.as_ref(),
+                                    )
+                                    .await;
                                 });
                             }
                             Err(e) => {
                                 log::error!("Failed to parse feed event: {:?}", e);
+                                let producer = kafka_producer_feed.clone();
+                                let payload = payload_str.to_string();
+                                let error = format!("invalid feed event: {}", e);
+                                tokio::spawn(async move {
+                                    forward_dead_letter(&producer, &topic, &key, &payload, &error)
+                                        .await;
+                                });
                             }
                         }
                     }
                     Err(e) => {
                         log::error!("Failed to decode feed event: {:?}", e);
+                        let producer = kafka_producer_feed.clone();
+                        let payload = String::from_utf8_lossy(&payload).into_owned();
+                        let error = format!("invalid UTF-8 payload: {}", e);
+                        tokio::spawn(async move {
+                            forward_dead_letter(&producer, &topic, &key, &payload, &error).await;
+                        });
                     }
                 }
             }
@@ -143,37 +224,193 @@ async fn main() -> std::io::Result<()> {
     let mysql_pool_job = mysql_pool.clone();
     let mongodb_db_job = mongodb_db.clone();
     let redis_client_job = redis_client.clone();
+    let config_job = config.clone();
 
     tokio::spawn(async move {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
         loop {
             interval.tick().await;
             log::info!("Calculating top stats...");
-            calculate_top_stats(&mysql_pool_job, &mongodb_db_job, &redis_client_job).await;
+            calculate_top_stats(
+                &mysql_pool_job,
+                &mongodb_db_job,
+                &redis_client_job,
+                &config_job,
+            )
+            .await;
+        }
+    });
+
+    if config.top_stats.trim_interval_seconds > 0 {
+        let redis_client_trim = redis_client.clone();
+        let config_trim = config.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+                config_trim.top_stats.trim_interval_seconds,
+            ));
+            loop {
+                interval.tick().await;
+                trim_rankings(&redis_client_trim, &config_trim).await;
+            }
+        });
+    }
+
+    if config.top_stats.orphan_sweep_interval_seconds > 0 {
+        let mysql_pool_sweep = mysql_pool.clone();
+        let redis_client_sweep = redis_client.clone();
+        let config_sweep = config.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+                config_sweep.top_stats.orphan_sweep_interval_seconds,
+            ));
+            loop {
+                interval.tick().await;
+                sweep_orphaned_rankings(&mysql_pool_sweep, &redis_client_sweep, &config_sweep)
+                    .await;
+            }
+        });
+    }
+
+    let mysql_pool_scheduler = mysql_pool.clone();
+    let redis_client_scheduler = redis_client.clone();
+    let kafka_producer_scheduler = kafka_producer.clone();
+    let config_scheduler = config.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            publish_scheduled_feeds(
+                &mysql_pool_scheduler,
+                &redis_client_scheduler,
+                &kafka_producer_scheduler,
+                &config_scheduler,
+            )
+            .await;
+        }
+    });
+
+    if config.feed_expiry.check_interval_seconds > 0 {
+        let mysql_pool_expiry = mysql_pool.clone();
+        let redis_client_expiry = redis_client.clone();
+        let kafka_producer_expiry = kafka_producer.clone();
+        let config_expiry = config.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+                config_expiry.feed_expiry.check_interval_seconds,
+            ));
+            loop {
+                interval.tick().await;
+                expire_feeds(
+                    &mysql_pool_expiry,
+                    &redis_client_expiry,
+                    &kafka_producer_expiry,
+                    &config_expiry,
+                )
+                .await;
+            }
+        });
+    }
+
+    let mongodb_db_trim = mongodb_db.clone();
+    let config_trim = config.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(
+            config_trim.notifications.trim_interval_seconds,
+        ));
+        loop {
+            interval.tick().await;
+            trim_notifications(&mongodb_db_trim, &config_trim).await;
         }
     });
 
+    if config.digests.enabled {
+        let mongodb_db_digest = mongodb_db.clone();
+        let kafka_producer_digest = kafka_producer.clone();
+        let config_digest = config.clone();
+        tokio::spawn(async move {
+            loop {
+                let next_run = next_digest_run(chrono::Utc::now(), config_digest.digests.hour_utc);
+                let delay = (next_run - chrono::Utc::now()).to_std().unwrap_or_default();
+                tokio::time::sleep(delay).await;
+                build_digests(
+                    &mongodb_db_digest,
+                    &kafka_producer_digest,
+                    &config_digest,
+                    chrono::Utc::now(),
+                )
+                .await;
+            }
+        });
+    }
+
     let mysql_pool_init = mysql_pool.clone();
     let mongodb_db_init = mongodb_db.clone();
     let redis_client_init = redis_client.clone();
+    let config_init = config.clone();
     tokio::spawn(async move {
         tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
         log::info!("Calculating initial top stats...");
-        calculate_top_stats(&mysql_pool_init, &mongodb_db_init, &redis_client_init).await;
+        calculate_top_stats(
+            &mysql_pool_init,
+            &mongodb_db_init,
+            &redis_client_init,
+            &config_init,
+        )
+        .await;
     });
 
+    if config.like_counts.denormalized {
+        let mysql_pool_likes = mysql_pool.clone();
+        let reconcile_interval = config.like_counts.reconcile_interval_seconds;
+        tokio::spawn(async move {
+            // The first tick fires immediately, correcting counters left stale while disabled
+            let mut interval =
+                tokio::time::interval(tokio::time::Duration::from_secs(reconcile_interval));
+            loop {
+                interval.tick().await;
+                run_like_count_reconciliation(&mysql_pool_likes).await;
+            }
+        });
+    }
+
+    let repository = Arc::new(
+        MysqlRepository::new(mysql_pool.clone())
+            .with_like_count_column(config.like_counts.denormalized),
+    );
+
     let openapi = api::ApiDoc::openapi();
 
     let server_host = config.server.host.clone();
     let server_port = config.server.port;
+    let compression = config.server.compression;
+    let query_count_logging = config.server.query_count_logging;
+    let body_logging = config.server.body_logging;
+    let sliding_sessions = config.jwt.expiry_mode == JwtExpiryMode::Sliding;
     HttpServer::new(move || {
         App::new()
-            .wrap(Logger::default())
+            .wrap(Condition::new(
+                query_count_logging,
+                from_fn(api::query_log::log_query_count),
+            ))
+            .wrap(Condition::new(
+                body_logging,
+                from_fn(api::redact::log_bodies),
+            ))
+            .wrap(Condition::new(
+                sliding_sessions,
+                from_fn(api::sliding_session::refresh_sliding_session),
+            ))
+            .wrap(Condition::new(compression, Compress::default()))
+            .wrap(api::redact::access_logger())
+            // Outermost so the access log sees the request ID
+            .wrap(from_fn(api::request_id::propagate_request_id))
             .app_data(web::Data::new(config.clone()))
             .app_data(web::Data::new(mysql_pool.clone()))
             .app_data(web::Data::new(mongodb_db.clone()))
             .app_data(web::Data::new(redis_client.clone()))
             .app_data(web::Data::new(kafka_producer.clone()))
+            .configure(repositories::register(repository.clone()))
+            .app_data(web::Data::from(search_backend.clone()))
             .route(
                 "/api/docs",
                 web::get().to(|| async {
@@ -185,19 +422,59 @@ async fn main() -> std::io::Result<()> {
             .service(
                 SwaggerUi::new("/api/docs/{_:.*}").url("/api-docs/openapi.json", openapi.clone()),
             )
+            .route("/ready", web::get().to(api::health::ready))
+            .route("/metrics", web::get().to(api::health::metrics))
             .service(
                 web::scope("/api")
+                    .wrap(from_fn(api::ip_throttle::throttle_by_ip))
                     .service(
                         web::scope("/auth")
+                            .wrap(api::cors::cors(&config.cors, "auth"))
                             .route("/signup", web::post().to(api::auth::signup))
-                            .route("/login", web::post().to(api::auth::login)),
+                            .route("/login", web::post().to(api::auth::login))
+                            .route("/sessions", web::get().to(api::auth::get_sessions))
+                            .route(
+                                "/sessions/{jti}",
+                                web::delete().to(api::auth::delete_session),
+                            )
+                            .route("/nonce", web::get().to(api::auth::get_nonce))
+                            .route("/password", web::put().to(api::auth::change_password)),
                     )
                     .service(
                         web::scope("/feed")
+                            .wrap(api::cors::cors(&config.cors, "feed"))
                             .route("", web::post().to(api::feed::create_feed))
                             .route("", web::get().to(api::feed::get_feeds))
+                            .route("/preview", web::post().to(api::feed::preview_feed))
+                            .route("/search", web::get().to(api::feed::search_feeds))
+                            .route("/liked-status", web::post().to(api::feed::get_liked_status))
+                            .route("/batch", web::post().to(api::feed::get_feeds_batch))
+                            .route("/unseen-count", web::get().to(api::feed::get_unseen_count))
+                            .route("/categories", web::get().to(api::feed::get_feed_categories))
+                            .route("/{feed_id}", web::get().to(api::feed::get_feed))
+                            .route("/{feed_id}", web::patch().to(api::feed::update_feed))
                             .route("/{feed_id}/like", web::post().to(api::feed::like_feed))
                             .route("/{feed_id}/like", web::delete().to(api::feed::unlike_feed))
+                            .route(
+                                "/{feed_id}/bookmark",
+                                web::post().to(api::feed::bookmark_feed),
+                            )
+                            .route(
+                                "/{feed_id}/bookmark",
+                                web::delete().to(api::feed::unbookmark_feed),
+                            )
+                            .route("/{feed_id}/pin", web::post().to(api::feed::pin_feed))
+                            .route("/{feed_id}/pin", web::delete().to(api::feed::unpin_feed))
+                            .route("/{feed_id}/report", web::post().to(api::feed::report_feed))
+                            .route(
+                                "/{feed_id}/history",
+                                web::get().to(api::feed::get_feed_history),
+                            )
+                            .route("/{feed_id}/react", web::post().to(api::feed::react_feed))
+                            .route(
+                                "/{feed_id}/react/{reaction_type}",
+                                web::delete().to(api::feed::unreact_feed),
+                            )
                             .route(
                                 "/{feed_id}/comment",
                                 web::post().to(api::feed::comment_feed),
@@ -206,18 +483,33 @@ async fn main() -> std::io::Result<()> {
                                 "/{feed_id}/comments",
                                 web::get().to(api::feed::get_comments),
                             )
+                            .route(
+                                "/{feed_id}/comments/export",
+                                web::get().to(api::feed::export_comments),
+                            )
+                            .route(
+                                "/{feed_id}/comment-tree",
+                                web::get().to(api::feed::get_comment_tree),
+                            )
                             .route("/{feed_id}/view", web::post().to(api::feed::view_feed)),
                     )
                     .service(
                         web::scope("/notify")
+                            .wrap(api::cors::cors(&config.cors, "notify"))
                             .route("", web::get().to(api::notify::get_notifications))
+                            .route("/read", web::put().to(api::notify::mark_notifications_read))
                             .route(
                                 "/{notification_id}/read",
                                 web::put().to(api::notify::mark_notification_read),
+                            )
+                            .route(
+                                "/{notification_id}/unread",
+                                web::put().to(api::notify::mark_notification_unread),
                             ),
                     )
                     .service(
                         web::scope("/top")
+                            .wrap(api::cors::cors(&config.cors, "top"))
                             .route("/users-liked", web::get().to(api::top::get_top_users_liked))
                             .route(
                                 "/feeds-commented",
@@ -227,7 +519,63 @@ async fn main() -> std::io::Result<()> {
                                 "/feeds-viewed",
                                 web::get().to(api::top::get_top_feeds_viewed),
                             )
-                            .route("/feeds-liked", web::get().to(api::top::get_top_feeds_liked)),
+                            .route(
+                                "/feeds-liked/stream",
+                                web::get().to(api::top::stream_top_feeds_liked),
+                            )
+                            .route("/feeds-liked", web::get().to(api::top::get_top_feeds_liked))
+                            .route("/trending", web::get().to(api::top::get_trending_feeds)),
+                    )
+                    .service(
+                        web::scope("/admin")
+                            .wrap(from_fn(api::audit::record_admin_action))
+                            .wrap(api::cors::cors(&config.cors, "admin"))
+                            .route("/users", web::get().to(api::admin::list_users))
+                            .route("/reports", web::get().to(api::admin::list_reports))
+                            .route(
+                                "/reports/{report_id}/resolve",
+                                web::post().to(api::admin::resolve_report),
+                            )
+                            .route("/dlq", web::get().to(api::admin::list_dead_letters))
+                            .route(
+                                "/dlq/replay",
+                                web::post().to(api::admin::replay_dead_letter),
+                            )
+                            .route(
+                                "/top/backfill",
+                                web::post().to(api::admin::backfill_rankings),
+                            )
+                            .route("/seed", web::post().to(api::admin::seed_data))
+                            .route(
+                                "/feed/{feed_id}/like/{user_id}",
+                                web::delete().to(api::admin::remove_like),
+                            ),
+                    )
+                    .service(
+                        web::scope("/users")
+                            .wrap(api::cors::cors(&config.cors, "users"))
+                            .route("/me", web::delete().to(api::users::delete_my_account))
+                            .route("/me/purge", web::delete().to(api::users::purge_my_account))
+                            .route("/me/export", web::get().to(api::users::export_my_data))
+                            .route("/me/bookmarks", web::get().to(api::users::get_my_bookmarks))
+                            .route("/me/profile", web::put().to(api::users::update_my_profile))
+                            .route(
+                                "/me/notification-preferences",
+                                web::put().to(api::users::update_my_notification_preferences),
+                            )
+                            .route("/{id}", web::get().to(api::users::get_user))
+                            .route(
+                                "/{id}/activity",
+                                web::get().to(api::users::get_user_activity),
+                            )
+                            .route(
+                                "/{id}/common-likes",
+                                web::get().to(api::users::get_common_likes),
+                            )
+                            .route(
+                                "/{id}/liked-feeds",
+                                web::get().to(api::users::get_user_liked_feeds),
+                            ),
                     ),
             )
     })
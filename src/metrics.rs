@@ -0,0 +1,183 @@
+//! Process-wide metrics, rendered in the Prometheus text format by `GET /metrics`.
+//! Background jobs have no request context, so metrics live in statics.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds (seconds) of the `top_stats_job_duration_seconds` buckets
+const TOP_STATS_DURATION_BUCKETS: [f64; 9] =
+    [1.0, 5.0, 15.0, 30.0, 60.0, 300.0, 900.0, 1800.0, 3600.0];
+
+/// Rankings written by the top stats job, used as the `ranking` label
+pub const TOP_STATS_RANKINGS: [&str; 4] =
+    ["users_liked", "comments", "feeds_viewed", "feeds_liked"];
+
+struct Histogram {
+    /// Observations per bucket (not cumulative); the last slot is `+Inf`
+    buckets: [u64; TOP_STATS_DURATION_BUCKETS.len() + 1],
+    sum: f64,
+    count: u64,
+}
+
+struct TopStatsMetrics {
+    duration: Histogram,
+    ranking_sizes: [u64; TOP_STATS_RANKINGS.len()],
+}
+
+static TOP_STATS: Mutex<TopStatsMetrics> = Mutex::new(TopStatsMetrics {
+    duration: Histogram {
+        buckets: [0; TOP_STATS_DURATION_BUCKETS.len() + 1],
+        sum: 0.0,
+        count: 0,
+    },
+    ranking_sizes: [0; TOP_STATS_RANKINGS.len()],
+});
+
+/// Kafka counters, keyed by topic
+struct KafkaMetrics {
+    send_retries: BTreeMap<String, u64>,
+    dead_letters: BTreeMap<String, u64>,
+    dead_letter_failures: BTreeMap<String, u64>,
+    replays: BTreeMap<String, u64>,
+}
+
+static KAFKA: Mutex<KafkaMetrics> = Mutex::new(KafkaMetrics {
+    send_retries: BTreeMap::new(),
+    dead_letters: BTreeMap::new(),
+    dead_letter_failures: BTreeMap::new(),
+    replays: BTreeMap::new(),
+});
+
+fn increment(counters: &mut BTreeMap<String, u64>, topic: &str) {
+    *counters.entry(topic.to_string()).or_insert(0) += 1;
+}
+
+/// Record a retried send by `send_message_with_retry`
+pub fn record_kafka_send_retry(topic: &str) {
+    increment(&mut KAFKA.lock().unwrap().send_retries, topic);
+}
+
+/// Record a message consumed from `topic` that was dead-lettered, or that should have
+/// been but could not be forwarded (and is lost)
+pub fn record_dead_letter(topic: &str, forwarded: bool) {
+    let mut metrics = KAFKA.lock().unwrap();
+    let counters = if forwarded {
+        &mut metrics.dead_letters
+    } else {
+        &mut metrics.dead_letter_failures
+    };
+    increment(counters, topic);
+}
+
+/// Record a dead letter republished to `topic` by an admin
+pub fn record_dead_letter_replay(topic: &str) {
+    increment(&mut KAFKA.lock().unwrap().replays, topic);
+}
+
+fn render_counter(out: &mut String, name: &str, help: &str, counters: &BTreeMap<String, u64>) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} counter", name);
+    for (topic, count) in counters {
+        let _ = writeln!(out, "{}{{topic=\"{}\"}} {}", name, topic, count);
+    }
+}
+
+/// Record one run of `calculate_top_stats`
+pub fn record_top_stats_duration(duration: Duration) {
+    let seconds = duration.as_secs_f64();
+    let bucket = TOP_STATS_DURATION_BUCKETS
+        .iter()
+        .position(|bound| seconds <= *bound)
+        .unwrap_or(TOP_STATS_DURATION_BUCKETS.len());
+
+    let mut metrics = TOP_STATS.lock().unwrap();
+    metrics.duration.buckets[bucket] += 1;
+    metrics.duration.sum += seconds;
+    metrics.duration.count += 1;
+}
+
+/// Set the number of entries last written to a ranking (one of `TOP_STATS_RANKINGS`)
+pub fn set_top_stats_ranking_size(ranking: &str, size: usize) {
+    if let Some(i) = TOP_STATS_RANKINGS.iter().position(|r| *r == ranking) {
+        TOP_STATS.lock().unwrap().ranking_sizes[i] = size as u64;
+    }
+}
+
+/// All metrics in the Prometheus text exposition format
+pub fn render() -> String {
+    let metrics = TOP_STATS.lock().unwrap();
+    let mut out = String::new();
+
+    out.push_str("# HELP top_stats_job_duration_seconds Duration of the top stats job\n");
+    out.push_str("# TYPE top_stats_job_duration_seconds histogram\n");
+    let mut cumulative = 0;
+    for (bound, count) in TOP_STATS_DURATION_BUCKETS
+        .iter()
+        .zip(metrics.duration.buckets.iter())
+    {
+        cumulative += count;
+        let _ = writeln!(
+            out,
+            "top_stats_job_duration_seconds_bucket{{le=\"{}\"}} {}",
+            bound, cumulative
+        );
+    }
+    let _ = writeln!(
+        out,
+        "top_stats_job_duration_seconds_bucket{{le=\"+Inf\"}} {}",
+        metrics.duration.count
+    );
+    let _ = writeln!(
+        out,
+        "top_stats_job_duration_seconds_sum {}",
+        metrics.duration.sum
+    );
+    let _ = writeln!(
+        out,
+        "top_stats_job_duration_seconds_count {}",
+        metrics.duration.count
+    );
+
+    out.push_str(
+        "# HELP top_stats_ranking_size Entries written to each ranking by the last top stats run\n",
+    );
+    out.push_str("# TYPE top_stats_ranking_size gauge\n");
+    for (ranking, size) in TOP_STATS_RANKINGS.iter().zip(metrics.ranking_sizes.iter()) {
+        let _ = writeln!(
+            out,
+            "top_stats_ranking_size{{ranking=\"{}\"}} {}",
+            ranking, size
+        );
+    }
+    drop(metrics);
+
+    let kafka = KAFKA.lock().unwrap();
+    render_counter(
+        &mut out,
+        "kafka_send_retries_total",
+        "Kafka sends retried after a failed attempt, by topic",
+        &kafka.send_retries,
+    );
+    render_counter(
+        &mut out,
+        "kafka_dead_letters_total",
+        "Consumed messages forwarded to the dead-letter topic, by source topic",
+        &kafka.dead_letters,
+    );
+    render_counter(
+        &mut out,
+        "kafka_dead_letter_failures_total",
+        "Consumed messages that could not be forwarded to the dead-letter topic, by source topic",
+        &kafka.dead_letter_failures,
+    );
+    render_counter(
+        &mut out,
+        "kafka_dead_letter_replays_total",
+        "Dead letters republished by an admin, by target topic",
+        &kafka.replays,
+    );
+
+    out
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use utoipa::ToSchema;
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -14,6 +15,75 @@ pub struct Feed {
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateFeedRequest {
     pub content: String,
+    /// Schedule the feed to go live at this time; omit to publish immediately
+    pub publish_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Stop serving the feed at this time, like a story; omit to keep it
+    #[serde(default)]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// One of `GET /api/feed/categories` (case-insensitive); omit to leave it uncategorized
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// Body of `PATCH /api/feed/{feed_id}`: omitted fields are left unchanged
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateFeedRequest {
+    /// New content, validated like `CreateFeedRequest::content`; must not be `null`
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<String>)]
+    pub content: Option<Option<String>>,
+    /// Reschedule a feed that is not published yet; `null` or a past time publishes it now
+    #[serde(default, deserialize_with = "present")]
+    #[schema(value_type = Option<chrono::DateTime<chrono::Utc>>, nullable)]
+    pub publish_at: Option<Option<chrono::DateTime<chrono::Utc>>>,
+}
+
+impl UpdateFeedRequest {
+    pub fn is_empty(&self) -> bool {
+        self.content.is_none() && self.publish_at.is_none()
+    }
+}
+
+/// Marks a field as present: `Some(None)` for an explicit `null`. With
+/// `#[serde(default)]`, an omitted field stays `None`.
+fn present<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FeedPreviewRequest {
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MentionPreview {
+    pub user_id: i64,
+    pub username: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FeedPreviewResponse {
+    /// Content as it would be stored
+    pub content: String,
+    /// HTML-escaped content with mentions and hashtags marked up
+    pub html: String,
+    /// Mentioned usernames that belong to existing users
+    pub mentions: Vec<MentionPreview>,
+    /// Distinct hashtags, lowercased, without `#`
+    pub hashtags: Vec<String>,
+}
+
+/// Result of `GET /api/feed/unseen-count`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UnseenCountResponse {
+    /// Visible feeds by other users newer than the marker; `0` without a marker
+    pub count: u64,
+    /// Newest feed ID the user has seen on the first timeline page, if recorded
+    pub last_seen_feed_id: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -23,33 +93,191 @@ pub struct FeedResponse {
     pub content: String,
     pub like_count: i64,
     pub comment_count: i64,
+    /// `comment_count` could not be read (MongoDB error) and is reported as 0
+    #[serde(default)]
+    pub counts_degraded: bool,
+    /// Count per reaction type, including `like`; types without reactions are omitted
+    #[serde(default)]
+    pub reactions: HashMap<String, i64>,
+    /// Detected ISO 639-3 language code, e.g. "eng"; `null` when not detected
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Category the author filed the feed under; `null` when uncategorized
+    #[serde(default)]
+    pub category: Option<String>,
     pub is_liked: bool,
+    /// Whether the caller bookmarked the feed; always `false` for anonymous callers
+    #[serde(default)]
+    pub is_bookmarked: bool,
+    /// Pinned by the author; pinned feeds lead `GET /api/feed?user_id=`
+    #[serde(default)]
+    pub is_pinned: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the feed stops being served; omitted for feeds that do not expire
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Last change to the feed after it was created; `null` if it never changed
+    #[serde(default)]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Response of `GET /api/feed/categories`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FeedCategoriesResponse {
+    /// Values accepted as `category` when creating a feed, in configured order
+    pub categories: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub struct FeedAuthor {
+    pub id: i64,
+    pub username: String,
+}
+
+/// `FeedResponse` for clients that send `Accept: application/vnd.{vendor}.v2+json`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FeedResponseV2 {
+    #[serde(flatten)]
+    pub feed: FeedResponse,
+    /// `null` if the author's account no longer exists
+    pub author: Option<FeedAuthor>,
+}
+
+/// A feed serialized in the API version the request negotiated
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum VersionedFeed {
+    V1(FeedResponse),
+    V2(FeedResponseV2),
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CommentRequest {
     pub content: String,
+    /// Comment on the same feed this one replies to; omit for a top-level comment
+    #[serde(default)]
+    pub parent_comment_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Comment {
-    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "_id",
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::db::deserialize_string_id"
+    )]
     pub id: Option<String>,
     pub feed_id: i64,
     pub user_id: i64,
     pub content: String,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Last edit; absent until the comment is edited
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "chrono::serde::ts_seconds_option"
+    )]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Comment this one replies to; absent for top-level comments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_comment_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CommentResponse {
     pub id: String,
     pub feed_id: i64,
     pub user_id: i64,
     pub content: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Last edit; `null` if the comment was never edited
+    #[serde(default)]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_comment_id: Option<String>,
+}
+
+/// Page of `GET /api/feed/{feed_id}/comments` in cursor mode
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CommentPage {
+    pub items: Vec<CommentResponse>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// A comment with its replies, oldest first
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CommentTreeNode {
+    pub id: String,
+    pub user_id: i64,
+    pub content: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Last edit; `null` if the comment was never edited
+    #[serde(default)]
+    pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub replies: Vec<CommentTreeNode>,
+}
+
+/// `GET /api/feed/{feed_id}/comment-tree`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CommentTreeResponse {
+    pub feed_id: i64,
+    /// Top-level comments, oldest first
+    pub comments: Vec<CommentTreeNode>,
+    /// Number of comments in `comments`, replies included
+    pub returned: usize,
+    /// Some comments were left out because of the `limit` or `max_depth` cap
+    pub truncated: bool,
+}
+
+/// Emoji reaction to a feed. `Like` is stored in `feed_likes` so the like endpoints,
+/// notifications and rankings keep working; the others are stored in `feed_reactions`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum ReactionType {
+    Like,
+    Love,
+    Laugh,
+    Wow,
+    Sad,
+    Angry,
+}
+
+impl ReactionType {
+    pub const ALL: [ReactionType; 6] = [
+        ReactionType::Like,
+        ReactionType::Love,
+        ReactionType::Laugh,
+        ReactionType::Wow,
+        ReactionType::Sad,
+        ReactionType::Angry,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReactionType::Like => "like",
+            ReactionType::Love => "love",
+            ReactionType::Laugh => "laugh",
+            ReactionType::Wow => "wow",
+            ReactionType::Sad => "sad",
+            ReactionType::Angry => "angry",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.as_str() == value)
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReactRequest {
+    #[serde(rename = "type")]
+    pub reaction_type: ReactionType,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
@@ -61,7 +289,12 @@ pub enum NotificationType {
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Notification {
-    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[serde(
+        rename = "_id",
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::db::deserialize_string_id"
+    )]
     pub id: Option<String>,
     pub user_id: i64,      // User receiving notification
     pub from_user_id: i64, // User performing action
@@ -70,8 +303,15 @@ pub struct Notification {
     pub notification_type: NotificationType,
     pub content: String, // Message displayed to user (e.g., "John liked your feed" or comment content)
     #[serde(with = "chrono::serde::ts_seconds")]
-    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>, // First like of an aggregate notification
     pub is_read: bool,
+    /// Likes folded into an aggregate notification (`NOTIFICATIONS_AGGREGATE_LIKES`);
+    /// absent on notifications for a single action
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor_count: Option<i64>,
+    /// Usernames of the latest likers of an aggregate notification, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_actors: Vec<String>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -85,6 +325,239 @@ pub struct NotificationResponse {
     #[serde(with = "chrono::serde::ts_seconds")]
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub is_read: bool,
+    /// Actions this notification stands for; more than 1 for aggregated likes, where
+    /// `from_user_id` and `from_username` are the latest liker
+    #[schema(example = 1)]
+    pub actor_count: i64,
+    /// Latest likers of an aggregate notification, oldest first (empty otherwise)
+    pub recent_actors: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReportFeedRequest {
+    /// Why the feed breaks the rules, up to `MODERATION_MAX_REASON_CHARS` characters
+    #[schema(example = "Spam")]
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportStatus {
+    /// Waiting for a moderator
+    Open,
+    /// A moderator kept the feed
+    Dismissed,
+    /// A moderator hid the feed
+    Hidden,
+}
+
+impl ReportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportStatus::Open => "open",
+            ReportStatus::Dismissed => "dismissed",
+            ReportStatus::Hidden => "hidden",
+        }
+    }
+}
+
+/// A user's report of a feed, stored in MongoDB collection `feed_reports`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FeedReport {
+    #[serde(
+        rename = "_id",
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::db::deserialize_string_id"
+    )]
+    pub id: Option<String>,
+    pub feed_id: i64,
+    /// Reporter
+    pub user_id: i64,
+    pub reason: String,
+    pub status: ReportStatus,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Admin who resolved the report; absent while it is open
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_by: Option<i64>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "chrono::serde::ts_seconds_option"
+    )]
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FeedReportResponse {
+    pub id: String,
+    pub feed_id: i64,
+    pub user_id: i64,
+    pub reason: String,
+    pub status: ReportStatus,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub resolved_by: Option<i64>,
+    pub resolved_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<FeedReport> for FeedReportResponse {
+    fn from(report: FeedReport) -> Self {
+        FeedReportResponse {
+            id: report.id.unwrap_or_default(),
+            feed_id: report.feed_id,
+            user_id: report.user_id,
+            reason: report.reason,
+            status: report.status,
+            created_at: report.created_at,
+            resolved_by: report.resolved_by,
+            resolved_at: report.resolved_at,
+        }
+    }
+}
+
+/// Content a feed had before one of its edits, stored in MongoDB collection `feed_edits`
+/// (only with `FEED_HISTORY_ENABLED`)
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FeedEdit {
+    #[serde(
+        rename = "_id",
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::db::deserialize_string_id"
+    )]
+    pub id: Option<String>,
+    pub feed_id: i64,
+    /// Author of the feed
+    pub user_id: i64,
+    /// Content replaced by the edit
+    pub content: String,
+    /// Milliseconds, so edits within the same second still sort in order
+    #[serde(with = "chrono::serde::ts_milliseconds")]
+    pub edited_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// One entry of `GET /api/feed/{feed_id}/history`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FeedRevision {
+    pub id: String,
+    /// Content the feed had until `edited_at`
+    pub content: String,
+    pub edited_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<FeedEdit> for FeedRevision {
+    fn from(edit: FeedEdit) -> Self {
+        FeedRevision {
+            id: edit.id.unwrap_or_default(),
+            content: edit.content,
+            edited_at: edit.edited_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportAction {
+    /// Keep the feed and close this report
+    Dismiss,
+    /// Hide the feed and close every open report of it
+    Hide,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResolveReportRequest {
+    pub action: ReportAction,
+}
+
+/// One user's daily summary of unread notifications, stored in `digests`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Digest {
+    /// `{user_id}:{date}`, so rerunning the job on the same day replaces the digest
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub user_id: i64,
+    /// UTC day the digest was built for, `YYYY-MM-DD`
+    pub date: String,
+    pub unread_total: i64,
+    /// Unread notifications per `notification_type`
+    pub counts: BTreeMap<String, i64>,
+    /// Users behind the most unread notifications, most first
+    pub top_actors: Vec<DigestActor>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub struct DigestActor {
+    pub user_id: i64,
+    pub username: String,
+    /// Unread notifications this user caused
+    pub count: i64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MarkReadRequest {
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LikedStatusRequest {
+    pub feed_ids: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FeedBatchRequest {
+    pub feed_ids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub struct LikedStatus {
+    pub feed_id: i64,
+    pub liked: bool,
+}
+
+/// A requested ID a batch endpoint could not process, and why
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+pub struct BatchFailure {
+    /// The requested ID, as a string for both feed and notification IDs
+    pub id: String,
+    pub error: String,
+}
+
+/// Partial-success response of batch endpoints: every distinct requested ID is in exactly
+/// one of `succeeded` and `failed`, so one bad ID does not fail the whole request
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(
+    FeedBatchResult = BatchResult<FeedResponse>,
+    LikedStatusResult = BatchResult<LikedStatus>,
+    MarkReadResult = BatchResult<String>
+)]
+pub struct BatchResult<T> {
+    pub succeeded: Vec<T>,
+    pub failed: Vec<BatchFailure>,
+}
+
+impl<T> BatchResult<T> {
+    pub fn new() -> Self {
+        Self {
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+        }
+    }
+
+    pub fn fail(&mut self, id: impl ToString, error: &str) {
+        self.failed.push(BatchFailure {
+            id: id.to_string(),
+            error: error.to_string(),
+        });
+    }
+}
+
+impl<T> Default for BatchResult<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -95,6 +568,13 @@ pub struct FeedView {
     pub user_id: i64,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub viewed_at: chrono::DateTime<chrono::Utc>,
+    /// Views this document stands for when writes are sampled (`1 / sample rate`)
+    #[serde(default = "default_view_weight")]
+    pub weight: f64,
+}
+
+fn default_view_weight() -> f64 {
+    1.0
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -112,3 +592,37 @@ pub struct TopFeed {
     pub content: String,
     pub count: i64,
 }
+
+/// One `top:*` sorted set entry as stored in Redis
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RawScore {
+    /// Feed or user ID, as the raw member string
+    pub member: String,
+    pub score: f64,
+}
+
+/// `debug` object of the top endpoints with `?debug=true` (admins only)
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct TopDebug {
+    /// Redis entries of the requested ranks, in rank order. Empty for ranks computed
+    /// from the databases (deep pages, `user_id` filters, trending)
+    pub raw_scores: Vec<RawScore>,
+    /// Members of `raw_scores` left out of the response: not a valid ID, or the feed or
+    /// user no longer exists (orphaned ranking entries)
+    pub dropped: Vec<String>,
+}
+
+/// How one feed's `GET /api/top/feeds-liked` score compares between Redis and MySQL
+/// (`?explain=true`, admins only)
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ScoreBreakdown {
+    pub feed_id: i64,
+    /// Score in `top:feeds_liked`, `None` if the feed is not in the sorted set
+    pub redis_score: Option<f64>,
+    /// Likes counted live in `feed_likes` within the ranking window
+    pub mysql_count: i64,
+    /// The same likes weighted by `TOP_STATS_LIKE_WEIGHT_*`, i.e. the expected score
+    pub mysql_score: f64,
+    /// `redis_score - mysql_score`, `None` without a Redis score
+    pub delta: Option<f64>,
+}
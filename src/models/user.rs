@@ -1,4 +1,6 @@
+use super::{CommentResponse, NotificationType};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use utoipa::ToSchema;
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -18,12 +20,40 @@ pub struct SignupRequest {
     pub email: String,
     pub username: String,
     pub password: String,
+    /// Client profile from `JWT_CLIENT_TYPES`, e.g. "mobile" (default `JWT_DEFAULT_CLIENT_TYPE`)
+    #[serde(default)]
+    pub client_type: Option<String>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub email: String,
     pub password: String,
+    /// Client profile from `JWT_CLIENT_TYPES`, e.g. "mobile" (default `JWT_DEFAULT_CLIENT_TYPE`)
+    #[serde(default)]
+    pub client_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+    /// One-time nonce from `GET /api/auth/nonce` (required when `AUTH_REQUIRE_NONCE` is set)
+    pub nonce: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+    /// One-time nonce from `GET /api/auth/nonce` (required when `AUTH_REQUIRE_NONCE` is set)
+    pub nonce: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NonceResponse {
+    pub nonce: String,
+    /// Seconds until the nonce expires
+    pub expires_in: u64,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
@@ -48,3 +78,281 @@ impl From<User> for UserResponse {
         }
     }
 }
+
+/// User as listed in the admin console
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AdminUserResponse {
+    pub id: i64,
+    pub email: String,
+    pub username: String,
+    pub is_admin: bool,
+    pub is_verified: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Demo data to create with `POST /api/admin/seed`
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SeedRequest {
+    /// Users to create (1-100)
+    #[schema(example = 10)]
+    pub users: u64,
+    /// Feeds per user (0-20)
+    #[schema(example = 3)]
+    pub feeds_per_user: u64,
+    /// Each feed gets a random number of likes up to this, from distinct seeded users (default 5)
+    #[serde(default)]
+    pub max_likes_per_feed: Option<u64>,
+    /// Each feed gets a random number of comments up to this (default 3, max 20)
+    #[serde(default)]
+    pub max_comments_per_feed: Option<u64>,
+    /// Each feed gets a random number of views up to this (default 20, max 100)
+    #[serde(default)]
+    pub max_views_per_feed: Option<u64>,
+}
+
+/// A message read from a `{topic}_dlq` dead-letter topic
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeadLetterMessage {
+    /// Dead-letter topic, e.g. `feed_events_dlq`
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    pub key: Option<String>,
+    /// Payload as text (invalid UTF-8 replaced); empty for dead-lettered empty messages
+    pub payload: String,
+    /// Topic the message was consumed from (`dlq-original-topic` header)
+    pub original_topic: Option<String>,
+    /// Why it was dead-lettered (`dlq-error` header), e.g. `empty payload`
+    pub error: Option<String>,
+    /// When it was written to the dead-letter topic
+    pub timestamp: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReplayDeadLetterRequest {
+    /// Source topic whose dead letters to read (default: `feed_events`)
+    #[schema(example = "feed_events")]
+    pub topic: Option<String>,
+    pub partition: i32,
+    pub offset: i64,
+    /// Payload to publish instead of the stored one, to fix a poison message
+    pub payload: Option<String>,
+}
+
+/// What `POST /api/admin/seed` created
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SeedResponse {
+    pub user_ids: Vec<i64>,
+    pub feed_ids: Vec<i64>,
+    pub comment_ids: Vec<String>,
+    pub likes: u64,
+    pub views: u64,
+    /// Password shared by all seeded users, so they can log in
+    pub password: String,
+}
+
+/// One request to `/api/admin`, stored in MongoDB collection `audit_log`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AuditLogEntry {
+    #[serde(
+        rename = "_id",
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::db::deserialize_string_id"
+    )]
+    pub id: Option<String>,
+    /// Caller; an admin unless the request was rejected with `403`
+    pub user_id: i64,
+    /// Method and route pattern, e.g. `DELETE /api/admin/feed/{feed_id}/like/{user_id}`
+    pub action: String,
+    /// Path parameters of the route, e.g. `{"feed_id": "1", "user_id": "2"}`
+    pub target: BTreeMap<String, String>,
+    /// HTTP status of the response
+    pub status: u16,
+    /// Whether the status was a `2xx`
+    pub success: bool,
+    /// `RequestId` of the request, to find it in the access log
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SessionResponse {
+    pub jti: String,
+    pub issued_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub user_agent: Option<String>,
+    /// Whether this session belongs to the token used for the request
+    pub is_current: bool,
+}
+
+/// Extended user attributes stored in MongoDB collection `user_profiles`, keyed by user_id
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserProfile {
+    #[serde(rename = "_id")]
+    pub user_id: i64,
+    pub bio: String,
+    pub avatar_url: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub joined_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub notification_preferences: NotificationPreferences,
+    /// Preferred language for notification text (lowercase tag, e.g. "fr"); English if unset
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Whether anyone may list the feeds this user liked (`GET /api/users/{id}/liked-feeds`)
+    #[serde(default = "enabled")]
+    pub likes_public: bool,
+}
+
+fn enabled() -> bool {
+    true
+}
+
+/// Which notification types a user receives; every type is enabled unless turned off
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NotificationPreferences {
+    #[serde(default = "enabled")]
+    pub likes_enabled: bool,
+    #[serde(default = "enabled")]
+    pub comments_enabled: bool,
+    /// Applies to mention notifications once they are produced
+    #[serde(default = "enabled")]
+    pub mentions_enabled: bool,
+}
+
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            likes_enabled: true,
+            comments_enabled: true,
+            mentions_enabled: true,
+        }
+    }
+}
+
+impl NotificationPreferences {
+    pub fn allows(&self, notification_type: &NotificationType) -> bool {
+        match notification_type {
+            NotificationType::Like => self.likes_enabled,
+            NotificationType::Comment => self.comments_enabled,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateNotificationPreferencesRequest {
+    /// Omit a field to leave it unchanged
+    pub likes_enabled: Option<bool>,
+    pub comments_enabled: Option<bool>,
+    pub mentions_enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateProfileRequest {
+    /// New bio; omit to leave unchanged
+    pub bio: Option<String>,
+    /// New avatar URL (http/https); omit to leave unchanged, empty string to clear
+    pub avatar_url: Option<String>,
+    /// Language tag for notifications, e.g. "fr" or "pt-BR"; omit to leave unchanged,
+    /// empty string to clear
+    pub language: Option<String>,
+    /// Let others list the feeds you liked; omit to leave unchanged
+    pub likes_public: Option<bool>,
+}
+
+/// Public user info combined with the `user_profiles` document
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserProfileResponse {
+    pub id: i64,
+    pub username: String,
+    pub bio: String,
+    pub avatar_url: Option<String>,
+    pub joined_at: chrono::DateTime<chrono::Utc>,
+    /// Language notifications are written in; `null` means English
+    pub language: Option<String>,
+    /// Whether others may list the feeds this user liked
+    pub likes_public: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityType {
+    Feed,
+    Like,
+    Comment,
+}
+
+/// One entry in a user's activity feed
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ActivityItem {
+    pub activity_type: ActivityType,
+    pub feed_id: i64,
+    /// Set for comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment_id: Option<String>,
+    /// Feed or comment text (not set for likes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ActivityResponse {
+    pub items: Vec<ActivityItem>,
+    /// Pass as `before` to fetch the next page; absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_before: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Everything stored about a user, for `GET /api/users/me/export`.
+/// Other users' identities are left out (e.g. who liked or commented on the user's feeds).
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserDataExport {
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    pub account: AdminUserResponse,
+    /// Absent if the profile was never created
+    pub profile: Option<ExportedProfile>,
+    pub feeds: Vec<ExportedFeed>,
+    pub likes: Vec<ExportedLike>,
+    pub comments: Vec<CommentResponse>,
+    pub notifications: Vec<ExportedNotification>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ExportedProfile {
+    pub bio: String,
+    pub avatar_url: Option<String>,
+    pub joined_at: chrono::DateTime<chrono::Utc>,
+    pub notification_preferences: NotificationPreferences,
+    pub language: Option<String>,
+    pub likes_public: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ExportedFeed {
+    pub id: i64,
+    pub content: String,
+    pub publish_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub is_published: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ExportedLike {
+    pub feed_id: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Notification received by the user, without the acting user or their comment text
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ExportedNotification {
+    pub id: String,
+    pub notification_type: NotificationType,
+    pub feed_id: i64,
+    pub is_read: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,598 @@
+use super::{
+    BookmarkRepository, FeedChanges, FeedRepository, LikeRepository, ReactionRepository,
+    UserRepository,
+};
+use crate::entities::{feed, feed_like, user};
+use crate::models::ReactionType;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::DbErr;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+#[derive(Default)]
+struct State {
+    users: Vec<user::Model>,
+    feeds: Vec<feed::Model>,
+    likes: Vec<feed_like::Model>,
+    reactions: Vec<(i64, i64, ReactionType)>,
+    /// `(feed_id, user_id)`, in the order they were bookmarked
+    bookmarks: Vec<(i64, i64)>,
+    next_id: i64,
+}
+
+impl State {
+    fn next_id(&mut self) -> i64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Keeps `feed::Model::like_count` in step with `likes`, as MySQL does with
+    /// `LIKE_COUNT_DENORMALIZED`
+    fn adjust_like_count(&mut self, feed_id: i64, delta: i64) {
+        if let Some(feed) = self.feeds.iter_mut().find(|f| f.id == feed_id) {
+            feed.like_count += delta;
+        }
+    }
+
+    /// Visible feeds liked by the user, most recently liked first
+    fn liked_by(&self, user_id: i64) -> Vec<feed::Model> {
+        let now = Utc::now();
+        self.likes
+            .iter()
+            .rev()
+            .filter(|l| l.user_id == user_id)
+            .filter_map(|l| self.feeds.iter().find(|f| f.id == l.feed_id))
+            .filter(|f| is_visible(f, now))
+            .cloned()
+            .collect()
+    }
+
+    /// Visible feeds bookmarked by the user, most recently bookmarked first
+    fn bookmarked_by(&self, user_id: i64) -> Vec<feed::Model> {
+        let now = Utc::now();
+        self.bookmarks
+            .iter()
+            .rev()
+            .filter(|(_, u)| *u == user_id)
+            .filter_map(|(feed_id, _)| self.feeds.iter().find(|f| f.id == *feed_id))
+            .filter(|f| is_visible(f, now))
+            .cloned()
+            .collect()
+    }
+}
+
+/// In-memory repositories for tests (`test-support` feature). Mirrors the MySQL
+/// constraints handlers rely on: unique emails/usernames, one like and one bookmark per
+/// user and feed, and one reaction of each type per user and feed.
+#[derive(Default)]
+pub struct InMemoryRepository {
+    state: Mutex<State>,
+}
+
+impl InMemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Seeds a user directly, bypassing signup
+    pub fn insert_user(&self, email: &str, username: &str, password_hash: &str) -> user::Model {
+        let mut state = self.state.lock().unwrap();
+        let now = Utc::now();
+        let model = user::Model {
+            id: state.next_id(),
+            email: email.to_string(),
+            username: username.to_string(),
+            password_hash: password_hash.to_string(),
+            is_admin: false,
+            is_verified: false,
+            created_at: now,
+            updated_at: now,
+        };
+        state.users.push(model.clone());
+        model
+    }
+
+    /// Seeds a published feed directly
+    pub fn insert_feed(&self, user_id: i64, content: &str) -> feed::Model {
+        let mut state = self.state.lock().unwrap();
+        let now = Utc::now();
+        let model = feed::Model {
+            id: state.next_id(),
+            user_id,
+            content: content.to_string(),
+            language: None,
+            category: None,
+            publish_at: None,
+            is_published: true,
+            like_count: 0,
+            pinned_at: None,
+            hidden_at: None,
+            expires_at: None,
+            is_expired: false,
+            created_at: now,
+            updated_at: now,
+        };
+        state.feeds.push(model.clone());
+        model
+    }
+
+    /// Seeds a like directly
+    pub fn insert_like(&self, feed_id: i64, user_id: i64) {
+        let mut state = self.state.lock().unwrap();
+        let model = feed_like::Model {
+            id: state.next_id(),
+            feed_id,
+            user_id,
+            created_at: Utc::now(),
+        };
+        state.likes.push(model);
+        state.adjust_like_count(feed_id, 1);
+    }
+
+    /// Visible feeds with a like from each of the two users, in insertion order
+    fn liked_by_both(&self, user_id: i64, other_user_id: i64) -> Vec<feed::Model> {
+        let state = self.state.lock().unwrap();
+        let now = Utc::now();
+        let liked_by = |user_id: i64| {
+            state
+                .likes
+                .iter()
+                .filter(move |l| l.user_id == user_id)
+                .map(|l| l.feed_id)
+                .collect::<HashSet<i64>>()
+        };
+        let (liked, other_liked) = (liked_by(user_id), liked_by(other_user_id));
+        state
+            .feeds
+            .iter()
+            .filter(|f| is_visible(f, now) && liked.contains(&f.id) && other_liked.contains(&f.id))
+            .cloned()
+            .collect()
+    }
+}
+
+fn is_visible(feed: &feed::Model, now: DateTime<Utc>) -> bool {
+    feed.publish_at.is_none_or(|publish_at| publish_at <= now)
+        && feed.hidden_at.is_none()
+        && !feed.has_expired(now)
+}
+
+fn in_language(feed: &feed::Model, language: Option<&str>) -> bool {
+    language.is_none_or(|language| feed.language.as_deref() == Some(language))
+}
+
+fn in_category(feed: &feed::Model, category: Option<&str>) -> bool {
+    category.is_none_or(|category| feed.category.as_deref() == Some(category))
+}
+
+#[async_trait]
+impl UserRepository for InMemoryRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<user::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state.users.iter().find(|u| u.id == id).cloned())
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<user::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state.users.iter().find(|u| u.email == email).cloned())
+    }
+
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<user::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .users
+            .iter()
+            .filter(|u| ids.contains(&u.id))
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_usernames(&self, usernames: &[String]) -> Result<Vec<user::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .users
+            .iter()
+            .filter(|u| {
+                usernames
+                    .iter()
+                    .any(|n| n.eq_ignore_ascii_case(&u.username))
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn find_by_email_or_username(
+        &self,
+        email: &str,
+        username: &str,
+    ) -> Result<Option<user::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .users
+            .iter()
+            .find(|u| u.email == email || u.username == username)
+            .cloned())
+    }
+
+    async fn create(
+        &self,
+        email: &str,
+        username: &str,
+        password_hash: &str,
+    ) -> Result<user::Model, DbErr> {
+        if self
+            .find_by_email_or_username(email, username)
+            .await?
+            .is_some()
+        {
+            return Err(DbErr::Custom("Duplicate entry for users".to_string()));
+        }
+        Ok(self.insert_user(email, username, password_hash))
+    }
+}
+
+#[async_trait]
+impl FeedRepository for InMemoryRepository {
+    async fn create(
+        &self,
+        user_id: i64,
+        content: &str,
+        language: Option<&str>,
+        category: Option<&str>,
+        publish_at: Option<DateTime<Utc>>,
+        expires_at: Option<DateTime<Utc>>,
+        is_published: bool,
+    ) -> Result<feed::Model, DbErr> {
+        let mut state = self.state.lock().unwrap();
+        let now = Utc::now();
+        let model = feed::Model {
+            id: state.next_id(),
+            user_id,
+            content: content.to_string(),
+            language: language.map(str::to_string),
+            category: category.map(str::to_string),
+            publish_at,
+            is_published,
+            like_count: 0,
+            pinned_at: None,
+            hidden_at: None,
+            expires_at,
+            is_expired: false,
+            created_at: now,
+            updated_at: now,
+        };
+        state.feeds.push(model.clone());
+        Ok(model)
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<feed::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state.feeds.iter().find(|f| f.id == id).cloned())
+    }
+
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<feed::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(ids
+            .iter()
+            .filter_map(|id| state.feeds.iter().find(|f| f.id == *id).cloned())
+            .collect())
+    }
+
+    async fn list_visible(
+        &self,
+        language: Option<&str>,
+        category: Option<&str>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        let now = Utc::now();
+        // Ids grow with insertion order, so they break created_at ties like MySQL would
+        let mut feeds: Vec<feed::Model> = state
+            .feeds
+            .iter()
+            .filter(|f| is_visible(f, now) && in_language(f, language) && in_category(f, category))
+            .cloned()
+            .collect();
+        feeds.sort_by_key(|f| std::cmp::Reverse((f.created_at, f.id)));
+        Ok(feeds
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn count_visible(
+        &self,
+        language: Option<&str>,
+        category: Option<&str>,
+    ) -> Result<u64, DbErr> {
+        let state = self.state.lock().unwrap();
+        let now = Utc::now();
+        Ok(state
+            .feeds
+            .iter()
+            .filter(|f| is_visible(f, now) && in_language(f, language) && in_category(f, category))
+            .count() as u64)
+    }
+
+    async fn list_visible_by_user(
+        &self,
+        user_id: i64,
+        language: Option<&str>,
+        category: Option<&str>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        let now = Utc::now();
+        let mut feeds: Vec<feed::Model> = state
+            .feeds
+            .iter()
+            .filter(|f| {
+                f.user_id == user_id
+                    && is_visible(f, now)
+                    && in_language(f, language)
+                    && in_category(f, category)
+            })
+            .cloned()
+            .collect();
+        // `None` sorts below any pin time, matching MySQL's NULLs-last descending order
+        feeds.sort_by_key(|f| std::cmp::Reverse((f.pinned_at, f.created_at, f.id)));
+        Ok(feeds
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn count_visible_by_user(
+        &self,
+        user_id: i64,
+        language: Option<&str>,
+        category: Option<&str>,
+    ) -> Result<u64, DbErr> {
+        let state = self.state.lock().unwrap();
+        let now = Utc::now();
+        Ok(state
+            .feeds
+            .iter()
+            .filter(|f| {
+                f.user_id == user_id
+                    && is_visible(f, now)
+                    && in_language(f, language)
+                    && in_category(f, category)
+            })
+            .count() as u64)
+    }
+
+    async fn count_visible_after(&self, feed_id: i64, excluded_user_id: i64) -> Result<u64, DbErr> {
+        let state = self.state.lock().unwrap();
+        let now = Utc::now();
+        Ok(state
+            .feeds
+            .iter()
+            .filter(|f| f.id > feed_id && f.user_id != excluded_user_id && is_visible(f, now))
+            .count() as u64)
+    }
+
+    async fn count_pinned(&self, user_id: i64) -> Result<u64, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .feeds
+            .iter()
+            .filter(|f| f.user_id == user_id && f.pinned_at.is_some())
+            .count() as u64)
+    }
+
+    async fn update(&self, id: i64, changes: FeedChanges) -> Result<feed::Model, DbErr> {
+        let mut state = self.state.lock().unwrap();
+        let feed = state
+            .feeds
+            .iter_mut()
+            .find(|f| f.id == id)
+            .ok_or_else(|| DbErr::RecordNotFound(format!("feed {}", id)))?;
+        if let Some(content) = changes.content {
+            feed.content = content;
+        }
+        if let Some(language) = changes.language {
+            feed.language = language;
+        }
+        if let Some(publish_at) = changes.publish_at {
+            feed.publish_at = publish_at;
+        }
+        if let Some(is_published) = changes.is_published {
+            feed.is_published = is_published;
+        }
+        if let Some(pinned_at) = changes.pinned_at {
+            feed.pinned_at = pinned_at;
+        }
+        if let Some(hidden_at) = changes.hidden_at {
+            feed.hidden_at = hidden_at;
+        }
+        if let Some(expires_at) = changes.expires_at {
+            feed.expires_at = expires_at;
+        }
+        feed.updated_at = Utc::now();
+        Ok(feed.clone())
+    }
+
+    async fn list_liked_by_both(
+        &self,
+        user_id: i64,
+        other_user_id: i64,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr> {
+        let mut feeds = self.liked_by_both(user_id, other_user_id);
+        feeds.sort_by_key(|f| std::cmp::Reverse((f.created_at, f.id)));
+        Ok(feeds
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn count_liked_by_both(&self, user_id: i64, other_user_id: i64) -> Result<u64, DbErr> {
+        Ok(self.liked_by_both(user_id, other_user_id).len() as u64)
+    }
+}
+
+#[async_trait]
+impl LikeRepository for InMemoryRepository {
+    async fn find(&self, feed_id: i64, user_id: i64) -> Result<Option<feed_like::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .likes
+            .iter()
+            .find(|l| l.feed_id == feed_id && l.user_id == user_id)
+            .cloned())
+    }
+
+    async fn insert(&self, feed_id: i64, user_id: i64) -> Result<(), DbErr> {
+        if self.find(feed_id, user_id).await?.is_some() {
+            return Err(DbErr::Custom("Duplicate entry for feed_likes".to_string()));
+        }
+        self.insert_like(feed_id, user_id);
+        Ok(())
+    }
+
+    async fn delete(&self, feed_id: i64, user_id: i64) -> Result<u64, DbErr> {
+        let mut state = self.state.lock().unwrap();
+        let before = state.likes.len();
+        state
+            .likes
+            .retain(|l| !(l.feed_id == feed_id && l.user_id == user_id));
+        let removed = (before - state.likes.len()) as u64;
+        state.adjust_like_count(feed_id, -(removed as i64));
+        Ok(removed)
+    }
+
+    async fn count_for_feed(&self, feed_id: i64) -> Result<u64, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state.likes.iter().filter(|l| l.feed_id == feed_id).count() as u64)
+    }
+
+    async fn liked_feed_ids(&self, user_id: i64, feed_ids: &[i64]) -> Result<Vec<i64>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .likes
+            .iter()
+            .filter(|l| l.user_id == user_id && feed_ids.contains(&l.feed_id))
+            .map(|l| l.feed_id)
+            .collect())
+    }
+
+    async fn list_liked(
+        &self,
+        user_id: i64,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .liked_by(user_id)
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn count_liked(&self, user_id: i64) -> Result<u64, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state.liked_by(user_id).len() as u64)
+    }
+}
+
+#[async_trait]
+impl ReactionRepository for InMemoryRepository {
+    async fn insert(
+        &self,
+        feed_id: i64,
+        user_id: i64,
+        reaction: ReactionType,
+    ) -> Result<(), DbErr> {
+        let mut state = self.state.lock().unwrap();
+        let key = (feed_id, user_id, reaction);
+        if state.reactions.contains(&key) {
+            return Err(DbErr::Custom(
+                "Duplicate entry for feed_reactions".to_string(),
+            ));
+        }
+        state.reactions.push(key);
+        Ok(())
+    }
+
+    async fn delete(
+        &self,
+        feed_id: i64,
+        user_id: i64,
+        reaction: ReactionType,
+    ) -> Result<u64, DbErr> {
+        let mut state = self.state.lock().unwrap();
+        let before = state.reactions.len();
+        state
+            .reactions
+            .retain(|r| *r != (feed_id, user_id, reaction));
+        Ok((before - state.reactions.len()) as u64)
+    }
+
+    async fn counts_for_feed(&self, feed_id: i64) -> Result<HashMap<ReactionType, u64>, DbErr> {
+        let state = self.state.lock().unwrap();
+        let mut counts = HashMap::new();
+        for (_, _, reaction) in state.reactions.iter().filter(|r| r.0 == feed_id) {
+            *counts.entry(*reaction).or_insert(0) += 1;
+        }
+        Ok(counts)
+    }
+}
+
+#[async_trait]
+impl BookmarkRepository for InMemoryRepository {
+    async fn insert(&self, feed_id: i64, user_id: i64) -> Result<(), DbErr> {
+        let mut state = self.state.lock().unwrap();
+        if state.bookmarks.contains(&(feed_id, user_id)) {
+            return Err(DbErr::Custom("Duplicate entry for bookmarks".to_string()));
+        }
+        state.bookmarks.push((feed_id, user_id));
+        Ok(())
+    }
+
+    async fn delete(&self, feed_id: i64, user_id: i64) -> Result<u64, DbErr> {
+        let mut state = self.state.lock().unwrap();
+        let before = state.bookmarks.len();
+        state.bookmarks.retain(|b| *b != (feed_id, user_id));
+        Ok((before - state.bookmarks.len()) as u64)
+    }
+
+    async fn bookmarked_feed_ids(&self, user_id: i64, feed_ids: &[i64]) -> Result<Vec<i64>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .bookmarks
+            .iter()
+            .filter(|(feed_id, u)| *u == user_id && feed_ids.contains(feed_id))
+            .map(|(feed_id, _)| *feed_id)
+            .collect())
+    }
+
+    async fn list_bookmarked(
+        &self,
+        user_id: i64,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .bookmarked_by(user_id)
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn count_bookmarked(&self, user_id: i64) -> Result<u64, DbErr> {
+        let state = self.state.lock().unwrap();
+        Ok(state.bookmarked_by(user_id).len() as u64)
+    }
+}
@@ -0,0 +1,233 @@
+//! Datastore access for users, feeds, likes, reactions and bookmarks behind traits, so handlers can run
+//! against MySQL in production and in-memory fakes in tests.
+
+#[cfg(feature = "test-support")]
+pub mod memory;
+pub mod mysql;
+
+#[cfg(feature = "test-support")]
+pub use memory::InMemoryRepository;
+pub use mysql::MysqlRepository;
+
+use crate::entities::{feed, feed_like, user};
+use crate::models::ReactionType;
+use actix_web::web;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::{DbErr, SqlErr};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Whether an insert failed on a unique index, e.g. a concurrent request won the race past
+/// an existence check. Handlers answer these with `409 Conflict` rather than `500`.
+/// The in-memory fakes report duplicates as `DbErr::Custom("Duplicate entry ...")`.
+pub fn is_unique_violation(err: &DbErr) -> bool {
+    match err.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => true,
+        _ => matches!(err, DbErr::Custom(message) if message.starts_with("Duplicate entry")),
+    }
+}
+
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn find_by_id(&self, id: i64) -> Result<Option<user::Model>, DbErr>;
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<user::Model>, DbErr>;
+
+    /// Users among `ids`; unknown IDs are skipped
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<user::Model>, DbErr>;
+
+    /// Users whose username is in `usernames` (case-insensitive, like MySQL's collation)
+    async fn find_by_usernames(&self, usernames: &[String]) -> Result<Vec<user::Model>, DbErr>;
+
+    /// A user whose email or username matches either value
+    async fn find_by_email_or_username(
+        &self,
+        email: &str,
+        username: &str,
+    ) -> Result<Option<user::Model>, DbErr>;
+
+    async fn create(
+        &self,
+        email: &str,
+        username: &str,
+        password_hash: &str,
+    ) -> Result<user::Model, DbErr>;
+}
+
+/// Columns changed by `FeedRepository::update`; `None` leaves a column untouched
+#[derive(Debug, Clone, Default)]
+pub struct FeedChanges {
+    pub content: Option<String>,
+    pub language: Option<Option<String>>,
+    pub publish_at: Option<Option<DateTime<Utc>>>,
+    pub is_published: Option<bool>,
+    pub pinned_at: Option<Option<DateTime<Utc>>>,
+    pub hidden_at: Option<Option<DateTime<Utc>>>,
+    pub expires_at: Option<Option<DateTime<Utc>>>,
+}
+
+#[async_trait]
+pub trait FeedRepository: Send + Sync {
+    #[allow(clippy::too_many_arguments)]
+    async fn create(
+        &self,
+        user_id: i64,
+        content: &str,
+        language: Option<&str>,
+        category: Option<&str>,
+        publish_at: Option<DateTime<Utc>>,
+        expires_at: Option<DateTime<Utc>>,
+        is_published: bool,
+    ) -> Result<feed::Model, DbErr>;
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<feed::Model>, DbErr>;
+
+    /// Feeds with these ids in the given order; missing ids are skipped
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<feed::Model>, DbErr>;
+
+    /// Feeds without a future `publish_at` that have not expired, newest first, only in
+    /// `language` and `category` if given
+    async fn list_visible(
+        &self,
+        language: Option<&str>,
+        category: Option<&str>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr>;
+
+    async fn count_visible(
+        &self,
+        language: Option<&str>,
+        category: Option<&str>,
+    ) -> Result<u64, DbErr>;
+
+    /// One author's visible feeds: pinned ones first (most recently pinned first), then the
+    /// rest newest first, only in `language` and `category` if given
+    async fn list_visible_by_user(
+        &self,
+        user_id: i64,
+        language: Option<&str>,
+        category: Option<&str>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr>;
+
+    async fn count_visible_by_user(
+        &self,
+        user_id: i64,
+        language: Option<&str>,
+        category: Option<&str>,
+    ) -> Result<u64, DbErr>;
+
+    /// Visible feeds with an ID above `feed_id`, except those by `excluded_user_id`
+    async fn count_visible_after(&self, feed_id: i64, excluded_user_id: i64) -> Result<u64, DbErr>;
+
+    /// Number of the user's feeds that are pinned, scheduled ones included
+    async fn count_pinned(&self, user_id: i64) -> Result<u64, DbErr>;
+
+    /// Writes only the columns set in `changes`; `DbErr::RecordNotFound` if the feed is gone
+    async fn update(&self, id: i64, changes: FeedChanges) -> Result<feed::Model, DbErr>;
+
+    /// Visible feeds liked by both users, newest first
+    async fn list_liked_by_both(
+        &self,
+        user_id: i64,
+        other_user_id: i64,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr>;
+
+    async fn count_liked_by_both(&self, user_id: i64, other_user_id: i64) -> Result<u64, DbErr>;
+}
+
+#[async_trait]
+pub trait LikeRepository: Send + Sync {
+    async fn find(&self, feed_id: i64, user_id: i64) -> Result<Option<feed_like::Model>, DbErr>;
+
+    /// Fails on a duplicate `(feed_id, user_id)` pair, like the unique index in MySQL
+    async fn insert(&self, feed_id: i64, user_id: i64) -> Result<(), DbErr>;
+
+    /// Returns the number of likes removed
+    async fn delete(&self, feed_id: i64, user_id: i64) -> Result<u64, DbErr>;
+
+    async fn count_for_feed(&self, feed_id: i64) -> Result<u64, DbErr>;
+
+    /// Subset of `feed_ids` liked by the user
+    async fn liked_feed_ids(&self, user_id: i64, feed_ids: &[i64]) -> Result<Vec<i64>, DbErr>;
+
+    /// Visible feeds liked by the user, most recently liked first
+    async fn list_liked(
+        &self,
+        user_id: i64,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr>;
+
+    async fn count_liked(&self, user_id: i64) -> Result<u64, DbErr>;
+}
+
+/// Reactions other than `like`, which stay in `feed_likes` (see `LikeRepository`)
+#[async_trait]
+pub trait ReactionRepository: Send + Sync {
+    /// Fails on a duplicate `(feed_id, user_id, reaction)`, like the unique index in MySQL
+    async fn insert(&self, feed_id: i64, user_id: i64, reaction: ReactionType)
+        -> Result<(), DbErr>;
+
+    /// Returns the number of reactions removed
+    async fn delete(
+        &self,
+        feed_id: i64,
+        user_id: i64,
+        reaction: ReactionType,
+    ) -> Result<u64, DbErr>;
+
+    /// Reaction counts of the feed; types without reactions are omitted
+    async fn counts_for_feed(&self, feed_id: i64) -> Result<HashMap<ReactionType, u64>, DbErr>;
+}
+
+#[async_trait]
+pub trait BookmarkRepository: Send + Sync {
+    /// Fails on a duplicate `(user_id, feed_id)` pair, like the unique index in MySQL
+    async fn insert(&self, feed_id: i64, user_id: i64) -> Result<(), DbErr>;
+
+    /// Returns the number of bookmarks removed
+    async fn delete(&self, feed_id: i64, user_id: i64) -> Result<u64, DbErr>;
+
+    /// Subset of `feed_ids` bookmarked by the user
+    async fn bookmarked_feed_ids(&self, user_id: i64, feed_ids: &[i64]) -> Result<Vec<i64>, DbErr>;
+
+    /// Visible feeds bookmarked by the user, most recently bookmarked first
+    async fn list_bookmarked(
+        &self,
+        user_id: i64,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr>;
+
+    async fn count_bookmarked(&self, user_id: i64) -> Result<u64, DbErr>;
+}
+
+/// Registers `repository` as the app's user, feed, like, reaction and bookmark repository
+pub fn register<R>(repository: Arc<R>) -> impl FnOnce(&mut web::ServiceConfig)
+where
+    R: UserRepository
+        + FeedRepository
+        + LikeRepository
+        + ReactionRepository
+        + BookmarkRepository
+        + 'static,
+{
+    move |cfg| {
+        let users: Arc<dyn UserRepository> = repository.clone();
+        let feeds: Arc<dyn FeedRepository> = repository.clone();
+        let likes: Arc<dyn LikeRepository> = repository.clone();
+        let reactions: Arc<dyn ReactionRepository> = repository.clone();
+        let bookmarks: Arc<dyn BookmarkRepository> = repository;
+        cfg.app_data(web::Data::from(users))
+            .app_data(web::Data::from(feeds))
+            .app_data(web::Data::from(likes))
+            .app_data(web::Data::from(reactions))
+            .app_data(web::Data::from(bookmarks));
+    }
+}
@@ -0,0 +1,558 @@
+use super::{
+    BookmarkRepository, FeedChanges, FeedRepository, LikeRepository, ReactionRepository,
+    UserRepository,
+};
+use crate::db::DbPool;
+use crate::entities::{bookmark, feed, feed_like, feed_reaction, user};
+use crate::models::ReactionType;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sea_orm::sea_query::{Expr, Query};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait, JoinType,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Select, TransactionTrait,
+};
+use std::collections::HashMap;
+
+/// SeaORM-backed repositories over the MySQL pool
+#[derive(Clone)]
+pub struct MysqlRepository {
+    pool: DbPool,
+    like_count_column: bool,
+}
+
+impl MysqlRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self {
+            pool,
+            like_count_column: false,
+        }
+    }
+
+    /// Also maintain `feeds.like_count` when likes are added or removed
+    /// (`LIKE_COUNT_DENORMALIZED`), in the same transaction as the `feed_likes` change
+    pub fn with_like_count_column(mut self, enabled: bool) -> Self {
+        self.like_count_column = enabled;
+        self
+    }
+}
+
+/// Add `delta` to the feed's denormalized `like_count`
+async fn adjust_like_count<C: ConnectionTrait>(
+    db: &C,
+    feed_id: i64,
+    delta: i64,
+) -> Result<(), DbErr> {
+    feed::Entity::update_many()
+        .col_expr(
+            feed::Column::LikeCount,
+            Expr::col(feed::Column::LikeCount).add(delta),
+        )
+        .filter(feed::Column::Id.eq(feed_id))
+        .exec(db)
+        .await
+        .map(|_| ())
+}
+
+#[async_trait]
+impl UserRepository for MysqlRepository {
+    async fn find_by_id(&self, id: i64) -> Result<Option<user::Model>, DbErr> {
+        user::Entity::find_by_id(id).one(&self.pool).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<user::Model>, DbErr> {
+        user::Entity::find()
+            .filter(user::Column::Email.eq(email))
+            .one(&self.pool)
+            .await
+    }
+
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<user::Model>, DbErr> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        user::Entity::find()
+            .filter(user::Column::Id.is_in(ids.to_vec()))
+            .all(&self.pool)
+            .await
+    }
+
+    async fn find_by_usernames(&self, usernames: &[String]) -> Result<Vec<user::Model>, DbErr> {
+        if usernames.is_empty() {
+            return Ok(Vec::new());
+        }
+        user::Entity::find()
+            .filter(user::Column::Username.is_in(usernames.to_vec()))
+            .all(&self.pool)
+            .await
+    }
+
+    async fn find_by_email_or_username(
+        &self,
+        email: &str,
+        username: &str,
+    ) -> Result<Option<user::Model>, DbErr> {
+        user::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(user::Column::Email.eq(email))
+                    .add(user::Column::Username.eq(username)),
+            )
+            .one(&self.pool)
+            .await
+    }
+
+    async fn create(
+        &self,
+        email: &str,
+        username: &str,
+        password_hash: &str,
+    ) -> Result<user::Model, DbErr> {
+        let new_user = user::ActiveModel {
+            email: sea_orm::Set(email.to_string()),
+            username: sea_orm::Set(username.to_string()),
+            password_hash: sea_orm::Set(password_hash.to_string()),
+            ..Default::default()
+        };
+        user::Entity::insert(new_user)
+            .exec_with_returning(&self.pool)
+            .await
+    }
+}
+
+/// Feeds without a future `publish_at` that no moderator hid and that have not expired,
+/// only in `language` if given
+fn visible_feeds(language: Option<&str>) -> Select<feed::Entity> {
+    let now = Utc::now();
+    let query = feed::Entity::find()
+        .filter(
+            Condition::any()
+                .add(feed::Column::PublishAt.is_null())
+                .add(feed::Column::PublishAt.lte(now)),
+        )
+        .filter(feed::Column::HiddenAt.is_null())
+        .filter(feed::unexpired(now));
+    match language {
+        Some(language) => query.filter(feed::Column::Language.eq(language)),
+        None => query,
+    }
+}
+
+/// `query` narrowed to feeds in `category` if given
+fn in_category(query: Select<feed::Entity>, category: Option<&str>) -> Select<feed::Entity> {
+    match category {
+        Some(category) => query.filter(feed::Column::Category.eq(category)),
+        None => query,
+    }
+}
+
+/// Visible feeds with a like from each of the two users
+fn feeds_liked_by_both(user_id: i64, other_user_id: i64) -> Select<feed::Entity> {
+    let liked_by = |user_id: i64| {
+        Query::select()
+            .column(feed_like::Column::FeedId)
+            .from(feed_like::Entity)
+            .and_where(feed_like::Column::UserId.eq(user_id))
+            .to_owned()
+    };
+    visible_feeds(None)
+        .filter(feed::Column::Id.in_subquery(liked_by(user_id)))
+        .filter(feed::Column::Id.in_subquery(liked_by(other_user_id)))
+}
+
+/// Visible feeds liked by the user, joined to their like rows
+fn feeds_liked_by(user_id: i64) -> Select<feed::Entity> {
+    visible_feeds(None)
+        .join_rev(JoinType::InnerJoin, feed_like::Relation::Feed.def())
+        .filter(feed_like::Column::UserId.eq(user_id))
+}
+
+/// Visible feeds bookmarked by the user, joined to their bookmark rows
+fn feeds_bookmarked_by(user_id: i64) -> Select<feed::Entity> {
+    visible_feeds(None)
+        .join_rev(JoinType::InnerJoin, bookmark::Relation::Feed.def())
+        .filter(bookmark::Column::UserId.eq(user_id))
+}
+
+#[async_trait]
+impl FeedRepository for MysqlRepository {
+    async fn create(
+        &self,
+        user_id: i64,
+        content: &str,
+        language: Option<&str>,
+        category: Option<&str>,
+        publish_at: Option<DateTime<Utc>>,
+        expires_at: Option<DateTime<Utc>>,
+        is_published: bool,
+    ) -> Result<feed::Model, DbErr> {
+        let new_feed = feed::ActiveModel {
+            user_id: sea_orm::Set(user_id),
+            content: sea_orm::Set(content.to_string()),
+            language: sea_orm::Set(language.map(str::to_string)),
+            category: sea_orm::Set(category.map(str::to_string)),
+            publish_at: sea_orm::Set(publish_at),
+            expires_at: sea_orm::Set(expires_at),
+            is_published: sea_orm::Set(is_published),
+            ..Default::default()
+        };
+        feed::Entity::insert(new_feed)
+            .exec_with_returning(&self.pool)
+            .await
+    }
+
+    async fn find_by_id(&self, id: i64) -> Result<Option<feed::Model>, DbErr> {
+        feed::Entity::find_by_id(id).one(&self.pool).await
+    }
+
+    async fn find_by_ids(&self, ids: &[i64]) -> Result<Vec<feed::Model>, DbErr> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut found: HashMap<i64, feed::Model> = feed::Entity::find()
+            .filter(feed::Column::Id.is_in(ids.iter().copied()))
+            .all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|f| (f.id, f))
+            .collect();
+        Ok(ids.iter().filter_map(|id| found.remove(id)).collect())
+    }
+
+    async fn list_visible(
+        &self,
+        language: Option<&str>,
+        category: Option<&str>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr> {
+        in_category(visible_feeds(language), category)
+            .order_by_desc(feed::Column::CreatedAt)
+            .limit(limit)
+            .offset(offset)
+            .all(&self.pool)
+            .await
+    }
+
+    async fn count_visible(
+        &self,
+        language: Option<&str>,
+        category: Option<&str>,
+    ) -> Result<u64, DbErr> {
+        in_category(visible_feeds(language), category)
+            .count(&self.pool)
+            .await
+    }
+
+    async fn list_visible_by_user(
+        &self,
+        user_id: i64,
+        language: Option<&str>,
+        category: Option<&str>,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr> {
+        // MySQL sorts NULLs last in descending order, so unpinned feeds follow the pinned
+        in_category(visible_feeds(language), category)
+            .filter(feed::Column::UserId.eq(user_id))
+            .order_by_desc(feed::Column::PinnedAt)
+            .order_by_desc(feed::Column::CreatedAt)
+            .limit(limit)
+            .offset(offset)
+            .all(&self.pool)
+            .await
+    }
+
+    async fn count_visible_by_user(
+        &self,
+        user_id: i64,
+        language: Option<&str>,
+        category: Option<&str>,
+    ) -> Result<u64, DbErr> {
+        in_category(visible_feeds(language), category)
+            .filter(feed::Column::UserId.eq(user_id))
+            .count(&self.pool)
+            .await
+    }
+
+    async fn count_visible_after(&self, feed_id: i64, excluded_user_id: i64) -> Result<u64, DbErr> {
+        visible_feeds(None)
+            .filter(feed::Column::Id.gt(feed_id))
+            .filter(feed::Column::UserId.ne(excluded_user_id))
+            .count(&self.pool)
+            .await
+    }
+
+    async fn count_pinned(&self, user_id: i64) -> Result<u64, DbErr> {
+        feed::Entity::find()
+            .filter(feed::Column::UserId.eq(user_id))
+            .filter(feed::Column::PinnedAt.is_not_null())
+            .count(&self.pool)
+            .await
+    }
+
+    async fn list_liked_by_both(
+        &self,
+        user_id: i64,
+        other_user_id: i64,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr> {
+        feeds_liked_by_both(user_id, other_user_id)
+            .order_by_desc(feed::Column::CreatedAt)
+            .limit(limit)
+            .offset(offset)
+            .all(&self.pool)
+            .await
+    }
+
+    async fn count_liked_by_both(&self, user_id: i64, other_user_id: i64) -> Result<u64, DbErr> {
+        feeds_liked_by_both(user_id, other_user_id)
+            .count(&self.pool)
+            .await
+    }
+
+    async fn update(&self, id: i64, changes: FeedChanges) -> Result<feed::Model, DbErr> {
+        // Unset columns stay `NotSet`, so the UPDATE only names the changed ones
+        let mut active = feed::ActiveModel {
+            id: sea_orm::Unchanged(id),
+            ..Default::default()
+        };
+        if let Some(content) = changes.content {
+            active.content = sea_orm::Set(content);
+        }
+        if let Some(language) = changes.language {
+            active.language = sea_orm::Set(language);
+        }
+        if let Some(publish_at) = changes.publish_at {
+            active.publish_at = sea_orm::Set(publish_at);
+        }
+        if let Some(is_published) = changes.is_published {
+            active.is_published = sea_orm::Set(is_published);
+        }
+        if let Some(pinned_at) = changes.pinned_at {
+            active.pinned_at = sea_orm::Set(pinned_at);
+        }
+        if let Some(hidden_at) = changes.hidden_at {
+            active.hidden_at = sea_orm::Set(hidden_at);
+        }
+        if let Some(expires_at) = changes.expires_at {
+            active.expires_at = sea_orm::Set(expires_at);
+        }
+        active.update(&self.pool).await
+    }
+}
+
+#[async_trait]
+impl LikeRepository for MysqlRepository {
+    async fn find(&self, feed_id: i64, user_id: i64) -> Result<Option<feed_like::Model>, DbErr> {
+        feed_like::Entity::find()
+            .filter(
+                Condition::all()
+                    .add(feed_like::Column::FeedId.eq(feed_id))
+                    .add(feed_like::Column::UserId.eq(user_id)),
+            )
+            .one(&self.pool)
+            .await
+    }
+
+    async fn insert(&self, feed_id: i64, user_id: i64) -> Result<(), DbErr> {
+        let new_like = feed_like::ActiveModel {
+            feed_id: sea_orm::Set(feed_id),
+            user_id: sea_orm::Set(user_id),
+            ..Default::default()
+        };
+        if !self.like_count_column {
+            return feed_like::Entity::insert(new_like)
+                .exec(&self.pool)
+                .await
+                .map(|_| ());
+        }
+        let txn = self.pool.begin().await?;
+        feed_like::Entity::insert(new_like).exec(&txn).await?;
+        adjust_like_count(&txn, feed_id, 1).await?;
+        txn.commit().await
+    }
+
+    async fn delete(&self, feed_id: i64, user_id: i64) -> Result<u64, DbErr> {
+        let delete = feed_like::Entity::delete_many().filter(
+            Condition::all()
+                .add(feed_like::Column::FeedId.eq(feed_id))
+                .add(feed_like::Column::UserId.eq(user_id)),
+        );
+        if !self.like_count_column {
+            return Ok(delete.exec(&self.pool).await?.rows_affected);
+        }
+        let txn = self.pool.begin().await?;
+        let removed = delete.exec(&txn).await?.rows_affected;
+        if removed > 0 {
+            adjust_like_count(&txn, feed_id, -(removed as i64)).await?;
+        }
+        txn.commit().await?;
+        Ok(removed)
+    }
+
+    async fn count_for_feed(&self, feed_id: i64) -> Result<u64, DbErr> {
+        feed_like::Entity::find()
+            .filter(feed_like::Column::FeedId.eq(feed_id))
+            .count(&self.pool)
+            .await
+    }
+
+    async fn liked_feed_ids(&self, user_id: i64, feed_ids: &[i64]) -> Result<Vec<i64>, DbErr> {
+        feed_like::Entity::find()
+            .select_only()
+            .column(feed_like::Column::FeedId)
+            .filter(
+                Condition::all()
+                    .add(feed_like::Column::UserId.eq(user_id))
+                    .add(feed_like::Column::FeedId.is_in(feed_ids.to_vec())),
+            )
+            .into_tuple()
+            .all(&self.pool)
+            .await
+    }
+
+    async fn list_liked(
+        &self,
+        user_id: i64,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr> {
+        // Like id breaks ties between likes made in the same second
+        feeds_liked_by(user_id)
+            .order_by_desc(feed_like::Column::CreatedAt)
+            .order_by_desc(feed_like::Column::Id)
+            .limit(limit)
+            .offset(offset)
+            .all(&self.pool)
+            .await
+    }
+
+    async fn count_liked(&self, user_id: i64) -> Result<u64, DbErr> {
+        feeds_liked_by(user_id).count(&self.pool).await
+    }
+}
+
+fn reaction_condition(feed_id: i64, user_id: i64, reaction: ReactionType) -> Condition {
+    Condition::all()
+        .add(feed_reaction::Column::FeedId.eq(feed_id))
+        .add(feed_reaction::Column::UserId.eq(user_id))
+        .add(feed_reaction::Column::ReactionType.eq(reaction.as_str()))
+}
+
+#[async_trait]
+impl ReactionRepository for MysqlRepository {
+    async fn insert(
+        &self,
+        feed_id: i64,
+        user_id: i64,
+        reaction: ReactionType,
+    ) -> Result<(), DbErr> {
+        let new_reaction = feed_reaction::ActiveModel {
+            feed_id: sea_orm::Set(feed_id),
+            user_id: sea_orm::Set(user_id),
+            reaction_type: sea_orm::Set(reaction.as_str().to_string()),
+            ..Default::default()
+        };
+        feed_reaction::Entity::insert(new_reaction)
+            .exec(&self.pool)
+            .await
+            .map(|_| ())
+    }
+
+    async fn delete(
+        &self,
+        feed_id: i64,
+        user_id: i64,
+        reaction: ReactionType,
+    ) -> Result<u64, DbErr> {
+        let result = feed_reaction::Entity::delete_many()
+            .filter(reaction_condition(feed_id, user_id, reaction))
+            .exec(&self.pool)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    async fn counts_for_feed(&self, feed_id: i64) -> Result<HashMap<ReactionType, u64>, DbErr> {
+        let rows: Vec<(String, i64)> = feed_reaction::Entity::find()
+            .select_only()
+            .column(feed_reaction::Column::ReactionType)
+            .column_as(feed_reaction::Column::Id.count(), "count")
+            .filter(feed_reaction::Column::FeedId.eq(feed_id))
+            .group_by(feed_reaction::Column::ReactionType)
+            .into_tuple()
+            .all(&self.pool)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(reaction, count)| Some((ReactionType::parse(&reaction)?, count as u64)))
+            .collect())
+    }
+}
+
+fn bookmark_condition(feed_id: i64, user_id: i64) -> Condition {
+    Condition::all()
+        .add(bookmark::Column::FeedId.eq(feed_id))
+        .add(bookmark::Column::UserId.eq(user_id))
+}
+
+#[async_trait]
+impl BookmarkRepository for MysqlRepository {
+    async fn insert(&self, feed_id: i64, user_id: i64) -> Result<(), DbErr> {
+        let new_bookmark = bookmark::ActiveModel {
+            feed_id: sea_orm::Set(feed_id),
+            user_id: sea_orm::Set(user_id),
+            ..Default::default()
+        };
+        bookmark::Entity::insert(new_bookmark)
+            .exec(&self.pool)
+            .await
+            .map(|_| ())
+    }
+
+    async fn delete(&self, feed_id: i64, user_id: i64) -> Result<u64, DbErr> {
+        let result = bookmark::Entity::delete_many()
+            .filter(bookmark_condition(feed_id, user_id))
+            .exec(&self.pool)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    async fn bookmarked_feed_ids(&self, user_id: i64, feed_ids: &[i64]) -> Result<Vec<i64>, DbErr> {
+        if feed_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        bookmark::Entity::find()
+            .select_only()
+            .column(bookmark::Column::FeedId)
+            .filter(
+                Condition::all()
+                    .add(bookmark::Column::UserId.eq(user_id))
+                    .add(bookmark::Column::FeedId.is_in(feed_ids.to_vec())),
+            )
+            .into_tuple()
+            .all(&self.pool)
+            .await
+    }
+
+    async fn list_bookmarked(
+        &self,
+        user_id: i64,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<feed::Model>, DbErr> {
+        // Bookmark id breaks ties between bookmarks made in the same second
+        feeds_bookmarked_by(user_id)
+            .order_by_desc(bookmark::Column::CreatedAt)
+            .order_by_desc(bookmark::Column::Id)
+            .limit(limit)
+            .offset(offset)
+            .all(&self.pool)
+            .await
+    }
+
+    async fn count_bookmarked(&self, user_id: i64) -> Result<u64, DbErr> {
+        feeds_bookmarked_by(user_id).count(&self.pool).await
+    }
+}
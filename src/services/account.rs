@@ -0,0 +1,88 @@
+use crate::config::Config;
+use crate::db::{mongo_collection, redis_key, DbPool};
+use crate::entities::{feed, user};
+use crate::jobs::remove_feeds_from_rankings;
+use log::info;
+use mongodb::bson::{doc, Document};
+use mongodb::Database as MongoDatabase;
+use redis::Client as RedisClient;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QuerySelect};
+
+/// Hard-delete a user and everything tied to them across MySQL, MongoDB and Redis.
+///
+/// MongoDB and Redis are cleaned first and the MySQL user row is deleted last (its
+/// feeds and likes go with it via `ON DELETE CASCADE`). If any step fails the user
+/// still exists, so the purge can simply be retried; every step is idempotent.
+pub async fn purge_user_data(
+    mysql_pool: &DbPool,
+    mongo_db: &MongoDatabase,
+    redis_client: &RedisClient,
+    config: &Config,
+    user_id: i64,
+) -> Result<(), anyhow::Error> {
+    // Collected before the cascade removes them; other stores key data by feed id
+    let feed_ids: Vec<i64> = feed::Entity::find()
+        .select_only()
+        .column(feed::Column::Id)
+        .filter(feed::Column::UserId.eq(user_id))
+        .into_tuple()
+        .all(mysql_pool)
+        .await?;
+
+    // Comments and views by the user, any on the user's feeds, and the feeds' edit history
+    let by_user_or_on_feeds = doc! {
+        "$or": [
+            {"user_id": user_id},
+            {"feed_id": {"$in": &feed_ids}},
+        ]
+    };
+    for collection in ["comments", "feed_views", "feed_edits"] {
+        let result = mongo_collection::<Document>(mongo_db, config, collection)
+            .delete_many(by_user_or_on_feeds.clone(), None)
+            .await?;
+        info!(
+            "Purged {} {} documents of user {}",
+            result.deleted_count, collection, user_id
+        );
+    }
+
+    // Notifications received by the user, caused by the user, or about the user's feeds
+    let result = mongo_collection::<Document>(mongo_db, config, "notifications")
+        .delete_many(
+            doc! {
+                "$or": [
+                    {"user_id": user_id},
+                    {"from_user_id": user_id},
+                    {"feed_id": {"$in": &feed_ids}},
+                ]
+            },
+            None,
+        )
+        .await?;
+    info!(
+        "Purged {} notifications of user {}",
+        result.deleted_count, user_id
+    );
+
+    mongo_collection::<Document>(mongo_db, config, "user_profiles")
+        .delete_one(doc! {"_id": user_id}, None)
+        .await?;
+
+    let mut conn = redis_client.get_async_connection().await?;
+    let mut pipe = redis::pipe();
+    pipe.cmd("ZREM")
+        .arg(redis_key(config, "top:users_liked"))
+        .arg(user_id)
+        .ignore();
+    remove_feeds_from_rankings(&mut pipe, config, &feed_ids);
+    pipe.query_async::<_, ()>(&mut conn).await?;
+
+    user::Entity::delete_by_id(user_id).exec(mysql_pool).await?;
+
+    info!(
+        "Purged user {} and {} feeds across all stores",
+        user_id,
+        feed_ids.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,244 @@
+//! Parsing of feed content: `@mentions`, `#hashtags`, the HTML rendering clients display
+//! and language detection. Shared by `create_feed` (validation) and `POST /api/feed/preview`.
+
+use crate::config::Config;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub const MAX_FEED_CONTENT_LENGTH: usize = 5000;
+
+/// Appended to content cut by `truncate_chars`
+pub const ELLIPSIS: char = '…';
+
+/// `s` cut to its first `n` user-perceived characters (grapheme clusters), followed by `…`
+/// when anything was cut; `s` itself when it is short enough or `n` is 0.
+///
+/// Counting graphemes rather than bytes or `char`s keeps multi-byte characters, emoji
+/// sequences such as 👩‍👩‍👧 or 🇫🇷 and letters with combining accents whole.
+pub fn truncate_chars(s: &str, n: usize) -> String {
+    if n == 0 {
+        return s.to_string();
+    }
+    match s.grapheme_indices(true).nth(n) {
+        Some((end, _)) => {
+            let mut truncated = s[..end].trim_end().to_string();
+            truncated.push(ELLIPSIS);
+            truncated
+        }
+        None => s.to_string(),
+    }
+}
+
+/// A run of feed content: plain text, or a mention/hashtag name without its `@`/`#`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    Mention(&'a str),
+    Hashtag(&'a str),
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Split content into segments. A mention or hashtag starts with `@`/`#` at the start of the
+/// content or after a non-word character (so `a@b.com` is text) and runs over letters,
+/// digits and `_`.
+pub fn segments(content: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut prev: Option<char> = None;
+    let mut chars = content.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let starts_token = (c == '@' || c == '#') && !prev.is_some_and(is_name_char);
+        prev = Some(c);
+        if !starts_token {
+            continue;
+        }
+
+        let name_start = i + c.len_utf8();
+        let mut name_end = name_start;
+        while let Some(&(j, next)) = chars.peek() {
+            if !is_name_char(next) {
+                break;
+            }
+            name_end = j + next.len_utf8();
+            prev = Some(next);
+            chars.next();
+        }
+        if name_end == name_start {
+            continue;
+        }
+
+        if text_start < i {
+            segments.push(Segment::Text(&content[text_start..i]));
+        }
+        let name = &content[name_start..name_end];
+        segments.push(if c == '@' {
+            Segment::Mention(name)
+        } else {
+            Segment::Hashtag(name)
+        });
+        text_start = name_end;
+    }
+
+    if text_start < content.len() {
+        segments.push(Segment::Text(&content[text_start..]));
+    }
+    segments
+}
+
+/// Distinct mentioned usernames as written, in order of first appearance
+pub fn mentions(content: &str) -> Vec<String> {
+    let mut seen: Vec<String> = Vec::new();
+    for segment in segments(content) {
+        if let Segment::Mention(name) = segment {
+            if !seen.iter().any(|s| s == name) {
+                seen.push(name.to_string());
+            }
+        }
+    }
+    seen
+}
+
+/// Distinct mentions after `normalize`, in order of first appearance, keeping at most
+/// `max` (0 keeps all). Returns the kept names and how many distinct mentions were
+/// ignored, so mention spam cannot fan out to hundreds of users.
+pub fn capped_mentions(
+    content: &str,
+    max: usize,
+    normalize: impl Fn(&str) -> String,
+) -> (Vec<String>, usize) {
+    let mut distinct: Vec<String> = Vec::new();
+    for name in mentions(content) {
+        let name = normalize(&name);
+        if !distinct.contains(&name) {
+            distinct.push(name);
+        }
+    }
+    if max == 0 || distinct.len() <= max {
+        return (distinct, 0);
+    }
+    let ignored = distinct.len() - max;
+    distinct.truncate(max);
+    (distinct, ignored)
+}
+
+/// With `CONTENT_REJECT_EXCESS_MENTIONS`, rejects content with more than
+/// `CONTENT_MAX_MENTIONS` distinct mentions
+pub fn validate_mentions(content: &str, config: &Config) -> Result<(), String> {
+    let max = config.content.max_mentions;
+    if !config.content.reject_excess_mentions {
+        return Ok(());
+    }
+    let (_, ignored) = capped_mentions(content, max, |name| config.usernames.normalize(name));
+    if ignored > 0 {
+        return Err(format!("content may mention at most {} users", max));
+    }
+    Ok(())
+}
+
+/// Distinct hashtags, lowercased, in order of first appearance
+pub fn hashtags(content: &str) -> Vec<String> {
+    let mut seen: Vec<String> = Vec::new();
+    for segment in segments(content) {
+        if let Segment::Hashtag(name) = segment {
+            let tag = name.to_lowercase();
+            if !seen.contains(&tag) {
+                seen.push(tag);
+            }
+        }
+    }
+    seen
+}
+
+/// `category` trimmed and lowercased, if it is one of `CONTENT_FEED_CATEGORIES`
+pub fn validate_category(category: &str, config: &Config) -> Result<String, String> {
+    let allowed = &config.content.feed_categories;
+    let category = category.trim().to_lowercase();
+    if allowed.contains(&category) {
+        Ok(category)
+    } else if allowed.is_empty() {
+        Err("feeds cannot be categorized".to_string())
+    } else {
+        Err(format!("category must be one of: {}", allowed.join(", ")))
+    }
+}
+
+/// Content a feed is stored with: surrounding whitespace trimmed
+pub fn sanitize(content: &str) -> String {
+    content.trim().to_string()
+}
+
+/// Rejects empty content and content over `MAX_FEED_CONTENT_LENGTH` characters
+pub fn validate_feed_content(content: &str) -> Result<(), String> {
+    if content.trim().is_empty() {
+        return Err("content must not be empty".to_string());
+    }
+    if content.chars().count() > MAX_FEED_CONTENT_LENGTH {
+        return Err(format!(
+            "content must be at most {} characters",
+            MAX_FEED_CONTENT_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+/// ISO 639-3 code of the content's language (e.g. "eng", "vie"), or `None` when
+/// `whatlang` is not confident, which is common for very short posts. Mentions and
+/// hashtags are left out so names do not skew the guess.
+pub fn detect_language(content: &str) -> Option<String> {
+    let text: String = segments(content)
+        .into_iter()
+        .filter_map(|segment| match segment {
+            Segment::Text(text) => Some(text),
+            _ => None,
+        })
+        .collect();
+    let info = whatlang::detect(&text)?;
+    info.is_reliable().then(|| info.lang().code().to_string())
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\n' => escaped.push_str("<br>"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// HTML-escaped content with line breaks as `<br>`, hashtags wrapped in
+/// `<span class="hashtag">` and mentions that `user_id` resolves in
+/// `<span class="mention" data-user-id="..">`. Unknown mentions stay plain text.
+pub fn render_html(content: &str, user_id: impl Fn(&str) -> Option<i64>) -> String {
+    let mut html = String::new();
+    for segment in segments(content) {
+        match segment {
+            Segment::Text(text) => html.push_str(&escape_html(text)),
+            Segment::Mention(name) => match user_id(name) {
+                Some(user_id) => html.push_str(&format!(
+                    "<span class=\"mention\" data-user-id=\"{}\">@{}</span>",
+                    user_id,
+                    escape_html(name)
+                )),
+                None => {
+                    html.push('@');
+                    html.push_str(&escape_html(name));
+                }
+            },
+            Segment::Hashtag(name) => html.push_str(&format!(
+                "<span class=\"hashtag\">#{}</span>",
+                escape_html(name)
+            )),
+        }
+    }
+    html
+}
@@ -0,0 +1,87 @@
+use crate::config::Config;
+use crate::db::redis_key;
+use crate::models::FeedResponse;
+use redis::Client as RedisClient;
+use serde::{Deserialize, Serialize};
+
+/// Counter embedded in every snapshot key. Bumping it orphans all cached pages,
+/// which then expire on their own TTL.
+const VERSION_KEY: &str = "feed_cache:version";
+
+/// Cached anonymous `GET /api/feed` page
+#[derive(Serialize, Deserialize)]
+pub struct FeedSnapshot {
+    /// `X-Total-Count`, when pagination headers are enabled
+    pub total: Option<u64>,
+    pub feeds: Vec<FeedResponse>,
+}
+
+/// Key of one page's snapshot under the current cache version.
+/// Read it before querying the databases: a write that lands in between bumps the
+/// version, so the possibly stale page is stored under a key nobody reads anymore.
+pub async fn snapshot_key(
+    redis_client: &RedisClient,
+    config: &Config,
+    page: u64,
+    limit: u64,
+    language: Option<&str>,
+    category: Option<&str>,
+) -> Result<String, anyhow::Error> {
+    let mut conn = redis_client.get_async_connection().await?;
+    let version: Option<u64> = redis::cmd("GET")
+        .arg(redis_key(config, VERSION_KEY))
+        .query_async(&mut conn)
+        .await?;
+    Ok(redis_key(
+        config,
+        &format!(
+            "feed_cache:v{}:page:{}:limit:{}:lang:{}:cat:{}",
+            version.unwrap_or(0),
+            page,
+            limit,
+            language.unwrap_or("*"),
+            category.unwrap_or("*")
+        ),
+    ))
+}
+
+/// Snapshot stored at `key`, if any
+pub async fn load_snapshot(
+    redis_client: &RedisClient,
+    key: &str,
+) -> Result<Option<FeedSnapshot>, anyhow::Error> {
+    let mut conn = redis_client.get_async_connection().await?;
+    let cached: Option<String> = redis::cmd("GET").arg(key).query_async(&mut conn).await?;
+    Ok(match cached {
+        Some(json) => Some(serde_json::from_str(&json)?),
+        None => None,
+    })
+}
+
+/// Store a snapshot at `key` for `FEED_CACHE_TTL_SECONDS`
+pub async fn store_snapshot(
+    redis_client: &RedisClient,
+    config: &Config,
+    key: &str,
+    snapshot: &FeedSnapshot,
+) -> Result<(), anyhow::Error> {
+    let mut conn = redis_client.get_async_connection().await?;
+    redis::cmd("SET")
+        .arg(key)
+        .arg(serde_json::to_string(snapshot)?)
+        .arg("EX")
+        .arg(config.feed_cache.ttl_seconds)
+        .query_async::<_, ()>(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Invalidate every cached page, after a write that changes what `GET /api/feed` returns
+pub async fn invalidate(redis_client: &RedisClient, config: &Config) -> Result<(), anyhow::Error> {
+    let mut conn = redis_client.get_async_connection().await?;
+    redis::cmd("INCR")
+        .arg(redis_key(config, VERSION_KEY))
+        .query_async::<_, ()>(&mut conn)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::config::Config;
+use crate::db::redis_key;
+use redis::Client as RedisClient;
+
+/// Raises the stored feed ID to `ARGV[1]` if it is higher, so an older page fetched
+/// late cannot move the marker back. `ARGV[2]` is the TTL in seconds, `0` for none.
+const ADVANCE_MARKER_SCRIPT: &str = r"
+local current = tonumber(redis.call('GET', KEYS[1]) or '0')
+local feed_id = tonumber(ARGV[1])
+if feed_id > current then
+  redis.call('SET', KEYS[1], feed_id)
+end
+local ttl = tonumber(ARGV[2])
+if ttl > 0 then
+  redis.call('EXPIRE', KEYS[1], ttl)
+end
+return math.max(feed_id, current)
+";
+
+fn marker_key(config: &Config, user_id: i64) -> String {
+    redis_key(config, &format!("feed_seen:{}", user_id))
+}
+
+/// Record that `user_id` has seen the timeline up to `feed_id`.
+/// Each write refreshes the `FEED_MARKER_TTL_DAYS` expiry.
+pub async fn advance_marker(
+    redis_client: &RedisClient,
+    config: &Config,
+    user_id: i64,
+    feed_id: i64,
+) -> Result<(), anyhow::Error> {
+    let mut conn = redis_client.get_async_connection().await?;
+    redis::Script::new(ADVANCE_MARKER_SCRIPT)
+        .key(marker_key(config, user_id))
+        .arg(feed_id)
+        .arg(config.feed_marker.ttl_days * 24 * 3600)
+        .invoke_async::<_, i64>(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Newest feed ID `user_id` has seen, if they loaded the timeline since the marker expired
+pub async fn load_marker(
+    redis_client: &RedisClient,
+    config: &Config,
+    user_id: i64,
+) -> Result<Option<i64>, anyhow::Error> {
+    let mut conn = redis_client.get_async_connection().await?;
+    let marker: Option<i64> = redis::cmd("GET")
+        .arg(marker_key(config, user_id))
+        .query_async(&mut conn)
+        .await?;
+    Ok(marker)
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+/// Lowercased BCP 47 language tag (`pt_BR` → `pt-br`), or `None` if `tag` is not one.
+/// Only the shape is checked: a 2–8 letter language subtag followed by 1–8 character
+/// alphanumeric subtags.
+pub fn normalize_language(tag: &str) -> Option<String> {
+    let tag = tag.trim().replace('_', "-").to_ascii_lowercase();
+    let mut subtags = tag.split('-');
+    let primary = subtags.next()?;
+    if !(2..=8).contains(&primary.len()) || !primary.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return None;
+    }
+    if subtags.any(|subtag| {
+        !(1..=8).contains(&subtag.len()) || !subtag.bytes().all(|b| b.is_ascii_alphanumeric())
+    }) {
+        return None;
+    }
+    Some(tag)
+}
+
+/// Highest-weighted language of an `Accept-Language` header value; earlier entries win
+/// ties. `*`, `q=0` and malformed entries are skipped.
+pub fn preferred_language(accept_language: &str) -> Option<String> {
+    let mut best: Option<(String, f32)> = None;
+    for entry in accept_language.split(',') {
+        let mut parts = entry.split(';');
+        let Some(language) = parts.next().and_then(normalize_language) else {
+            continue;
+        };
+        let weight = parts
+            .find_map(|param| param.trim().strip_prefix("q="))
+            .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok());
+        let Some(weight) = weight.filter(|q| *q > 0.0) else {
+            continue;
+        };
+        if best.as_ref().is_none_or(|(_, top)| weight > *top) {
+            best = Some((language, weight));
+        }
+    }
+    best.map(|(language, _)| language)
+}
+
+/// Template for `language`: an exact match in `localized`, then its primary language
+/// subtag (`pt-br` → `pt`), then `default`
+pub fn localized_template<'a>(
+    default: &'a str,
+    localized: &'a HashMap<String, String>,
+    language: Option<&str>,
+) -> &'a str {
+    let Some(language) = language else {
+        return default;
+    };
+    let primary = language.split('-').next().unwrap_or(language);
+    localized
+        .get(language)
+        .or_else(|| localized.get(primary))
+        .map_or(default, String::as_str)
+}
@@ -1 +1,9 @@
+pub mod account;
+pub mod content;
+pub mod feed_cache;
+pub mod feed_marker;
+pub mod locale;
 pub mod notification;
+pub mod rate_limit;
+pub mod search;
+pub mod seed;
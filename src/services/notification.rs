@@ -1,24 +1,84 @@
-use crate::db::DbPool;
+use crate::config::Config;
+use crate::db::{mongo_collection, redis_key, DbPool};
 use crate::entities::{feed, user};
-use crate::models::{Notification, NotificationType};
-use chrono::Utc;
+use crate::kafka::event_request_id;
+use crate::models::{Notification, NotificationType, UserProfile};
+use crate::services::content::truncate_chars;
+use chrono::{DateTime, Utc};
 use log::{error, info};
+use mongodb::bson::doc;
+use mongodb::options::UpdateOptions;
 use mongodb::Database as MongoDatabase;
 use redis::Client as RedisClient;
 use sea_orm::EntityTrait;
 use serde_json::Value;
 use uuid::Uuid;
 
+/// Fill `{name}` placeholders from `values` in a single pass, so braces inside the values
+/// (e.g. comment text) are kept as written. `{{` and `}}` are literal braces.
+/// Returns the name of the first placeholder missing from `values` as the error.
+pub fn render_template(template: &str, values: &[(&str, &str)]) -> Result<String, String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        rendered.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            rendered.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        // A lone `}`, or a `{` that is never closed, is plain text
+        let end = match (tail.starts_with('{'), tail.find('}')) {
+            (true, Some(end)) => end,
+            (true, None) => {
+                rest = tail;
+                break;
+            }
+            (false, _) => {
+                rendered.push('}');
+                rest = &tail[1..];
+                continue;
+            }
+        };
+        let name = &tail[1..end];
+        let value = values
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+            .ok_or_else(|| name.to_string())?;
+        rendered.push_str(value);
+        rest = &tail[end + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Notification text from the configured template; falls back to `fallback` if the
+/// template cannot be rendered (startup validation normally rules that out)
+fn notification_content(template: &str, values: &[(&str, &str)], fallback: String) -> String {
+    render_template(template, values).unwrap_or_else(|name| {
+        error!(
+            "Notification template uses unknown placeholder {{{}}}",
+            name
+        );
+        fallback
+    })
+}
+
 pub async fn handle_feed_liked_event(
     event_data: &Value,
     mongo_db: &MongoDatabase,
     mysql_pool: &DbPool,
     redis_client: &RedisClient,
+    config: &Config,
 ) {
     if let (Some(user_id), Some(feed_id)) = (
         event_data.get("user_id").and_then(|v| v.as_i64()),
         event_data.get("feed_id").and_then(|v| v.as_i64()),
     ) {
+        let request_id = event_request_id(event_data);
+
         // Get feed owner info using SeaORM
         let feed_owner_info =
             if let Ok(Some(feed_model)) = feed::Entity::find_by_id(feed_id).one(mysql_pool).await {
@@ -37,15 +97,33 @@ pub async fn handle_feed_liked_event(
         let (feed_owner_id, feed_owner_username) = match feed_owner_info {
             Some((owner_id, username)) => (owner_id, username),
             None => {
-                error!("Feed {} not found when processing like event", feed_id);
+                error!(
+                    "[request {}] Feed {} not found when processing like event",
+                    request_id, feed_id
+                );
                 return;
             }
         };
 
-        update_top_users_liked_realtime(redis_client, feed_owner_id, &feed_owner_username).await;
+        // The liker weighs the like in top:feeds_liked and names it in the notification
+        let liker = user::Entity::find_by_id(user_id)
+            .one(mysql_pool)
+            .await
+            .ok()
+            .flatten();
+        let weight = liker.as_ref().map_or(1.0, |liker| {
+            config
+                .top_stats
+                .like_weight(liker.is_admin, liker.is_verified)
+        });
+
+        update_top_users_liked_realtime(redis_client, config, feed_owner_id, &feed_owner_username)
+            .await;
         update_top_feeds_liked_realtime(
             redis_client,
+            config,
             feed_id,
+            weight,
             feed_owner_id,
             &feed_owner_username,
             mysql_pool,
@@ -56,16 +134,42 @@ pub async fn handle_feed_liked_event(
             return;
         }
 
-        // Get username using SeaORM
-        let username: Option<String> = user::Entity::find_by_id(user_id)
-            .one(mysql_pool)
-            .await
-            .ok()
-            .flatten()
-            .map(|user_model| user_model.username);
+        let recipient = recipient_profile(mongo_db, feed_owner_id).await;
+        if !recipient_allows(recipient.as_ref(), &NotificationType::Like) {
+            info!(
+                "[request {}] User {} disabled like notifications",
+                request_id, feed_owner_id
+            );
+            return;
+        }
 
-        if let Some(username) = username {
-            let content = format!("{} liked your feed", username);
+        if let Some(username) = liker.map(|user_model| user_model.username) {
+            let language = recipient.and_then(|profile| profile.language);
+            if config.notifications.aggregate_likes {
+                match aggregate_like(
+                    mongo_db,
+                    config,
+                    feed_owner_id,
+                    user_id,
+                    &username,
+                    feed_id,
+                    language.as_deref(),
+                )
+                .await
+                {
+                    Ok(()) => info!(
+                        "[request {}] Added like from user {} to the notification of user {} for feed {}",
+                        request_id, user_id, feed_owner_id, feed_id
+                    ),
+                    Err(e) => error!(
+                        "[request {}] Failed to update aggregate notification: {:?}",
+                        request_id, e
+                    ),
+                }
+                return;
+            }
+
+            let content = like_content(config, language.as_deref(), feed_id, 1, &username, &[]);
             let notification = Notification {
                 id: Some(Uuid::new_v4().to_string()),
                 user_id: feed_owner_id,
@@ -76,24 +180,149 @@ pub async fn handle_feed_liked_event(
                 content,
                 created_at: Utc::now(),
                 is_read: false,
+                actor_count: None,
+                recent_actors: Vec::new(),
             };
 
-            let collection = mongo_db.collection::<Notification>("notifications");
+            let collection = mongo_collection::<Notification>(mongo_db, config, "notifications");
             if let Err(e) = collection.insert_one(&notification, None).await {
-                error!("Failed to create notification: {:?}", e);
+                error!(
+                    "[request {}] Failed to create notification: {:?}",
+                    request_id, e
+                );
             } else {
                 info!(
-                    "Created like notification for user {} from user {}",
-                    feed_owner_id, user_id
+                    "[request {}] Created like notification for user {} from user {}",
+                    request_id, feed_owner_id, user_id
                 );
             }
         }
     }
 }
 
+/// Text of a like notification standing for `actor_count` likes, `username` being the
+/// latest liker: the like template for one like, the aggregate template for more
+pub fn like_content(
+    config: &Config,
+    language: Option<&str>,
+    feed_id: i64,
+    actor_count: i64,
+    username: &str,
+    recent_actors: &[String],
+) -> String {
+    let feed_id = feed_id.to_string();
+    if actor_count <= 1 {
+        return notification_content(
+            config.notifications.like_template_for(language),
+            &[("username", username), ("feed_id", &feed_id)],
+            format!("{} liked your feed", username),
+        );
+    }
+    let count = actor_count.to_string();
+    let usernames = recent_actors.join(", ");
+    notification_content(
+        config.notifications.like_aggregate_template_for(language),
+        &[
+            ("username", username),
+            ("usernames", &usernames),
+            ("count", &count),
+            ("feed_id", &feed_id),
+        ],
+        format!("{} people liked your feed", count),
+    )
+}
+
+/// `_id` of the aggregate like notification of `recipient_id` for `feed_id` in the
+/// `NOTIFICATIONS_AGGREGATE_WINDOW_MINUTES` window containing `at` (windows are aligned to
+/// the Unix epoch)
+pub fn aggregate_notification_id(
+    config: &Config,
+    recipient_id: i64,
+    feed_id: i64,
+    at: DateTime<Utc>,
+) -> String {
+    let window_seconds = (config.notifications.aggregate_window_minutes.max(1) * 60) as i64;
+    format!(
+        "like:{}:{}:{}",
+        recipient_id,
+        feed_id,
+        at.timestamp().div_euclid(window_seconds)
+    )
+}
+
+/// Fold a like into the recipient's aggregate notification for `feed_id` in the current
+/// window, creating it on the first like. This is a single upsert on a deterministic `_id`,
+/// so concurrent likes (feed events are handled on their own tasks) land in one document:
+/// MongoDB retries an upsert that loses the insert race on `_id` as an update. A fold
+/// marks the notification unread again. Only the first liker's text is stored; `content`
+/// of an aggregate is rendered from `actor_count` when read (`displayed_content`).
+async fn aggregate_like(
+    mongo_db: &MongoDatabase,
+    config: &Config,
+    recipient_id: i64,
+    user_id: i64,
+    username: &str,
+    feed_id: i64,
+    language: Option<&str>,
+) -> mongodb::error::Result<()> {
+    let now = Utc::now();
+    let id = aggregate_notification_id(config, recipient_id, feed_id, now);
+    let content = like_content(config, language, feed_id, 1, username, &[]);
+    let update = doc! {
+        "$setOnInsert": {
+            "user_id": recipient_id,
+            "feed_id": feed_id,
+            "notification_type": "like",
+            "content": content,
+            "created_at": now.timestamp(),
+        },
+        "$set": {"from_user_id": user_id, "from_username": username, "is_read": false},
+        "$inc": {"actor_count": 1_i64},
+        "$push": {"recent_actors": {
+            "$each": [username],
+            "$slice": -(config.notifications.aggregate_max_actors as i64),
+        }},
+    };
+    let options = UpdateOptions::builder().upsert(true).build();
+    mongo_collection::<Notification>(mongo_db, config, "notifications")
+        .update_one(doc! {"_id": id}, update, options)
+        .await?;
+    Ok(())
+}
+
+/// Text shown for `notification`: aggregate like notifications are rendered from their
+/// current `actor_count` and `recent_actors` in the recipient's `language`, everything else
+/// keeps the `content` stored when it was written
+pub fn displayed_content(
+    notification: &Notification,
+    config: &Config,
+    language: Option<&str>,
+) -> String {
+    match notification.actor_count {
+        Some(actor_count) if actor_count > 1 => like_content(
+            config,
+            language,
+            notification.feed_id,
+            actor_count,
+            &notification.from_username,
+            &notification.recent_actors,
+        ),
+        _ => notification.content.clone(),
+    }
+}
+
+/// Profile language of `user_id`, for rendering their notifications
+pub async fn recipient_language(mongo_db: &MongoDatabase, user_id: i64) -> Option<String> {
+    recipient_profile(mongo_db, user_id)
+        .await
+        .and_then(|profile| profile.language)
+}
+
 async fn update_top_feeds_liked_realtime(
     redis_client: &RedisClient,
+    config: &Config,
     feed_id: i64,
+    weight: f64,
     _user_id: i64,
     _username: &str,
     _mysql_pool: &DbPool,
@@ -112,8 +341,8 @@ async fn update_top_feeds_liked_realtime(
     // Simply increment score for feed_id - much simpler and faster!
     let feed_id_str = feed_id.to_string();
     let _: Result<(), _> = redis::cmd("ZINCRBY")
-        .arg("top:feeds_liked")
-        .arg(1.0)
+        .arg(redis_key(config, "top:feeds_liked"))
+        .arg(weight)
         .arg(&feed_id_str)
         .query_async(&mut conn)
         .await;
@@ -121,6 +350,7 @@ async fn update_top_feeds_liked_realtime(
 
 async fn update_top_feeds_commented_realtime(
     redis_client: &RedisClient,
+    config: &Config,
     feed_id: i64,
     _mysql_pool: &DbPool,
 ) {
@@ -134,7 +364,7 @@ async fn update_top_feeds_commented_realtime(
 
     let feed_id_str = feed_id.to_string();
     match redis::cmd("ZINCRBY")
-        .arg("top:comments")
+        .arg(redis_key(config, "top:comments"))
         .arg(1.0)
         .arg(&feed_id_str)
         .query_async::<_, f64>(&mut conn)
@@ -155,7 +385,11 @@ async fn update_top_feeds_commented_realtime(
     }
 }
 
-async fn update_top_feeds_viewed_realtime(redis_client: &RedisClient, feed_id: i64) {
+async fn update_top_feeds_viewed_realtime(
+    redis_client: &RedisClient,
+    config: &Config,
+    feed_id: i64,
+) {
     let mut conn = match redis_client.get_async_connection().await {
         Ok(conn) => conn,
         Err(e) => {
@@ -169,16 +403,109 @@ async fn update_top_feeds_viewed_realtime(redis_client: &RedisClient, feed_id: i
 
     let feed_id_str = feed_id.to_string();
     let _: Result<(), _> = redis::cmd("ZINCRBY")
-        .arg("top:feeds_viewed")
+        .arg(redis_key(config, "top:feeds_viewed"))
         .arg(1.0)
         .arg(&feed_id_str)
         .query_async(&mut conn)
         .await;
 }
 
-pub async fn handle_feed_viewed_event(event_data: &Value, redis_client: &RedisClient) {
+/// Undo a like's contribution to the realtime rankings (e.g. a moderator removed it)
+pub async fn handle_feed_unliked_event(
+    event_data: &Value,
+    mysql_pool: &DbPool,
+    redis_client: &RedisClient,
+    config: &Config,
+) {
+    let Some(feed_id) = event_data.get("feed_id").and_then(|v| v.as_i64()) else {
+        return;
+    };
+
+    let feed_owner_id = match feed::Entity::find_by_id(feed_id).one(mysql_pool).await {
+        Ok(Some(feed_model)) => feed_model.user_id,
+        _ => {
+            error!("Feed {} not found when processing unlike event", feed_id);
+            return;
+        }
+    };
+    // Take back what the like added; the liker's flags may have changed since, which the
+    // next top stats rebuild corrects
+    let weight = match event_data.get("user_id").and_then(|v| v.as_i64()) {
+        Some(user_id) => user::Entity::find_by_id(user_id)
+            .one(mysql_pool)
+            .await
+            .ok()
+            .flatten()
+            .map_or(1.0, |liker| {
+                config
+                    .top_stats
+                    .like_weight(liker.is_admin, liker.is_verified)
+            }),
+        None => 1.0,
+    };
+
+    let mut conn = match redis_client.get_async_connection().await {
+        Ok(conn) => conn,
+        Err(e) => {
+            error!("Failed to get Redis connection for unlike event: {:?}", e);
+            return;
+        }
+    };
+
+    for (key, member, weight) in [
+        ("top:users_liked", feed_owner_id.to_string(), 1.0),
+        ("top:feeds_liked", feed_id.to_string(), weight),
+    ] {
+        let key = redis_key(config, key);
+        match redis::cmd("ZINCRBY")
+            .arg(&key)
+            .arg(-weight)
+            .arg(&member)
+            .query_async::<_, f64>(&mut conn)
+            .await
+        {
+            // Drop members that no longer have any likes so they don't rank at 0
+            Ok(score) if score <= 0.0 => {
+                let _: Result<(), _> = redis::cmd("ZREM")
+                    .arg(&key)
+                    .arg(&member)
+                    .query_async(&mut conn)
+                    .await;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                error!("Failed to decrement {} for {}: {:?}", key, member, e);
+            }
+        }
+    }
+    info!("Removed like on feed {} from realtime rankings", feed_id);
+}
+
+/// Whether a moderator has hidden `feed_id`, so events queued before that do not put it
+/// back into the realtime rankings. Lookup errors count as not hidden; the orphan sweep
+/// removes anything that slips through.
+async fn feed_is_hidden(mysql_pool: &DbPool, feed_id: i64) -> bool {
+    match feed::Entity::find_by_id(feed_id).one(mysql_pool).await {
+        Ok(feed_model) => feed_model.is_some_and(|feed_model| feed_model.hidden_at.is_some()),
+        Err(e) => {
+            error!("Failed to look up feed {}: {:?}", feed_id, e);
+            false
+        }
+    }
+}
+
+pub async fn handle_feed_viewed_event(
+    event_data: &Value,
+    mysql_pool: &DbPool,
+    redis_client: &RedisClient,
+    config: &Config,
+) {
     if let Some(feed_id) = event_data.get("feed_id").and_then(|v| v.as_i64()) {
-        update_top_feeds_viewed_realtime(redis_client, feed_id).await;
+        if feed_is_hidden(mysql_pool, feed_id).await {
+            info!("Skipping view of hidden feed {}", feed_id);
+            return;
+        }
+        update_top_feeds_viewed_realtime(redis_client, config, feed_id).await;
         info!("Updated top:feeds_viewed for feed {}", feed_id);
     }
 }
@@ -188,6 +515,7 @@ pub async fn handle_feed_commented_event(
     mongo_db: &MongoDatabase,
     mysql_pool: &DbPool,
     redis_client: &RedisClient,
+    config: &Config,
 ) {
     info!("Processing feed commented event: {:?}", event_data);
     if let (Some(user_id), Some(feed_id), Some(content)) = (
@@ -195,12 +523,21 @@ pub async fn handle_feed_commented_event(
         event_data.get("feed_id").and_then(|v| v.as_i64()),
         event_data.get("content").and_then(|v| v.as_str()),
     ) {
+        let request_id = event_request_id(event_data);
         info!(
-            "Comment event - feed_id: {}, user_id: {}, content: {}",
-            feed_id, user_id, content
+            "[request {}] Comment event - feed_id: {}, user_id: {}, content: {}",
+            request_id, feed_id, user_id, content
         );
-        // Update top:comments first (always update, even if notification creation fails)
-        update_top_feeds_commented_realtime(redis_client, feed_id, mysql_pool).await;
+        // Update top:comments first (always update, even if notification creation fails),
+        // unless the feed was hidden after the comment was made
+        if feed_is_hidden(mysql_pool, feed_id).await {
+            info!(
+                "[request {}] Skipping top:comments for hidden feed {}",
+                request_id, feed_id
+            );
+        } else {
+            update_top_feeds_commented_realtime(redis_client, config, feed_id, mysql_pool).await;
+        }
 
         // Get feed owner info using SeaORM
         let feed_owner_info =
@@ -213,7 +550,10 @@ pub async fn handle_feed_commented_event(
         let feed_owner_id = match feed_owner_info {
             Some(owner_id) => owner_id,
             None => {
-                error!("Feed {} not found when processing comment event", feed_id);
+                error!(
+                    "[request {}] Feed {} not found when processing comment event",
+                    request_id, feed_id
+                );
                 return;
             }
         };
@@ -222,6 +562,15 @@ pub async fn handle_feed_commented_event(
             return;
         }
 
+        let recipient = recipient_profile(mongo_db, feed_owner_id).await;
+        if !recipient_allows(recipient.as_ref(), &NotificationType::Comment) {
+            info!(
+                "[request {}] User {} disabled comment notifications",
+                request_id, feed_owner_id
+            );
+            return;
+        }
+
         // Get username using SeaORM
         let username: Option<String> = user::Entity::find_by_id(user_id)
             .one(mysql_pool)
@@ -231,6 +580,20 @@ pub async fn handle_feed_commented_event(
             .map(|user_model| user_model.username);
 
         if let Some(username) = username {
+            let feed_id_str = feed_id.to_string();
+            let language = recipient.and_then(|profile| profile.language);
+            let preview = truncate_chars(content, config.notifications.content_preview_chars);
+            let content = notification_content(
+                config
+                    .notifications
+                    .comment_template_for(language.as_deref()),
+                &[
+                    ("username", &username),
+                    ("feed_id", &feed_id_str),
+                    ("content", &preview),
+                ],
+                preview.clone(),
+            );
             let notification = Notification {
                 id: Some(Uuid::new_v4().to_string()),
                 user_id: feed_owner_id,
@@ -238,18 +601,23 @@ pub async fn handle_feed_commented_event(
                 from_username: username,
                 feed_id,
                 notification_type: NotificationType::Comment,
-                content: content.to_string(),
+                content,
                 created_at: Utc::now(),
                 is_read: false,
+                actor_count: None,
+                recent_actors: Vec::new(),
             };
 
-            let collection = mongo_db.collection::<Notification>("notifications");
+            let collection = mongo_collection::<Notification>(mongo_db, config, "notifications");
             if let Err(e) = collection.insert_one(&notification, None).await {
-                error!("Failed to create notification: {:?}", e);
+                error!(
+                    "[request {}] Failed to create notification: {:?}",
+                    request_id, e
+                );
             } else {
                 info!(
-                    "Created comment notification for user {} from user {}",
-                    feed_owner_id, user_id
+                    "[request {}] Created comment notification for user {} from user {}",
+                    request_id, feed_owner_id, user_id
                 );
             }
         }
@@ -258,6 +626,7 @@ pub async fn handle_feed_commented_event(
 
 async fn update_top_users_liked_realtime(
     redis_client: &RedisClient,
+    config: &Config,
     user_id: i64,
     _username: &str,
 ) {
@@ -274,9 +643,30 @@ async fn update_top_users_liked_realtime(
 
     let user_id_str = user_id.to_string();
     let _: Result<(), _> = redis::cmd("ZINCRBY")
-        .arg("top:users_liked")
+        .arg(redis_key(config, "top:users_liked"))
         .arg(1.0)
         .arg(&user_id_str)
         .query_async(&mut conn)
         .await;
 }
+
+/// Recipient's profile, for notification preferences and language. Users without a
+/// profile, and lookup errors, get `None` rather than silently dropping the notification.
+async fn recipient_profile(mongo_db: &MongoDatabase, user_id: i64) -> Option<UserProfile> {
+    mongo_db
+        .collection::<UserProfile>("user_profiles")
+        .find_one(doc! {"_id": user_id}, None)
+        .await
+        .unwrap_or_else(|e| {
+            error!(
+                "Failed to load notification preferences for user {}: {:?}",
+                user_id, e
+            );
+            None
+        })
+}
+
+/// Check the recipient's notification preferences; every type is allowed without a profile
+fn recipient_allows(profile: Option<&UserProfile>, notification_type: &NotificationType) -> bool {
+    profile.is_none_or(|profile| profile.notification_preferences.allows(notification_type))
+}
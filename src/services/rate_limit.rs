@@ -0,0 +1,118 @@
+use crate::config::Config;
+use crate::db::redis_key;
+use redis::Client as RedisClient;
+
+/// Start a cooldown on `key` unless one is already running.
+/// Returns `None` if the action is allowed, or `Some(seconds)` remaining on the active cooldown.
+/// The key is set atomically with `SET NX EX`, so concurrent requests cannot both pass.
+pub async fn acquire_cooldown(
+    redis_client: &RedisClient,
+    config: &Config,
+    key: &str,
+    seconds: u64,
+) -> Result<Option<u64>, anyhow::Error> {
+    if seconds == 0 {
+        return Ok(None);
+    }
+
+    let key = redis_key(config, key);
+    let mut conn = redis_client.get_async_connection().await?;
+    let acquired: Option<String> = redis::cmd("SET")
+        .arg(&key)
+        .arg(1)
+        .arg("NX")
+        .arg("EX")
+        .arg(seconds)
+        .query_async(&mut conn)
+        .await?;
+
+    if acquired.is_some() {
+        return Ok(None);
+    }
+
+    let ttl: i64 = redis::cmd("TTL").arg(&key).query_async(&mut conn).await?;
+    Ok(Some(ttl.max(1) as u64))
+}
+
+/// Clear a cooldown, e.g. when the action it guarded failed
+pub async fn release_cooldown(
+    redis_client: &RedisClient,
+    config: &Config,
+    key: &str,
+) -> Result<(), anyhow::Error> {
+    let mut conn = redis_client.get_async_connection().await?;
+    redis::cmd("DEL")
+        .arg(redis_key(config, key))
+        .query_async::<_, ()>(&mut conn)
+        .await?;
+    Ok(())
+}
+
+/// Refills the bucket for the time elapsed since the last call (Redis clock), then
+/// takes one token if available. Returns `{allowed, milliseconds until a token}`.
+const TOKEN_BUCKET_SCRIPT: &str = r"
+local capacity = tonumber(ARGV[1])
+local refill_per_ms = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+local tokens = tonumber(bucket[1]) or capacity
+local ts = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - ts) * refill_per_ms)
+local allowed = 0
+local wait_ms = 0
+if tokens >= 1 then
+  tokens = tokens - 1
+  allowed = 1
+else
+  wait_ms = math.ceil((1 - tokens) / refill_per_ms)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', now)
+redis.call('PEXPIRE', KEYS[1], math.ceil(capacity / refill_per_ms) + 1000)
+return {allowed, wait_ms}
+";
+
+/// Take one token from the bucket at `key`, holding up to `capacity` tokens and
+/// refilled at `per_minute` tokens per minute.
+/// Returns `None` if the action is allowed, or `Some(seconds)` until a token is available.
+/// A `per_minute` of `0` always allows.
+pub async fn take_token(
+    redis_client: &RedisClient,
+    config: &Config,
+    key: &str,
+    capacity: u64,
+    per_minute: u64,
+) -> Result<Option<u64>, anyhow::Error> {
+    if per_minute == 0 {
+        return Ok(None);
+    }
+
+    let mut conn = redis_client.get_async_connection().await?;
+    let (allowed, wait_ms): (i64, i64) = redis::Script::new(TOKEN_BUCKET_SCRIPT)
+        .key(redis_key(config, key))
+        .arg(capacity.max(1))
+        .arg(per_minute as f64 / 60_000.0)
+        .invoke_async(&mut conn)
+        .await?;
+
+    if allowed == 1 {
+        Ok(None)
+    } else {
+        Ok(Some(((wait_ms as u64).div_ceil(1000)).max(1)))
+    }
+}
+
+/// Key for the token bucket of `action` by `user_id`
+pub fn token_bucket_key(action: &str, user_id: i64) -> String {
+    format!("ratelimit:bucket:{}:{}", action, user_id)
+}
+
+/// Key for the token bucket of `action` by client IP, for endpoints open to anonymous users
+pub fn ip_bucket_key(action: &str, ip: &str) -> String {
+    format!("ratelimit:bucket:{}:ip:{}", action, ip)
+}
+
+/// Key for the per-user, per-feed comment cooldown
+pub fn comment_cooldown_key(feed_id: i64, user_id: i64) -> String {
+    format!("ratelimit:comment:{}:{}", feed_id, user_id)
+}
@@ -0,0 +1,195 @@
+//! Feed search behind `SearchBackend`, so `GET /api/feed/search` does not depend on how
+//! matching is done. `SEARCH_BACKEND` picks the implementation at startup.
+
+use crate::config::{Config, SearchBackendKind};
+use crate::db::DbPool;
+use crate::entities::feed;
+use crate::kafka::event_request_id;
+use async_trait::async_trait;
+use chrono::Utc;
+use sea_orm::{ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde_json::Value;
+use std::sync::Arc;
+
+#[async_trait]
+pub trait SearchBackend: Send + Sync {
+    /// Whether feeds must be pushed with `index_feed` when they are published
+    fn needs_indexing(&self) -> bool;
+
+    /// Add a published feed to the index (called from the `created` feed event)
+    async fn index_feed(
+        &self,
+        feed_id: i64,
+        user_id: i64,
+        content: &str,
+    ) -> Result<(), anyhow::Error>;
+
+    /// Drop a feed pushed with `index_feed` from the index
+    async fn remove_feed(&self, feed_id: i64) -> Result<(), anyhow::Error>;
+
+    /// Ids of published feeds matching `query`, best match first
+    async fn search_feeds(
+        &self,
+        query: &str,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<i64>, anyhow::Error>;
+}
+
+/// `LIKE %query%` over `feeds.content`, newest first. Reads the table directly, so
+/// nothing needs indexing, but every search scans the visible feeds.
+pub struct MysqlSearchBackend {
+    pool: DbPool,
+}
+
+impl MysqlSearchBackend {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Escape `LIKE` wildcards so they match literally
+fn like_pattern(query: &str) -> String {
+    let mut pattern = String::with_capacity(query.len() + 2);
+    pattern.push('%');
+    for c in query.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
+#[async_trait]
+impl SearchBackend for MysqlSearchBackend {
+    fn needs_indexing(&self) -> bool {
+        false
+    }
+
+    async fn index_feed(
+        &self,
+        _feed_id: i64,
+        _user_id: i64,
+        _content: &str,
+    ) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    async fn remove_feed(&self, _feed_id: i64) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    async fn search_feeds(
+        &self,
+        query: &str,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<i64>, anyhow::Error> {
+        let ids: Vec<i64> = feed::Entity::find()
+            .select_only()
+            .column(feed::Column::Id)
+            .filter(feed::Column::Content.like(like_pattern(query)))
+            .filter(
+                Condition::any()
+                    .add(feed::Column::PublishAt.is_null())
+                    .add(feed::Column::PublishAt.lte(Utc::now())),
+            )
+            .filter(feed::Column::HiddenAt.is_null())
+            .filter(feed::unexpired(Utc::now()))
+            .order_by_desc(feed::Column::CreatedAt)
+            .offset(offset)
+            .limit(limit)
+            .into_tuple()
+            .all(&self.pool)
+            .await?;
+        Ok(ids)
+    }
+}
+
+/// Placeholder for an external engine such as Meilisearch or Elasticsearch at
+/// `SEARCH_EXTERNAL_URL`. Indexing is accepted but only logged, and searches fail,
+/// until a client for the chosen engine is added here.
+pub struct ExternalSearchBackend {
+    url: String,
+}
+
+impl ExternalSearchBackend {
+    pub fn new(url: String) -> Self {
+        Self { url }
+    }
+}
+
+#[async_trait]
+impl SearchBackend for ExternalSearchBackend {
+    fn needs_indexing(&self) -> bool {
+        true
+    }
+
+    async fn index_feed(
+        &self,
+        feed_id: i64,
+        _user_id: i64,
+        _content: &str,
+    ) -> Result<(), anyhow::Error> {
+        log::debug!(
+            "External search at {} is a stub; not indexing feed {}",
+            self.url,
+            feed_id
+        );
+        Ok(())
+    }
+
+    async fn remove_feed(&self, feed_id: i64) -> Result<(), anyhow::Error> {
+        log::debug!(
+            "External search at {} is a stub; not removing feed {}",
+            self.url,
+            feed_id
+        );
+        Ok(())
+    }
+
+    async fn search_feeds(
+        &self,
+        _query: &str,
+        _offset: u64,
+        _limit: u64,
+    ) -> Result<Vec<i64>, anyhow::Error> {
+        Err(anyhow::anyhow!(
+            "External search backend at {} is not implemented",
+            self.url
+        ))
+    }
+}
+
+/// The backend selected by `SEARCH_BACKEND`
+pub fn create_search_backend(config: &Config, pool: &DbPool) -> Arc<dyn SearchBackend> {
+    match config.search.backend {
+        SearchBackendKind::Mysql => Arc::new(MysqlSearchBackend::new(pool.clone())),
+        SearchBackendKind::External => Arc::new(ExternalSearchBackend::new(
+            config.search.external_url.clone(),
+        )),
+    }
+}
+
+/// Index a newly published feed when the active backend keeps its own index
+pub async fn handle_feed_created_event(event_data: &Value, search: &dyn SearchBackend) {
+    if !search.needs_indexing() {
+        return;
+    }
+    if let (Some(feed_id), Some(user_id), Some(content)) = (
+        event_data.get("feed_id").and_then(|v| v.as_i64()),
+        event_data.get("user_id").and_then(|v| v.as_i64()),
+        event_data.get("content").and_then(|v| v.as_str()),
+    ) {
+        if let Err(e) = search.index_feed(feed_id, user_id, content).await {
+            log::error!(
+                "[request {}] Failed to index feed {} for search: {:?}",
+                event_request_id(event_data),
+                feed_id,
+                e
+            );
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::auth::hash_password;
+use crate::config::Config;
+use crate::db::mongo_collection;
+use crate::kafka::{
+    FeedCommentedEvent, FeedCreatedEvent, FeedLikedEvent, FeedViewedEvent, KafkaProducer,
+    UserCreatedEvent,
+};
+use crate::models::{Comment, FeedView, SeedResponse};
+use crate::repositories::{FeedRepository, LikeRepository, UserRepository};
+use chrono::Utc;
+use log::{info, warn};
+use mongodb::Database as MongoDatabase;
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::Rng;
+use serde::Serialize;
+use uuid::Uuid;
+
+const FEED_LINES: [&str; 6] = [
+    "Just shipped a new feature, feeling great!",
+    "Anyone else up this early?",
+    "Coffee first, code second.",
+    "Reading a great book on distributed systems.",
+    "Weekend hike photos coming soon.",
+    "Hot take: tabs are fine.",
+];
+
+const COMMENT_LINES: [&str; 5] = [
+    "Nice one!",
+    "Totally agree.",
+    "Thanks for sharing.",
+    "Congrats!",
+    "Interesting, tell me more.",
+];
+
+/// What to create for one feed; user and feed positions index the seeded lists
+struct FeedPlan {
+    author: usize,
+    content: &'static str,
+    likers: Vec<usize>,
+    comments: Vec<(usize, &'static str)>,
+    viewers: Vec<usize>,
+}
+
+/// Everything `seed` creates, drawn up front so no RNG is held across awaits
+pub struct SeedPlan {
+    users: usize,
+    feeds: Vec<FeedPlan>,
+}
+
+impl SeedPlan {
+    /// `users` users with `feeds_per_user` feeds each. Every feed gets up to the given
+    /// number of likes (from distinct users other than the author), comments and views.
+    pub fn random(
+        users: u64,
+        feeds_per_user: u64,
+        max_likes: u64,
+        max_comments: u64,
+        max_views: u64,
+    ) -> Self {
+        let mut rng = rand::thread_rng();
+        let users = users as usize;
+        let mut feeds = Vec::new();
+        for author in 0..users {
+            for _ in 0..feeds_per_user {
+                let like_count = rng.gen_range(0..=max_likes as usize);
+                let likers = (0..users)
+                    .filter(|&user| user != author)
+                    .choose_multiple(&mut rng, like_count);
+                let comments = (0..rng.gen_range(0..=max_comments))
+                    .map(|_| {
+                        (
+                            rng.gen_range(0..users),
+                            *COMMENT_LINES.choose(&mut rng).unwrap_or(&COMMENT_LINES[0]),
+                        )
+                    })
+                    .collect();
+                let viewers = (0..rng.gen_range(0..=max_views))
+                    .map(|_| rng.gen_range(0..users))
+                    .collect();
+                feeds.push(FeedPlan {
+                    author,
+                    content: FEED_LINES.choose(&mut rng).unwrap_or(&FEED_LINES[0]),
+                    likers,
+                    comments,
+                    viewers,
+                });
+            }
+        }
+        SeedPlan { users, feeds }
+    }
+}
+
+/// Create the users, feeds, likes, comments and views of `plan`.
+///
+/// Writes go through the same stores and Kafka events as the API, so profiles, search,
+/// notifications and the `top:*` rankings pick the data up. Not transactional: on error,
+/// whatever was created so far stays.
+#[allow(clippy::too_many_arguments)]
+pub async fn seed(
+    users: &dyn UserRepository,
+    feeds: &dyn FeedRepository,
+    likes: &dyn LikeRepository,
+    mongo_db: &MongoDatabase,
+    kafka_producer: &KafkaProducer,
+    config: &Config,
+    plan: &SeedPlan,
+) -> Result<SeedResponse, anyhow::Error> {
+    // One bcrypt hash for everyone; hashing per user would dominate the run
+    let password = Uuid::new_v4().simple().to_string();
+    let password_hash = hash_password(&password)?;
+    // Keeps emails and usernames unique across runs
+    let run = &Uuid::new_v4().simple().to_string()[..8];
+
+    let mut user_ids = Vec::with_capacity(plan.users);
+    for i in 0..plan.users {
+        let email = format!("seed-{}-{}@example.com", run, i);
+        let username = config.usernames.normalize(&format!("seed_{}_{}", run, i));
+        let user = users.create(&email, &username, &password_hash).await?;
+        send_event(
+            kafka_producer,
+            "user_events",
+            user.id,
+            &UserCreatedEvent::new(user.id as u64, user.email, user.username),
+        )
+        .await;
+        user_ids.push(user.id);
+    }
+
+    let comment_collection = mongo_collection::<Comment>(mongo_db, config, "comments");
+    let view_collection = mongo_collection::<FeedView>(mongo_db, config, "feed_views");
+    let mut feed_ids = Vec::with_capacity(plan.feeds.len());
+    let mut comment_ids = Vec::new();
+    let (mut like_total, mut view_total) = (0, 0);
+    for feed_plan in &plan.feeds {
+        let author_id = user_ids[feed_plan.author];
+        let feed = feeds
+            .create(author_id, feed_plan.content, None, None, None, None, true)
+            .await?;
+        send_event(
+            kafka_producer,
+            "feed_events",
+            feed.id,
+            &FeedCreatedEvent::new(feed.id as u64, author_id, feed.content.clone()),
+        )
+        .await;
+        feed_ids.push(feed.id);
+
+        for &liker in &feed_plan.likers {
+            let user_id = user_ids[liker];
+            likes.insert(feed.id, user_id).await?;
+            send_event(
+                kafka_producer,
+                "feed_events",
+                feed.id,
+                &FeedLikedEvent::new(feed.id, user_id),
+            )
+            .await;
+            like_total += 1;
+        }
+
+        for &(commenter, content) in &feed_plan.comments {
+            let comment = Comment {
+                id: Some(Uuid::new_v4().to_string()),
+                feed_id: feed.id,
+                user_id: user_ids[commenter],
+                content: content.to_string(),
+                created_at: Utc::now(),
+                updated_at: None,
+                parent_comment_id: None,
+            };
+            comment_collection.insert_one(&comment, None).await?;
+            let comment_id = comment.id.unwrap_or_default();
+            send_event(
+                kafka_producer,
+                "feed_events",
+                feed.id,
+                &FeedCommentedEvent::new(
+                    feed.id,
+                    comment.user_id,
+                    comment_id.clone(),
+                    comment.content,
+                ),
+            )
+            .await;
+            comment_ids.push(comment_id);
+        }
+
+        // Stored unsampled: seeded data is small and should count exactly
+        let views: Vec<FeedView> = feed_plan
+            .viewers
+            .iter()
+            .map(|&viewer| FeedView {
+                id: Some(Uuid::new_v4().to_string()),
+                feed_id: feed.id,
+                user_id: user_ids[viewer],
+                viewed_at: Utc::now(),
+                weight: 1.0,
+            })
+            .collect();
+        if !views.is_empty() {
+            view_collection.insert_many(&views, None).await?;
+        }
+        for view in &views {
+            send_event(
+                kafka_producer,
+                "feed_events",
+                feed.id,
+                &FeedViewedEvent::new(feed.id, view.user_id),
+            )
+            .await;
+        }
+        view_total += views.len() as u64;
+    }
+
+    info!(
+        "Seeded {} users, {} feeds, {} likes, {} comments and {} views",
+        user_ids.len(),
+        feed_ids.len(),
+        like_total,
+        comment_ids.len(),
+        view_total
+    );
+
+    Ok(SeedResponse {
+        user_ids,
+        feed_ids,
+        comment_ids,
+        likes: like_total,
+        views: view_total,
+        password,
+    })
+}
+
+async fn send_event<E: Serialize>(
+    kafka_producer: &KafkaProducer,
+    topic: &str,
+    key: i64,
+    event: &E,
+) {
+    if let Ok(event_json) = serde_json::to_string(event) {
+        if let Err(e) = kafka_producer
+            .send_message(topic, &key.to_string(), &event_json)
+            .await
+        {
+            warn!("Failed to send Kafka event: {:?}", e);
+        }
+    }
+}
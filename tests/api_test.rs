@@ -2,15 +2,27 @@
 // These tests can be run in CI/CD pipelines (e.g., GitHub Actions)
 // Run with: cargo test --test api_test
 
-use actix_web::{http::StatusCode, test, web, App};
+use actix_web::{
+    http::{header, StatusCode},
+    middleware::{from_fn, Compress, Condition},
+    test, web, App,
+};
 use example_rust_web_service::{
-    api, config::Config, db,
+    api,
+    config::{Config, JwtExpiryMode},
+    db,
     kafka::KafkaProducer,
+    repositories::{self, MysqlRepository},
+    services,
     models::{
-        AuthResponse, FeedResponse,
+        ActivityResponse, ActivityType, AuthResponse, CommentPage, CommentResponse,
+        FeedCategoriesResponse, FeedResponse, FeedRevision, NotificationPreferences, SessionResponse, UserProfileResponse,
     },
 };
 use serde_json::json;
+use std::sync::Arc;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 /// Generate unique test identifier using nanoseconds for better uniqueness
 fn generate_test_id() -> String {
@@ -26,7 +38,7 @@ async fn create_test_app() -> App<
     impl actix_web::dev::ServiceFactory<
         actix_web::dev::ServiceRequest,
         Config = (),
-        Response = actix_web::dev::ServiceResponse,
+        Response = actix_web::dev::ServiceResponse<impl actix_web::body::MessageBody>,
         Error = actix_web::Error,
         InitError = (),
     >,
@@ -41,25 +53,82 @@ async fn create_test_app() -> App<
     let redis_client = db::create_redis_client(&config).expect("Failed to create Redis client");
     let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
 
+    let repository = Arc::new(MysqlRepository::new(mysql_pool.clone()));
+    let search_backend = services::search::create_search_backend(&config, &mysql_pool);
+
+    let compression = config.server.compression;
+    let cors_config = config.cors.clone();
+    let sliding_sessions = config.jwt.expiry_mode == JwtExpiryMode::Sliding;
     App::new()
+        .wrap(Condition::new(
+            sliding_sessions,
+            from_fn(api::sliding_session::refresh_sliding_session),
+        ))
+        .wrap(Condition::new(compression, Compress::default()))
+        .wrap(from_fn(api::request_id::propagate_request_id))
         .app_data(web::Data::new(config))
         .app_data(web::Data::new(mysql_pool))
         .app_data(web::Data::new(mongodb_db))
         .app_data(web::Data::new(redis_client))
         .app_data(web::Data::new(kafka_producer))
+        .configure(repositories::register(repository))
+        .app_data(web::Data::from(search_backend))
+        .route("/ready", web::get().to(api::health::ready))
+        .route("/metrics", web::get().to(api::health::metrics))
         .service(
             web::scope("/api")
+                .wrap(from_fn(api::ip_throttle::throttle_by_ip))
                 .service(
                     web::scope("/auth")
+                        .wrap(api::cors::cors(&cors_config, "auth"))
                         .route("/signup", web::post().to(api::auth::signup))
-                        .route("/login", web::post().to(api::auth::login)),
+                        .route("/login", web::post().to(api::auth::login))
+                        .route("/sessions", web::get().to(api::auth::get_sessions))
+                        .route(
+                            "/sessions/{jti}",
+                            web::delete().to(api::auth::delete_session),
+                        )
+                        .route("/nonce", web::get().to(api::auth::get_nonce))
+                        .route("/password", web::put().to(api::auth::change_password)),
                 )
                 .service(
                     web::scope("/feed")
+                        .wrap(api::cors::cors(&cors_config, "feed"))
                         .route("", web::post().to(api::feed::create_feed))
                         .route("", web::get().to(api::feed::get_feeds))
+                        .route("/preview", web::post().to(api::feed::preview_feed))
+                        .route("/search", web::get().to(api::feed::search_feeds))
+                        .route(
+                            "/liked-status",
+                            web::post().to(api::feed::get_liked_status),
+                        )
+                        .route("/batch", web::post().to(api::feed::get_feeds_batch))
+                        .route(
+                            "/unseen-count",
+                            web::get().to(api::feed::get_unseen_count),
+                        )
+                        .route(
+                            "/categories",
+                            web::get().to(api::feed::get_feed_categories),
+                        )
+                        .route("/{feed_id}", web::get().to(api::feed::get_feed))
+                        .route("/{feed_id}", web::patch().to(api::feed::update_feed))
                         .route("/{feed_id}/like", web::post().to(api::feed::like_feed))
                         .route("/{feed_id}/like", web::delete().to(api::feed::unlike_feed))
+                        .route("/{feed_id}/bookmark", web::post().to(api::feed::bookmark_feed))
+                        .route(
+                            "/{feed_id}/bookmark",
+                            web::delete().to(api::feed::unbookmark_feed),
+                        )
+                        .route("/{feed_id}/pin", web::post().to(api::feed::pin_feed))
+                        .route("/{feed_id}/pin", web::delete().to(api::feed::unpin_feed))
+                        .route("/{feed_id}/report", web::post().to(api::feed::report_feed))
+                        .route("/{feed_id}/history", web::get().to(api::feed::get_feed_history))
+                        .route("/{feed_id}/react", web::post().to(api::feed::react_feed))
+                        .route(
+                            "/{feed_id}/react/{reaction_type}",
+                            web::delete().to(api::feed::unreact_feed),
+                        )
                         .route(
                             "/{feed_id}/comment",
                             web::post().to(api::feed::comment_feed),
@@ -68,18 +137,36 @@ async fn create_test_app() -> App<
                             "/{feed_id}/comments",
                             web::get().to(api::feed::get_comments),
                         )
+                        .route(
+                            "/{feed_id}/comments/export",
+                            web::get().to(api::feed::export_comments),
+                        )
+                        .route(
+                            "/{feed_id}/comment-tree",
+                            web::get().to(api::feed::get_comment_tree),
+                        )
                         .route("/{feed_id}/view", web::post().to(api::feed::view_feed)),
                 )
                 .service(
                     web::scope("/notify")
+                        .wrap(api::cors::cors(&cors_config, "notify"))
                         .route("", web::get().to(api::notify::get_notifications))
+                        .route(
+                            "/read",
+                            web::put().to(api::notify::mark_notifications_read),
+                        )
                         .route(
                             "/{notification_id}/read",
                             web::put().to(api::notify::mark_notification_read),
+                        )
+                        .route(
+                            "/{notification_id}/unread",
+                            web::put().to(api::notify::mark_notification_unread),
                         ),
                 )
                 .service(
                     web::scope("/top")
+                        .wrap(api::cors::cors(&cors_config, "top"))
                         .route("/users-liked", web::get().to(api::top::get_top_users_liked))
                         .route(
                             "/feeds-commented",
@@ -89,7 +176,63 @@ async fn create_test_app() -> App<
                             "/feeds-viewed",
                             web::get().to(api::top::get_top_feeds_viewed),
                         )
-                        .route("/feeds-liked", web::get().to(api::top::get_top_feeds_liked)),
+                        .route(
+                            "/feeds-liked/stream",
+                            web::get().to(api::top::stream_top_feeds_liked),
+                        )
+                        .route("/feeds-liked", web::get().to(api::top::get_top_feeds_liked))
+                        .route("/trending", web::get().to(api::top::get_trending_feeds)),
+                )
+                .service(
+                    web::scope("/admin")
+                        .wrap(from_fn(api::audit::record_admin_action))
+                        .wrap(api::cors::cors(&cors_config, "admin"))
+                        .route("/users", web::get().to(api::admin::list_users))
+                        .route("/reports", web::get().to(api::admin::list_reports))
+                        .route(
+                            "/reports/{report_id}/resolve",
+                            web::post().to(api::admin::resolve_report),
+                        )
+                        .route("/dlq", web::get().to(api::admin::list_dead_letters))
+                        .route(
+                            "/dlq/replay",
+                            web::post().to(api::admin::replay_dead_letter),
+                        )
+                        .route(
+                            "/top/backfill",
+                            web::post().to(api::admin::backfill_rankings),
+                        )
+                        .route("/seed", web::post().to(api::admin::seed_data))
+                        .route(
+                            "/feed/{feed_id}/like/{user_id}",
+                            web::delete().to(api::admin::remove_like),
+                        ),
+                )
+                .service(
+                    web::scope("/users")
+                        .wrap(api::cors::cors(&cors_config, "users"))
+                        .route("/me", web::delete().to(api::users::delete_my_account))
+                        .route("/me/purge", web::delete().to(api::users::purge_my_account))
+                        .route("/me/export", web::get().to(api::users::export_my_data))
+                        .route("/me/bookmarks", web::get().to(api::users::get_my_bookmarks))
+                        .route("/me/profile", web::put().to(api::users::update_my_profile))
+                        .route(
+                            "/me/notification-preferences",
+                            web::put().to(api::users::update_my_notification_preferences),
+                        )
+                        .route("/{id}", web::get().to(api::users::get_user))
+                        .route(
+                            "/{id}/activity",
+                            web::get().to(api::users::get_user_activity),
+                        )
+                        .route(
+                            "/{id}/common-likes",
+                            web::get().to(api::users::get_common_likes),
+                        )
+                        .route(
+                            "/{id}/liked-feeds",
+                            web::get().to(api::users::get_user_liked_feeds),
+                        ),
                 ),
         )
 }
@@ -275,7 +418,7 @@ async fn test_create_feed() {
     assert_eq!(feed.content, "Test feed content", "Feed content should match");
     assert_eq!(feed.like_count, 0, "New feed should have 0 likes");
     assert_eq!(feed.comment_count, 0, "New feed should have 0 comments");
-    assert_eq!(feed.is_liked, false, "New feed should not be liked");
+    assert!(!feed.is_liked, "New feed should not be liked");
 }
 
 #[actix_web::test]
@@ -679,7 +822,7 @@ async fn test_get_comments() {
     );
 
     let comments: Vec<serde_json::Value> = test::read_body_json(resp).await;
-    assert!(comments.len() > 0, "Comments list should not be empty");
+    assert!(!comments.is_empty(), "Comments list should not be empty");
 }
 
 #[actix_web::test]
@@ -843,3 +986,3592 @@ async fn test_login_wrong_password() {
     assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
 }
 
+
+#[actix_web::test]
+async fn test_scheduled_feed_hidden_until_publish_at() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("scheduled{}@example.com", test_id),
+        "username": format!("scheduled{}", test_id),
+        "password": "password123"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+
+    // Schedule a feed one hour in the future
+    let publish_at = chrono::Utc::now() + chrono::Duration::hours(1);
+    let feed_req = json!({
+        "content": "Scheduled feed content",
+        "publish_at": publish_at.to_rfc3339()
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(&feed_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let feed: FeedResponse = test::read_body_json(resp).await;
+    assert!(feed.publish_at.is_some(), "Scheduled feed should return publish_at");
+
+    // Scheduled feed should not be listed yet
+    let req = test::TestRequest::get().uri("/api/feed").to_request();
+    let resp = test::call_service(&app, req).await;
+    let feeds: Vec<FeedResponse> = test::read_body_json(resp).await;
+    assert!(
+        feeds.iter().all(|f| f.id != feed.id),
+        "Scheduled feed should be excluded from get_feeds"
+    );
+}
+
+#[actix_web::test]
+async fn test_list_and_revoke_sessions() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("session{}@example.com", test_id),
+        "username": format!("session{}", test_id),
+        "password": "password123"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .insert_header(("User-Agent", "api-test"))
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+
+    // The signup token should be listed as the current session
+    let req = test::TestRequest::get()
+        .uri("/api/auth/sessions")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let sessions: Vec<SessionResponse> = test::read_body_json(resp).await;
+    assert_eq!(sessions.len(), 1, "New user should have one session");
+    assert!(sessions[0].is_current, "Session should be the current one");
+    assert_eq!(sessions[0].user_agent.as_deref(), Some("api-test"));
+
+    // Revoke it
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/auth/sessions/{}", sessions[0].jti))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Revoked token should no longer be accepted
+    let req = test::TestRequest::get()
+        .uri("/api/auth/sessions")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::UNAUTHORIZED,
+        "Revoked token should be rejected"
+    );
+}
+
+#[actix_web::test]
+async fn test_mark_notifications_read_batch() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("markread{}@example.com", test_id),
+        "username": format!("markread{}", test_id),
+        "password": "password123"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+
+    // IDs the user doesn't own are reported as failed
+    let req = test::TestRequest::put()
+        .uri("/api/notify/read")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"ids": ["not-my-notification"]}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["succeeded"], json!([]));
+    assert_eq!(body["failed"][0]["id"], "not-my-notification");
+    assert_eq!(body["failed"][0]["error"], "Notification not found");
+
+    // Too many IDs is rejected
+    let ids: Vec<String> = (0..101).map(|i| i.to_string()).collect();
+    let req = test::TestRequest::put()
+        .uri("/api/notify/read")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "ids": ids }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_get_comments_sort() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/feed/1/comments?sort=oldest")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK, "sort=oldest should be accepted");
+
+    let req = test::TestRequest::get()
+        .uri("/api/feed/1/comments?sort=sideways")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::BAD_REQUEST,
+        "Unknown sort value should be rejected"
+    );
+}
+
+#[actix_web::test]
+async fn test_update_and_get_profile() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("profile{}@example.com", test_id),
+        "username": format!("profile{}", test_id),
+        "password": "password123"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+    let user_id = body.user.id;
+
+    // Update bio and avatar
+    let req = test::TestRequest::put()
+        .uri("/api/users/me/profile")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({
+            "bio": "Hello there",
+            "avatar_url": "https://example.com/avatar.png"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let profile: UserProfileResponse = test::read_body_json(resp).await;
+    assert_eq!(profile.bio, "Hello there");
+
+    // Invalid avatar URL is rejected
+    let req = test::TestRequest::put()
+        .uri("/api/users/me/profile")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"avatar_url": "javascript:alert(1)"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // Public profile includes the updated fields
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/users/{}", user_id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let profile: UserProfileResponse = test::read_body_json(resp).await;
+    assert_eq!(profile.bio, "Hello there");
+    assert_eq!(
+        profile.avatar_url.as_deref(),
+        Some("https://example.com/avatar.png")
+    );
+}
+
+#[actix_web::test]
+async fn test_get_liked_status() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("likedstatus{}@example.com", test_id),
+        "username": format!("likedstatus{}", test_id),
+        "password": "password123"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+
+    let mut feed_ids = Vec::new();
+    for content in ["Liked feed", "Not liked feed"] {
+        let req = test::TestRequest::post()
+            .uri("/api/feed")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({ "content": content }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let feed: FeedResponse = test::read_body_json(resp).await;
+        feed_ids.push(feed.id);
+    }
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/like", feed_ids[0]))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed/liked-status")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "feed_ids": [feed_ids[0], feed_ids[1], -1] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(
+        body["succeeded"],
+        json!([
+            { "feed_id": feed_ids[0], "liked": true },
+            { "feed_id": feed_ids[1], "liked": false }
+        ])
+    );
+    assert_eq!(body["failed"], json!([{ "id": "-1", "error": "Feed not found" }]));
+
+    // Batch fetch returns found feeds and reports unknown IDs
+    let req = test::TestRequest::post()
+        .uri("/api/feed/batch")
+        .set_json(json!({ "feed_ids": [feed_ids[1], -1, feed_ids[0]] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["succeeded"][0]["id"], feed_ids[1]);
+    assert_eq!(body["succeeded"][1]["id"], feed_ids[0]);
+    assert_eq!(body["failed"], json!([{ "id": "-1", "error": "Feed not found" }]));
+
+    // Too many IDs is rejected
+    let ids: Vec<i64> = (0..101).collect();
+    let req = test::TestRequest::post()
+        .uri("/api/feed/liked-status")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "feed_ids": ids }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_openapi_json_served_compressed() {
+    let app = test::init_service(
+        App::new().wrap(Compress::default()).service(
+            SwaggerUi::new("/api/docs/{_:.*}")
+                .url("/api-docs/openapi.json", api::ApiDoc::openapi()),
+        ),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api-docs/openapi.json")
+        .insert_header((header::ACCEPT_ENCODING, "gzip"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers().get(header::CONTENT_ENCODING).unwrap(),
+        "gzip",
+        "OpenAPI JSON should be gzip encoded when requested"
+    );
+
+    // Clients that don't ask for compression still get plain JSON
+    let req = test::TestRequest::get()
+        .uri("/api-docs/openapi.json")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp.headers().get(header::CONTENT_ENCODING).is_none());
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["paths"]["/api/feed"].is_object());
+}
+
+#[actix_web::test]
+async fn test_comment_rate_limited_per_feed() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("commentlimit{}@example.com", test_id),
+        "username": format!("commentlimit{}", test_id),
+        "password": "password123"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"content": "Feed for comment rate limit"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+
+    let comment = |content: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/comment", feed.id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({ "content": content }))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, comment("First")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Second comment on the same feed within the cooldown is rejected
+    let resp = test::call_service(&app, comment("Second")).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("Retry-After"));
+}
+
+#[actix_web::test]
+async fn test_get_top_feeds_liked_beyond_cache() {
+    let app = test::init_service(create_test_app().await).await;
+
+    // Page 101 of 10 starts at rank 1000, past what the job caches in Redis
+    let req = test::TestRequest::get()
+        .uri("/api/top/feeds-liked?page=101&limit=10")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::OK,
+        "Deep top pages should be served from the database"
+    );
+    let body: Vec<serde_json::Value> = test::read_body_json(resp).await;
+    assert!(body.len() <= 10);
+}
+
+#[actix_web::test]
+async fn test_get_trending_feeds() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("trending{}@example.com", test_id),
+        "username": format!("trending{}", test_id),
+        "password": "password123"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"content": "Trending feed"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/like", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    for window in ["day", "week"] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/top/trending?window={}&limit=1000", window))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get("X-Trending-Window").unwrap(),
+            window,
+            "Window should be echoed"
+        );
+        let body: Vec<serde_json::Value> = test::read_body_json(resp).await;
+        let entry = body
+            .iter()
+            .find(|f| f["feed_id"] == feed.id)
+            .expect("Liked feed should be trending");
+        assert!(entry["count"].as_i64().unwrap() >= 3, "A like is worth 3 points");
+        assert!(body
+            .windows(2)
+            .all(|w| w[0]["count"].as_i64() >= w[1]["count"].as_i64()));
+    }
+
+    // Window defaults to day
+    let req = test::TestRequest::get().uri("/api/top/trending").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("X-Trending-Window").unwrap(), "day");
+
+    let req = test::TestRequest::get()
+        .uri("/api/top/trending?window=month")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_get_user_activity() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("activity{}@example.com", test_id),
+        "username": format!("activity{}", test_id),
+        "password": "password123"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+    let user_id = body.user.id;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"content": "Activity feed"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/like", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/comment", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"content": "Activity comment"}))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/users/{}/activity", user_id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let activity: ActivityResponse = test::read_body_json(resp).await;
+    assert_eq!(activity.items.len(), 3, "Feed, like and comment expected");
+    for activity_type in [ActivityType::Feed, ActivityType::Like, ActivityType::Comment] {
+        assert!(activity
+            .items
+            .iter()
+            .any(|item| item.activity_type == activity_type && item.feed_id == feed.id));
+    }
+    assert!(activity
+        .items
+        .windows(2)
+        .all(|w| w[0].created_at >= w[1].created_at));
+    assert!(activity.next_before.is_none());
+
+    // A full page points at the next one
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/users/{}/activity?limit=1", user_id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let activity: ActivityResponse = test::read_body_json(resp).await;
+    assert_eq!(activity.items.len(), 1);
+    assert!(activity.next_before.is_some());
+
+    let req = test::TestRequest::get()
+        .uri("/api/users/999999999999/activity")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_admin_remove_like() {
+    use example_rust_web_service::entities::user;
+    use example_rust_web_service::models::AuditLogEntry;
+    use mongodb::bson::doc;
+    use mongodb::options::FindOptions;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    let mut user_ids = Vec::new();
+    for role in ["liker", "moderator"] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("{}{}@example.com", role, test_id),
+                "username": format!("{}{}", role, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let body: AuthResponse = test::read_body_json(resp).await;
+        tokens.push(body.token);
+        user_ids.push(body.user.id);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({"content": "Feed with moderated like"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/like", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let remove_like = || {
+        test::TestRequest::delete()
+            .uri(&format!("/api/admin/feed/{}/like/{}", feed.id, user_ids[0]))
+            .insert_header(("Authorization", format!("Bearer {}", tokens[1])))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, remove_like()).await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::FORBIDDEN,
+        "Non-admin users must not remove likes"
+    );
+
+    // Promote the moderator directly in the database
+    let config = Config::from_env().expect("Failed to load configuration");
+    let pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    user::ActiveModel {
+        id: Set(user_ids[1]),
+        is_admin: Set(true),
+        ..Default::default()
+    }
+    .update(&pool)
+    .await
+    .expect("Failed to promote user");
+
+    let resp = test::call_service(&app, remove_like()).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = test::call_service(&app, remove_like()).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // Every attempt is audited, the rejected one included
+    let mongo_db = db::create_mongodb_client(&config)
+        .await
+        .expect("Failed to create MongoDB client");
+    let options = FindOptions::builder().sort(doc! {"created_at": 1, "_id": 1}).build();
+    let mut cursor = mongo_db
+        .collection::<AuditLogEntry>("audit_log")
+        .find(doc! {"user_id": user_ids[1]}, options)
+        .await
+        .expect("Failed to query audit log");
+    let mut entries = Vec::new();
+    while cursor.advance().await.unwrap() {
+        entries.push(cursor.deserialize_current().unwrap());
+    }
+    let mut statuses: Vec<u16> = entries.iter().map(|e| e.status).collect();
+    statuses.sort();
+    assert_eq!(statuses, vec![200, 403, 404]);
+    for entry in &entries {
+        assert_eq!(entry.action, "DELETE /api/admin/feed/{feed_id}/like/{user_id}");
+        assert_eq!(entry.target["feed_id"], feed.id.to_string());
+        assert_eq!(entry.target["user_id"], user_ids[0].to_string());
+        assert_eq!(entry.success, entry.status == 200);
+    }
+}
+
+#[actix_web::test]
+async fn test_update_notification_preferences() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("prefs{}@example.com", test_id),
+        "username": format!("prefs{}", test_id),
+        "password": "password123"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+
+    let req = test::TestRequest::put()
+        .uri("/api/users/me/notification-preferences")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"likes_enabled": false}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let prefs: NotificationPreferences = test::read_body_json(resp).await;
+    assert!(!prefs.likes_enabled);
+    assert!(prefs.comments_enabled, "Untouched toggles stay enabled");
+    assert!(prefs.mentions_enabled);
+
+    // Omitted fields are left unchanged
+    let req = test::TestRequest::put()
+        .uri("/api/users/me/notification-preferences")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"comments_enabled": false}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let prefs: NotificationPreferences = test::read_body_json(resp).await;
+    assert!(!prefs.likes_enabled);
+    assert!(!prefs.comments_enabled);
+
+    let req = test::TestRequest::put()
+        .uri("/api/users/me/notification-preferences")
+        .set_json(json!({"likes_enabled": true}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_mark_notification_read_with_object_id() {
+    use mongodb::bson::{doc, oid::ObjectId, Document};
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("oid{}@example.com", test_id),
+        "username": format!("oid{}", test_id),
+        "password": "password123"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+    let user_id = body.user.id;
+
+    // Insert a notification the way an external writer might, with a native ObjectId _id
+    let config = Config::from_env().expect("Failed to load configuration");
+    let mongo_db = db::create_mongodb_client(&config)
+        .await
+        .expect("Failed to create MongoDB client");
+    let collection = mongo_db.collection::<Document>("notifications");
+    let oid = ObjectId::new();
+    collection
+        .insert_one(
+            doc! {
+                "_id": oid,
+                "user_id": user_id,
+                "from_user_id": 0_i64,
+                "from_username": "system",
+                "feed_id": 0_i64,
+                "notification_type": "like",
+                "content": "legacy notification",
+                "created_at": chrono::Utc::now().timestamp(),
+                "is_read": false,
+            },
+            None,
+        )
+        .await
+        .expect("Failed to insert notification");
+
+    // Listing returns the ObjectId as its hex string
+    let req = test::TestRequest::get()
+        .uri("/api/notify")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let ids: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|n| n["id"].as_str())
+        .collect();
+    assert_eq!(ids, vec![oid.to_hex().as_str()]);
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/notify/{}/read", oid.to_hex()))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let unread = collection
+        .count_documents(doc! {"user_id": user_id, "is_read": false}, None)
+        .await
+        .unwrap();
+    let read = collection
+        .count_documents(doc! {"user_id": user_id, "is_read": true}, None)
+        .await
+        .unwrap();
+    assert_eq!((unread, read), (0, 1), "Exactly one document should be updated");
+
+    // Marking it unread again restores is_read = false
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/notify/{}/unread", oid.to_hex()))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let unread = collection
+        .count_documents(doc! {"user_id": user_id, "is_read": false}, None)
+        .await
+        .unwrap();
+    assert_eq!(unread, 1);
+
+    // An ID that matches nothing is reported as not found
+    for action in ["read", "unread"] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/api/notify/{}/{}", ObjectId::new().to_hex(), action))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
+
+#[actix_web::test]
+async fn test_trim_notifications_keeps_newest() {
+    use mongodb::bson::{doc, Document};
+
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.notifications.max_per_user = 2;
+    let mongo_db = db::create_mongodb_client(&config)
+        .await
+        .expect("Failed to create MongoDB client");
+    let collection = mongo_db.collection::<Document>("notifications");
+
+    // Use a user id no real account has so other tests' data is not trimmed
+    let user_id = -generate_test_id().parse::<i64>().unwrap();
+    let now = chrono::Utc::now().timestamp();
+    for age in 0..4 {
+        collection
+            .insert_one(
+                doc! {
+                    "_id": uuid::Uuid::new_v4().to_string(),
+                    "user_id": user_id,
+                    "from_user_id": 0_i64,
+                    "from_username": "system",
+                    "feed_id": 0_i64,
+                    "notification_type": "like",
+                    "content": format!("age {}", age),
+                    "created_at": now - age,
+                    "is_read": false,
+                },
+                None,
+            )
+            .await
+            .expect("Failed to insert notification");
+    }
+
+    example_rust_web_service::jobs::trim_notifications(&mongo_db, &config).await;
+
+    let mut cursor = collection
+        .find(doc! {"user_id": user_id}, None)
+        .await
+        .unwrap();
+    let mut remaining = Vec::new();
+    while cursor.advance().await.unwrap() {
+        let notification = cursor.deserialize_current().unwrap();
+        remaining.push(notification.get_str("content").unwrap().to_string());
+    }
+    remaining.sort();
+    assert_eq!(remaining, vec!["age 0", "age 1"], "Only the newest two are kept");
+}
+
+#[actix_web::test]
+async fn test_ready_reflects_consumer_heartbeat() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    // A group of its own so a running server's consumers don't affect the result
+    config.kafka.group_id = format!("ready_test_{}", generate_test_id());
+    let redis_client = db::create_redis_client(&config).expect("Failed to create Redis client");
+    let key = example_rust_web_service::kafka::consumer_heartbeat_key(&config);
+    let max_age = config.kafka.consumer_heartbeat_max_age_seconds as i64;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(redis_client.clone()))
+            .route("/ready", web::get().to(api::health::ready)),
+    )
+    .await;
+
+    // No consumer has checked in yet
+    let req = test::TestRequest::get().uri("/ready").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let mut conn = redis_client.get_async_connection().await.unwrap();
+    let now = chrono::Utc::now().timestamp();
+    let _: () = redis::cmd("HSET")
+        .arg(&key)
+        .arg("feed_events")
+        .arg(now)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+
+    let req = test::TestRequest::get().uri("/ready").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // A second consumer whose loop stopped makes the service unready
+    let _: () = redis::cmd("HSET")
+        .arg(&key)
+        .arg("user_events")
+        .arg(now - max_age - 1)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+
+    let req = test::TestRequest::get().uri("/ready").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["consumers"]["user_events"].as_i64().unwrap() > max_age);
+
+    let _: () = redis::cmd("DEL")
+        .arg(&key)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn test_admin_list_users() {
+    use example_rust_web_service::entities::user;
+    use example_rust_web_service::models::AdminUserResponse;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    let mut user_ids = Vec::new();
+    for name in ["admin", "bob", "carol"] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("{}{}@example.com", name, test_id),
+                "username": format!("{}{}", name, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let body: AuthResponse = test::read_body_json(resp).await;
+        tokens.push(body.token);
+        user_ids.push(body.user.id);
+    }
+
+    let list = |query: String| {
+        test::TestRequest::get()
+            .uri(&format!("/api/admin/users?{}", query))
+            .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, list(String::new())).await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::FORBIDDEN,
+        "Non-admin users must not list users"
+    );
+
+    let config = Config::from_env().expect("Failed to load configuration");
+    let pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    user::ActiveModel {
+        id: Set(user_ids[0]),
+        is_admin: Set(true),
+        ..Default::default()
+    }
+    .update(&pool)
+    .await
+    .expect("Failed to promote user");
+
+    let resp = test::call_service(&app, list(format!("username={}&sort=username", test_id))).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let users: Vec<AdminUserResponse> = test::read_body_json(resp).await;
+    let names: Vec<String> = users.iter().map(|u| u.username.clone()).collect();
+    assert_eq!(
+        names,
+        vec![
+            format!("admin{}", test_id),
+            format!("bob{}", test_id),
+            format!("carol{}", test_id)
+        ]
+    );
+    assert!(users[0].is_admin);
+
+    // Pagination
+    let resp = test::call_service(
+        &app,
+        list(format!("username={}&sort=username&page=2&limit=2", test_id)),
+    )
+    .await;
+    let users: Vec<AdminUserResponse> = test::read_body_json(resp).await;
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].id, user_ids[2]);
+
+    // Email filter
+    let resp = test::call_service(&app, list(format!("email=bob{}", test_id))).await;
+    let users: Vec<AdminUserResponse> = test::read_body_json(resp).await;
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].id, user_ids[1]);
+
+    // Date filter
+    let resp = test::call_service(
+        &app,
+        list(format!(
+            "username={}&created_after=2999-01-01T00:00:00Z",
+            test_id
+        )),
+    )
+    .await;
+    let users: Vec<AdminUserResponse> = test::read_body_json(resp).await;
+    assert!(users.is_empty());
+
+    let resp = test::call_service(&app, list("sort=random".to_string())).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_sensitive_operations_consume_nonce() {
+    use example_rust_web_service::models::NonceResponse;
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let email = format!("nonce{}@example.com", test_id);
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": email,
+            "username": format!("nonce{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+
+    let req = test::TestRequest::get()
+        .uri("/api/auth/nonce")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let nonce: NonceResponse = test::read_body_json(resp).await;
+
+    let change_password = |nonce: &str, new_password: &str| {
+        test::TestRequest::put()
+            .uri("/api/auth/password")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({
+                "current_password": "password123",
+                "new_password": new_password,
+                "nonce": nonce
+            }))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, change_password(&nonce.nonce, "password456")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // The same nonce cannot be replayed
+    let resp = test::call_service(&app, change_password(&nonce.nonce, "password789")).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = test::call_service(&app, change_password("made-up", "password789")).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({"email": email, "password": "password456"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK, "New password should work");
+
+    let req = test::TestRequest::get()
+        .uri("/api/auth/nonce")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let nonce: NonceResponse = test::read_body_json(resp).await;
+
+    let req = test::TestRequest::delete()
+        .uri("/api/users/me")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"password": "password456", "nonce": nonce.nonce}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({"email": email, "password": "password456"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND, "Account should be gone");
+}
+
+#[actix_web::test]
+async fn test_export_my_data() {
+    use example_rust_web_service::models::UserDataExport;
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    let mut user_ids = Vec::new();
+    for name in ["exporter", "visitor"] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("{}{}@example.com", name, test_id),
+                "username": format!("{}{}", name, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let body: AuthResponse = test::read_body_json(resp).await;
+        tokens.push(body.token);
+        user_ids.push(body.user.id);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({"content": "Feed to export"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+
+    for (token, content) in [(&tokens[0], "Own comment"), (&tokens[1], "Visitor comment")] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/comment", feed.id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"content": content}))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/like", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", tokens[1])))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/users/me/export")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp
+        .headers()
+        .get(header::CONTENT_DISPOSITION)
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("attachment"));
+    let body = test::read_body(resp).await;
+    let raw = std::str::from_utf8(&body).unwrap();
+    assert!(
+        !raw.contains(&format!("visitor{}", test_id)) && !raw.contains("Visitor comment"),
+        "Other users' data must not be exported"
+    );
+
+    let export: UserDataExport = serde_json::from_slice(&body).unwrap();
+    assert_eq!(export.account.id, user_ids[0]);
+    assert_eq!(export.feeds.len(), 1);
+    assert_eq!(export.feeds[0].id, feed.id);
+    assert!(export.likes.is_empty());
+    assert_eq!(export.comments.len(), 1);
+    assert_eq!(export.comments[0].content, "Own comment");
+
+    let req = test::TestRequest::get()
+        .uri("/api/users/me/export")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[1])))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let export: UserDataExport = test::read_body_json(resp).await;
+    assert!(export.feeds.is_empty());
+    assert_eq!(export.likes.len(), 1);
+    assert_eq!(export.likes[0].feed_id, feed.id);
+    assert_eq!(export.comments[0].content, "Visitor comment");
+}
+
+#[actix_web::test]
+async fn test_purge_my_account() {
+    use example_rust_web_service::models::UserDataExport;
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    for name in ["purged", "bystander"] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("{}{}@example.com", name, test_id),
+                "username": format!("{}{}", name, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let body: AuthResponse = test::read_body_json(resp).await;
+        tokens.push(body.token);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({"content": "Feed to purge"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/comment", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", tokens[1])))
+        .set_json(json!({"content": "Comment on a purged feed"}))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/like", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", tokens[1])))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    // Wrong password leaves everything in place
+    let req = test::TestRequest::delete()
+        .uri("/api/users/me/purge")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({"password": "wrong-password"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::delete()
+        .uri("/api/users/me/purge")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({"password": "password123"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({
+            "email": format!("purged{}@example.com", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // The bystander's comment and like on the purged feed are gone too
+    let req = test::TestRequest::get()
+        .uri("/api/users/me/export")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[1])))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let export: UserDataExport = test::read_body_json(resp).await;
+    assert!(export.comments.is_empty());
+    assert!(export.likes.is_empty());
+}
+
+#[actix_web::test]
+async fn test_comment_pagination_headers() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    for name in ["pager_a", "pager_b", "pager_c"] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("{}{}@example.com", name, test_id),
+                "username": format!("{}{}", name, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let body: AuthResponse = test::read_body_json(resp).await;
+        tokens.push(body.token);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({"content": "Feed with paged comments"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+
+    // One comment per user to stay clear of the comment cooldown
+    for token in &tokens {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/comment", feed.id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"content": "Paged comment"}))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/{}/comments?sort=oldest&limit=2", feed.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "3");
+    let link = resp.headers().get("Link").unwrap().to_str().unwrap();
+    assert!(link.contains("sort=oldest&limit=2&page=2>; rel=\"next\""));
+    assert!(!link.contains("rel=\"prev\""));
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/{}/comments?page=2&sort=oldest&limit=2", feed.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let link = resp.headers().get("Link").unwrap().to_str().unwrap();
+    assert!(link.contains("sort=oldest&limit=2&page=1>; rel=\"prev\""));
+    assert!(!link.contains("rel=\"next\""));
+    let comments: Vec<serde_json::Value> = test::read_body_json(resp).await;
+    assert_eq!(comments.len(), 1);
+}
+
+#[actix_web::test]
+async fn test_export_comments_streams_ndjson() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    for name in ["export_a", "export_b"] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("{}{}@example.com", name, test_id),
+                "username": format!("{}{}", name, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let body: AuthResponse = test::read_body_json(resp).await;
+        tokens.push(body.token);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({"content": "Feed with exported comments"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+
+    for (token, content) in [(&tokens[0], "First"), (&tokens[1], "Second")] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/comment", feed.id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"content": content}))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/{}/comments/export", feed.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers().get(header::CONTENT_TYPE).unwrap(),
+        "application/x-ndjson"
+    );
+    let body = test::read_body(resp).await;
+    let lines: Vec<serde_json::Value> = std::str::from_utf8(&body)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["content"], "First");
+    assert_eq!(lines[1]["content"], "Second");
+    assert_eq!(lines[0]["feed_id"], feed.id);
+
+    let req = test::TestRequest::get()
+        .uri("/api/feed/999999999/comments/export")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_like_rate_limit() {
+    let app = test::init_service(create_test_app().await).await;
+    let config = Config::from_env().expect("Failed to load configuration");
+    if config.rate_limit.like_per_minute == 0 {
+        return;
+    }
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("liker{}@example.com", test_id),
+            "username": format!("liker{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let auth: AuthResponse = test::read_body_json(resp).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", auth.token)))
+        .set_json(json!({"content": "Feed to like repeatedly"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+
+    // Toggling spends the same bucket as liking
+    for i in 0..config.rate_limit.like_burst {
+        let req = if i % 2 == 0 {
+            test::TestRequest::post()
+        } else {
+            test::TestRequest::delete()
+        }
+        .uri(&format!("/api/feed/{}/like", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", auth.token)))
+        .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/like", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", auth.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(resp.headers().contains_key("Retry-After"));
+}
+
+#[actix_web::test]
+async fn test_mongodb_schema_rejects_malformed_documents() {
+    use mongodb::bson::{doc, Document};
+
+    let config = Config::from_env().expect("Failed to load configuration");
+    let mongo_db = db::create_mongodb_client(&config)
+        .await
+        .expect("Failed to create MongoDB client");
+
+    // Running twice must be a no-op the second time
+    db::ensure_mongodb_schema(&mongo_db)
+        .await
+        .expect("Failed to bootstrap MongoDB schema");
+    db::ensure_mongodb_schema(&mongo_db)
+        .await
+        .expect("Bootstrap should be idempotent");
+
+    let collection = mongo_db.collection::<Document>("comments");
+    let feed_id = -generate_test_id().parse::<i64>().unwrap();
+    collection
+        .insert_one(
+            doc! {
+                "feed_id": feed_id,
+                "user_id": 0_i64,
+                "content": "well formed",
+                "created_at": chrono::Utc::now().timestamp(),
+            },
+            None,
+        )
+        .await
+        .expect("A valid comment should be accepted");
+
+    // A BSON date instead of epoch seconds is rejected at write time
+    let result = collection
+        .insert_one(
+            doc! {
+                "feed_id": feed_id,
+                "user_id": 0_i64,
+                "content": "wrong timestamp type",
+                "created_at": mongodb::bson::DateTime::now(),
+            },
+            None,
+        )
+        .await;
+    assert!(result.is_err(), "Malformed comment should be rejected");
+
+    collection
+        .delete_many(doc! {"feed_id": feed_id}, None)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn test_count_queries_per_task() {
+    use example_rust_web_service::entities::user;
+    use sea_orm::EntityTrait;
+
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.server.query_count_logging = true;
+    let mysql_pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+
+    let (_, stats) = db::count_queries(async {
+        user::Entity::find_by_id(0).one(&mysql_pool).await.unwrap();
+        user::Entity::find_by_id(1).one(&mysql_pool).await.unwrap();
+    })
+    .await;
+    assert_eq!(stats.count, 2);
+
+    // Queries outside a counted future are not attributed to it
+    user::Entity::find_by_id(0).one(&mysql_pool).await.unwrap();
+    let (_, stats) = db::count_queries(async {}).await;
+    assert_eq!(stats.count, 0);
+}
+
+#[actix_web::test]
+async fn test_top_feeds_liked_by_user() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("topuser{}@example.com", test_id),
+            "username": format!("topuser{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+    let user_id = body.user.id;
+
+    let mut feed_ids = Vec::new();
+    for content in ["Liked feed", "Unliked feed"] {
+        let req = test::TestRequest::post()
+            .uri("/api/feed")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"content": content}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let feed: FeedResponse = test::read_body_json(resp).await;
+        feed_ids.push(feed.id);
+    }
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/like", feed_ids[0]))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // Only this user's liked feed is ranked, even though it is not in the Redis cache yet
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/top/feeds-liked?user_id={}", user_id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Vec<serde_json::Value> = test::read_body_json(resp).await;
+    assert_eq!(body.len(), 1);
+    assert_eq!(body[0]["feed_id"], feed_ids[0]);
+    assert_eq!(body[0]["user_id"], user_id);
+    assert_eq!(body[0]["count"], 1);
+
+    // No comments yet
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/top/feeds-commented?user_id={}", user_id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Vec<serde_json::Value> = test::read_body_json(resp).await;
+    assert!(body.is_empty());
+}
+
+#[actix_web::test]
+async fn test_signup_normalizes_username() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("normalize{}@example.com", test_id),
+            "username": format!("  Normalize{}  ", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let body: AuthResponse = test::read_body_json(resp).await;
+    assert_eq!(body.user.username, format!("normalize{}", test_id));
+
+    // Differs only by case and whitespace, so it is the same username
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("normalize2{}@example.com", test_id),
+            "username": format!("NORMALIZE{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("normalize3{}@example.com", test_id),
+            "username": "   ",
+            "password": "password123"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_react_feed_reaction_breakdown() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("reactuser{}@example.com", test_id),
+            "username": format!("reactuser{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let token = body.token;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"content": "Feed to react to"}))
+        .to_request();
+    let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+
+    for reaction in ["like", "love", "laugh"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/react", feed.id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"type": reaction}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK, "React {} should succeed", reaction);
+    }
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/feed/{}/react/laugh", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/api/feed?limit=100")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let feeds: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    let reacted = feeds
+        .iter()
+        .find(|f| f.id == feed.id)
+        .expect("Feed should be listed");
+    assert_eq!(reacted.like_count, 1, "A like reaction is a like");
+    assert!(reacted.is_liked);
+    assert_eq!(reacted.reactions.get("like"), Some(&1));
+    assert_eq!(reacted.reactions.get("love"), Some(&1));
+    assert_eq!(reacted.reactions.get("laugh"), None);
+}
+
+#[actix_web::test]
+async fn test_view_rate_limited_per_ip() {
+    let app = test::init_service(create_test_app().await).await;
+    let config = Config::from_env().expect("Failed to load configuration");
+    let rate_limit = &config.rate_limit;
+
+    // A fresh address per run so earlier runs' buckets do not interfere
+    let test_id: u128 = generate_test_id().parse().unwrap();
+    let ip = format!("10.{}.{}.{}", (test_id >> 16) % 256, (test_id >> 8) % 256, test_id % 256);
+    let view = |ip: &str| {
+        test::TestRequest::post()
+            .uri("/api/feed/1/view")
+            .insert_header(("X-Forwarded-For", ip.to_string()))
+            .to_request()
+    };
+
+    for _ in 0..rate_limit.view_burst {
+        let resp = test::call_service(&app, view(&ip)).await;
+        assert_ne!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    let resp = test::call_service(&app, view(&ip)).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = resp
+        .headers()
+        .get("Retry-After")
+        .expect("429 should carry Retry-After")
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after >= 1);
+    assert!(retry_after <= 60 + rate_limit.view_retry_jitter_seconds);
+
+    // Other clients keep flowing
+    let resp = test::call_service(&app, view("192.0.2.1")).await;
+    assert_ne!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[actix_web::test]
+async fn test_concurrent_signups_same_email_conflict() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let email = format!("racer{}@example.com", test_id);
+    let signup = |username: String| {
+        test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": email,
+                "username": username,
+                "password": "password123"
+            }))
+            .to_request()
+    };
+
+    // Both requests can pass the existence check before either inserts
+    let (first, second) = futures::join!(
+        test::call_service(&app, signup(format!("racer{}a", test_id))),
+        test::call_service(&app, signup(format!("racer{}b", test_id)))
+    );
+    let mut statuses = vec![first.status(), second.status()];
+    statuses.sort();
+    assert_eq!(
+        statuses,
+        vec![StatusCode::CREATED, StatusCode::CONFLICT],
+        "Exactly one signup should win; the other must be 409, never 500"
+    );
+}
+
+#[actix_web::test]
+async fn test_search_feeds() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("searchuser{}@example.com", test_id),
+            "username": format!("searchuser{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", body.token)))
+        .set_json(json!({"content": format!("Searchable post 100%_{}", test_id)}))
+        .to_request();
+    let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/search?q=100%25_{}", test_id))
+        .to_request();
+    let found: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(found.iter().map(|f| f.id).collect::<Vec<_>>(), vec![feed.id]);
+
+    // Wildcards in the query match literally
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/search?q=100__{}", test_id))
+        .to_request();
+    let found: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    assert!(found.is_empty());
+
+    let req = test::TestRequest::get()
+        .uri("/api/feed/search?q=%20")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_patch_feed_content() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("patchfeed{}@example.com", test_id),
+        "username": format!("patchfeed{}", test_id),
+        "password": "password123"
+    });
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "content": "Before edit" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/feed/{}", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "content": "After edit" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let edited: FeedResponse = test::read_body_json(resp).await;
+    assert_eq!(edited.id, feed.id);
+    assert_eq!(edited.content, "After edit");
+    assert_eq!(edited.created_at, feed.created_at);
+
+    // Published feeds cannot be rescheduled
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/feed/{}", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "publish_at": "2099-01-01T00:00:00Z" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_web::test]
+async fn test_feed_cache_invalidated_by_writes() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.feed_cache.enabled = true;
+    config.feed_cache.ttl_seconds = 300;
+    let mysql_pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    let mongodb_db = db::create_mongodb_client(&config)
+        .await
+        .expect("Failed to create MongoDB client");
+    let redis_client = db::create_redis_client(&config).expect("Failed to create Redis client");
+    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+    let repository = Arc::new(MysqlRepository::new(mysql_pool.clone()));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mysql_pool))
+            .app_data(web::Data::new(mongodb_db))
+            .app_data(web::Data::new(redis_client))
+            .app_data(web::Data::new(kafka_producer))
+            .configure(repositories::register(repository))
+            .route("/api/auth/signup", web::post().to(api::auth::signup))
+            .route("/api/feed", web::post().to(api::feed::create_feed))
+            .route("/api/feed", web::get().to(api::feed::get_feeds))
+            .route("/api/feed/{feed_id}/like", web::post().to(api::feed::like_feed)),
+    )
+    .await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("feedcache{}@example.com", test_id),
+            "username": format!("feedcache{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let token = body.token;
+
+    // Fill the cache before the feed exists
+    let req = test::TestRequest::get().uri("/api/feed?limit=50").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let content = format!("Cached feed {}", test_id);
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "content": content }))
+        .to_request();
+    let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get().uri("/api/feed?limit=50").to_request();
+    let listed: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    let cached = listed.iter().find(|f| f.id == feed.id);
+    assert!(cached.is_some(), "New feed must not be hidden by the cache");
+    assert_eq!(cached.unwrap().like_count, 0);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/like", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get().uri("/api/feed?limit=50").to_request();
+    let listed: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    let liked = listed.iter().find(|f| f.id == feed.id).unwrap();
+    assert_eq!(liked.like_count, 1);
+}
+
+#[actix_web::test]
+async fn test_get_common_likes() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    let mut user_ids = Vec::new();
+    for name in ["commona", "commonb"] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("{}{}@example.com", name, test_id),
+                "username": format!("{}{}", name, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+        tokens.push(body.token);
+        user_ids.push(body.user.id);
+    }
+
+    let mut feed_ids = Vec::new();
+    for content in ["Liked by both", "Liked by one"] {
+        let req = test::TestRequest::post()
+            .uri("/api/feed")
+            .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+            .set_json(json!({ "content": format!("{} {}", content, test_id) }))
+            .to_request();
+        let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+        feed_ids.push(feed.id);
+    }
+
+    let likes = [(0, feed_ids[0]), (1, feed_ids[0]), (0, feed_ids[1])];
+    for (user, feed_id) in likes {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/like", feed_id))
+            .insert_header(("Authorization", format!("Bearer {}", tokens[user])))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/users/{}/common-likes", user_ids[1]))
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .to_request();
+    let common: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    let ids: Vec<i64> = common.iter().map(|f| f.id).collect();
+    assert_eq!(ids, vec![feed_ids[0]]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/users/{}/common-likes", user_ids[1]))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_admin_backfill_rankings() {
+    use example_rust_web_service::entities::user;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("backfill{}@example.com", test_id),
+            "username": format!("backfill{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let backfill = || {
+        test::TestRequest::post()
+            .uri("/api/admin/top/backfill")
+            .insert_header(("Authorization", format!("Bearer {}", body.token)))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, backfill()).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let config = Config::from_env().expect("Failed to load configuration");
+    let pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    user::ActiveModel {
+        id: Set(body.user.id),
+        is_admin: Set(true),
+        ..Default::default()
+    }
+    .update(&pool)
+    .await
+    .expect("Failed to promote admin");
+
+    // A backfill in progress elsewhere holds the lock
+    let redis_client = db::create_redis_client(&config).expect("Failed to create Redis client");
+    let lock_key = db::redis_key(&config, "top:backfill:lock");
+    let mut conn = redis_client.get_async_connection().await.unwrap();
+    let _: () = redis::cmd("SET")
+        .arg(&lock_key)
+        .arg(1)
+        .arg("EX")
+        .arg(60)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+    let resp = test::call_service(&app, backfill()).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let _: () = redis::cmd("DEL")
+        .arg(&lock_key)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+    let resp = test::call_service(&app, backfill()).await;
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+}
+
+#[actix_web::test]
+async fn test_denormalized_like_count_and_reconciliation() {
+    use example_rust_web_service::repositories::{FeedRepository, LikeRepository, UserRepository};
+    use sea_orm::ConnectionTrait;
+
+    let config = Config::from_env().expect("Failed to load configuration");
+    let mysql_pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    let repository = MysqlRepository::new(mysql_pool.clone()).with_like_count_column(true);
+
+    let test_id = generate_test_id();
+    let author = UserRepository::create(
+        &repository,
+        &format!("likecount_a_{}@example.com", test_id),
+        &format!("likecount_a_{}", test_id),
+        "",
+    )
+    .await
+    .unwrap();
+    let fan = UserRepository::create(
+        &repository,
+        &format!("likecount_b_{}@example.com", test_id),
+        &format!("likecount_b_{}", test_id),
+        "",
+    )
+    .await
+    .unwrap();
+    let feed = FeedRepository::create(
+        &repository,
+        author.id,
+        "Hot feed",
+        None,
+        None,
+        None,
+        None,
+        true,
+    )
+    .await
+    .unwrap();
+
+    LikeRepository::insert(&repository, feed.id, author.id).await.unwrap();
+    LikeRepository::insert(&repository, feed.id, fan.id).await.unwrap();
+    // A duplicate like rolls back without touching the counter
+    assert!(LikeRepository::insert(&repository, feed.id, fan.id).await.is_err());
+    LikeRepository::delete(&repository, feed.id, author.id).await.unwrap();
+    let stored = FeedRepository::find_by_id(&repository, feed.id).await.unwrap().unwrap();
+    assert_eq!(stored.like_count, 1);
+
+    // Drift, e.g. from a like removed while the feature was off, is corrected
+    mysql_pool
+        .execute(sea_orm::Statement::from_sql_and_values(
+            sea_orm::DatabaseBackend::MySql,
+            "UPDATE feeds SET like_count = 7 WHERE id = ?",
+            [feed.id.into()],
+        ))
+        .await
+        .unwrap();
+    let corrected = example_rust_web_service::jobs::reconcile_like_counts(&mysql_pool)
+        .await
+        .unwrap();
+    assert!(corrected >= 1);
+    let stored = FeedRepository::find_by_id(&repository, feed.id).await.unwrap().unwrap();
+    assert_eq!(stored.like_count, 1);
+}
+
+#[actix_web::test]
+async fn test_get_notifications_filtered_by_feed() {
+    use mongodb::bson::{doc, Document};
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("notifyfeed{}@example.com", test_id),
+        "username": format!("notifyfeed{}", test_id),
+        "password": "password123"
+    });
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+    let user_id = body.user.id;
+
+    let config = Config::from_env().expect("Failed to load configuration");
+    let mongo_db = db::create_mongodb_client(&config)
+        .await
+        .expect("Failed to create MongoDB client");
+    let collection = mongo_db.collection::<Document>("notifications");
+    for (feed_id, content) in [(11_i64, "like on 11"), (12, "like on 12"), (11, "comment on 11")] {
+        collection
+            .insert_one(
+                doc! {
+                    "_id": uuid::Uuid::new_v4().to_string(),
+                    "user_id": user_id,
+                    "from_user_id": 0_i64,
+                    "from_username": "system",
+                    "feed_id": feed_id,
+                    "notification_type": "like",
+                    "content": content,
+                    "created_at": chrono::Utc::now().timestamp(),
+                    "is_read": false,
+                },
+                None,
+            )
+            .await
+            .expect("Failed to insert notification");
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/api/notify?feed_id=11")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    // The total counts the same filter as the page
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "2");
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let mut contents: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|n| n["content"].as_str())
+        .collect();
+    contents.sort();
+    assert_eq!(contents, vec!["comment on 11", "like on 11"]);
+
+    for feed_id in ["0", "abc"] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/api/notify?feed_id={}", feed_id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/api/notify?limit=1")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "3");
+    let link = resp.headers().get("Link").unwrap().to_str().unwrap();
+    assert!(link.contains("rel=\"next\""));
+}
+
+#[actix_web::test]
+async fn test_trim_rankings_keeps_top_members() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    // A prefix of its own so the shared rankings are not trimmed
+    config.redis.key_prefix = format!("trim_test_{}:", generate_test_id());
+    config.top_stats.cache_size = 2;
+    let redis_client = db::create_redis_client(&config).expect("Failed to create Redis client");
+    let mut conn = redis_client.get_async_connection().await.unwrap();
+
+    let key = db::redis_key(&config, "top:feeds_liked");
+    for (feed_id, score) in [(1, 5.0), (2, 1.0), (3, 9.0), (4, 2.0)] {
+        let _: () = redis::cmd("ZADD")
+            .arg(&key)
+            .arg(score)
+            .arg(feed_id)
+            .query_async(&mut conn)
+            .await
+            .unwrap();
+    }
+
+    let removed = example_rust_web_service::jobs::trim_rankings(&redis_client, &config).await;
+    assert_eq!(removed, 2);
+    let remaining: Vec<String> = redis::cmd("ZREVRANGE")
+        .arg(&key)
+        .arg(0)
+        .arg(-1)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(remaining, vec!["3", "1"]);
+
+    let _: () = redis::cmd("DEL").arg(&key).query_async(&mut conn).await.unwrap();
+}
+
+#[actix_web::test]
+async fn test_get_comment_tree() {
+    use example_rust_web_service::models::CommentTreeResponse;
+    use mongodb::bson::{doc, Document};
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("tree{}@example.com", test_id),
+        "username": format!("tree{}", test_id),
+        "password": "password123"
+    });
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+    let user_id = body.user.id;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"content": "Feed with a thread"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+    let feed_id = feed.id;
+
+    // A thread written directly, so the comment cooldown does not get in the way
+    let config = Config::from_env().expect("Failed to load configuration");
+    let mongo_db = db::create_mongodb_client(&config)
+        .await
+        .expect("Failed to create MongoDB client");
+    let collection = mongo_db.collection::<Document>("comments");
+    let now = chrono::Utc::now().timestamp();
+    let ids: Vec<String> = (0..4).map(|i| format!("tree_{}_{}", test_id, i)).collect();
+    let thread = [
+        (&ids[0], None, "root a"),
+        (&ids[1], None, "root b"),
+        (&ids[2], Some(&ids[0]), "reply to a"),
+        (&ids[3], Some(&ids[2]), "reply to reply"),
+    ];
+    for (age, (id, parent, content)) in thread.iter().enumerate() {
+        let mut comment = doc! {
+            "_id": id.as_str(),
+            "feed_id": feed_id,
+            "user_id": user_id,
+            "content": *content,
+            "created_at": now - 10 + age as i64,
+        };
+        if let Some(parent) = parent {
+            comment.insert("parent_comment_id", parent.as_str());
+        }
+        collection.insert_one(comment, None).await.unwrap();
+    }
+
+    // Replies must point at a comment on the same feed
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/comment", feed_id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"content": "lost reply", "parent_comment_id": "missing"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/comment", feed_id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({"content": "reply to b", "parent_comment_id": ids[1]}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let reply: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(reply["parent_comment_id"], ids[1].as_str());
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/{}/comment-tree", feed_id))
+        .to_request();
+    let tree: CommentTreeResponse = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(tree.returned, 5);
+    assert!(!tree.truncated);
+    let roots: Vec<&str> = tree.comments.iter().map(|c| c.content.as_str()).collect();
+    assert_eq!(roots, vec!["root a", "root b"]);
+    assert_eq!(
+        tree.comments[0].replies[0].replies[0].content,
+        "reply to reply"
+    );
+    assert_eq!(tree.comments[1].replies[0].content, "reply to b");
+
+    // Depth and node caps cut the tree and say so
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/{}/comment-tree?max_depth=2", feed_id))
+        .to_request();
+    let tree: CommentTreeResponse = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(tree.returned, 4);
+    assert!(tree.truncated);
+    assert!(tree.comments[0].replies[0].replies.is_empty());
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/{}/comment-tree?limit=1", feed_id))
+        .to_request();
+    let tree: CommentTreeResponse = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(tree.returned, 1);
+    assert!(tree.truncated);
+
+    let req = test::TestRequest::get()
+        .uri("/api/feed/0/comment-tree")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_sliding_session_refreshes_expiring_token() {
+    use example_rust_web_service::auth::{create_token, verify_token, AuthenticatedUser, Claims};
+
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.jwt.expiry_mode = JwtExpiryMode::Sliding;
+    config.jwt.sliding_threshold_minutes = 60;
+    let secret = config.jwt.secret.clone();
+    let audiences = config.jwt.audiences();
+    let app = test::init_service(
+        App::new()
+            .wrap(from_fn(api::sliding_session::refresh_sliding_session))
+            .app_data(web::Data::new(config))
+            .route(
+                "/me",
+                web::get().to(|user: AuthenticatedUser| async move {
+                    actix_web::HttpResponse::Ok().body(user.user_id.to_string())
+                }),
+            ),
+    )
+    .await;
+
+    // Fresh token: more than the threshold left, no refresh
+    let fresh = Claims::new(42, "sliding@example.com".to_string(), 24).with_audience("web");
+    let req = test::TestRequest::get()
+        .uri("/me")
+        .insert_header((
+            "Authorization",
+            format!("Bearer {}", create_token(&fresh, &secret).unwrap()),
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(resp.headers().get("X-Refreshed-Token").is_none());
+
+    // Ten minutes left: a new token for the same session comes back
+    let mut expiring = fresh.clone();
+    expiring.exp = chrono::Utc::now().timestamp() + 600;
+    let req = test::TestRequest::get()
+        .uri("/me")
+        .insert_header((
+            "Authorization",
+            format!("Bearer {}", create_token(&expiring, &secret).unwrap()),
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let refreshed = resp
+        .headers()
+        .get("X-Refreshed-Token")
+        .expect("Expiring token should be refreshed")
+        .to_str()
+        .unwrap();
+    let claims = verify_token(refreshed, &secret, &audiences).unwrap();
+    assert_eq!(claims.sub, "42");
+    assert_eq!(claims.jti, expiring.jti, "Refresh keeps the session");
+    assert_eq!(claims.aud.as_deref(), Some("web"));
+    assert!(claims.exp > expiring.exp + 3600);
+
+    // Unauthenticated requests get nothing
+    let req = test::TestRequest::get().uri("/me").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert!(resp.headers().get("X-Refreshed-Token").is_none());
+}
+
+#[actix_web::test]
+async fn test_admin_seed() {
+    use example_rust_web_service::entities::user;
+    use example_rust_web_service::repositories::{FeedRepository, LikeRepository};
+    use example_rust_web_service::services::seed::{seed, SeedPlan};
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let config = Config::from_env().expect("Failed to load configuration");
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("seeder{}@example.com", test_id),
+            "username": format!("seeder{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let seed_request = |payload: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/api/admin/seed")
+            .insert_header(("Authorization", format!("Bearer {}", body.token)))
+            .set_json(payload)
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, seed_request(json!({"users": 2, "feeds_per_user": 1}))).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    user::ActiveModel {
+        id: Set(body.user.id),
+        is_admin: Set(true),
+        ..Default::default()
+    }
+    .update(&pool)
+    .await
+    .expect("Failed to promote admin");
+
+    let resp = test::call_service(&app, seed_request(json!({"users": 2, "feeds_per_user": 1}))).await;
+    if !config.server.seed_endpoint {
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND, "Seeding is off by default");
+    } else {
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        let resp = test::call_service(&app, seed_request(json!({"users": 0, "feeds_per_user": 1}))).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    // The seeding itself, independent of the flag
+    let mongodb_db = db::create_mongodb_client(&config)
+        .await
+        .expect("Failed to create MongoDB client");
+    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+    let repository = MysqlRepository::new(pool.clone());
+    let plan = SeedPlan::random(3, 2, 2, 1, 4);
+    let created = seed(
+        &repository,
+        &repository,
+        &repository,
+        &mongodb_db,
+        &kafka_producer,
+        &config,
+        &plan,
+    )
+    .await
+    .expect("Seeding failed");
+
+    assert_eq!(created.user_ids.len(), 3);
+    assert_eq!(created.feed_ids.len(), 6);
+    assert!(created.comment_ids.len() <= 6);
+    assert!(created.views <= 24);
+    let mut likes = 0;
+    for feed_id in &created.feed_ids {
+        let feed = FeedRepository::find_by_id(&repository, *feed_id).await.unwrap().unwrap();
+        assert!(created.user_ids.contains(&feed.user_id));
+        let count = LikeRepository::count_for_feed(&repository, *feed_id).await.unwrap();
+        assert!(count <= 2);
+        // Authors never like their own seeded feeds
+        assert!(LikeRepository::find(&repository, *feed_id, feed.user_id).await.unwrap().is_none());
+        likes += count;
+    }
+    assert_eq!(likes, created.likes);
+
+    // Seeded users can log in with the returned password
+    let seeded = repositories::UserRepository::find_by_id(&repository, created.user_ids[0])
+        .await
+        .unwrap()
+        .unwrap();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({ "email": seeded.email, "password": created.password }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_bookmark_feed() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let signup_req = json!({
+        "email": format!("bookmarkuser{}@example.com", test_id),
+        "username": format!("bookmarkuser{}", test_id),
+        "password": "password123"
+    });
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(&signup_req)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: AuthResponse = test::read_body_json(resp).await;
+    let token = body.token;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .set_json(json!({ "content": "Feed to bookmark" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let feed: FeedResponse = test::read_body_json(resp).await;
+    assert!(!feed.is_bookmarked);
+
+    // Bookmarking twice is not an error
+    for _ in 0..2 {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/bookmark", feed.id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/api/users/me/bookmarks")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bookmarked: Vec<FeedResponse> = test::read_body_json(resp).await;
+    assert_eq!(bookmarked.len(), 1);
+    assert_eq!(bookmarked[0].id, feed.id);
+    assert!(bookmarked[0].is_bookmarked);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/feed/{}/bookmark", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/api/users/me/bookmarks")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let bookmarked: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    assert!(bookmarked.is_empty());
+
+    // Requires authentication
+    let req = test::TestRequest::get()
+        .uri("/api/users/me/bookmarks")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_top_debug_reports_orphaned_entries() {
+    use example_rust_web_service::entities::user;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let config = Config::from_env().expect("Failed to load configuration");
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("topdebug{}@example.com", test_id),
+            "username": format!("topdebug{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let debug_request = |token: Option<&str>| {
+        let req = test::TestRequest::get().uri("/api/top/feeds-liked?debug=true");
+        match token {
+            Some(token) => req.insert_header(("Authorization", format!("Bearer {}", token))),
+            None => req,
+        }
+        .to_request()
+    };
+
+    let resp = test::call_service(&app, debug_request(None)).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = test::call_service(&app, debug_request(Some(&body.token))).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // Without debug the endpoint stays public
+    let req = test::TestRequest::get().uri("/api/top/feeds-liked").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    user::ActiveModel {
+        id: Set(body.user.id),
+        is_admin: Set(true),
+        ..Default::default()
+    }
+    .update(&pool)
+    .await
+    .expect("Failed to promote admin");
+
+    // A ranking entry for a feed that does not exist, ranked first
+    let redis_client = db::create_redis_client(&config).expect("Failed to create Redis client");
+    let ranking_key = db::redis_key(&config, "top:feeds_liked");
+    let orphan = format!("9{}", &test_id[test_id.len() - 12..]);
+    let mut conn = redis_client.get_async_connection().await.unwrap();
+    let _: () = redis::cmd("ZADD")
+        .arg(&ranking_key)
+        .arg(f64::MAX)
+        .arg(&orphan)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+
+    let resp = test::call_service(&app, debug_request(Some(&body.token))).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let debug_body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(debug_body["debug"]["raw_scores"][0]["member"], orphan.as_str());
+    assert!(debug_body["debug"]["dropped"]
+        .as_array()
+        .unwrap()
+        .contains(&json!(orphan)));
+    assert!(debug_body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|item| item["feed_id"].as_i64() != orphan.parse().ok()));
+
+    let _: () = redis::cmd("ZREM")
+        .arg(&ranking_key)
+        .arg(&orphan)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn test_sweep_orphaned_rankings_removes_deleted_feeds() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("sweeper{}@example.com", test_id),
+            "username": format!("sweeper{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", body.token)))
+        .set_json(json!({ "content": "Still here" }))
+        .to_request();
+    let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+
+    // A prefix of its own so the shared rankings are not swept
+    config.redis.key_prefix = format!("sweep_test_{}:", test_id);
+    let redis_client = db::create_redis_client(&config).expect("Failed to create Redis client");
+    let mut conn = redis_client.get_async_connection().await.unwrap();
+    let key = db::redis_key(&config, "top:comments");
+    let orphan = format!("9{}", &test_id[test_id.len() - 12..]);
+    for member in [feed.id.to_string(), orphan, "not-a-feed".to_string()] {
+        let _: () = redis::cmd("ZADD")
+            .arg(&key)
+            .arg(1)
+            .arg(member)
+            .query_async(&mut conn)
+            .await
+            .unwrap();
+    }
+
+    let pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    let removed =
+        example_rust_web_service::jobs::sweep_orphaned_rankings(&pool, &redis_client, &config)
+            .await;
+    assert_eq!(removed, 2);
+    let remaining: Vec<String> = redis::cmd("ZRANGE")
+        .arg(&key)
+        .arg(0)
+        .arg(-1)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(remaining, vec![feed.id.to_string()]);
+
+    let _: () = redis::cmd("DEL").arg(&key).query_async(&mut conn).await.unwrap();
+}
+
+#[actix_web::test]
+async fn test_build_digests_summarizes_unread_notifications() {
+    use mongodb::bson::{doc, Document};
+
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.digests.min_unread = 3;
+    config.digests.top_actors = 1;
+    config.digests.active_within_days = 1;
+    config.digests.publish = false;
+    let mongo_db = db::create_mongodb_client(&config)
+        .await
+        .expect("Failed to create MongoDB client");
+    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+    let notifications = mongo_db.collection::<Document>("notifications");
+
+    // User ids no real account has; `inactive` has as many unread notifications but no activity
+    let test_id = generate_test_id().parse::<i64>().unwrap();
+    let (active, inactive) = (-test_id, -test_id - 1);
+    let now = chrono::Utc::now();
+    let unread = [(2, "alice", "like"), (2, "alice", "comment"), (3, "bob", "like")];
+    for user_id in [active, inactive] {
+        for (from_user_id, from_username, notification_type) in unread {
+            notifications
+                .insert_one(
+                    doc! {
+                        "_id": uuid::Uuid::new_v4().to_string(),
+                        "user_id": user_id,
+                        "from_user_id": from_user_id as i64,
+                        "from_username": from_username,
+                        "feed_id": 0_i64,
+                        "notification_type": notification_type,
+                        "content": "unread",
+                        "created_at": now.timestamp(),
+                        "is_read": false,
+                    },
+                    None,
+                )
+                .await
+                .expect("Failed to insert notification");
+        }
+    }
+    mongo_db
+        .collection::<Document>("feed_views")
+        .insert_one(
+            doc! {
+                "_id": uuid::Uuid::new_v4().to_string(),
+                "feed_id": 0_i64,
+                "user_id": active,
+                "viewed_at": now.timestamp(),
+                "weight": 1.0,
+            },
+            None,
+        )
+        .await
+        .expect("Failed to insert view");
+
+    let written =
+        example_rust_web_service::jobs::build_digests(&mongo_db, &kafka_producer, &config, now)
+            .await;
+    assert!(written >= 1);
+
+    let digests = mongo_db.collection::<example_rust_web_service::models::Digest>("digests");
+    let date = now.format("%Y-%m-%d").to_string();
+    let digest = digests
+        .find_one(doc! {"_id": format!("{}:{}", active, date)}, None)
+        .await
+        .unwrap()
+        .expect("Active user should get a digest");
+    assert_eq!(digest.unread_total, 3);
+    assert_eq!(digest.counts.get("like"), Some(&2));
+    assert_eq!(digest.counts.get("comment"), Some(&1));
+    assert_eq!(digest.top_actors.len(), 1);
+    assert_eq!(digest.top_actors[0].username, "alice");
+    assert_eq!(digest.top_actors[0].count, 2);
+    let skipped = digests
+        .find_one(doc! {"user_id": inactive}, None)
+        .await
+        .unwrap();
+    assert!(skipped.is_none(), "Inactive user should not get a digest");
+
+    // Rerunning on the same day replaces the digest
+    example_rust_web_service::jobs::build_digests(&mongo_db, &kafka_producer, &config, now).await;
+    let count = digests
+        .count_documents(doc! {"user_id": active}, None)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+
+    notifications
+        .delete_many(doc! {"user_id": {"$in": [active, inactive]}}, None)
+        .await
+        .unwrap();
+    digests
+        .delete_many(doc! {"user_id": active}, None)
+        .await
+        .unwrap();
+    mongo_db
+        .collection::<Document>("feed_views")
+        .delete_many(doc! {"user_id": active}, None)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn test_pinned_feed_leads_user_timeline() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("pinuser{}@example.com", test_id),
+            "username": format!("pinuser{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let token = body.token;
+
+    let mut feed_ids = Vec::new();
+    for content in ["First post", "Second post"] {
+        let req = test::TestRequest::post()
+            .uri("/api/feed")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({ "content": content }))
+            .to_request();
+        let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+        assert!(!feed.is_pinned);
+        feed_ids.push(feed.id);
+    }
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/pin", feed_ids[0]))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // The older feed comes first once pinned, in MySQL's NULLs-last ordering
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed?user_id={}", body.user.id))
+        .to_request();
+    let timeline: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    let ids: Vec<i64> = timeline.iter().map(|feed| feed.id).collect();
+    assert_eq!(ids, vec![feed_ids[0], feed_ids[1]]);
+    assert!(timeline[0].is_pinned);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/feed/{}/pin", feed_ids[0]))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed?user_id={}", body.user.id))
+        .to_request();
+    let timeline: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    let ids: Vec<i64> = timeline.iter().map(|feed| feed.id).collect();
+    assert_eq!(ids, vec![feed_ids[1], feed_ids[0]]);
+}
+
+#[actix_web::test]
+async fn test_unseen_count_follows_first_page_marker() {
+    use example_rust_web_service::models::UnseenCountResponse;
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    for name in ["reader", "poster"] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("{}{}@example.com", name, test_id),
+                "username": format!("{}{}", name, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let body: AuthResponse = test::read_body_json(resp).await;
+        tokens.push(body.token);
+    }
+    let unseen_count = |token: &str| {
+        test::TestRequest::get()
+            .uri("/api/feed/unseen-count")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request()
+    };
+    let load_timeline = |token: &str| {
+        test::TestRequest::get()
+            .uri("/api/feed?page=1&limit=5")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request()
+    };
+
+    // No marker before the first visit
+    let resp = test::call_service(&app, unseen_count(&tokens[0])).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: UnseenCountResponse = test::read_body_json(resp).await;
+    assert_eq!(body.count, 0);
+    assert!(body.last_seen_feed_id.is_none());
+
+    let resp = test::call_service(&app, load_timeline(&tokens[0])).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    for content in ["First unseen", "Second unseen"] {
+        let req = test::TestRequest::post()
+            .uri("/api/feed")
+            .insert_header(("Authorization", format!("Bearer {}", tokens[1])))
+            .set_json(json!({"content": content}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+    }
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({"content": "My own post"}))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    // Other tests may post concurrently, so only a lower bound is exact
+    let resp = test::call_service(&app, unseen_count(&tokens[0])).await;
+    let body: UnseenCountResponse = test::read_body_json(resp).await;
+    assert!(body.count >= 2, "Expected the poster's two feeds, got {}", body.count);
+    assert!(body.last_seen_feed_id.is_some());
+
+    let resp = test::call_service(&app, load_timeline(&tokens[0])).await;
+    let timeline: Vec<FeedResponse> = test::read_body_json(resp).await;
+    let resp = test::call_service(&app, unseen_count(&tokens[0])).await;
+    let body: UnseenCountResponse = test::read_body_json(resp).await;
+    assert_eq!(body.last_seen_feed_id, timeline.iter().map(|f| f.id).max());
+}
+
+#[actix_web::test]
+async fn test_top_feeds_liked_stream_sends_current_ranking_first() {
+    use actix_web::body::MessageBody;
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/top/feeds-liked/stream?limit=3")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers().get(header::CONTENT_TYPE).unwrap(),
+        "text/event-stream"
+    );
+
+    // The stream never ends on its own; read only the first event
+    let mut body = resp.into_body();
+    let chunk = std::future::poll_fn(|cx| std::pin::Pin::new(&mut body).poll_next(cx))
+        .await
+        .expect("Stream ended before the first event")
+        .unwrap_or_else(|_| panic!("Failed to read the first event"));
+    let event = std::str::from_utf8(&chunk).unwrap();
+    assert!(event.starts_with("event: top-feeds-liked\ndata: "));
+    assert!(event.ends_with("\n\n"));
+    let data = event
+        .trim_end()
+        .strip_prefix("event: top-feeds-liked\ndata: ")
+        .unwrap();
+    let ranking: Vec<serde_json::Value> = serde_json::from_str(data).unwrap();
+    assert!(ranking.len() <= 3);
+}
+
+#[actix_web::test]
+async fn test_reported_feed_is_reviewed_and_hidden() {
+    use example_rust_web_service::entities::user;
+    use example_rust_web_service::models::{FeedReportResponse, ReportStatus};
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    let mut user_ids = Vec::new();
+    for role in ["author", "reporter", "moderator"] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("report{}{}@example.com", role, test_id),
+                "username": format!("report{}{}", role, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+        tokens.push(body.token);
+        user_ids.push(body.user.id);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({"content": "Buy cheap followers"}))
+        .to_request();
+    let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+
+    let report = || {
+        test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/report", feed.id))
+            .insert_header(("Authorization", format!("Bearer {}", tokens[1])))
+            .set_json(json!({"reason": "Spam"}))
+            .to_request()
+    };
+    let resp = test::call_service(&app, report()).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let created: FeedReportResponse = test::read_body_json(resp).await;
+    assert_eq!(created.status, ReportStatus::Open);
+    let resp = test::call_service(&app, report()).await;
+    assert_eq!(resp.status(), StatusCode::OK, "One open report per reporter");
+
+    let list_reports = || {
+        test::TestRequest::get()
+            .uri(&format!("/api/admin/reports?feed_id={}", feed.id))
+            .insert_header(("Authorization", format!("Bearer {}", tokens[2])))
+            .to_request()
+    };
+    let resp = test::call_service(&app, list_reports()).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let config = Config::from_env().expect("Failed to load configuration");
+    let pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    user::ActiveModel {
+        id: Set(user_ids[2]),
+        is_admin: Set(true),
+        ..Default::default()
+    }
+    .update(&pool)
+    .await
+    .expect("Failed to promote user");
+
+    let reports: Vec<FeedReportResponse> =
+        test::call_and_read_body_json(&app, list_reports()).await;
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].id, created.id);
+    assert_eq!(reports[0].user_id, user_ids[1]);
+
+    let resolve = || {
+        test::TestRequest::post()
+            .uri(&format!("/api/admin/reports/{}/resolve", created.id))
+            .insert_header(("Authorization", format!("Bearer {}", tokens[2])))
+            .set_json(json!({"action": "hide"}))
+            .to_request()
+    };
+    let resolved: FeedReportResponse = test::call_and_read_body_json(&app, resolve()).await;
+    assert_eq!(resolved.status, ReportStatus::Hidden);
+    assert_eq!(resolved.resolved_by, Some(user_ids[2]));
+    let resp = test::call_service(&app, resolve()).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    // Out of the queue and out of the author's timeline
+    let reports: Vec<FeedReportResponse> =
+        test::call_and_read_body_json(&app, list_reports()).await;
+    assert!(reports.is_empty());
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed?user_id={}", user_ids[0]))
+        .to_request();
+    let feeds: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    assert!(feeds.iter().all(|f| f.id != feed.id));
+}
+
+#[actix_web::test]
+async fn test_admin_dead_letter_endpoints() {
+    use example_rust_web_service::entities::user;
+    use example_rust_web_service::models::DeadLetterMessage;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("dlqadmin{}@example.com", test_id),
+            "username": format!("dlqadmin{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let auth = ("Authorization", format!("Bearer {}", body.token));
+
+    let req = test::TestRequest::get()
+        .uri("/api/admin/dlq")
+        .insert_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let config = Config::from_env().expect("Failed to load configuration");
+    let pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    user::ActiveModel {
+        id: Set(body.user.id),
+        is_admin: Set(true),
+        ..Default::default()
+    }
+    .update(&pool)
+    .await
+    .expect("Failed to promote user");
+
+    let req = test::TestRequest::get()
+        .uri("/api/admin/dlq?topic=unknown_events")
+        .insert_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::get()
+        .uri("/api/admin/dlq?topic=feed_events&limit=5")
+        .insert_header(auth.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let letters: Vec<DeadLetterMessage> = test::read_body_json(resp).await;
+    assert!(letters.len() <= 5);
+    assert!(letters.iter().all(|letter| letter.topic == "feed_events_dlq"));
+
+    // Offsets far past the end of the topic hold no dead letter
+    let req = test::TestRequest::post()
+        .uri("/api/admin/dlq/replay")
+        .insert_header(auth)
+        .set_json(json!({"topic": "feed_events", "partition": 0, "offset": i64::MAX - 1}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_api_requests_throttled_per_ip() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.rate_limit.ip_per_minute = 1;
+    config.rate_limit.ip_burst = 2;
+    config.rate_limit.ip_allowlist = vec!["192.0.2.0/24".parse().unwrap()];
+    let redis_client = db::create_redis_client(&config).expect("Failed to create Redis client");
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(redis_client))
+            .route("/ready", web::get().to(actix_web::HttpResponse::Ok))
+            .service(
+                web::scope("/api")
+                    .wrap(from_fn(api::ip_throttle::throttle_by_ip))
+                    .route("/ping", web::get().to(actix_web::HttpResponse::Ok)),
+            ),
+    )
+    .await;
+
+    // A fresh address per run so earlier runs' buckets do not interfere
+    let test_id: u128 = generate_test_id().parse().unwrap();
+    let ip = format!("10.{}.{}.{}", (test_id >> 16) % 256, (test_id >> 8) % 256, test_id % 256);
+    let get = |uri: &str, ip: &str| {
+        test::TestRequest::get()
+            .uri(uri)
+            .peer_addr(format!("{}:4000", ip).parse().unwrap())
+            .to_request()
+    };
+
+    for _ in 0..2 {
+        let resp = test::call_service(&app, get("/api/ping", &ip)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+    let resp = test::call_service(&app, get("/api/ping", &ip)).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = resp
+        .headers()
+        .get("Retry-After")
+        .expect("429 should carry Retry-After")
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=60).contains(&retry_after));
+
+    // Probes outside /api and allowlisted addresses are never throttled
+    let resp = test::call_service(&app, get("/ready", &ip)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    for _ in 0..3 {
+        let resp = test::call_service(&app, get("/api/ping", "192.0.2.7")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
+
+#[actix_web::test]
+async fn test_top_feeds_liked_explain_compares_redis_and_mysql() {
+    use example_rust_web_service::entities::user;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    let config = Config::from_env().expect("Failed to load configuration");
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("topexplain{}@example.com", test_id),
+            "username": format!("topexplain{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let explain_request = |token: Option<&str>| {
+        let req = test::TestRequest::get().uri("/api/top/feeds-liked?explain=true");
+        match token {
+            Some(token) => req.insert_header(("Authorization", format!("Bearer {}", token))),
+            None => req,
+        }
+        .to_request()
+    };
+
+    let resp = test::call_service(&app, explain_request(None)).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let resp = test::call_service(&app, explain_request(Some(&body.token))).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    let pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    user::ActiveModel {
+        id: Set(body.user.id),
+        is_admin: Set(true),
+        ..Default::default()
+    }
+    .update(&pool)
+    .await
+    .expect("Failed to promote admin");
+
+    // A liked feed whose cached score drifted from its one like in MySQL
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", body.token)))
+        .set_json(json!({ "content": format!("Explain me {}", test_id) }))
+        .to_request();
+    let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/like", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", body.token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    let redis_client = db::create_redis_client(&config).expect("Failed to create Redis client");
+    let ranking_key = db::redis_key(&config, "top:feeds_liked");
+    let mut conn = redis_client.get_async_connection().await.unwrap();
+    let _: () = redis::cmd("ZADD")
+        .arg(&ranking_key)
+        .arg(f64::MAX)
+        .arg(feed.id)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+
+    let resp = test::call_service(&app, explain_request(Some(&body.token))).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let explain_body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(explain_body["items"][0]["feed_id"], feed.id);
+    let breakdown = &explain_body["explain"][0];
+    assert_eq!(breakdown["feed_id"], feed.id);
+    assert_eq!(breakdown["redis_score"], f64::MAX);
+    assert_eq!(breakdown["mysql_count"], 1);
+    assert!(breakdown["delta"].as_f64().unwrap() > 0.0);
+
+    let _: () = redis::cmd("ZREM")
+        .arg(&ranking_key)
+        .arg(feed.id)
+        .query_async(&mut conn)
+        .await
+        .unwrap();
+}
+
+#[actix_web::test]
+async fn test_get_comments_cursor_pagination() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("commentcursor{}@example.com", test_id),
+            "username": format!("commentcursor{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", body.token)))
+        .set_json(json!({ "content": "Feed for cursor pagination" }))
+        .to_request();
+    let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+
+    // Same-second comments are told apart by the `_id` tiebreaker
+    for i in 0..5 {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/comment", feed.id))
+            .insert_header(("Authorization", format!("Bearer {}", body.token)))
+            .set_json(json!({ "content": format!("Comment {}", i) }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    let mut seen = Vec::new();
+    let mut uri = format!("/api/feed/{}/comments?pagination=cursor&limit=2", feed.id);
+    loop {
+        let req = test::TestRequest::get().uri(&uri).to_request();
+        let page: CommentPage = test::call_and_read_body_json(&app, req).await;
+        assert!(page.items.len() <= 2);
+        seen.extend(page.items.into_iter().map(|comment| comment.id));
+        match page.next_cursor {
+            Some(cursor) => {
+                uri = format!("/api/feed/{}/comments?limit=2&cursor={}", feed.id, cursor)
+            }
+            None => break,
+        }
+    }
+    let unique: std::collections::HashSet<_> = seen.iter().collect();
+    assert_eq!(seen.len(), 5, "Every comment is returned once: {:?}", seen);
+    assert_eq!(unique.len(), 5);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/{}/comments?cursor=not-a-cursor", feed.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    // Offset mode still returns a plain array
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/{}/comments?page=1&limit=2", feed.id))
+        .to_request();
+    let comments: Vec<CommentResponse> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(comments.len(), 2);
+}
+
+#[actix_web::test]
+async fn test_liked_feeds_respect_likes_public() {
+    let app = test::init_service(create_test_app().await).await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    let mut user_ids = Vec::new();
+    for name in ["likedfeeds", "likedviewer"] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("{}{}@example.com", name, test_id),
+                "username": format!("{}{}", name, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+        tokens.push(body.token);
+        user_ids.push(body.user.id);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[1])))
+        .set_json(json!({ "content": format!("Liked publicly {}", test_id) }))
+        .to_request();
+    let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/feed/{}/like", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert!(resp.status().is_success());
+
+    // Public by default, also to anonymous callers
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/users/{}/liked-feeds", user_ids[0]))
+        .to_request();
+    let liked: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(liked.len(), 1);
+    assert_eq!(liked[0].id, feed.id);
+
+    let req = test::TestRequest::put()
+        .uri("/api/users/me/profile")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({ "likes_public": false }))
+        .to_request();
+    let profile: UserProfileResponse = test::call_and_read_body_json(&app, req).await;
+    assert!(!profile.likes_public);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/users/{}/liked-feeds", user_ids[0]))
+        .insert_header(("Authorization", format!("Bearer {}", tokens[1])))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    // The owner still sees them
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/users/{}/liked-feeds", user_ids[0]))
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .to_request();
+    let liked: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(liked.len(), 1);
+}
+
+#[actix_web::test]
+async fn test_get_feeds_filters_by_category() {
+    let app = test::init_service(create_test_app().await).await;
+    let test_id = generate_test_id();
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/signup")
+        .set_json(json!({
+            "email": format!("category{}@example.com", test_id),
+            "username": format!("category{}", test_id),
+            "password": "password123"
+        }))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/feed/categories")
+        .to_request();
+    let categories: FeedCategoriesResponse = test::call_and_read_body_json(&app, req).await;
+    assert!(categories.categories.contains(&"sports".to_string()));
+
+    for (content, category) in [("Match report", Some("Sports")), ("Morning thoughts", None)] {
+        let req = test::TestRequest::post()
+            .uri("/api/feed")
+            .insert_header(("Authorization", format!("Bearer {}", body.token)))
+            .set_json(json!({ "content": content, "category": category }))
+            .to_request();
+        let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(feed.category, category.map(str::to_lowercase));
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed?user_id={}&category=sports", body.user.id))
+        .to_request();
+    let feeds: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(feeds.len(), 1);
+    assert_eq!(feeds[0].content, "Match report");
+    assert_eq!(feeds[0].category.as_deref(), Some("sports"));
+}
+
+#[actix_web::test]
+async fn test_feed_history_keeps_previous_contents() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.feed_history.enabled = true;
+    config.feed_history.max_revisions = 2;
+    let mysql_pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    let mongodb_db = db::create_mongodb_client(&config)
+        .await
+        .expect("Failed to create MongoDB client");
+    let redis_client = db::create_redis_client(&config).expect("Failed to create Redis client");
+    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+    let repository = Arc::new(MysqlRepository::new(mysql_pool.clone()));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mysql_pool))
+            .app_data(web::Data::new(mongodb_db))
+            .app_data(web::Data::new(redis_client))
+            .app_data(web::Data::new(kafka_producer))
+            .configure(repositories::register(repository))
+            .route("/api/auth/signup", web::post().to(api::auth::signup))
+            .route("/api/feed", web::post().to(api::feed::create_feed))
+            .route("/api/feed/{feed_id}", web::patch().to(api::feed::update_feed))
+            .route("/api/feed/{feed_id}/history", web::get().to(api::feed::get_feed_history)),
+    )
+    .await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    for role in ["author", "other"] {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("history{}{}@example.com", role, test_id),
+                "username": format!("history{}{}", role, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+        tokens.push(body.token);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({"content": "First draft"}))
+        .to_request();
+    let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+
+    for content in ["Second draft", "Third draft", "Third draft", "Final"] {
+        let req = test::TestRequest::patch()
+            .uri(&format!("/api/feed/{}", feed.id))
+            .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+            .set_json(json!({ "content": content }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    // Unchanged content is not a revision, and only the newest two are kept
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/{}/history", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .to_request();
+    let revisions: Vec<FeedRevision> = test::call_and_read_body_json(&app, req).await;
+    let contents: Vec<&str> = revisions.iter().map(|r| r.content.as_str()).collect();
+    assert_eq!(contents, vec!["Third draft", "Second draft"]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed/{}/history", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", tokens[1])))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[actix_web::test]
+async fn test_concurrent_likes_share_one_aggregate_notification() {
+    use example_rust_web_service::kafka::FeedLikedEvent;
+    use example_rust_web_service::services::notification::handle_feed_liked_event;
+
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.notifications.aggregate_likes = true;
+    config.notifications.aggregate_window_minutes = 24 * 60;
+    config.notifications.aggregate_max_actors = 3;
+    let mysql_pool = db::create_mysql_pool(&config)
+        .await
+        .expect("Failed to create MySQL pool");
+    let mongodb_db = db::create_mongodb_client(&config)
+        .await
+        .expect("Failed to create MongoDB client");
+    let redis_client = db::create_redis_client(&config).expect("Failed to create Redis client");
+    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+    let repository = Arc::new(MysqlRepository::new(mysql_pool.clone()));
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config.clone()))
+            .app_data(web::Data::new(mysql_pool.clone()))
+            .app_data(web::Data::new(mongodb_db.clone()))
+            .app_data(web::Data::new(redis_client.clone()))
+            .app_data(web::Data::new(kafka_producer))
+            .configure(repositories::register(repository))
+            .route("/api/auth/signup", web::post().to(api::auth::signup))
+            .route("/api/feed", web::post().to(api::feed::create_feed))
+            .route("/api/notify", web::get().to(api::notify::get_notifications)),
+    )
+    .await;
+
+    let test_id = generate_test_id();
+    let mut tokens = Vec::new();
+    let mut user_ids = Vec::new();
+    for i in 0..5 {
+        let req = test::TestRequest::post()
+            .uri("/api/auth/signup")
+            .set_json(json!({
+                "email": format!("aggregate{}{}@example.com", i, test_id),
+                "username": format!("aggregate{}{}", i, test_id),
+                "password": "password123"
+            }))
+            .to_request();
+        let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+        tokens.push(body.token);
+        user_ids.push(body.user.id);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed")
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .set_json(json!({"content": "Like me"}))
+        .to_request();
+    let feed: FeedResponse = test::call_and_read_body_json(&app, req).await;
+
+    // Like events are handled on their own tasks, so the four likes race each other
+    let events: Vec<serde_json::Value> = user_ids[1..]
+        .iter()
+        .map(|user_id| serde_json::to_value(FeedLikedEvent::new(feed.id, *user_id)).unwrap())
+        .collect();
+    futures::future::join_all(events.iter().map(|event| {
+        handle_feed_liked_event(event, &mongodb_db, &mysql_pool, &redis_client, &config)
+    }))
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/notify?feed_id={}", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", tokens[0])))
+        .to_request();
+    let notifications: Vec<serde_json::Value> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(notifications.len(), 1, "One notification for all the likes");
+    let notification = &notifications[0];
+    assert_eq!(notification["actor_count"], 4);
+    assert_eq!(notification["content"], "4 people liked your feed");
+    assert_eq!(notification["is_read"], false);
+    assert_eq!(notification["recent_actors"].as_array().unwrap().len(), 3);
+}
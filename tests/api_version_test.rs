@@ -0,0 +1,126 @@
+// Tests for Accept header version negotiation
+// These do not need any external services
+// Run with: cargo test --test api_version_test
+
+use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+use actix_web::{http::StatusCode, web, App, HttpResponse};
+use example_rust_web_service::{
+    api::versioning::{insert_version_headers, negotiate},
+    config::{ApiVersion, Config},
+};
+
+const V1: Option<ApiVersion> = Some(ApiVersion::V1);
+const V2: Option<ApiVersion> = Some(ApiVersion::V2);
+
+fn negotiate_v1_default(accept: &str) -> Option<ApiVersion> {
+    negotiate(Some(accept), "example", ApiVersion::V1)
+}
+
+#[test]
+fn test_clients_without_a_version_get_the_default() {
+    assert_eq!(negotiate(None, "example", ApiVersion::V1), V1);
+    assert_eq!(negotiate(None, "example", ApiVersion::V2), V2);
+    assert_eq!(negotiate_v1_default(""), V1);
+    assert_eq!(negotiate_v1_default("application/json"), V1);
+    assert_eq!(negotiate_v1_default("*/*"), V1);
+    assert_eq!(negotiate_v1_default("text/html,application/xhtml+xml"), V1);
+}
+
+#[test]
+fn test_versioned_media_type_selects_the_version() {
+    assert_eq!(negotiate_v1_default("application/vnd.example.v2+json"), V2);
+    assert_eq!(negotiate_v1_default("Application/VND.Example.V2+JSON"), V2);
+    assert_eq!(
+        negotiate(
+            Some("application/vnd.example.v1+json"),
+            "example",
+            ApiVersion::V2
+        ),
+        V1
+    );
+    // Another vendor's media type is not a version request
+    assert_eq!(negotiate_v1_default("application/vnd.other.v2+json"), V1);
+}
+
+#[test]
+fn test_quality_values_pick_among_versions() {
+    assert_eq!(
+        negotiate_v1_default("application/vnd.example.v1+json, application/vnd.example.v2+json"),
+        V2,
+        "Equal quality prefers the newest version"
+    );
+    assert_eq!(
+        negotiate_v1_default(
+            "application/vnd.example.v2+json;q=0.5, application/vnd.example.v1+json;q=0.9"
+        ),
+        V1
+    );
+    assert_eq!(
+        negotiate_v1_default("application/vnd.example.v2+json; q=0, application/json"),
+        V1,
+        "q=0 rules the range out"
+    );
+}
+
+#[test]
+fn test_only_unknown_versions_are_not_acceptable() {
+    assert_eq!(
+        negotiate_v1_default("application/vnd.example.v9+json"),
+        None
+    );
+    assert_eq!(
+        negotiate_v1_default("application/vnd.example.vX+json"),
+        None
+    );
+    assert_eq!(
+        negotiate_v1_default("application/vnd.example.v9+json, application/json;q=0.1"),
+        V1,
+        "Plain JSON is an acceptable fallback"
+    );
+    assert_eq!(
+        negotiate_v1_default("application/vnd.example.v9+json, application/vnd.example.v2+json"),
+        V2
+    );
+}
+
+async fn echo_version(version: ApiVersion) -> HttpResponse {
+    let mut response = HttpResponse::Ok();
+    insert_version_headers(&mut response, version);
+    response.finish()
+}
+
+#[actix_web::test]
+async fn test_extractor_uses_configured_vendor_and_rejects_unknown_versions() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.server.media_type_vendor = "acme".to_string();
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .route("/", web::get().to(echo_version)),
+    )
+    .await;
+
+    let req = TestRequest::get()
+        .uri("/")
+        .insert_header(("Accept", "application/vnd.acme.v2+json"))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get("X-Api-Version").unwrap(), "2");
+    assert_eq!(resp.headers().get("Vary").unwrap(), "Accept");
+
+    let req = TestRequest::get()
+        .uri("/")
+        .insert_header(("Accept", "application/vnd.acme.v3+json"))
+        .to_request();
+    let resp = call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+    let body: serde_json::Value = read_body_json(resp).await;
+    assert_eq!(
+        body["supported"],
+        serde_json::json!([
+            "application/vnd.acme.v1+json",
+            "application/vnd.acme.v2+json"
+        ])
+    );
+}
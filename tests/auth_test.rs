@@ -0,0 +1,138 @@
+// Tests for the AuthenticatedUser extractor
+// These do not need any external services
+// Run with: cargo test --test auth_test
+
+use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+use example_rust_web_service::{
+    auth::{create_token, AuthenticatedUser, Claims},
+    config::Config,
+};
+
+async fn whoami(user: AuthenticatedUser) -> HttpResponse {
+    HttpResponse::Ok().body(user.user_id.to_string())
+}
+
+fn config_with_scheme(scheme: &str) -> Config {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.jwt.header_scheme = scheme.to_string();
+    config
+}
+
+#[actix_web::test]
+async fn test_custom_auth_header_scheme() {
+    let config = config_with_scheme("Token");
+    let claims = Claims::new(42, "scheme@example.com".to_string(), 1);
+    let token = create_token(&claims, &config.jwt.secret).expect("Failed to create token");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .route("/whoami", web::get().to(whoami)),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/whoami")
+        .insert_header(("Authorization", format!("Token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::OK,
+        "Configured scheme should be accepted"
+    );
+    let body = test::read_body(resp).await;
+    assert_eq!(body, "42");
+
+    // The default scheme is no longer accepted
+    let req = test::TestRequest::get()
+        .uri("/whoami")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Scheme matching is case-sensitive
+    let req = test::TestRequest::get()
+        .uri("/whoami")
+        .insert_header(("Authorization", format!("token {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    // Scheme must be followed by a space
+    let req = test::TestRequest::get()
+        .uri("/whoami")
+        .insert_header(("Authorization", format!("Token{}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_token_audience_must_be_a_configured_client_type() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.jwt.client_types = [("web".to_string(), 24), ("mobile".to_string(), 720)].into();
+    let secret = config.jwt.secret.clone();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .route("/whoami", web::get().to(whoami)),
+    )
+    .await;
+
+    // Tokens issued before audiences existed carry no `aud` and stay valid
+    let cases = [
+        (Some("mobile"), StatusCode::OK),
+        (None, StatusCode::OK),
+        (Some("integration"), StatusCode::UNAUTHORIZED),
+    ];
+    for (audience, expected) in cases {
+        let mut claims = Claims::new(42, "aud@example.com".to_string(), 1);
+        if let Some(audience) = audience {
+            claims = claims.with_audience(audience);
+        }
+        let token = create_token(&claims, &secret).expect("Failed to create token");
+        let req = test::TestRequest::get()
+            .uri("/whoami")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), expected, "audience {:?}", audience);
+    }
+}
+
+#[actix_web::test]
+async fn test_oversized_token_is_rejected_before_decoding() {
+    let claims = Claims::new(7, "long@example.com".to_string(), 1);
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    let token = create_token(&claims, &config.jwt.secret).expect("Failed to create token");
+    config.jwt.max_token_length = token.len();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .route("/whoami", web::get().to(whoami)),
+    )
+    .await;
+
+    // Exactly at the limit is fine
+    let req = test::TestRequest::get()
+        .uri("/whoami")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    for oversized in [format!("{}x", token), "a".repeat(1 << 20)] {
+        let req = test::TestRequest::get()
+            .uri("/whoami")
+            .insert_header(("Authorization", format!("Bearer {}", oversized)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        let body = test::read_body(resp).await;
+        assert_eq!(body, "Token too long");
+    }
+}
@@ -0,0 +1,68 @@
+// Tests for resolving the client IP behind trusted proxies
+// These do not need any external services
+// Run with: cargo test --test client_ip_test
+
+use actix_web::{test::TestRequest, web, HttpRequest};
+use example_rust_web_service::{
+    api::client_ip::{client_ip, resolve_client_ip},
+    config::Config,
+};
+use std::net::IpAddr;
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+fn request(peer: &str, forwarded_for: Option<&str>, trusted: &[&str]) -> HttpRequest {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.server.trusted_proxies = trusted.iter().map(|net| net.parse().unwrap()).collect();
+    let mut req = TestRequest::get()
+        .peer_addr(format!("{}:4000", peer).parse().unwrap())
+        .app_data(web::Data::new(config));
+    if let Some(forwarded_for) = forwarded_for {
+        req = req.insert_header(("X-Forwarded-For", forwarded_for));
+    }
+    req.to_http_request()
+}
+
+#[test]
+fn test_untrusted_peer_ignores_forwarded_for() {
+    let req = request("203.0.113.5", Some("198.51.100.1"), &["10.0.0.0/8"]);
+    assert_eq!(client_ip(&req), Some(ip("203.0.113.5")));
+}
+
+#[test]
+fn test_trusted_peer_uses_forwarded_for() {
+    let req = request("10.0.0.2", Some("198.51.100.1"), &["10.0.0.0/8"]);
+    assert_eq!(client_ip(&req), Some(ip("198.51.100.1")));
+}
+
+#[test]
+fn test_no_trusted_proxies_uses_peer() {
+    let req = request("10.0.0.2", Some("198.51.100.1"), &[]);
+    assert_eq!(client_ip(&req), Some(ip("10.0.0.2")));
+}
+
+#[test]
+fn test_forwarded_for_skips_trusted_hops_but_not_spoofed_ones() {
+    let trusted = ["10.0.0.0/8".parse().unwrap()];
+    // The client claimed 1.1.1.1 itself; only the hop our proxy appended counts
+    assert_eq!(
+        resolve_client_ip(
+            ip("10.0.0.2"),
+            &["1.1.1.1, 198.51.100.1", "10.0.0.7"],
+            &trusted
+        ),
+        ip("198.51.100.1")
+    );
+    // Every hop trusted: the leftmost one is the best we know
+    assert_eq!(
+        resolve_client_ip(ip("10.0.0.2"), &["10.0.0.9, 10.0.0.7"], &trusted),
+        ip("10.0.0.9")
+    );
+    // A malformed hop stops the walk at the last valid address
+    assert_eq!(
+        resolve_client_ip(ip("10.0.0.2"), &["198.51.100.1, garbage"], &trusted),
+        ip("10.0.0.2")
+    );
+}
@@ -0,0 +1,502 @@
+// Tests for configuration validation
+// These do not need any external services
+// Run with: cargo test --test config_test
+
+use example_rust_web_service::config::{
+    ApiVersion, Config, EmptyPayloadAction, JwtExpiryMode, SearchBackendKind,
+};
+
+fn base_config() -> Config {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.app_env = "development".to_string();
+    config.invalid_vars.clear();
+    config
+}
+
+#[test]
+fn test_default_config_is_valid_in_development() {
+    let config = base_config();
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_insecure_jwt_secret_rejected_in_production() {
+    let mut config = base_config();
+    config.app_env = "production".to_string();
+    config.jwt.secret = "your-secret-key-change-this".to_string();
+    assert!(
+        config.validate().is_err(),
+        "Default JWT secret should be rejected in production"
+    );
+
+    config.jwt.secret = "a-long-random-production-secret-0123456789".to_string();
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_invalid_numeric_var_rejected() {
+    let mut config = base_config();
+    config.invalid_vars.push("SERVER_PORT".to_string());
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("SERVER_PORT"), "Error should name the bad var");
+}
+
+#[test]
+fn test_empty_required_field_rejected() {
+    let mut config = base_config();
+    config.kafka.brokers = String::new();
+    let err = config.validate().unwrap_err().to_string();
+    assert!(
+        err.contains("KAFKA_BROKERS"),
+        "Error should name the empty var"
+    );
+}
+
+#[test]
+fn test_zero_kafka_send_attempts_rejected() {
+    let mut config = base_config();
+    config.kafka.send_max_attempts = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("KAFKA_SEND_MAX_ATTEMPTS"));
+}
+
+#[test]
+fn test_zero_notification_trim_interval_rejected() {
+    let mut config = base_config();
+    config.notifications.trim_interval_seconds = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("NOTIFICATIONS_TRIM_INTERVAL_SECONDS"));
+}
+
+#[test]
+fn test_heartbeat_max_age_must_exceed_interval() {
+    let mut config = base_config();
+    config.kafka.consumer_heartbeat_interval_seconds = 30;
+    config.kafka.consumer_heartbeat_max_age_seconds = 30;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("KAFKA_CONSUMER_HEARTBEAT_MAX_AGE_SECONDS"));
+}
+
+#[test]
+fn test_zero_nonce_ttl_rejected() {
+    let mut config = base_config();
+    config.jwt.nonce_ttl_seconds = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("AUTH_NONCE_TTL_SECONDS"));
+}
+
+#[test]
+fn test_invalid_cors_header_rejected() {
+    let mut config = base_config();
+    config
+        .cors
+        .route_allowed_headers
+        .insert("feed".to_string(), vec!["Bad Header".to_string()]);
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("Bad Header"));
+}
+
+#[test]
+fn test_zero_like_burst_rejected() {
+    let mut config = base_config();
+    config.rate_limit.like_per_minute = 30;
+    config.rate_limit.like_burst = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("RATE_LIMIT_LIKE_BURST"));
+
+    config.rate_limit.like_per_minute = 0;
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_feed_view_sample_rate_out_of_range_rejected() {
+    for rate in [0.0, -0.5, 1.5, f64::NAN] {
+        let mut config = base_config();
+        config.mongodb.feed_view_sample_rate = rate;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("MONGODB_FEED_VIEW_SAMPLE_RATE"),
+            "rate {}",
+            rate
+        );
+    }
+}
+
+#[test]
+fn test_zero_view_burst_rejected() {
+    let mut config = base_config();
+    config.rate_limit.view_per_minute = 120;
+    config.rate_limit.view_burst = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("RATE_LIMIT_VIEW_BURST"));
+
+    config.rate_limit.view_per_minute = 0;
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_external_search_requires_url() {
+    let mut config = base_config();
+    config.search.backend = SearchBackendKind::External;
+    config.search.external_url = String::new();
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("SEARCH_EXTERNAL_URL"));
+
+    config.search.external_url = "http://localhost:7700".to_string();
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_invalid_request_id_header_rejected() {
+    let mut config = base_config();
+    config.server.request_id_header = "X Request Id".to_string();
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("SERVER_REQUEST_ID_HEADER"));
+}
+
+#[test]
+fn test_default_client_type_must_be_configured() {
+    let mut config = base_config();
+    config.jwt.default_client_type = "desktop".to_string();
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("JWT_DEFAULT_CLIENT_TYPE"));
+
+    let mut config = base_config();
+    config.jwt.client_types.insert("kiosk".to_string(), 0);
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("kiosk"));
+}
+
+#[test]
+fn test_authenticated_feed_depth_must_not_be_below_anonymous() {
+    let mut config = base_config();
+    config.server.feed_max_depth_anonymous = 1000;
+    config.server.feed_max_depth_authenticated = 500;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("SERVER_FEED_MAX_DEPTH_AUTHENTICATED"));
+
+    config.server.feed_max_depth_authenticated = 0;
+    assert!(config.validate().is_ok());
+
+    config.server.feed_max_depth_authenticated = 5000;
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_enabled_feed_cache_needs_ttl() {
+    let mut config = base_config();
+    config.feed_cache.ttl_seconds = 0;
+    assert!(config.validate().is_ok(), "TTL is unused while disabled");
+
+    config.feed_cache.enabled = true;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("FEED_CACHE_TTL_SECONDS"));
+}
+
+#[test]
+fn test_notification_templates_only_use_known_placeholders() {
+    let mut config = base_config();
+    config.notifications.like_template = "{username} a aimé votre publication".to_string();
+    assert!(config.validate().is_ok());
+
+    // `{content}` is only available for comments
+    config.notifications.like_template = "{username}: {content}".to_string();
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("NOTIFICATIONS_LIKE_TEMPLATE"));
+
+    let mut config = base_config();
+    config.notifications.comment_template = " ".to_string();
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("NOTIFICATIONS_COMMENT_TEMPLATE"));
+}
+
+#[test]
+fn test_localized_notification_templates_are_validated() {
+    let mut config = base_config();
+    config
+        .notifications
+        .like_templates
+        .insert("fr".to_string(), "{username} a aimé votre publication".to_string());
+    assert!(config.validate().is_ok());
+
+    config
+        .notifications
+        .comment_templates
+        .insert("de".to_string(), "{username}: {text}".to_string());
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("NOTIFICATIONS_COMMENT_TEMPLATES (de)"));
+
+    let mut config = base_config();
+    config
+        .notifications
+        .like_templates
+        .insert("français".to_string(), "{username}".to_string());
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("not a valid language tag"));
+}
+
+#[test]
+fn test_denormalized_like_counts_need_reconcile_interval() {
+    let mut config = base_config();
+    config.like_counts.reconcile_interval_seconds = 0;
+    assert!(config.validate().is_ok(), "Interval is unused while disabled");
+
+    config.like_counts.denormalized = true;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("LIKE_COUNT_RECONCILE_INTERVAL_SECONDS"));
+}
+
+#[test]
+fn test_top_stats_cache_size_must_be_positive() {
+    let mut config = base_config();
+    config.top_stats.cache_size = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("TOP_STATS_CACHE_SIZE"));
+}
+
+#[test]
+fn test_sliding_threshold_must_be_shorter_than_token_lifetimes() {
+    let mut config = base_config();
+    config.jwt.expiry_mode = JwtExpiryMode::Sliding;
+    config.jwt.sliding_threshold_minutes = 60;
+    config.jwt.client_types.insert("integration".to_string(), 1);
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("JWT_SLIDING_THRESHOLD_MINUTES"));
+
+    config.jwt.client_types.insert("integration".to_string(), 2);
+    assert!(config.validate().is_ok());
+
+    config.jwt.sliding_threshold_minutes = 0;
+    assert!(config.validate().is_err());
+
+    // Fixed mode ignores the threshold
+    config.jwt.expiry_mode = JwtExpiryMode::Fixed;
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_seed_endpoint_rejected_in_production() {
+    let mut config = base_config();
+    config.server.seed_endpoint = true;
+    assert!(config.validate().is_ok());
+
+    config.app_env = "production".to_string();
+    config.jwt.secret = "a-long-random-production-secret-0123456789".to_string();
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("ENABLE_SEED_ENDPOINT"));
+}
+
+#[test]
+fn test_startup_connect_retry_settings_validated() {
+    let mut config = base_config();
+    config.startup.connect_max_attempts = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("STARTUP_CONNECT_MAX_ATTEMPTS"));
+
+    config.startup.connect_max_attempts = 1;
+    config.startup.connect_retry_backoff_ms = 1000;
+    config.startup.connect_max_backoff_ms = 500;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("STARTUP_CONNECT_MAX_BACKOFF_MS"));
+}
+
+#[test]
+fn test_max_token_length_must_be_positive() {
+    let mut config = base_config();
+    config.jwt.max_token_length = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("JWT_MAX_TOKEN_LENGTH"));
+}
+
+#[test]
+fn test_digest_hour_and_min_unread_are_checked() {
+    let mut config = base_config();
+    config.digests.hour_utc = 24;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("DIGESTS_HOUR_UTC"));
+
+    let mut config = base_config();
+    config.digests.min_unread = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("DIGESTS_MIN_UNREAD"));
+}
+
+#[test]
+fn test_max_pinned_feeds_must_be_positive() {
+    let mut config = base_config();
+    config.content.max_pinned_feeds = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("CONTENT_MAX_PINNED_FEEDS"));
+}
+
+#[test]
+fn test_empty_payload_action_parses() {
+    assert_eq!("skip".parse(), Ok(EmptyPayloadAction::Skip));
+    assert_eq!("DLQ".parse(), Ok(EmptyPayloadAction::DeadLetter));
+    assert!("drop".parse::<EmptyPayloadAction>().is_err());
+}
+
+#[test]
+fn test_audit_log_defaults_to_enabled() {
+    let config = base_config();
+    assert!(config.audit_log.enabled);
+    assert!(config.audit_log.include_reads);
+}
+
+#[test]
+fn test_api_version_parses_and_vendor_is_validated() {
+    assert_eq!("v2".parse(), Ok(ApiVersion::V2));
+    assert_eq!("1".parse(), Ok(ApiVersion::V1));
+    assert!("v3".parse::<ApiVersion>().is_err());
+
+    let mut config = base_config();
+    config.server.media_type_vendor = "my vendor".to_string();
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("SERVER_MEDIA_TYPE_VENDOR"));
+}
+
+#[test]
+fn test_moderation_reason_length_must_be_positive() {
+    let mut config = base_config();
+    assert!(config.moderation.reports_enabled);
+    assert_eq!(config.moderation.max_reason_chars, 500);
+    config.moderation.max_reason_chars = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("MODERATION_MAX_REASON_CHARS"), "{}", err);
+}
+
+#[test]
+fn test_zero_ip_burst_rejected() {
+    let mut config = base_config();
+    config.rate_limit.ip_per_minute = 600;
+    config.rate_limit.ip_burst = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("RATE_LIMIT_IP_BURST"));
+
+    config.rate_limit.ip_per_minute = 0;
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_like_weights_default_to_one_and_must_be_positive() {
+    let mut config = base_config();
+    assert_eq!(config.top_stats.like_weight(false, false), 1.0);
+    assert_eq!(config.top_stats.like_weight(false, true), 1.0);
+    assert_eq!(config.top_stats.like_weight(true, false), 1.0);
+
+    config.top_stats.like_weight_verified = 2.0;
+    config.top_stats.like_weight_admin = 3.0;
+    assert_eq!(config.top_stats.like_weight(false, true), 2.0);
+    assert_eq!(config.top_stats.like_weight(true, true), 3.0);
+    assert!(config.validate().is_ok());
+
+    config.top_stats.like_weight_verified = 0.0;
+    config.top_stats.like_weight_admin = f64::NAN;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("TOP_STATS_LIKE_WEIGHT_VERIFIED"));
+    assert!(err.contains("TOP_STATS_LIKE_WEIGHT_ADMIN"));
+}
+
+#[test]
+fn test_self_test_is_off_by_default_and_strict_needs_it() {
+    let mut config = base_config();
+    assert!(!config.startup.self_test);
+    assert!(!config.startup.self_test_strict);
+
+    config.startup.self_test_strict = true;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("SELF_TEST_STRICT"));
+
+    config.startup.self_test = true;
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_feed_categories_must_be_simple_names() {
+    let mut config = base_config();
+    assert!(config.content.feed_categories.contains(&"news".to_string()));
+
+    config.content.feed_categories = vec!["news".to_string(), "arts & crafts".to_string()];
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("CONTENT_FEED_CATEGORIES"));
+    assert!(err.contains("arts & crafts"));
+
+    config.content.feed_categories = Vec::new();
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn test_top_stats_max_limit_must_be_positive() {
+    let mut config = base_config();
+    assert_eq!(config.top_stats.max_limit, 100);
+    config.top_stats.max_limit = 0;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("TOP_STATS_MAX_LIMIT"));
+}
+
+#[test]
+fn test_feed_history_max_revisions_must_be_positive() {
+    let mut config = base_config();
+    assert!(!config.feed_history.enabled);
+    config.feed_history.max_revisions = 0;
+    assert!(config.validate().is_ok(), "Only checked when history is enabled");
+    config.feed_history.enabled = true;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("FEED_HISTORY_MAX_REVISIONS"));
+}
+
+#[test]
+fn test_like_aggregation_needs_a_window_and_actors() {
+    let mut config = base_config();
+    assert!(!config.notifications.aggregate_likes);
+    config.notifications.aggregate_window_minutes = 0;
+    config.notifications.aggregate_max_actors = 0;
+    assert!(config.validate().is_ok(), "Only checked when aggregation is on");
+    config.notifications.aggregate_likes = true;
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("NOTIFICATIONS_AGGREGATE_WINDOW_MINUTES"));
+    assert!(err.contains("NOTIFICATIONS_AGGREGATE_MAX_ACTORS"));
+
+    let mut config = base_config();
+    config.notifications.like_aggregate_template = "{count} likes, latest {content}".to_string();
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("NOTIFICATIONS_LIKE_AGGREGATE_TEMPLATE"));
+}
+
+#[test]
+fn test_max_token_lifetime_covers_every_client_type() {
+    let mut config = base_config();
+    config.jwt.expiration_hours = 24;
+    config.jwt.client_types.clear();
+    assert_eq!(config.jwt.max_lifetime_hours(), 24);
+
+    config.jwt.client_types.insert("mobile".to_string(), 720);
+    config.jwt.client_types.insert("kiosk".to_string(), 1);
+    assert_eq!(config.jwt.max_lifetime_hours(), 720);
+}
+
+#[test]
+fn test_write_concerns_must_be_known() {
+    let mut config = base_config();
+    for write_concern in ["majority", "MAJORITY", " Majority ", "1", "3"] {
+        config.mongodb.write_concern = Some(write_concern.to_string());
+        assert!(config.validate().is_ok(), "{}", write_concern);
+    }
+
+    config.mongodb.write_concern = Some("majorty".to_string());
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("MONGODB_WRITE_CONCERN"));
+    assert!(err.contains("majorty"));
+
+    config.mongodb.write_concern = Some("multiDC".to_string());
+    assert!(config.validate().is_err());
+    config.mongodb.write_concern_tags = vec!["multiDC".to_string()];
+    assert!(config.validate().is_ok());
+
+    config
+        .mongodb
+        .collection_write_concerns
+        .insert("feed_views".to_string(), "one".to_string());
+    let err = config.validate().unwrap_err().to_string();
+    assert!(err.contains("MONGODB_COLLECTION_WRITE_CONCERNS (feed_views)"));
+}
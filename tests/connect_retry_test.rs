@@ -0,0 +1,55 @@
+// Tests for retrying startup connections
+// These do not need any external services
+// Run with: cargo test --test connect_retry_test
+
+use example_rust_web_service::{config::Config, db::connect_with_retry};
+use std::cell::Cell;
+
+fn retry_config(max_attempts: u32) -> Config {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.startup.connect_max_attempts = max_attempts;
+    config.startup.connect_retry_backoff_ms = 1;
+    config.startup.connect_max_backoff_ms = 4;
+    config
+}
+
+#[actix_web::test]
+async fn test_connect_succeeds_after_transient_failures() {
+    let config = retry_config(5);
+    let attempts = Cell::new(0);
+
+    let value = connect_with_retry(&config, "Test", || {
+        attempts.set(attempts.get() + 1);
+        let attempt = attempts.get();
+        async move {
+            if attempt < 3 {
+                Err(anyhow::anyhow!("not ready"))
+            } else {
+                Ok(attempt)
+            }
+        }
+    })
+    .await
+    .expect("Third attempt should succeed");
+
+    assert_eq!(value, 3);
+    assert_eq!(attempts.get(), 3);
+}
+
+#[actix_web::test]
+async fn test_connect_gives_up_after_max_attempts() {
+    let config = retry_config(3);
+    let attempts = Cell::new(0);
+
+    let err = connect_with_retry(&config, "Test", || {
+        attempts.set(attempts.get() + 1);
+        async { Err::<(), _>(anyhow::anyhow!("connection refused")) }
+    })
+    .await
+    .unwrap_err();
+
+    assert_eq!(attempts.get(), 3);
+    let message = format!("{:#}", err);
+    assert!(message.contains("Test unreachable after 3 attempts"));
+    assert!(message.contains("connection refused"), "Keeps the last error");
+}
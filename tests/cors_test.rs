@@ -0,0 +1,89 @@
+// Tests for CORS preflight handling on /api scopes
+// These do not need any external services
+// Run with: cargo test --test cors_test
+
+use actix_web::{http::StatusCode, test, web, App};
+use example_rust_web_service::{api, config::Config};
+
+fn preflight(uri: &str, headers: &str) -> test::TestRequest {
+    test::TestRequest::default()
+        .method(actix_web::http::Method::OPTIONS)
+        .uri(uri)
+        .insert_header(("Origin", "https://app.example.com"))
+        .insert_header(("Access-Control-Request-Method", "POST"))
+        .insert_header(("Access-Control-Request-Headers", headers))
+}
+
+#[actix_web::test]
+async fn test_preflight_skips_auth_and_honours_route_headers() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.cors.max_age_seconds = 600;
+    config
+        .cors
+        .route_allowed_headers
+        .insert("feed".to_string(), vec!["X-Client-Version".to_string()]);
+    let cors_config = config.cors.clone();
+
+    // Handlers requiring AuthenticatedUser; none of their other app data is registered
+    let app = test::init_service(
+        App::new().app_data(web::Data::new(config)).service(
+            web::scope("/api")
+                .service(
+                    web::scope("/feed")
+                        .wrap(api::cors::cors(&cors_config, "feed"))
+                        .route("/{feed_id}/like", web::post().to(api::feed::like_feed)),
+                )
+                .service(
+                    web::scope("/notify")
+                        .wrap(api::cors::cors(&cors_config, "notify"))
+                        .route("/read", web::put().to(api::notify::mark_notifications_read)),
+                ),
+        ),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        preflight(
+            "/api/feed/1/like",
+            "authorization, idempotency-key, x-client-version",
+        )
+        .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get("Access-Control-Max-Age").unwrap(), "600");
+    let allowed = resp
+        .headers()
+        .get("Access-Control-Allow-Headers")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_ascii_lowercase();
+    assert!(allowed.contains("x-client-version"));
+    assert!(allowed.contains("idempotency-key"));
+
+    // Route-specific headers are not allowed on other scopes
+    let resp = test::call_service(
+        &app,
+        preflight("/api/notify/read", "authorization, x-client-version").to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = test::call_service(
+        &app,
+        preflight("/api/notify/read", "authorization, x-request-id").to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // The actual request still goes through auth
+    let req = test::TestRequest::post()
+        .uri("/api/feed/1/like")
+        .insert_header(("Origin", "https://app.example.com"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert!(resp.headers().contains_key("Access-Control-Allow-Origin"));
+}
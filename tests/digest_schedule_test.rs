@@ -0,0 +1,31 @@
+// Tests for the daily digest schedule (next_digest_run)
+// These do not need any external services
+// Run with: cargo test --test digest_schedule_test
+
+use chrono::{TimeZone, Utc};
+use example_rust_web_service::jobs::next_digest_run;
+
+#[test]
+fn test_runs_later_the_same_day_before_the_hour() {
+    let now = Utc.with_ymd_and_hms(2024, 3, 10, 6, 30, 0).unwrap();
+    assert_eq!(
+        next_digest_run(now, 8),
+        Utc.with_ymd_and_hms(2024, 3, 10, 8, 0, 0).unwrap()
+    );
+}
+
+#[test]
+fn test_runs_the_next_day_at_or_after_the_hour() {
+    let now = Utc.with_ymd_and_hms(2024, 3, 10, 8, 0, 0).unwrap();
+    assert_eq!(
+        next_digest_run(now, 8),
+        Utc.with_ymd_and_hms(2024, 3, 11, 8, 0, 0).unwrap(),
+        "A run at exactly the hour must not be scheduled again immediately"
+    );
+
+    let now = Utc.with_ymd_and_hms(2024, 12, 31, 23, 15, 0).unwrap();
+    assert_eq!(
+        next_digest_run(now, 0),
+        Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()
+    );
+}
@@ -0,0 +1,1446 @@
+// Handler tests against in-memory repositories
+// These do not need MySQL; Redis is optional (sessions and rate limits fail open)
+// Run with: cargo test --features test-support --test in_memory_test
+
+use actix_web::{http::StatusCode, test, web, App};
+use example_rust_web_service::{
+    api,
+    auth::{create_token, hash_password, verify_token, Claims},
+    config::Config,
+    kafka::KafkaProducer,
+    models::{
+        AuthResponse, FeedAuthor, FeedCategoriesResponse, FeedPreviewResponse, FeedResponse,
+        FeedResponseV2, LikedStatusResult, ReactionType,
+    },
+    repositories::{self, FeedRepository, InMemoryRepository, LikeRepository, ReactionRepository},
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+fn token_for(config: &Config, user_id: i64) -> String {
+    let claims = Claims::new(user_id, format!("user{}@example.com", user_id), 1);
+    create_token(&claims, &config.jwt.secret).expect("Failed to create token")
+}
+
+#[actix_web::test]
+async fn test_login_against_in_memory_users() {
+    let config = Config::from_env().expect("Failed to load configuration");
+    let repository = Arc::new(InMemoryRepository::new());
+    let password_hash = hash_password("password123").unwrap();
+    let user = repository.insert_user("memory@example.com", "memory", &password_hash);
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(redis_client))
+            .configure(repositories::register(repository))
+            .route("/api/auth/login", web::post().to(api::auth::login)),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({"email": "memory@example.com", "password": "password123"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: AuthResponse = test::read_body_json(resp).await;
+    assert_eq!(body.user.id, user.id);
+
+    // Tokens are issued for the requested client type, with its lifetime
+    let config = Config::from_env().expect("Failed to load configuration");
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({"email": "memory@example.com", "password": "password123", "client_type": "mobile"}))
+        .to_request();
+    let body: AuthResponse = test::call_and_read_body_json(&app, req).await;
+    let claims = verify_token(&body.token, &config.jwt.secret, &config.jwt.audiences()).unwrap();
+    assert_eq!(claims.aud.as_deref(), Some("mobile"));
+    assert_eq!(
+        claims.exp - claims.iat,
+        config.jwt.client_types["mobile"] * 3600
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({"email": "memory@example.com", "password": "password123", "client_type": "toaster"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({"email": "memory@example.com", "password": "wrong"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({"email": "missing@example.com", "password": "password123"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_liked_status_and_unlike_against_in_memory_likes() {
+    let config = Config::from_env().expect("Failed to load configuration");
+    let repository = Arc::new(InMemoryRepository::new());
+    let author = repository.insert_user("author@example.com", "author", "");
+    let reader = repository.insert_user("reader@example.com", "reader", "");
+    let liked = repository.insert_feed(author.id, "Liked feed");
+    let other = repository.insert_feed(author.id, "Other feed");
+    repository.insert_like(liked.id, reader.id);
+    let token = token_for(&config, reader.id);
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(redis_client))
+            .configure(repositories::register(repository))
+            .route(
+                "/api/feed/liked-status",
+                web::post().to(api::feed::get_liked_status),
+            )
+            .route(
+                "/api/feed/{feed_id}/like",
+                web::delete().to(api::feed::unlike_feed),
+            ),
+    )
+    .await;
+
+    let liked_status = || {
+        test::TestRequest::post()
+            .uri("/api/feed/liked-status")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"feed_ids": [liked.id, other.id, -1]}))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, liked_status()).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let status: LikedStatusResult = test::read_body_json(resp).await;
+    let liked_by_id: HashMap<i64, bool> = status
+        .succeeded
+        .iter()
+        .map(|s| (s.feed_id, s.liked))
+        .collect();
+    assert!(liked_by_id[&liked.id]);
+    assert!(!liked_by_id[&other.id]);
+    assert_eq!(status.failed.len(), 1);
+    assert_eq!(status.failed[0].id, "-1");
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/feed/{}/like", liked.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let resp = test::call_service(&app, liked_status()).await;
+    let status: LikedStatusResult = test::read_body_json(resp).await;
+    assert!(status.succeeded.iter().all(|s| !s.liked));
+}
+
+#[actix_web::test]
+async fn test_preview_feed_resolves_mentions_and_hashtags() {
+    let config = Config::from_env().expect("Failed to load configuration");
+    let repository = Arc::new(InMemoryRepository::new());
+    let author = repository.insert_user("author@example.com", "author", "");
+    let alice = repository.insert_user("alice@example.com", "Alice", "");
+    let token = token_for(&config, author.id);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .configure(repositories::register(repository))
+            .route("/api/feed/preview", web::post().to(api::feed::preview_feed)),
+    )
+    .await;
+
+    let preview = |content: &str| {
+        test::TestRequest::post()
+            .uri("/api/feed/preview")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"content": content}))
+            .to_request()
+    };
+
+    let content = "  Hi @alice and @ghost, mail me@example.com <b>#Rust</b> #rust #2024\n";
+    let resp = test::call_service(&app, preview(content)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: FeedPreviewResponse = test::read_body_json(resp).await;
+    assert_eq!(body.content, content.trim());
+    assert_eq!(body.hashtags, vec!["rust", "2024"]);
+    assert_eq!(
+        body.mentions.len(),
+        1,
+        "Unknown users and emails are not mentions"
+    );
+    assert_eq!(body.mentions[0].user_id, alice.id);
+    assert_eq!(body.mentions[0].username, "Alice");
+    assert_eq!(
+        body.html,
+        format!(
+            "Hi <span class=\"mention\" data-user-id=\"{}\">@alice</span> and @ghost, \
+             mail me@example.com &lt;b&gt;<span class=\"hashtag\">#Rust</span>&lt;/b&gt; \
+             <span class=\"hashtag\">#rust</span> <span class=\"hashtag\">#2024</span>",
+            alice.id
+        )
+    );
+
+    let resp = test::call_service(&app, preview("   ")).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, preview(&"a".repeat(5001))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed/preview")
+        .set_json(json!({"content": "hello"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[actix_web::test]
+async fn test_preview_mention_case_sensitivity_follows_config() {
+    for case_insensitive in [true, false] {
+        let mut config = Config::from_env().expect("Failed to load configuration");
+        config.usernames.case_insensitive = case_insensitive;
+        let repository = Arc::new(InMemoryRepository::new());
+        let alice = repository.insert_user("alice@example.com", "alice", "");
+        let token = token_for(&config, alice.id);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config))
+                .configure(repositories::register(repository))
+                .route("/api/feed/preview", web::post().to(api::feed::preview_feed)),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/feed/preview")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"content": "Thanks @Alice"}))
+            .to_request();
+        let body: FeedPreviewResponse = test::call_and_read_body_json(&app, req).await;
+        let resolved: Vec<i64> = body.mentions.iter().map(|m| m.user_id).collect();
+        if case_insensitive {
+            assert_eq!(resolved, vec![alice.id], "@Alice should resolve to alice");
+        } else {
+            assert!(resolved.is_empty(), "@Alice should not resolve to alice");
+        }
+    }
+}
+
+#[actix_web::test]
+async fn test_preview_ignores_or_rejects_mentions_over_the_cap() {
+    for reject in [false, true] {
+        let mut config = Config::from_env().expect("Failed to load configuration");
+        config.usernames.case_insensitive = true;
+        config.content.max_mentions = 2;
+        config.content.reject_excess_mentions = reject;
+        let repository = Arc::new(InMemoryRepository::new());
+        let author = repository.insert_user("author@example.com", "author", "");
+        let users: Vec<_> = ["ann", "bob", "cat"]
+            .iter()
+            .map(|name| repository.insert_user(&format!("{}@example.com", name), name, ""))
+            .collect();
+        let token = token_for(&config, author.id);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config))
+                .configure(repositories::register(repository))
+                .route("/api/feed/preview", web::post().to(api::feed::preview_feed)),
+        )
+        .await;
+
+        // Repeats count once, so this is three distinct mentions
+        let req = test::TestRequest::post()
+            .uri("/api/feed/preview")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"content": "@ann @ANN @bob @cat"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        if reject {
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            continue;
+        }
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: FeedPreviewResponse = test::read_body_json(resp).await;
+        let resolved: Vec<i64> = body.mentions.iter().map(|m| m.user_id).collect();
+        assert_eq!(resolved, vec![users[0].id, users[1].id]);
+        assert!(body.html.ends_with(" @cat"), "Ignored mentions stay plain text");
+    }
+}
+
+#[actix_web::test]
+async fn test_react_and_unreact_against_in_memory_reactions() {
+    let config = Config::from_env().expect("Failed to load configuration");
+    let repository = Arc::new(InMemoryRepository::new());
+    let author = repository.insert_user("author@example.com", "author", "");
+    let reader = repository.insert_user("reader@example.com", "reader", "");
+    let feed = repository.insert_feed(author.id, "Reacted feed");
+    let token = token_for(&config, reader.id);
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(redis_client))
+            .app_data(web::Data::new(kafka_producer))
+            .configure(repositories::register(repository.clone()))
+            .route(
+                "/api/feed/{feed_id}/react",
+                web::post().to(api::feed::react_feed),
+            )
+            .route(
+                "/api/feed/{feed_id}/react/{reaction_type}",
+                web::delete().to(api::feed::unreact_feed),
+            ),
+    )
+    .await;
+
+    let react = |feed_id: i64, reaction: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/react", feed_id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"type": reaction}))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, react(feed.id, "love")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = test::call_service(&app, react(feed.id, "wow")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = test::call_service(&app, react(feed.id, "love")).await;
+    assert_eq!(
+        resp.status(),
+        StatusCode::CONFLICT,
+        "Same reaction twice should be rejected"
+    );
+    let resp = test::call_service(&app, react(feed.id, "meh")).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, react(feed.id + 100, "love")).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let counts = repository.counts_for_feed(feed.id).await.unwrap();
+    assert_eq!(counts.get(&ReactionType::Love), Some(&1));
+    assert_eq!(counts.get(&ReactionType::Wow), Some(&1));
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/feed/{}/react/love", feed.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let counts = repository.counts_for_feed(feed.id).await.unwrap();
+    assert_eq!(counts.get(&ReactionType::Love), None);
+    assert_eq!(counts.get(&ReactionType::Wow), Some(&1));
+}
+
+#[actix_web::test]
+async fn test_create_feed_detects_language_when_enabled() {
+    for language_detection in [true, false] {
+        let mut config = Config::from_env().expect("Failed to load configuration");
+        config.content.language_detection = language_detection;
+        let repository = Arc::new(InMemoryRepository::new());
+        let author = repository.insert_user("author@example.com", "author", "");
+        let token = token_for(&config, author.id);
+        let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+        let redis_client =
+            redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(kafka_producer))
+                .app_data(web::Data::new(redis_client))
+                .configure(repositories::register(repository.clone()))
+                .route("/api/feed", web::post().to(api::feed::create_feed)),
+        )
+        .await;
+
+        let create = |content: &str| {
+            test::TestRequest::post()
+                .uri("/api/feed")
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .set_json(json!({ "content": content }))
+                .to_request()
+        };
+
+        let english: FeedResponse = test::call_and_read_body_json(
+            &app,
+            create("The weather is lovely today and we are going for a long walk in the park"),
+        )
+        .await;
+        let spanish: FeedResponse = test::call_and_read_body_json(
+            &app,
+            create("Hoy hace muy buen tiempo y vamos a dar un paseo largo por el parque"),
+        )
+        .await;
+
+        if !language_detection {
+            assert_eq!(english.language, None);
+            assert_eq!(spanish.language, None);
+            continue;
+        }
+        assert_eq!(english.language.as_deref(), Some("eng"));
+        assert_eq!(spanish.language.as_deref(), Some("spa"));
+
+        let english_feeds = repository.list_visible(Some("eng"), None, 0, 20).await.unwrap();
+        assert_eq!(
+            english_feeds.iter().map(|f| f.id).collect::<Vec<_>>(),
+            vec![english.id]
+        );
+        assert_eq!(repository.count_visible(Some("spa"), None).await.unwrap(), 1);
+        assert_eq!(repository.count_visible(None, None).await.unwrap(), 2);
+    }
+}
+
+#[actix_web::test]
+async fn test_feed_batch_flags_degraded_comment_counts() {
+    // Nothing listens on port 1, so every comment count fails quickly
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+
+    for degrade_counts in [true, false] {
+        let mut config = Config::from_env().expect("Failed to load configuration");
+        config.mongodb.degrade_counts = degrade_counts;
+        let repository = Arc::new(InMemoryRepository::new());
+        let author = repository.insert_user("author@example.com", "author", "");
+        let feed = repository.insert_feed(author.id, "Feed while MongoDB is down");
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config))
+                .app_data(web::Data::new(mongo_db.clone()))
+                .configure(repositories::register(repository))
+                .route(
+                    "/api/feed/batch",
+                    web::post().to(api::feed::get_feeds_batch),
+                ),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/feed/batch")
+            .set_json(json!({ "feed_ids": [feed.id] }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        if !degrade_counts {
+            assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+            continue;
+        }
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["succeeded"][0]["comment_count"], 0);
+        assert_eq!(body["succeeded"][0]["counts_degraded"], true);
+    }
+}
+
+#[actix_web::test]
+async fn test_patch_feed_updates_only_given_fields() {
+    let config = Config::from_env().expect("Failed to load configuration");
+    let repository = Arc::new(InMemoryRepository::new());
+    let author = repository.insert_user("author@example.com", "author", "");
+    let other = repository.insert_user("other@example.com", "other", "");
+    let published = repository.insert_feed(author.id, "Original content");
+    let publish_at = chrono::Utc::now() + chrono::Duration::days(1);
+    let scheduled = repository
+        .create(author.id, "Scheduled", None, None, Some(publish_at), None, false)
+        .await
+        .unwrap();
+    let token = token_for(&config, author.id);
+    let other_token = token_for(&config, other.id);
+    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+    // Comment counts are not under test; an unreachable MongoDB just degrades them
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(kafka_producer))
+            .app_data(web::Data::new(mongo_db))
+            .app_data(web::Data::new(redis_client))
+            .configure(repositories::register(repository.clone()))
+            .route(
+                "/api/feed/{feed_id}",
+                web::patch().to(api::feed::update_feed),
+            ),
+    )
+    .await;
+
+    let patch = |feed_id: i64, token: &str, body: serde_json::Value| {
+        test::TestRequest::patch()
+            .uri(&format!("/api/feed/{}", feed_id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(body)
+            .to_request()
+    };
+
+    let resp = test::call_service(
+        &app,
+        patch(scheduled.id, &token, json!({"content": "  Edited  "})),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let feed: FeedResponse = test::read_body_json(resp).await;
+    assert_eq!(feed.content, "Edited");
+    assert_eq!(feed.publish_at, Some(publish_at), "publish_at was not sent");
+    assert!(
+        feed.updated_at.is_some_and(|updated_at| updated_at > feed.created_at),
+        "An edited feed reports when it changed"
+    );
+
+    for (feed_id, token, body, expected) in [
+        (published.id, &token, json!({}), StatusCode::BAD_REQUEST),
+        (
+            published.id,
+            &token,
+            json!({"content": null}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            published.id,
+            &token,
+            json!({"content": ""}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            published.id,
+            &token,
+            json!({"publish_at": null}),
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            published.id,
+            &other_token,
+            json!({"content": "Mine now"}),
+            StatusCode::FORBIDDEN,
+        ),
+        (
+            -1,
+            &token,
+            json!({"content": "Nothing here"}),
+            StatusCode::NOT_FOUND,
+        ),
+    ] {
+        let resp = test::call_service(&app, patch(feed_id, token, body.clone())).await;
+        assert_eq!(resp.status(), expected, "body {}", body);
+    }
+    let unchanged = repository.find_by_id(published.id).await.unwrap().unwrap();
+    assert_eq!(unchanged.content, "Original content");
+
+    // An explicit null publishes the scheduled feed now
+    let resp = test::call_service(
+        &app,
+        patch(scheduled.id, &token, json!({"publish_at": null})),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let stored = repository.find_by_id(scheduled.id).await.unwrap().unwrap();
+    assert!(stored.is_published);
+    assert_eq!(stored.publish_at, None);
+    assert_eq!(stored.content, "Edited");
+}
+
+#[actix_web::test]
+async fn test_feed_paging_depth_is_capped_for_anonymous_callers() {
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.server.feed_max_depth_anonymous = 1000;
+    config.server.feed_max_depth_authenticated = 0;
+    let token = token_for(&config, 1);
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+    let repository = Arc::new(InMemoryRepository::new());
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mongo_db))
+            .app_data(web::Data::new(redis_client))
+            .configure(repositories::register(repository))
+            .route("/api/feed", web::get().to(api::feed::get_feeds)),
+    )
+    .await;
+
+    // page 50 of 20 ends exactly at the cap
+    let req = test::TestRequest::get()
+        .uri("/api/feed?page=50&limit=20")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/api/feed?page=51&limit=20")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["max_depth"], 1000);
+
+    // Signed-in callers are not capped
+    let req = test::TestRequest::get()
+        .uri("/api/feed?page=51&limit=20")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    // ...but a page whose offset does not fit in a u64 is still rejected, and page 0 is page 1
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed?page={}&limit=20", u64::MAX))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "page is out of range");
+    let req = test::TestRequest::get()
+        .uri("/api/feed?page=0&limit=20")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_common_likes_intersects_both_users_likes() {
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let config = Config::from_env().expect("Failed to load configuration");
+    let repository = Arc::new(InMemoryRepository::new());
+    let me = repository.insert_user("me@example.com", "me", "");
+    let other = repository.insert_user("other@example.com", "other", "");
+    let only_mine = repository.insert_feed(other.id, "Only I liked this");
+    let shared = repository.insert_feed(other.id, "We both liked this");
+    let only_theirs = repository.insert_feed(me.id, "Only they liked this");
+    repository.insert_like(only_mine.id, me.id);
+    repository.insert_like(shared.id, me.id);
+    repository.insert_like(shared.id, other.id);
+    repository.insert_like(only_theirs.id, other.id);
+    let token = token_for(&config, me.id);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mongo_db))
+            .configure(repositories::register(repository))
+            .route(
+                "/api/users/{id}/common-likes",
+                web::get().to(api::users::get_common_likes),
+            ),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/users/{}/common-likes", other.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "1");
+    let common: Vec<FeedResponse> = test::read_body_json(resp).await;
+    assert_eq!(common.len(), 1);
+    assert_eq!(common[0].id, shared.id);
+    assert_eq!(common[0].like_count, 2);
+    assert!(common[0].is_liked);
+
+    let req = test::TestRequest::get()
+        .uri("/api/users/999999/common-likes")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_denormalized_like_count_follows_likes() {
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.mongodb.degrade_counts = true;
+    config.like_counts.denormalized = true;
+    let repository = Arc::new(InMemoryRepository::new());
+    let author = repository.insert_user("author@example.com", "author", "");
+    let fan = repository.insert_user("fan@example.com", "fan", "");
+    let feed = repository.insert_feed(author.id, "Hot feed");
+
+    LikeRepository::insert(repository.as_ref(), feed.id, author.id).await.unwrap();
+    LikeRepository::insert(repository.as_ref(), feed.id, fan.id).await.unwrap();
+    LikeRepository::delete(repository.as_ref(), feed.id, author.id).await.unwrap();
+    // Removing a like that does not exist leaves the counter alone
+    LikeRepository::delete(repository.as_ref(), feed.id, author.id).await.unwrap();
+
+    let stored = FeedRepository::find_by_id(repository.as_ref(), feed.id).await.unwrap();
+    assert_eq!(stored.unwrap().like_count, 1);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mongo_db))
+            .configure(repositories::register(repository))
+            .route(
+                "/api/feed/batch",
+                web::post().to(api::feed::get_feeds_batch),
+            ),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed/batch")
+        .set_json(json!({ "feed_ids": [feed.id] }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["succeeded"][0]["like_count"], 1);
+    assert_eq!(body["succeeded"][0]["reactions"]["like"], 1);
+}
+
+#[actix_web::test]
+async fn test_bookmarks_are_listed_newest_first_and_flagged() {
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let config = Config::from_env().expect("Failed to load configuration");
+    let repository = Arc::new(InMemoryRepository::new());
+    let me = repository.insert_user("me@example.com", "me", "");
+    let author = repository.insert_user("author@example.com", "author", "");
+    let first = repository.insert_feed(author.id, "Saved first");
+    let second = repository.insert_feed(author.id, "Saved second");
+    let token = token_for(&config, me.id);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mongo_db))
+            .configure(repositories::register(repository))
+            .route(
+                "/api/feed/{feed_id}/bookmark",
+                web::post().to(api::feed::bookmark_feed),
+            )
+            .route(
+                "/api/feed/{feed_id}/bookmark",
+                web::delete().to(api::feed::unbookmark_feed),
+            )
+            .route(
+                "/api/users/me/bookmarks",
+                web::get().to(api::users::get_my_bookmarks),
+            ),
+    )
+    .await;
+
+    for feed_id in [first.id, second.id, first.id] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/bookmark", feed_id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed/999999/bookmark")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::get()
+        .uri("/api/users/me/bookmarks")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "2");
+    let bookmarked: Vec<FeedResponse> = test::read_body_json(resp).await;
+    let ids: Vec<i64> = bookmarked.iter().map(|feed| feed.id).collect();
+    assert_eq!(ids, vec![second.id, first.id]);
+    assert!(bookmarked.iter().all(|feed| feed.is_bookmarked));
+    // Never edited, so only created_at is set
+    assert!(bookmarked.iter().all(|feed| feed.updated_at.is_none()));
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/feed/{}/bookmark", second.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri("/api/users/me/bookmarks")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let bookmarked: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(bookmarked.len(), 1);
+    assert_eq!(bookmarked[0].id, first.id);
+}
+
+#[actix_web::test]
+async fn test_pinned_feeds_lead_the_author_timeline() {
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.mongodb.degrade_counts = true;
+    config.feed_cache.enabled = false;
+    config.content.max_pinned_feeds = 1;
+    let repository = Arc::new(InMemoryRepository::new());
+    let author = repository.insert_user("pinner@example.com", "pinner", "");
+    let other = repository.insert_user("other@example.com", "other", "");
+    let oldest = repository.insert_feed(author.id, "Oldest");
+    let middle = repository.insert_feed(author.id, "Middle");
+    let newest = repository.insert_feed(author.id, "Newest");
+    repository.insert_feed(other.id, "Someone else's");
+    let token = token_for(&config, author.id);
+    let other_token = token_for(&config, other.id);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mongo_db))
+            .app_data(web::Data::new(redis_client))
+            .configure(repositories::register(repository))
+            .route("/api/feed", web::get().to(api::feed::get_feeds))
+            .route("/api/feed/{feed_id}/pin", web::post().to(api::feed::pin_feed))
+            .route(
+                "/api/feed/{feed_id}/pin",
+                web::delete().to(api::feed::unpin_feed),
+            ),
+    )
+    .await;
+
+    let pin = |feed_id: i64, token: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/pin", feed_id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request()
+    };
+    let resp = test::call_service(&app, pin(oldest.id, &token)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = test::call_service(&app, pin(oldest.id, &token)).await;
+    assert_eq!(resp.status(), StatusCode::OK, "Pinning again is a no-op");
+    let resp = test::call_service(&app, pin(middle.id, &token)).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "Over the pin limit");
+    let resp = test::call_service(&app, pin(newest.id, &other_token)).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = test::call_service(&app, pin(999999, &token)).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/feed?user_id={}", author.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "3");
+    let timeline: Vec<FeedResponse> = test::read_body_json(resp).await;
+    let ids: Vec<i64> = timeline.iter().map(|feed| feed.id).collect();
+    assert_eq!(ids, vec![oldest.id, newest.id, middle.id]);
+    assert!(timeline[0].is_pinned);
+    assert!(!timeline[1].is_pinned);
+
+    // Unpinning frees the slot
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/feed/{}/pin", oldest.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = test::call_service(&app, pin(middle.id, &token)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_unseen_count_skips_own_and_scheduled_feeds() {
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.feed_marker.enabled = false;
+    let repository = Arc::new(InMemoryRepository::new());
+    let reader = repository.insert_user("reader@example.com", "reader", "");
+    let author = repository.insert_user("author@example.com", "author", "");
+    let seen = repository.insert_feed(author.id, "Seen");
+    repository.insert_feed(author.id, "New");
+    repository.insert_feed(reader.id, "Own");
+    let tomorrow = chrono::Utc::now() + chrono::Duration::days(1);
+    repository
+        .create(author.id, "Scheduled", None, None, Some(tomorrow), None, false)
+        .await
+        .unwrap();
+
+    let unseen = repository
+        .count_visible_after(seen.id, reader.id)
+        .await
+        .unwrap();
+    assert_eq!(unseen, 1);
+    let unseen = repository
+        .count_visible_after(seen.id, author.id)
+        .await
+        .unwrap();
+    assert_eq!(unseen, 1, "The author's own new feed is not counted");
+
+    let token = token_for(&config, reader.id);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(redis_client))
+            .configure(repositories::register(repository))
+            .route(
+                "/api/feed/unseen-count",
+                web::get().to(api::feed::get_unseen_count),
+            ),
+    )
+    .await;
+    let req = test::TestRequest::get()
+        .uri("/api/feed/unseen-count")
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND, "Disabled by config");
+}
+
+#[actix_web::test]
+async fn test_feed_batch_embeds_author_in_v2() {
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.mongodb.degrade_counts = true;
+    config.server.media_type_vendor = "example".to_string();
+    let repository = Arc::new(InMemoryRepository::new());
+    let author = repository.insert_user("versioned@example.com", "versioned", "");
+    let feed = repository.insert_feed(author.id, "Versioned feed");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mongo_db))
+            .configure(repositories::register(repository))
+            .route(
+                "/api/feed/batch",
+                web::post().to(api::feed::get_feeds_batch),
+            ),
+    )
+    .await;
+    let batch = |accept: &str| {
+        test::TestRequest::post()
+            .uri("/api/feed/batch")
+            .insert_header(("Accept", accept))
+            .set_json(json!({ "feed_ids": [feed.id] }))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, batch("application/json")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get("X-Api-Version").unwrap(), "1");
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["succeeded"][0]["user_id"], author.id);
+    assert!(body["succeeded"][0].get("author").is_none());
+
+    let resp = test::call_service(&app, batch("application/vnd.example.v2+json")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get("X-Api-Version").unwrap(), "2");
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let v2: FeedResponseV2 = serde_json::from_value(body["succeeded"][0].clone()).unwrap();
+    assert_eq!(v2.feed.id, feed.id);
+    assert_eq!(
+        v2.author,
+        Some(FeedAuthor {
+            id: author.id,
+            username: "versioned".to_string()
+        })
+    );
+
+    let resp = test::call_service(&app, batch("application/vnd.example.v9+json")).await;
+    assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+}
+
+#[actix_web::test]
+async fn test_hidden_feeds_are_left_out_of_listings() {
+    use example_rust_web_service::repositories::FeedChanges;
+
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.mongodb.degrade_counts = true;
+    config.feed_cache.enabled = false;
+    config.moderation.max_reason_chars = 10;
+    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+    let repository = Arc::new(InMemoryRepository::new());
+    let author = repository.insert_user("author@example.com", "author", "");
+    let kept = repository.insert_feed(author.id, "Kept");
+    let hidden = repository.insert_feed(author.id, "Hidden");
+    let changes = FeedChanges {
+        hidden_at: Some(Some(chrono::Utc::now())),
+        ..Default::default()
+    };
+    repository.update(hidden.id, changes).await.unwrap();
+    let token = token_for(&config, author.id);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mongo_db))
+            .app_data(web::Data::new(redis_client))
+            .app_data(web::Data::new(kafka_producer))
+            .configure(repositories::register(repository))
+            .route("/api/feed", web::get().to(api::feed::get_feeds))
+            .route(
+                "/api/feed/{feed_id}/report",
+                web::post().to(api::feed::report_feed),
+            )
+            .route("/api/feed/{feed_id}/like", web::post().to(api::feed::like_feed))
+            .route(
+                "/api/feed/{feed_id}/bookmark",
+                web::post().to(api::feed::bookmark_feed),
+            )
+            .route("/api/feed/{feed_id}/react", web::post().to(api::feed::react_feed))
+            .route(
+                "/api/feed/{feed_id}/comment",
+                web::post().to(api::feed::comment_feed),
+            )
+            .route("/api/feed/{feed_id}/view", web::post().to(api::feed::view_feed)),
+    )
+    .await;
+
+    for uri in ["/api/feed".to_string(), format!("/api/feed?user_id={}", author.id)] {
+        let req = test::TestRequest::get().uri(&uri).to_request();
+        let feeds: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+        let ids: Vec<i64> = feeds.iter().map(|feed| feed.id).collect();
+        assert_eq!(ids, vec![kept.id], "{} should leave out the hidden feed", uri);
+    }
+
+    let report = |feed_id: i64, reason: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/report", feed_id))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"reason": reason}))
+            .to_request()
+    };
+    let resp = test::call_service(&app, report(kept.id, "  ")).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, report(kept.id, "Way too long a reason")).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let resp = test::call_service(&app, report(hidden.id, "Spam")).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND, "Hidden feeds cannot be reported");
+
+    for action in ["like", "bookmark"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/{}", hidden.id, action))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND, "Hidden feeds cannot be {}d", action);
+    }
+
+    // Comments, views and reactions would rank the hidden feed again
+    for (action, body) in [
+        ("react", json!({"type": "love"})),
+        ("comment", json!({"content": "Still here?"})),
+        ("view", json!({})),
+    ] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/{}", hidden.id, action))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND, "Hidden feeds cannot get a {}", action);
+    }
+}
+
+#[actix_web::test]
+async fn test_expired_feeds_are_gone() {
+    use example_rust_web_service::repositories::FeedChanges;
+
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.mongodb.degrade_counts = true;
+    config.feed_cache.enabled = false;
+    config.feed_expiry.max_lifetime_hours = 24;
+    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+    let repository = Arc::new(InMemoryRepository::new());
+    let author = repository.insert_user("author@example.com", "author", "");
+    let kept = repository.insert_feed(author.id, "Kept");
+    let expired = repository.insert_feed(author.id, "Expired");
+    let changes = FeedChanges {
+        expires_at: Some(Some(chrono::Utc::now() - chrono::Duration::minutes(1))),
+        ..Default::default()
+    };
+    repository.update(expired.id, changes).await.unwrap();
+    let token = token_for(&config, author.id);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mongo_db))
+            .app_data(web::Data::new(redis_client))
+            .app_data(web::Data::new(kafka_producer))
+            .configure(repositories::register(repository))
+            .route("/api/feed", web::post().to(api::feed::create_feed))
+            .route("/api/feed", web::get().to(api::feed::get_feeds))
+            .route("/api/feed/batch", web::post().to(api::feed::get_feeds_batch))
+            .route("/api/feed/{feed_id}", web::get().to(api::feed::get_feed))
+            .route("/api/feed/{feed_id}/like", web::post().to(api::feed::like_feed))
+            .route("/api/feed/{feed_id}/react", web::post().to(api::feed::react_feed))
+            .route(
+                "/api/feed/{feed_id}/comment",
+                web::post().to(api::feed::comment_feed),
+            )
+            .route("/api/feed/{feed_id}/view", web::post().to(api::feed::view_feed)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/api/feed").to_request();
+    let feeds: Vec<FeedResponse> = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(feeds.iter().map(|f| f.id).collect::<Vec<_>>(), vec![kept.id]);
+
+    let req = test::TestRequest::post()
+        .uri("/api/feed/batch")
+        .set_json(json!({"feed_ids": [kept.id, expired.id]}))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["succeeded"].as_array().unwrap().len(), 1);
+    assert_eq!(body["failed"][0]["id"], expired.id.to_string());
+
+    let get = |feed_id: i64| {
+        test::TestRequest::get()
+            .uri(&format!("/api/feed/{}", feed_id))
+            .to_request()
+    };
+    let feed: FeedResponse = test::call_and_read_body_json(&app, get(kept.id)).await;
+    assert_eq!(feed.expires_at, None);
+    let resp = test::call_service(&app, get(expired.id)).await;
+    assert_eq!(resp.status(), StatusCode::GONE);
+    let resp = test::call_service(&app, get(expired.id + 100)).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    // Writes would put the expired feed back into the realtime rankings
+    for action in ["like", "react", "comment", "view"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/{}", expired.id, action))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"content": "Still here?", "type": "love"}))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::GONE, "Expired feeds cannot be {}ed", action);
+    }
+
+    let create = |expires_at: chrono::DateTime<chrono::Utc>| {
+        test::TestRequest::post()
+            .uri("/api/feed")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(json!({"content": "Story", "expires_at": expires_at}))
+            .to_request()
+    };
+    let now = chrono::Utc::now();
+    let resp = test::call_service(&app, create(now - chrono::Duration::hours(1))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "expires_at in the past");
+    let resp = test::call_service(&app, create(now + chrono::Duration::hours(48))).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "Beyond the max lifetime");
+    let expires_at = now + chrono::Duration::hours(1);
+    let story: FeedResponse = test::call_and_read_body_json(&app, create(expires_at)).await;
+    assert_eq!(story.expires_at, Some(expires_at));
+    let feed: FeedResponse = test::call_and_read_body_json(&app, get(story.id)).await;
+    assert_eq!(feed.expires_at, Some(expires_at));
+}
+
+#[actix_web::test]
+async fn test_comment_and_notification_pages_past_u64_are_rejected() {
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+    let config = Config::from_env().expect("Failed to load configuration");
+    let token = token_for(&config, 1);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mongo_db))
+            .app_data(web::Data::new(redis_client))
+            .route(
+                "/api/feed/{feed_id}/comments",
+                web::get().to(api::feed::get_comments),
+            )
+            .route("/api/notify", web::get().to(api::notify::get_notifications)),
+    )
+    .await;
+
+    // The offset is rejected before MongoDB is asked for anything
+    for uri in [
+        format!("/api/feed/1/comments?page={}&limit=20", u64::MAX),
+        format!("/api/notify?page={}&limit=50", u64::MAX),
+    ] {
+        let req = test::TestRequest::get()
+            .uri(&uri)
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"], "page is out of range");
+    }
+}
+
+#[actix_web::test]
+async fn test_scheduled_feeds_are_unreachable_until_live() {
+    use example_rust_web_service::repositories::FeedChanges;
+
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.mongodb.degrade_counts = true;
+    config.feed_cache.enabled = false;
+    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+    let repository = Arc::new(InMemoryRepository::new());
+    let author = repository.insert_user("author@example.com", "author", "");
+    let reader = repository.insert_user("reader@example.com", "reader", "");
+    let scheduled = repository.insert_feed(author.id, "Not yet");
+    let changes = FeedChanges {
+        publish_at: Some(Some(chrono::Utc::now() + chrono::Duration::hours(1))),
+        ..Default::default()
+    };
+    repository.update(scheduled.id, changes).await.unwrap();
+    let author_token = token_for(&config, author.id);
+    let reader_token = token_for(&config, reader.id);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mongo_db))
+            .app_data(web::Data::new(redis_client))
+            .app_data(web::Data::new(kafka_producer))
+            .configure(repositories::register(repository))
+            .route("/api/feed/{feed_id}/like", web::post().to(api::feed::like_feed))
+            .route("/api/feed/{feed_id}/react", web::post().to(api::feed::react_feed))
+            .route(
+                "/api/feed/{feed_id}/bookmark",
+                web::post().to(api::feed::bookmark_feed),
+            )
+            .route(
+                "/api/feed/{feed_id}/report",
+                web::post().to(api::feed::report_feed),
+            )
+            .route(
+                "/api/feed/{feed_id}/comment",
+                web::post().to(api::feed::comment_feed),
+            )
+            .route("/api/feed/{feed_id}/view", web::post().to(api::feed::view_feed)),
+    )
+    .await;
+
+    let act = |action: &str, token: &str| {
+        let body = match action {
+            "react" => json!({"type": "love"}),
+            "report" => json!({"reason": "Spam"}),
+            "comment" => json!({"content": "First!"}),
+            _ => json!({}),
+        };
+        test::TestRequest::post()
+            .uri(&format!("/api/feed/{}/{}", scheduled.id, action))
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(body)
+            .to_request()
+    };
+    for action in ["like", "react", "bookmark", "report", "comment", "view"] {
+        let resp = test::call_service(&app, act(action, &reader_token)).await;
+        assert_eq!(
+            resp.status(),
+            StatusCode::NOT_FOUND,
+            "Scheduled feeds cannot be reached through {}",
+            action
+        );
+    }
+
+    // The author can still act on their own scheduled feed
+    let resp = test::call_service(&app, act("bookmark", &author_token)).await;
+    assert_ne!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_own_liked_feeds_are_listed_most_recently_liked_first() {
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+    let config = Config::from_env().expect("Failed to load configuration");
+    let repository = Arc::new(InMemoryRepository::new());
+    let me = repository.insert_user("liker@example.com", "liker", "");
+    let author = repository.insert_user("liked@example.com", "liked", "");
+    let first = repository.insert_feed(author.id, "Liked first");
+    let second = repository.insert_feed(author.id, "Liked second");
+    repository.insert_feed(author.id, "Never liked");
+    repository.insert_like(first.id, me.id);
+    repository.insert_like(second.id, me.id);
+    repository.insert_like(first.id, author.id);
+    let token = token_for(&config, me.id);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(mongo_db))
+            .configure(repositories::register(repository))
+            .route(
+                "/api/users/{id}/liked-feeds",
+                web::get().to(api::users::get_user_liked_feeds),
+            ),
+    )
+    .await;
+
+    // Your own likes never need the privacy setting from the profile
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/users/{}/liked-feeds", me.id))
+        .insert_header(("Authorization", format!("Bearer {}", token)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get("X-Total-Count").unwrap(), "2");
+    let liked: Vec<FeedResponse> = test::read_body_json(resp).await;
+    let ids: Vec<i64> = liked.iter().map(|feed| feed.id).collect();
+    assert_eq!(ids, vec![second.id, first.id]);
+    assert!(liked.iter().all(|feed| feed.is_liked));
+
+    let req = test::TestRequest::get()
+        .uri("/api/users/999999/liked-feeds")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn test_feeds_are_filed_under_allowed_categories() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.content.feed_categories = vec!["news".to_string(), "sports".to_string()];
+    let repository = Arc::new(InMemoryRepository::new());
+    let author = repository.insert_user("author@example.com", "author", "");
+    let token = token_for(&config, author.id);
+    let kafka_producer = KafkaProducer::new(&config).expect("Failed to create Kafka producer");
+    let redis_client = redis::Client::open("redis://127.0.0.1:6379").expect("Invalid Redis URL");
+    let mongo_db =
+        mongodb::Client::with_uri_str("mongodb://127.0.0.1:1/?serverSelectionTimeoutMS=100")
+            .await
+            .expect("Invalid MongoDB URI")
+            .database("unreachable");
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .app_data(web::Data::new(kafka_producer))
+            .app_data(web::Data::new(redis_client))
+            .app_data(web::Data::new(mongo_db))
+            .configure(repositories::register(repository.clone()))
+            .route(
+                "/api/feed/categories",
+                web::get().to(api::feed::get_feed_categories),
+            )
+            .route("/api/feed", web::post().to(api::feed::create_feed))
+            .route("/api/feed", web::get().to(api::feed::get_feeds)),
+    )
+    .await;
+
+    let categories: FeedCategoriesResponse = test::call_and_read_body_json(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/feed/categories")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(categories.categories, vec!["news", "sports"]);
+
+    let create = |body: serde_json::Value| {
+        test::TestRequest::post()
+            .uri("/api/feed")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .set_json(body)
+            .to_request()
+    };
+    let news: FeedResponse = test::call_and_read_body_json(
+        &app,
+        create(json!({ "content": "Election results", "category": " News " })),
+    )
+    .await;
+    assert_eq!(news.category.as_deref(), Some("news"));
+    let plain: FeedResponse =
+        test::call_and_read_body_json(&app, create(json!({ "content": "Just a thought" }))).await;
+    assert_eq!(plain.category, None);
+
+    let resp = test::call_service(
+        &app,
+        create(json!({ "content": "Pasta night", "category": "cooking" })),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let in_news = repository
+        .list_visible(None, Some("news"), 0, 20)
+        .await
+        .unwrap();
+    assert_eq!(
+        in_news.iter().map(|f| f.id).collect::<Vec<_>>(),
+        vec![news.id]
+    );
+    assert_eq!(repository.count_visible(None, None).await.unwrap(), 2);
+    assert_eq!(
+        repository
+            .count_visible_by_user(author.id, None, Some("sports"))
+            .await
+            .unwrap(),
+        0
+    );
+
+    // Filters are checked like new feeds, so unknown ones never reach queries or cache keys
+    let req = test::TestRequest::get()
+        .uri("/api/feed?category=cooking")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,129 @@
+// Tests for handing consumed Kafka messages to handlers (dispatch_message)
+// These do not need a broker: messages are built in memory
+// Run with: cargo test --test kafka_dispatch_test
+
+use example_rust_web_service::config::EmptyPayloadAction;
+use example_rust_web_service::kafka::{
+    dead_letter_from_message, dispatch_message, Dispatch, DLQ_ERROR_HEADER,
+    DLQ_ORIGINAL_TOPIC_HEADER, EMPTY_PAYLOAD_ERROR,
+};
+use rdkafka::message::{Header, OwnedHeaders, OwnedMessage};
+use rdkafka::Timestamp;
+use std::cell::RefCell;
+
+fn message(payload: Option<&[u8]>) -> OwnedMessage {
+    OwnedMessage::new(
+        payload.map(<[u8]>::to_vec),
+        Some(b"42".to_vec()),
+        "feed_events".to_string(),
+        Timestamp::NotAvailable,
+        0,
+        7,
+        None,
+    )
+}
+
+#[test]
+fn test_payload_is_passed_to_handler() {
+    let calls = RefCell::new(Vec::new());
+    let handler = |topic: String, key: String, payload: Vec<u8>| {
+        calls.borrow_mut().push((topic, key, payload));
+    };
+
+    let dispatched = dispatch_message(
+        &message(Some(br#"{"event_type":"liked"}"#)),
+        EmptyPayloadAction::Skip,
+        &handler,
+    );
+    assert_eq!(dispatched, Dispatch::Handled);
+    assert_eq!(
+        calls.into_inner(),
+        vec![(
+            "feed_events".to_string(),
+            "42".to_string(),
+            br#"{"event_type":"liked"}"#.to_vec()
+        )]
+    );
+}
+
+#[test]
+fn test_empty_payload_is_skipped_or_dead_lettered() {
+    let handler = |_: String, _: String, _: Vec<u8>| panic!("Empty payloads must not be handled");
+
+    for payload in [None, Some(&b""[..])] {
+        assert_eq!(
+            dispatch_message(&message(payload), EmptyPayloadAction::Skip, &handler),
+            Dispatch::SkippedEmpty
+        );
+        assert_eq!(
+            dispatch_message(&message(payload), EmptyPayloadAction::DeadLetter, &handler),
+            Dispatch::DeadLetter {
+                topic: "feed_events_dlq".to_string(),
+                key: "42".to_string(),
+                payload: String::new(),
+                error: EMPTY_PAYLOAD_ERROR.to_string()
+            }
+        );
+    }
+}
+
+#[test]
+fn test_non_utf8_payload_is_dead_lettered() {
+    let handler = |_: String, _: String, _: Vec<u8>| panic!("Invalid payloads must not be handled");
+    let dispatched = dispatch_message(
+        &message(Some(&[0xff, 0xfe])),
+        EmptyPayloadAction::DeadLetter,
+        &handler,
+    );
+    match dispatched {
+        Dispatch::DeadLetter {
+            topic,
+            key,
+            payload,
+            error,
+        } => {
+            assert_eq!(topic, "feed_events_dlq");
+            assert_eq!(key, "42");
+            assert_eq!(payload, "\u{fffd}\u{fffd}");
+            assert!(error.starts_with("invalid UTF-8 payload"), "{}", error);
+        }
+        other => panic!("Expected a dead letter, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_dead_letter_reads_source_and_error_headers() {
+    let headers = OwnedHeaders::new()
+        .insert(Header {
+            key: DLQ_ORIGINAL_TOPIC_HEADER,
+            value: Some("feed_events"),
+        })
+        .insert(Header {
+            key: DLQ_ERROR_HEADER,
+            value: Some(EMPTY_PAYLOAD_ERROR),
+        });
+    let dead_letter = OwnedMessage::new(
+        None,
+        Some(b"42".to_vec()),
+        "feed_events_dlq".to_string(),
+        Timestamp::CreateTime(1_700_000_000_000),
+        2,
+        9,
+        Some(headers),
+    );
+
+    let letter = dead_letter_from_message(&dead_letter);
+    assert_eq!(letter.topic, "feed_events_dlq");
+    assert_eq!((letter.partition, letter.offset), (2, 9));
+    assert_eq!(letter.key.as_deref(), Some("42"));
+    assert_eq!(letter.payload, "");
+    assert_eq!(letter.original_topic.as_deref(), Some("feed_events"));
+    assert_eq!(letter.error.as_deref(), Some("empty payload"));
+    assert_eq!(letter.timestamp.unwrap().timestamp(), 1_700_000_000);
+
+    // Dead letters from before the headers existed
+    let letter = dead_letter_from_message(&message(Some(b"{}")));
+    assert_eq!(letter.original_topic, None);
+    assert_eq!(letter.error, None);
+    assert_eq!(letter.timestamp, None);
+}
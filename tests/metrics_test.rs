@@ -0,0 +1,48 @@
+// Tests for the Prometheus /metrics endpoint
+// These do not need any external services
+// Run with: cargo test --test metrics_test
+
+use actix_web::{http::StatusCode, test, web, App};
+use example_rust_web_service::{api, metrics};
+use std::time::Duration;
+
+#[actix_web::test]
+async fn test_metrics_expose_top_stats_job() {
+    metrics::record_top_stats_duration(Duration::from_secs(20));
+    metrics::set_top_stats_ranking_size("feeds_liked", 42);
+
+    let app =
+        test::init_service(App::new().route("/metrics", web::get().to(api::health::metrics))).await;
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+    let lines: Vec<&str> = body.lines().collect();
+    assert!(lines.contains(&"# TYPE top_stats_job_duration_seconds histogram"));
+    // 20s falls in the le=30 bucket, and buckets are cumulative
+    assert!(lines.contains(&"top_stats_job_duration_seconds_bucket{le=\"15\"} 0"));
+    assert!(lines.contains(&"top_stats_job_duration_seconds_bucket{le=\"30\"} 1"));
+    assert!(lines.contains(&"top_stats_job_duration_seconds_bucket{le=\"+Inf\"} 1"));
+    assert!(lines.contains(&"top_stats_job_duration_seconds_sum 20"));
+    assert!(lines.contains(&"top_stats_job_duration_seconds_count 1"));
+    assert!(lines.contains(&"top_stats_ranking_size{ranking=\"feeds_liked\"} 42"));
+    assert!(lines.contains(&"top_stats_ranking_size{ranking=\"comments\"} 0"));
+}
+
+#[actix_web::test]
+async fn test_metrics_count_dead_letters_per_topic() {
+    metrics::record_dead_letter("user_events", true);
+    metrics::record_dead_letter("user_events", true);
+    metrics::record_dead_letter("user_events", false);
+    metrics::record_dead_letter_replay("user_events");
+    metrics::record_kafka_send_retry("notifications");
+
+    let body = metrics::render();
+    let lines: Vec<&str> = body.lines().collect();
+    assert!(lines.contains(&"# TYPE kafka_dead_letters_total counter"));
+    assert!(lines.contains(&"kafka_dead_letters_total{topic=\"user_events\"} 2"));
+    assert!(lines.contains(&"kafka_dead_letter_failures_total{topic=\"user_events\"} 1"));
+    assert!(lines.contains(&"kafka_dead_letter_replays_total{topic=\"user_events\"} 1"));
+    assert!(lines.contains(&"kafka_send_retries_total{topic=\"notifications\"} 1"));
+}
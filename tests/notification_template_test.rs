@@ -0,0 +1,165 @@
+// Tests for notification templates (NOTIFICATIONS_*_TEMPLATE and per-language templates)
+// These do not need any external services
+// Run with: cargo test --test notification_template_test
+
+use example_rust_web_service::config::Config;
+use example_rust_web_service::services::locale::{normalize_language, preferred_language};
+use example_rust_web_service::models::{Notification, NotificationType};
+use example_rust_web_service::services::notification::{
+    aggregate_notification_id, displayed_content, like_content, render_template,
+};
+
+#[test]
+fn test_placeholders_are_filled_in_one_pass() {
+    let rendered = render_template(
+        "{username} commented on #{feed_id}: {content}",
+        &[
+            ("username", "alice"),
+            ("feed_id", "7"),
+            ("content", "see {username}"),
+        ],
+    );
+    assert_eq!(rendered.unwrap(), "alice commented on #7: see {username}");
+}
+
+#[test]
+fn test_literal_and_unbalanced_braces() {
+    let values = [("username", "bob")];
+    assert_eq!(
+        render_template("{{{username}}} }{", &values).unwrap(),
+        "{bob} }{"
+    );
+    assert_eq!(
+        render_template("{nickname} liked it", &values).unwrap_err(),
+        "nickname"
+    );
+}
+
+#[test]
+fn test_default_templates_match_previous_wording() {
+    let config = Config::from_env().expect("Failed to load configuration");
+    let like = render_template(
+        &config.notifications.like_template,
+        &[("username", "carol"), ("feed_id", "1")],
+    );
+    assert_eq!(like.unwrap(), "carol liked your feed");
+    let comment = render_template(
+        &config.notifications.comment_template,
+        &[
+            ("username", "carol"),
+            ("feed_id", "1"),
+            ("content", "Nice!"),
+        ],
+    );
+    assert_eq!(comment.unwrap(), "Nice!");
+}
+
+#[test]
+fn test_preferred_language_from_accept_language() {
+    assert_eq!(
+        preferred_language("fr-CH, fr;q=0.9, en;q=0.8, *;q=0.5").as_deref(),
+        Some("fr-ch")
+    );
+    assert_eq!(
+        preferred_language("en;q=0.5, de;q=0.7").as_deref(),
+        Some("de")
+    );
+    assert_eq!(preferred_language("*, es;q=0").as_deref(), None);
+    assert_eq!(normalize_language("pt_BR").as_deref(), Some("pt-br"));
+    assert_eq!(normalize_language("e"), None);
+}
+
+#[test]
+fn test_templates_fall_back_from_region_to_language_to_english() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    let notifications = &mut config.notifications;
+    notifications
+        .like_templates
+        .insert("pt".to_string(), "{username} curtiu sua publicação".to_string());
+    notifications
+        .like_templates
+        .insert("pt-pt".to_string(), "{username} gostou da sua publicação".to_string());
+
+    assert_eq!(
+        notifications.like_template_for(Some("pt-pt")),
+        "{username} gostou da sua publicação"
+    );
+    assert_eq!(
+        notifications.like_template_for(Some("pt-br")),
+        "{username} curtiu sua publicação"
+    );
+    assert_eq!(
+        notifications.like_template_for(Some("ja")),
+        notifications.like_template
+    );
+    assert_eq!(
+        notifications.comment_template_for(Some("pt")),
+        notifications.comment_template
+    );
+    assert_eq!(notifications.like_template_for(None), "{username} liked your feed");
+}
+
+#[test]
+fn test_aggregate_like_content_switches_after_first_like() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    let recent = vec!["bob".to_string(), "carol".to_string()];
+    assert_eq!(
+        like_content(&config, None, 1, 1, "bob", &recent[..1]),
+        "bob liked your feed"
+    );
+    assert_eq!(
+        like_content(&config, None, 1, 5, "carol", &recent),
+        "5 people liked your feed"
+    );
+
+    config.notifications.like_aggregate_template =
+        "{usernames} and {count} in total liked #{feed_id}".to_string();
+    config.notifications.like_aggregate_templates.insert(
+        "fr".to_string(),
+        "{count} personnes ont aimé votre publication".to_string(),
+    );
+    assert_eq!(
+        like_content(&config, None, 9, 4, "carol", &recent),
+        "bob, carol and 4 in total liked #9"
+    );
+    assert_eq!(
+        like_content(&config, Some("fr-ca"), 9, 4, "carol", &recent),
+        "4 personnes ont aimé votre publication"
+    );
+}
+
+#[test]
+fn test_aggregate_notifications_are_keyed_by_window_and_worded_on_read() {
+    use chrono::TimeZone;
+
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.notifications.aggregate_window_minutes = 60;
+    let at = |h: u32, m: u32| chrono::Utc.with_ymd_and_hms(2024, 5, 1, h, m, 0).unwrap();
+    let id = aggregate_notification_id(&config, 7, 9, at(10, 0));
+    assert_eq!(id, aggregate_notification_id(&config, 7, 9, at(10, 59)));
+    assert_ne!(id, aggregate_notification_id(&config, 7, 9, at(11, 0)));
+    assert_ne!(id, aggregate_notification_id(&config, 8, 9, at(10, 0)));
+
+    let mut notification = Notification {
+        id: Some(id),
+        user_id: 7,
+        from_user_id: 2,
+        from_username: "bob".to_string(),
+        feed_id: 9,
+        notification_type: NotificationType::Like,
+        content: "alice liked your feed".to_string(),
+        created_at: at(10, 0),
+        is_read: false,
+        actor_count: Some(1),
+        recent_actors: vec!["alice".to_string()],
+    };
+    assert_eq!(
+        displayed_content(&notification, &config, None),
+        "alice liked your feed"
+    );
+    notification.actor_count = Some(3);
+    assert_eq!(
+        displayed_content(&notification, &config, None),
+        "3 people liked your feed"
+    );
+}
@@ -0,0 +1,76 @@
+// Tests for log redaction of request/response bodies and query strings
+// These do not need any external services
+// Run with: cargo test --test redact_test
+
+use actix_web::test::{call_and_read_body_json, init_service, TestRequest};
+use actix_web::{middleware::from_fn, web, App, HttpResponse};
+use example_rust_web_service::{
+    api::redact::{log_bodies, redact_body, redact_json, redact_query},
+    config::Config,
+};
+use serde_json::{json, Value};
+
+fn fields() -> Vec<String> {
+    vec![
+        "password".to_string(),
+        "token".to_string(),
+        "email".to_string(),
+    ]
+}
+
+#[test]
+fn test_redact_json_masks_nested_fields() {
+    let mut body = json!({
+        "token": "eyJhbGciOi",
+        "user": {"id": 1, "email": "a@example.com", "username": "alice"},
+        "sessions": [{"refresh_token": "abc", "token_type": "Bearer"}],
+        "current_password": "hunter2",
+    });
+    redact_json(&mut body, &fields());
+    assert_eq!(
+        body,
+        json!({
+            "token": "***",
+            "user": {"id": 1, "email": "***", "username": "alice"},
+            "sessions": [{"refresh_token": "***", "token_type": "Bearer"}],
+            "current_password": "***",
+        })
+    );
+}
+
+#[test]
+fn test_redact_body_and_query() {
+    assert_eq!(
+        redact_body(b"not json token=abc", &fields()),
+        "<18 bytes, not JSON>"
+    );
+    assert_eq!(
+        redact_query("page=2&Token=abc&email=a%40b.com&flag", &fields()),
+        "page=2&Token=***&email=***&flag"
+    );
+}
+
+#[actix_web::test]
+async fn test_log_bodies_passes_bodies_through() {
+    let config = Config::from_env().expect("Failed to load configuration");
+    let app = init_service(
+        App::new()
+            .app_data(web::Data::new(config))
+            .wrap(from_fn(log_bodies))
+            .route(
+                "/echo",
+                web::post().to(|body: web::Json<Value>| async move {
+                    HttpResponse::Ok().json(body.into_inner())
+                }),
+            ),
+    )
+    .await;
+
+    let payload = json!({"email": "a@example.com", "password": "secret"});
+    let req = TestRequest::post()
+        .uri("/echo")
+        .set_json(&payload)
+        .to_request();
+    let body: Value = call_and_read_body_json(&app, req).await;
+    assert_eq!(body, payload, "Redaction only applies to logs");
+}
@@ -0,0 +1,74 @@
+// Tests for request ID propagation
+// These do not need any external services
+// Run with: cargo test --test request_id_test
+
+use actix_web::{middleware::from_fn, test, web, App, HttpResponse};
+use example_rust_web_service::{api, api::request_id::RequestId, config::Config};
+
+async fn echo_request_id(request_id: RequestId) -> HttpResponse {
+    HttpResponse::Ok().body(request_id.0)
+}
+
+macro_rules! init_app {
+    ($config:expr) => {
+        test::init_service(
+            App::new()
+                .wrap(from_fn(api::request_id::propagate_request_id))
+                .app_data(web::Data::new($config))
+                .route("/", web::get().to(echo_request_id)),
+        )
+        .await
+    };
+}
+
+#[actix_web::test]
+async fn test_incoming_request_id_is_reused_and_echoed() {
+    let config = Config::from_env().expect("Failed to load configuration");
+    let app = init_app!(config);
+
+    let req = test::TestRequest::get()
+        .uri("/")
+        .insert_header(("X-Request-Id", "abc-123"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("X-Request-Id").unwrap(), "abc-123");
+    let body = test::read_body(resp).await;
+    assert_eq!(body, "abc-123");
+}
+
+#[actix_web::test]
+async fn test_missing_or_invalid_request_id_is_generated() {
+    let config = Config::from_env().expect("Failed to load configuration");
+    let app = init_app!(config);
+
+    for header in [None, Some("has spaces"), Some(&*"x".repeat(200))] {
+        let mut req = test::TestRequest::get().uri("/");
+        if let Some(header) = header {
+            req = req.insert_header(("X-Request-Id", header));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        let echoed = resp
+            .headers()
+            .get("X-Request-Id")
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert_eq!(echoed.len(), 36, "expected a UUID, got {:?}", echoed);
+        assert_ne!(Some(echoed), header);
+    }
+}
+
+#[actix_web::test]
+async fn test_request_id_header_is_configurable() {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.server.request_id_header = "X-Correlation-Id".to_string();
+    let app = init_app!(config);
+
+    let req = test::TestRequest::get()
+        .uri("/")
+        .insert_header(("X-Correlation-Id", "trace-1"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.headers().get("X-Correlation-Id").unwrap(), "trace-1");
+    assert!(resp.headers().get("X-Request-Id").is_none());
+}
@@ -0,0 +1,100 @@
+// Tests for strict JSON request bodies (SERVER_STRICT_JSON)
+// These do not need any external services
+// Run with: cargo test --test strict_json_test
+
+use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+use example_rust_web_service::{
+    api::json::RequestJson,
+    config::Config,
+    models::{CommentRequest, CreateFeedRequest, LoginRequest, SignupRequest},
+};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+async fn accept<T: DeserializeOwned + 'static>(_body: RequestJson<T>) -> HttpResponse {
+    HttpResponse::Ok().finish()
+}
+
+macro_rules! init_app {
+    ($strict:expr) => {{
+        let mut config = Config::from_env().expect("Failed to load configuration");
+        config.server.strict_json = $strict;
+        test::init_service(
+            App::new()
+                .app_data(web::Data::new(config))
+                .route("/feed", web::post().to(accept::<CreateFeedRequest>))
+                .route("/comment", web::post().to(accept::<CommentRequest>))
+                .route("/signup", web::post().to(accept::<SignupRequest>))
+                .route("/login", web::post().to(accept::<LoginRequest>)),
+        )
+        .await
+    }};
+}
+
+fn bodies_with_typos() -> Vec<(&'static str, serde_json::Value, &'static str)> {
+    vec![
+        (
+            "/feed",
+            json!({"content": "Hello", "publsh_at": "2099-01-01T00:00:00Z"}),
+            "publsh_at",
+        ),
+        ("/comment", json!({"content": "Nice", "contnet": "Nice"}), "contnet"),
+        (
+            "/signup",
+            json!({"email": "a@example.com", "username": "a", "password": "password123", "client_typ": "mobile"}),
+            "client_typ",
+        ),
+        (
+            "/login",
+            json!({"email": "a@example.com", "password": "password123", "clienttype": "mobile"}),
+            "clienttype",
+        ),
+    ]
+}
+
+#[actix_web::test]
+async fn test_strict_json_rejects_unknown_fields() {
+    let app = init_app!(true);
+
+    for (uri, body, field) in bodies_with_typos() {
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .set_json(&body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        let error: serde_json::Value = test::read_body_json(resp).await;
+        let message = error["error"].as_str().unwrap();
+        assert!(message.contains(field), "{}: {}", uri, message);
+    }
+
+    // Known fields, including optional ones, are still accepted
+    let req = test::TestRequest::post()
+        .uri("/login")
+        .set_json(json!({"email": "a@example.com", "password": "password123", "client_type": "mobile"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[actix_web::test]
+async fn test_lenient_json_ignores_unknown_fields() {
+    let app = init_app!(false);
+
+    for (uri, body, _) in bodies_with_typos() {
+        let req = test::TestRequest::post()
+            .uri(uri)
+            .set_json(&body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK, "{}", uri);
+    }
+
+    // Missing required fields fail in both modes
+    let req = test::TestRequest::post()
+        .uri("/comment")
+        .set_json(json!({"contnet": "Nice"}))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,59 @@
+// Tests for the page and limit of the /api/top/* rankings
+// These do not need any external services
+// Run with: cargo test --test top_query_test
+
+use example_rust_web_service::{
+    api::top::{page_and_limit, rank_range, TopQuery},
+    config::Config,
+};
+
+fn config(max_limit: u64) -> Config {
+    let mut config = Config::from_env().expect("Failed to load configuration");
+    config.top_stats.max_limit = max_limit;
+    config
+}
+
+fn query(page: Option<u64>, limit: Option<u64>) -> TopQuery {
+    TopQuery {
+        page,
+        limit,
+        debug: None,
+    }
+}
+
+#[test]
+fn test_defaults_apply_without_page_or_limit() {
+    let config = config(100);
+    assert_eq!(page_and_limit(&query(None, None), &config), (1, 10));
+    assert_eq!(rank_range(&query(None, None), &config), Some((0, 9)));
+    assert_eq!(rank_range(&query(Some(3), Some(20)), &config), Some((40, 59)));
+}
+
+#[test]
+fn test_limit_is_clamped_to_max_limit() {
+    let config = config(100);
+    assert_eq!(
+        page_and_limit(&query(Some(1), Some(1_000_000_000)), &config),
+        (1, 100)
+    );
+    assert_eq!(
+        rank_range(&query(Some(2), Some(1_000_000_000)), &config),
+        Some((100, 199))
+    );
+    // Zero pages and limits count as one
+    assert_eq!(page_and_limit(&query(Some(0), Some(0)), &config), (1, 1));
+}
+
+#[test]
+fn test_unaddressable_pages_are_rejected() {
+    let config = config(100);
+    assert_eq!(rank_range(&query(Some(u64::MAX), Some(100)), &config), None);
+    assert_eq!(
+        rank_range(&query(Some(i64::MAX as u64), Some(2)), &config),
+        None
+    );
+    assert_eq!(
+        rank_range(&query(Some(i64::MAX as u64 / 2), Some(2)), &config),
+        Some((i64::MAX as u64 - 3, i64::MAX as u64 - 2))
+    );
+}
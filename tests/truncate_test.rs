@@ -0,0 +1,45 @@
+// Tests for grapheme-safe content truncation (truncate_chars)
+// These do not need any external services
+// Run with: cargo test --test truncate_test
+
+use example_rust_web_service::services::content::truncate_chars;
+
+#[test]
+fn test_short_content_is_unchanged() {
+    assert_eq!(truncate_chars("hello", 5), "hello");
+    assert_eq!(truncate_chars("hello", 10), "hello");
+    assert_eq!(truncate_chars("", 3), "");
+    // 0 disables truncation
+    assert_eq!(truncate_chars("hello world", 0), "hello world");
+}
+
+#[test]
+fn test_long_content_is_cut_with_ellipsis() {
+    assert_eq!(truncate_chars("hello world", 5), "hello…");
+    // Whitespace before the cut is dropped so the ellipsis follows the last word
+    assert_eq!(truncate_chars("hello world", 6), "hello…");
+}
+
+#[test]
+fn test_multi_byte_characters_stay_whole() {
+    assert_eq!(truncate_chars("日本語のテキスト", 3), "日本語…");
+    assert_eq!(truncate_chars("Привет мир", 6), "Привет…");
+}
+
+#[test]
+fn test_emoji_sequences_stay_whole() {
+    // Family (ZWJ sequence), flag (regional indicator pair) and skin-tone modifier:
+    // each is one grapheme made of several chars
+    let content = "👩‍👩‍👧🇫🇷👍🏽 party";
+    assert_eq!(truncate_chars(content, 1), "👩‍👩‍👧…");
+    assert_eq!(truncate_chars(content, 2), "👩‍👩‍👧🇫🇷…");
+    assert_eq!(truncate_chars(content, 3), "👩‍👩‍👧🇫🇷👍🏽…");
+}
+
+#[test]
+fn test_combining_characters_stay_with_their_base() {
+    // "e" + U+0301 COMBINING ACUTE ACCENT renders as one "é"
+    let content = "cafe\u{301} au lait";
+    assert_eq!(truncate_chars(content, 4), "cafe\u{301}…");
+    assert!(!truncate_chars(content, 3).contains('\u{301}'));
+}